@@ -0,0 +1,111 @@
+use std::env;
+use std::process::ExitCode;
+
+use rustls::crypto::ring::default_provider;
+use sqlx::postgres::PgPoolOptions;
+
+use rusty_database::Database;
+use rusty_database::migrations::{
+    MigrationState, migration_status, rollback_migrations, run_migrations,
+};
+
+const USAGE: &str = "Usage: rusty-admin <command>
+
+Commands:
+  migrate            Apply every pending migration
+  rollback [steps]   Revert the most recent migrations (default: 1)
+  status             List migrations and whether they are applied
+  verify             Exit with an error unless the schema matches this build";
+
+enum AdminCommand {
+    Migrate,
+    Rollback { steps: usize },
+    Status,
+    Verify,
+}
+
+fn parse_command(mut args: impl Iterator<Item = String>) -> Option<AdminCommand> {
+    let command = match args.next()?.as_str() {
+        "migrate" => AdminCommand::Migrate,
+        "rollback" => {
+            let steps = match args.next() {
+                Some(raw) => raw.parse().ok().filter(|steps| *steps > 0)?,
+                None => 1,
+            };
+            AdminCommand::Rollback { steps }
+        }
+        "status" => AdminCommand::Status,
+        "verify" => AdminCommand::Verify,
+        _ => return None,
+    };
+
+    // Trailing arguments are more likely a typo than something to ignore
+    args.next().is_none().then_some(command)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<ExitCode> {
+    let Some(command) = parse_command(env::args().skip(1)) else {
+        eprintln!("{USAGE}");
+        return Ok(ExitCode::FAILURE);
+    };
+
+    default_provider()
+        .install_default()
+        .map_err(|_| anyhow::anyhow!("failed to install rustls ring provider"))?;
+
+    dotenvy::dotenv().ok();
+    let database_url = env::var("DATABASE_URL")?;
+    let db_pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await?;
+    let db = Database::new(db_pool);
+
+    match command {
+        AdminCommand::Migrate => {
+            let applied = run_migrations(&db).await?;
+            if applied.is_empty() {
+                println!("Schema is already up to date.");
+            }
+            for version in applied {
+                println!("Applied {version}");
+            }
+        }
+        AdminCommand::Rollback { steps } => {
+            let reverted = rollback_migrations(&db, steps).await?;
+            if reverted.is_empty() {
+                println!("No applied migrations to roll back.");
+            }
+            for version in reverted {
+                println!("Reverted {version}");
+            }
+        }
+        AdminCommand::Status => {
+            for status in migration_status(&db).await? {
+                println!(
+                    "{:<16} {:<18} {}",
+                    status.version, status.state, status.description
+                );
+            }
+        }
+        AdminCommand::Verify => {
+            let problems: Vec<_> = migration_status(&db)
+                .await?
+                .into_iter()
+                .filter(|status| status.state != MigrationState::Applied)
+                .collect();
+            if problems.is_empty() {
+                println!("Schema matches all migrations in this build.");
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            for status in &problems {
+                println!("{} is {}", status.version, status.state);
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -1,17 +1,34 @@
+mod shards;
+
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 
-use tracing::{error, info};
-use twilight_gateway::{EventTypeFlags, Intents, Shard, ShardId, StreamExt as _};
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+use twilight_gateway::Intents;
 use twilight_http::Client;
-use twilight_model::gateway::event::Event;
+use twilight_model::id::Id;
 
 use rustls::crypto::ring::default_provider;
 use sqlx::postgres::PgPoolOptions;
 
-use rusty_commands::{handle_interaction, handle_message};
+use rusty_commands::{Dispatcher, job_registry, register_application_commands, validate_registry};
 use rusty_core::Context;
+use rusty_core::jobs::spawn_job_runner;
 use rusty_database::Database;
+use rusty_database::impls::guild_settings::raid_action_guild_count;
+use rusty_database::migrations::run_migrations;
+use rusty_utils::pagination::respond::cleanup_pending_components;
+
+use crate::shards::{create_shards, run_shard};
+
+/// How long shards get to close and save their sessions.
+const SHARD_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long running commands, and separately scheduled jobs, get to finish.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(20);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -21,6 +38,9 @@ async fn main() -> anyhow::Result<()> {
         .install_default()
         .map_err(|_| anyhow::anyhow!("failed to install rustls ring provider"))?;
 
+    // Catch duplicate command names or overlapping component prefixes early
+    validate_registry()?;
+
     // Load the .env file
     dotenvy::dotenv().ok();
 
@@ -36,40 +56,160 @@ async fn main() -> anyhow::Result<()> {
         .await?;
     info!("PostgreSQL connection established.");
     let db = Database::new(db_pool);
-    let ctx = Context::new(Arc::clone(&http), db);
+
+    // Bring the schema up to date unless deploys manage migrations themselves
+    let run_migrations_enabled = env::var("RUN_MIGRATIONS")
+        .map(|raw| !raw.trim().eq_ignore_ascii_case("false"))
+        .unwrap_or(true);
+    if run_migrations_enabled {
+        let applied = run_migrations(&db).await?;
+        info!(
+            count = applied.len(),
+            ?applied,
+            "database migrations applied"
+        );
+    }
+
+    // A shard is one Gateway WebSocket connection to Discord; large bots need several.
+    // SHARD_TOTAL and SHARD_RANGE let several processes split the shards between them,
+    // so work one process leaves behind is tagged with its range.
+    let shard_total = env::var("SHARD_TOTAL").ok();
+    let shard_range = env::var("SHARD_RANGE").ok();
+    let instance = shard_range.as_deref().map_or("all", str::trim);
+
+    // Member events keep the cache's member list complete and drive raid detection,
+    // but need the privileged GUILD_MEMBERS intent; without them members are cached
+    // as they are seen and raids go unnoticed
+    let member_events_enabled = env::var("MEMBER_EVENTS")
+        .map(|raw| raw.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if !member_events_enabled {
+        match raid_action_guild_count(&db).await {
+            Ok(0) => {}
+            Ok(guilds) => warn!(
+                guilds,
+                "raid actions are configured but MEMBER_EVENTS is off; raids will not be detected"
+            ),
+            Err(source) => error!(?source, "failed to count guilds with a raid action"),
+        }
+    }
+    let ctx = Context::new(Arc::clone(&http), db, instance, member_events_enabled);
+
+    // Register slash commands, per guild when a development guild is configured
+    let application_id = http.current_user_application().await?.model().await?.id;
+    let command_guild_id = env::var("DISCORD_COMMAND_GUILD_ID")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .and_then(Id::new_checked);
+    match register_application_commands(&http, application_id, command_guild_id).await {
+        Ok(count) => info!(count, guild_id = ?command_guild_id, "application commands registered"),
+        Err(source) => error!(?source, "failed to register application commands"),
+    }
+
+    // Cancelled on SIGINT/SIGTERM; shards, the dispatcher and the job runner wind down
+    let shutdown = CancellationToken::new();
+
+    // Run scheduled jobs, including any that came due while the bot was offline
+    let job_runner = spawn_job_runner(ctx.clone(), job_registry(), shutdown.clone());
+
+    // Optionally cap how many events each guild may have in flight at once
+    let guild_concurrency_limit = env::var("GUILD_CONCURRENCY_LIMIT")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .filter(|limit| *limit > 0);
+    let dispatcher = Dispatcher::new(ctx.clone(), guild_concurrency_limit);
+
+    // Prefix commands need the privileged MESSAGE_CONTENT intent; slash commands do not
+    let prefix_commands_enabled = env::var("PREFIX_COMMANDS")
+        .map(|raw| !raw.trim().eq_ignore_ascii_case("false"))
+        .unwrap_or(true);
 
     // Declare which intents the bot has
-    let intents = Intents::GUILDS | Intents::GUILD_MESSAGES | Intents::MESSAGE_CONTENT;
+    let mut intents = Intents::GUILDS | Intents::GUILD_MESSAGES;
+    if prefix_commands_enabled {
+        intents |= Intents::MESSAGE_CONTENT;
+    }
+
+    if member_events_enabled {
+        intents |= Intents::GUILD_MEMBERS;
+    }
 
-    // A shard is one Gateway WebSocket connection to Discord
-    // Declare how many shards we want to be running and input our token and intents
-    let mut shard = Shard::new(ShardId::new(0, 1), token, intents);
+    let shards = create_shards(
+        &http,
+        &ctx.db,
+        token,
+        intents,
+        shard_total.as_deref(),
+        shard_range.as_deref(),
+    )
+    .await?;
 
     info!("Rusty is connecting...");
 
-    // Our ears, listens for stuff to do
-    while let Some(item) = shard.next_event(EventTypeFlags::all()).await {
-        let event = match item {
-            Ok(event) => event,
-            Err(source) => {
-                error!(?source, "gateway event stream error");
-                continue;
-            }
-        };
-
-        match event {
-            Event::Ready(_) => {
-                info!("Rusty has successfully awoken!");
-            }
-
-            Event::MessageCreate(msg) => {
-                handle_message(ctx.clone(), msg).await?;
-            }
-            Event::InteractionCreate(interaction) => {
-                handle_interaction(ctx.clone(), interaction).await?;
-            }
-            _ => {} // Ignore unused events
+    // Our ears, one listener per shard feeding the shared dispatcher
+    let mut listeners = JoinSet::new();
+    for shard in shards {
+        listeners.spawn(run_shard(
+            shard,
+            dispatcher.clone(),
+            ctx.clone(),
+            shutdown.clone(),
+        ));
+    }
+
+    tokio::select! {
+        result = shutdown_signal() => {
+            result?;
+            info!("Shutdown requested, closing shards...");
         }
+        () = join_all(&mut listeners) => error!("every shard stopped, shutting down"),
+    }
+    shutdown.cancel();
+
+    // Shards close with a resumable code and save their sessions for the next start
+    if timeout(SHARD_CLOSE_TIMEOUT, join_all(&mut listeners))
+        .await
+        .is_err()
+    {
+        warn!("shards did not close in time; the next start will identify");
+    }
+
+    // Let commands that are already running finish, then the job batch in progress
+    if !dispatcher.drain(DRAIN_TIMEOUT).await {
+        warn!("command handlers still running after the drain timeout");
+    }
+    if timeout(DRAIN_TIMEOUT, job_runner).await.is_err() {
+        warn!("job runner still busy after the drain timeout");
+    }
+
+    // Nobody can page through results while the bot is away
+    match cleanup_pending_components(&ctx).await {
+        Ok(count) => info!(count, "pagination components removed"),
+        Err(source) => error!(?source, "failed to remove pagination components"),
     }
+
+    info!("Rusty has shut down.");
     Ok(()) // Return Success, shutdown cleanly
 }
+
+async fn join_all(listeners: &mut JoinSet<()>) {
+    while listeners.join_next().await.is_some() {}
+}
+
+/// Resolve on Ctrl+C, or SIGTERM where available.
+async fn shutdown_signal() -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+//! Shard planning, the per-shard gateway event loop and session resume.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Duration;
+
+use anyhow::{Context as _, bail};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+use twilight_gateway::{
+    CloseFrame, ConfigBuilder, EventTypeFlags, Intents, Session, Shard, StreamExt as _,
+    create_iterator, queue::InMemoryQueue,
+};
+use twilight_http::Client;
+use twilight_model::gateway::event::Event;
+
+use rusty_commands::Dispatcher;
+use rusty_core::Context;
+use rusty_database::Database;
+use rusty_database::impls::gateway_sessions::{save_gateway_session, take_gateway_sessions};
+use rusty_database::impls::warnings::now_unix_secs;
+
+/// How old a saved session may be and still be worth resuming. Discord drops
+/// sessions soon after their connection closes; a stale one costs a failed
+/// resume before the shard identifies normally.
+const RESUME_WINDOW_SECS: u64 = 3 * 60;
+
+/// Which shards this process runs, out of how many in total.
+struct ShardPlan {
+    range: Range<u32>,
+    total: u32,
+}
+
+impl ShardPlan {
+    /// Build a plan from the optional `SHARD_TOTAL` and `SHARD_RANGE` settings.
+    ///
+    /// Without a total, Discord's recommended count is used and every shard
+    /// runs here. A range such as `0-7` (inclusive) needs an explicit total so
+    /// that processes splitting the shards agree on it.
+    fn from_settings(
+        total: Option<&str>,
+        range: Option<&str>,
+        recommended: u32,
+    ) -> anyhow::Result<Self> {
+        let total = total
+            .map(|raw| {
+                raw.trim()
+                    .parse::<u32>()
+                    .context("SHARD_TOTAL must be a number")
+            })
+            .transpose()?;
+
+        let plan = match (total, range) {
+            (Some(total), Some(range)) => Self {
+                range: parse_shard_range(range)?,
+                total,
+            },
+            (Some(total), None) => Self {
+                range: 0..total,
+                total,
+            },
+            (None, Some(_)) => bail!("SHARD_RANGE needs SHARD_TOTAL to be set"),
+            (None, None) => Self {
+                range: 0..recommended,
+                total: recommended,
+            },
+        };
+
+        if plan.total == 0 || plan.range.is_empty() || plan.range.end > plan.total {
+            bail!(
+                "shard range {}..{} does not fit a total of {}",
+                plan.range.start,
+                plan.range.end,
+                plan.total
+            );
+        }
+
+        Ok(plan)
+    }
+}
+
+fn parse_shard_range(raw: &str) -> anyhow::Result<Range<u32>> {
+    let (first, last) = raw
+        .trim()
+        .split_once('-')
+        .context("SHARD_RANGE must look like `0-3`")?;
+    let first: u32 = first.trim().parse().context("invalid SHARD_RANGE start")?;
+    let last: u32 = last.trim().parse().context("invalid SHARD_RANGE end")?;
+
+    Ok(first..last.saturating_add(1))
+}
+
+/// Create the shards for this process, sharing one identify queue.
+///
+/// Shards with a recently saved session resume it instead of identifying.
+pub async fn create_shards(
+    http: &Client,
+    db: &Database,
+    token: String,
+    intents: Intents,
+    total: Option<&str>,
+    range: Option<&str>,
+) -> anyhow::Result<Vec<Shard>> {
+    let info = http.gateway().authed().await?.model().await?;
+    let plan = ShardPlan::from_settings(total, range, info.shards)?;
+
+    // Identifies are rate limited per bot, so every shard must wait in the same queue
+    let limit = info.session_start_limit;
+    let queue = InMemoryQueue::new(
+        limit.max_concurrency,
+        limit.remaining,
+        Duration::from_millis(limit.reset_after),
+        limit.total,
+    );
+    let config = ConfigBuilder::new(token, intents).queue(queue).build();
+
+    let since = now_unix_secs().saturating_sub(RESUME_WINDOW_SECS);
+    let sessions: HashMap<u32, _> =
+        match take_gateway_sessions(db, plan.range.clone(), plan.total, since).await {
+            Ok(sessions) => sessions
+                .into_iter()
+                .map(|session| (session.shard_id, session))
+                .collect(),
+            Err(source) => {
+                error!(?source, "failed to load saved gateway sessions");
+                HashMap::new()
+            }
+        };
+
+    info!(
+        first = plan.range.start,
+        last = plan.range.end - 1,
+        total = plan.total,
+        recommended = info.shards,
+        resuming = sessions.len(),
+        "starting shards"
+    );
+
+    let shards = create_iterator(plan.range, plan.total, config, |shard_id, builder| {
+        let Some(saved) = sessions.get(&shard_id.number()) else {
+            return builder.build();
+        };
+
+        let builder = builder.session(Session::new(saved.sequence, saved.session_id.clone()));
+        match &saved.resume_url {
+            Some(url) => builder.resume_url(url.clone()).build(),
+            None => builder.build(),
+        }
+    });
+
+    Ok(shards.collect())
+}
+
+/// Receive events from one shard, update the cache and hand them to the dispatcher.
+///
+/// When `shutdown` is cancelled the shard closes with a resumable close code
+/// and saves its session so the next start can pick up where it left off.
+pub async fn run_shard(
+    mut shard: Shard,
+    dispatcher: Dispatcher,
+    ctx: Context,
+    shutdown: CancellationToken,
+) {
+    let shard_id = shard.id();
+    let mut closing = false;
+
+    loop {
+        let item = tokio::select! {
+            item = shard.next_event(EventTypeFlags::all()) => item,
+            () = shutdown.cancelled(), if !closing => {
+                closing = true;
+                shard.close(CloseFrame::RESUME);
+                continue;
+            }
+        };
+        let Some(item) = item else {
+            break;
+        };
+
+        let event = match item {
+            Ok(event) => event,
+            Err(_) if closing => break,
+            Err(source) => {
+                error!(?source, shard = %shard_id, "gateway event stream error");
+                continue;
+            }
+        };
+
+        // Keep the cache current before any handler reads from it
+        ctx.cache.update(&event);
+
+        match event {
+            Event::Ready(ready) => {
+                info!(shard = %shard_id, guilds = ready.guilds.len(), "shard ready");
+            }
+            Event::Resumed => info!(shard = %shard_id, "shard resumed"),
+            Event::GatewayClose(_) if closing => break,
+            Event::GatewayClose(frame) => {
+                warn!(shard = %shard_id, ?frame, "shard disconnected");
+            }
+            Event::GatewayReconnect => info!(shard = %shard_id, "shard asked to reconnect"),
+            Event::GatewayInvalidateSession(resumable) => {
+                warn!(shard = %shard_id, resumable, "shard session invalidated");
+            }
+
+            // Each event runs on its own task; failures are logged and reported there
+            Event::MessageCreate(msg) => dispatcher.dispatch_message(msg),
+            Event::InteractionCreate(interaction) => dispatcher.dispatch_interaction(interaction),
+            Event::MemberAdd(member) => dispatcher.dispatch_member_add(member),
+            _ => {} // Ignore unused events
+        }
+    }
+
+    if !closing {
+        error!(shard = %shard_id, "shard stopped receiving events");
+        return;
+    }
+
+    let Some(session) = shard.session() else {
+        info!(shard = %shard_id, "shard closed without a session to save");
+        return;
+    };
+    match save_gateway_session(
+        &ctx.db,
+        shard_id.number(),
+        shard_id.total(),
+        session.id(),
+        session.sequence(),
+        shard.resume_url(),
+    )
+    .await
+    {
+        Ok(()) => info!(shard = %shard_id, sequence = session.sequence(), "shard session saved"),
+        Err(source) => error!(?source, shard = %shard_id, "failed to save shard session"),
+    }
+}
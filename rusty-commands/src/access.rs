@@ -0,0 +1,158 @@
+//! Guild command overrides, checked once before a command runs.
+//!
+//! Overrides are resolved in this order, stopping at the first that applies:
+//!
+//! 1. A disabled command refuses everyone.
+//! 2. A channel deny refuses everyone in that channel (and its threads).
+//! 3. A user override allows or denies that user.
+//! 4. Role overrides: any allowed role grants the command, otherwise any
+//!    denied role refuses it, matching how Discord combines role overwrites.
+//! 5. A channel allow grants the command to everyone in that channel.
+//!
+//! Without a matching override the command runs its usual Discord
+//! permission checks.
+
+use twilight_model::{
+    gateway::payload::incoming::InteractionCreate,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+    },
+};
+
+use crate::moderation::overrides;
+use rusty_core::Context;
+use rusty_database::impls::command_overrides::command_overrides;
+use rusty_database::model::command_overrides::{CommandOverride, OverrideMode, OverrideTarget};
+
+/// Outcome of checking a guild's overrides for one invocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CommandAccess {
+    /// No override applies; the command checks Discord permissions itself.
+    Default,
+    /// An override grants the command regardless of Discord permissions.
+    Granted,
+    /// The command is disabled in this guild.
+    Disabled,
+    /// An override refuses the command to this member or channel.
+    Denied,
+}
+
+impl CommandAccess {
+    /// Whether permission checks inside the command should pass.
+    pub(crate) fn is_granted(self) -> bool {
+        self == Self::Granted
+    }
+
+    /// Reply for a refused invocation, or `None` when the command may run.
+    pub(crate) fn refusal(self) -> Option<&'static str> {
+        match self {
+            Self::Default | Self::Granted => None,
+            Self::Disabled => Some("That command is disabled in this server."),
+            Self::Denied => Some("You're not allowed to use that command here."),
+        }
+    }
+}
+
+/// Who is running a command, and where.
+pub(crate) struct Caller<'a> {
+    pub guild_id: Id<GuildMarker>,
+    pub channel_id: Id<ChannelMarker>,
+    pub user_id: Id<UserMarker>,
+    pub roles: &'a [Id<RoleMarker>],
+}
+
+/// Check a guild's overrides for `command`.
+///
+/// The overrides command itself is exempt so a guild can't lock itself out.
+pub(crate) async fn command_access(
+    ctx: &Context,
+    command: &str,
+    caller: Caller<'_>,
+) -> anyhow::Result<CommandAccess> {
+    if command == overrides::META.name {
+        return Ok(CommandAccess::Default);
+    }
+
+    let overrides = command_overrides(&ctx.db, caller.guild_id.get(), Some(command)).await?;
+    if overrides.is_empty() {
+        return Ok(CommandAccess::Default);
+    }
+
+    // Threads follow the overrides of the channel they were started in
+    let parent_id = ctx
+        .cache
+        .channel(caller.channel_id)
+        .filter(|channel| channel.kind.is_thread())
+        .and_then(|channel| channel.parent_id);
+
+    Ok(resolve(&overrides, &caller, parent_id))
+}
+
+/// Check a guild's overrides for an interaction aimed at `command`.
+///
+/// Interactions outside a guild, or without a channel or author, have no overrides.
+pub(crate) async fn interaction_access(
+    ctx: &Context,
+    command: &str,
+    interaction: &InteractionCreate,
+) -> anyhow::Result<CommandAccess> {
+    let (Some(guild_id), Some(channel), Some(user_id)) = (
+        interaction.guild_id,
+        interaction.channel.as_ref(),
+        interaction.author_id(),
+    ) else {
+        return Ok(CommandAccess::Default);
+    };
+    let roles = interaction
+        .member
+        .as_ref()
+        .map_or(&[][..], |member| member.roles.as_slice());
+
+    let caller = Caller {
+        guild_id,
+        channel_id: channel.id,
+        user_id,
+        roles,
+    };
+    command_access(ctx, command, caller).await
+}
+
+fn resolve(
+    overrides: &[CommandOverride],
+    caller: &Caller<'_>,
+    parent_id: Option<Id<ChannelMarker>>,
+) -> CommandAccess {
+    let has = |mode: OverrideMode, applies: &dyn Fn(OverrideTarget) -> bool| {
+        overrides
+            .iter()
+            .any(|entry| entry.mode == mode && applies(entry.target))
+    };
+    let in_channel = |target| match target {
+        OverrideTarget::Channel(id) => {
+            id == caller.channel_id.get() || parent_id.is_some_and(|parent| parent.get() == id)
+        }
+        _ => false,
+    };
+    let is_user = |target| target == OverrideTarget::User(caller.user_id.get());
+    let has_role = |target| match target {
+        OverrideTarget::Role(id) => caller.roles.iter().any(|role| role.get() == id),
+        _ => false,
+    };
+
+    if has(OverrideMode::Deny, &|target| {
+        target == OverrideTarget::Guild
+    }) {
+        CommandAccess::Disabled
+    } else if has(OverrideMode::Deny, &in_channel) || has(OverrideMode::Deny, &is_user) {
+        CommandAccess::Denied
+    } else if has(OverrideMode::Allow, &is_user) || has(OverrideMode::Allow, &has_role) {
+        CommandAccess::Granted
+    } else if has(OverrideMode::Deny, &has_role) {
+        CommandAccess::Denied
+    } else if has(OverrideMode::Allow, &in_channel) {
+        CommandAccess::Granted
+    } else {
+        CommandAccess::Default
+    }
+}
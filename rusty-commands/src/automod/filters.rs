@@ -0,0 +1,120 @@
+//! Message checks behind the non-pattern automod rule kinds.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use rusty_database::model::automod::AutomodKind;
+
+/// Messages with fewer letters than this are never treated as excessive caps.
+const MIN_CAPS_LETTERS: usize = 10;
+
+static INVITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:discord(?:app)?\.com/invite|discord\.gg)/[a-z0-9-]+")
+        .expect("invite pattern compiles")
+});
+static LINK_HOST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)https?://([^\s/?#<>]+)").expect("link pattern compiles"));
+static MENTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<@[!&]?\d+>|@everyone|@here").expect("mention pattern compiles"));
+static CUSTOM_EMOJI: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<a?:\w+:\d+>").expect("emoji pattern compiles"));
+
+/// Threshold used when a rule does not set one.
+pub(crate) fn default_threshold(kind: AutomodKind) -> Option<u32> {
+    match kind {
+        AutomodKind::Caps => Some(70),
+        AutomodKind::Mentions => Some(5),
+        AutomodKind::Emoji => Some(10),
+        AutomodKind::Zalgo => Some(3),
+        _ => None,
+    }
+}
+
+/// Whether the content links to a Discord invite.
+pub(crate) fn contains_invite(content: &str) -> bool {
+    INVITE.is_match(content)
+}
+
+/// Whether the content has an `http` or `https` link.
+pub(crate) fn contains_link(content: &str) -> bool {
+    LINK_HOST.is_match(content)
+}
+
+/// Lowercased hosts of every `http` or `https` link in the content.
+pub(crate) fn link_hosts(content: &str) -> impl Iterator<Item = String> + '_ {
+    LINK_HOST.captures_iter(content).filter_map(|captures| {
+        let authority = captures.get(1)?.as_str();
+        let host = authority.rsplit('@').next()?;
+        let host = host.split(':').next()?.trim_end_matches('.');
+        (!host.is_empty()).then(|| host.to_ascii_lowercase())
+    })
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+pub(crate) fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Percentage of letters that are uppercase, or `None` for short messages.
+pub(crate) fn caps_percent(content: &str) -> Option<u32> {
+    let (letters, upper) = content
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .fold((0usize, 0usize), |(letters, upper), c| {
+            (letters + 1, upper + usize::from(c.is_uppercase()))
+        });
+    if letters < MIN_CAPS_LETTERS {
+        return None;
+    }
+    u32::try_from(upper * 100 / letters).ok()
+}
+
+/// User, role, `@everyone` and `@here` mentions in the content.
+pub(crate) fn mention_count(content: &str) -> usize {
+    MENTION.find_iter(content).count()
+}
+
+/// Custom emoji plus unicode emoji codepoints in the content.
+///
+/// Unicode emoji are counted by codepoint, so a joined sequence such as a
+/// family emoji counts once per person in it.
+pub(crate) fn emoji_count(content: &str) -> usize {
+    let custom = CUSTOM_EMOJI.find_iter(content).count();
+    let without_custom = CUSTOM_EMOJI.replace_all(content, "");
+    let unicode = without_custom.chars().filter(|&c| is_emoji(c)).count();
+    custom + unicode
+}
+
+/// Most combining marks stacked on any single character.
+pub(crate) fn max_combining_marks(content: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        if is_combining_mark(c) {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    longest
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF
+    )
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F | 0x0483..=0x0489 | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF
+            | 0xFE20..=0xFE2F
+    )
+}
@@ -0,0 +1,343 @@
+//! Automod: per-guild rules checked against every guild message before
+//! command dispatch.
+//!
+//! A matching message is deleted and its author punished with the most severe
+//! action among the rules it broke. Members who can manage messages are never
+//! moderated, and any failure lets the message through. Message floods are
+//! caught before the rules by the [`spam`] limits.
+
+pub(crate) mod filters;
+pub(crate) mod raid;
+pub(crate) mod spam;
+
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+    },
+    util::Timestamp,
+};
+
+use tracing::{info, warn};
+
+use crate::automod::filters::{
+    caps_percent, contains_invite, default_threshold, domain_matches, emoji_count, link_hosts,
+    max_combining_marks, mention_count,
+};
+use crate::moderation::case_log::{ActionReport, bot_user_id, report_action};
+use crate::moderation::escalation::{MAX_TIMEOUT_SECS, escalate_after_warning};
+use crate::moderation::targets::target_refusal;
+use crate::moderation::tempban::{clear_tempban, store_tempban};
+use crate::moderation::timeout::DEFAULT_TIMEOUT_SECS;
+use rusty_core::Context;
+use rusty_core::automod::CompiledRule;
+use rusty_database::impls::warnings::record_warning;
+use rusty_database::model::automod::{AutomodAction, AutomodKind, AutomodRule};
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::cleanup::schedule_message_delete;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::permissions::resolve_message_author_permissions;
+use rusty_utils::time::{format_duration_secs, now_unix_secs};
+
+/// How long the notice for a deleted message stays up.
+const NOTICE_LIFETIME_SECS: u64 = 10;
+
+/// Check a guild message against its guild's spam limits and automod rules.
+///
+/// Returns `true` when the message was removed, in which
+/// case it must not be treated as a command.
+pub(crate) async fn moderate_message(ctx: &Context, msg: &MessageCreate) -> bool {
+    match check_message(ctx, msg).await {
+        Ok(removed) => removed,
+        Err(source) => {
+            warn!(?source, message_id = msg.id.get(), "automod check failed");
+            false
+        }
+    }
+}
+
+async fn check_message(ctx: &Context, msg: &MessageCreate) -> anyhow::Result<bool> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(false);
+    };
+    if spam::check_spam(ctx, msg, guild_id).await? {
+        return Ok(true);
+    }
+
+    let rules = ctx.automod_rules(guild_id.get()).await?;
+    if rules.is_empty() {
+        return Ok(false);
+    }
+
+    // Exempting a channel also exempts the threads started in it
+    let parent_id = ctx
+        .cache
+        .channel(msg.channel_id)
+        .filter(|channel| channel.kind.is_thread())
+        .and_then(|channel| channel.parent_id);
+    let roles = msg
+        .member
+        .as_ref()
+        .map_or(&[][..], |member| member.roles.as_slice());
+
+    let broken = rules
+        .iter()
+        .filter(|compiled| !is_exempt(&compiled.rule, msg.channel_id, parent_id, roles))
+        .filter(|compiled| matches(compiled, &msg.content))
+        .max_by_key(|compiled| {
+            let rule = &compiled.rule;
+            (rule.action, rule.duration_secs.unwrap_or(u64::MAX))
+        });
+    let Some(CompiledRule { rule, .. }) = broken else {
+        return Ok(false);
+    };
+
+    // Checked last so ordinary messages never cost a permission lookup
+    let perms = resolve_message_author_permissions(ctx, msg).await?;
+    if perms.is_some_and(|perms| {
+        perms.intersects(Permissions::MANAGE_MESSAGES | Permissions::ADMINISTRATOR)
+    }) {
+        return Ok(false);
+    }
+
+    let reason = format!("Automod rule #{} ({})", rule.id, rule.kind);
+    if let Err(source) = ctx
+        .http
+        .delete_message(msg.channel_id, msg.id)
+        .reason(&reason)
+        .await
+    {
+        warn!(?source, rule_id = rule.id, "automod message delete failed");
+    }
+
+    let notice = format!(
+        "your message was removed by automod rule #{} ({}).",
+        rule.id, rule.kind
+    );
+    let violation = Violation {
+        action: rule.action,
+        duration_secs: rule.duration_secs,
+        reason: &reason,
+        notice: &notice,
+    };
+    if let Err(source) = punish(ctx, msg, guild_id, msg.author.id, &violation).await {
+        warn!(?source, rule_id = rule.id, "automod action failed");
+    }
+    Ok(true)
+}
+
+fn is_exempt(
+    rule: &AutomodRule,
+    channel_id: Id<ChannelMarker>,
+    parent_id: Option<Id<ChannelMarker>>,
+    roles: &[Id<RoleMarker>],
+) -> bool {
+    rule.exempt_channel_ids
+        .iter()
+        .any(|&id| id == channel_id.get() || parent_id.is_some_and(|parent| parent.get() == id))
+        || roles
+            .iter()
+            .any(|role| rule.exempt_role_ids.contains(&role.get()))
+}
+
+fn matches(compiled: &CompiledRule, content: &str) -> bool {
+    let rule = &compiled.rule;
+    let threshold = rule
+        .threshold
+        .or_else(|| default_threshold(rule.kind))
+        .unwrap_or(0) as usize;
+    let domain_listed = |host: &str| {
+        rule.patterns
+            .iter()
+            .any(|domain| domain_matches(host, domain))
+    };
+
+    match rule.kind {
+        AutomodKind::Words | AutomodKind::Regex => compiled
+            .matcher
+            .as_ref()
+            .is_some_and(|matcher| matcher.is_match(content)),
+        AutomodKind::Invites => contains_invite(content),
+        AutomodKind::LinkAllowlist => link_hosts(content).any(|host| !domain_listed(&host)),
+        AutomodKind::LinkDenylist => link_hosts(content).any(|host| domain_listed(&host)),
+        AutomodKind::Caps => {
+            caps_percent(content).is_some_and(|percent| percent as usize >= threshold)
+        }
+        AutomodKind::Mentions => mention_count(content) > threshold,
+        AutomodKind::Emoji => emoji_count(content) > threshold,
+        AutomodKind::Zalgo => max_combining_marks(content) > threshold,
+    }
+}
+
+/// What to do about a message, or burst of messages, automod removed.
+struct Violation<'a> {
+    action: AutomodAction,
+    /// Timeout length, or ban length for a temporary ban.
+    duration_secs: Option<u64>,
+    /// Case and audit log reason.
+    reason: &'a str,
+    /// Short-lived channel notice used when the only action is deleting.
+    notice: &'a str,
+}
+
+/// Apply a violation's action to one author. Replies go to the channel of `msg`.
+async fn punish(
+    ctx: &Context,
+    msg: &MessageCreate,
+    guild_id: Id<GuildMarker>,
+    target_user_id: Id<UserMarker>,
+    violation: &Violation<'_>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let reason = violation.reason;
+    let invocation = Invocation::from_message(Box::new(msg.clone()));
+
+    if violation.action == AutomodAction::Delete {
+        let notice = format!("<@{target_user_id}>, {}", violation.notice);
+        let notice = http
+            .create_message(msg.channel_id)
+            .content(&notice)
+            .await?
+            .model()
+            .await?;
+        return schedule_message_delete(ctx, notice.channel_id, notice.id, NOTICE_LIFETIME_SECS)
+            .await;
+    }
+
+    let Some(moderator_id) = bot_user_id(ctx).await else {
+        anyhow::bail!("bot user unavailable");
+    };
+    let bot_id = Id::new(moderator_id);
+    if let Some(refusal) = target_refusal(ctx, guild_id, bot_id, target_user_id).await? {
+        info!(
+            user_id = target_user_id.get(),
+            ?refusal,
+            reason,
+            "automod skipped punishment"
+        );
+        return Ok(());
+    }
+
+    let now = now_unix_secs();
+    match violation.action {
+        // Handled above, before looking up the bot
+        AutomodAction::Delete => Ok(()),
+        AutomodAction::Warn => {
+            let warning = record_warning(
+                &ctx.db,
+                guild_id.get(),
+                target_user_id.get(),
+                moderator_id,
+                reason,
+            )
+            .await?;
+            let action = format!("warned (warning #{})", warning.id);
+
+            report_action(
+                ctx,
+                &invocation,
+                ActionReport {
+                    case: NewCase {
+                        guild_id: guild_id.get(),
+                        action: CaseAction::Warn,
+                        user_id: Some(target_user_id.get()),
+                        moderator_id,
+                        reason: Some(reason),
+                        duration_secs: None,
+                        expires_at: None,
+                    },
+                    target_user_id,
+                    action_past_tense: &action,
+                    duration_label: None,
+                },
+            )
+            .await?;
+
+            escalate_after_warning(ctx, &invocation, guild_id, target_user_id, warning, reason)
+                .await
+        }
+        AutomodAction::Timeout => {
+            let default_secs = ctx
+                .guild_settings(guild_id.get())
+                .await?
+                .default_timeout_secs
+                .unwrap_or(DEFAULT_TIMEOUT_SECS);
+            let duration = violation
+                .duration_secs
+                .unwrap_or(default_secs)
+                .min(MAX_TIMEOUT_SECS);
+            let expires_at = now.saturating_add(duration);
+            http.update_guild_member(guild_id, target_user_id)
+                .communication_disabled_until(Some(Timestamp::from_secs(expires_at as i64)?))
+                .reason(reason)
+                .await?;
+
+            let duration_label = format_duration_secs(duration);
+            report_action(
+                ctx,
+                &invocation,
+                ActionReport {
+                    case: NewCase {
+                        guild_id: guild_id.get(),
+                        action: CaseAction::Timeout,
+                        user_id: Some(target_user_id.get()),
+                        moderator_id,
+                        reason: Some(reason),
+                        duration_secs: Some(duration),
+                        expires_at: Some(expires_at),
+                    },
+                    target_user_id,
+                    action_past_tense: "timed out",
+                    duration_label: Some(&duration_label),
+                },
+            )
+            .await
+        }
+        AutomodAction::Ban => {
+            http.create_ban(guild_id, target_user_id)
+                .reason(reason)
+                .await?;
+
+            let expires_at = violation
+                .duration_secs
+                .map(|duration| now.saturating_add(duration));
+            match expires_at {
+                Some(expires_at) => {
+                    store_tempban(
+                        ctx,
+                        guild_id.get(),
+                        target_user_id.get(),
+                        moderator_id,
+                        expires_at,
+                    )
+                    .await?
+                }
+                None => clear_tempban(ctx, guild_id.get(), target_user_id.get()).await?,
+            }
+
+            let duration_label = violation.duration_secs.map(format_duration_secs);
+            report_action(
+                ctx,
+                &invocation,
+                ActionReport {
+                    case: NewCase {
+                        guild_id: guild_id.get(),
+                        action: CaseAction::Ban,
+                        user_id: Some(target_user_id.get()),
+                        moderator_id,
+                        reason: Some(reason),
+                        duration_secs: violation.duration_secs,
+                        expires_at,
+                    },
+                    target_user_id,
+                    action_past_tense: "banned",
+                    duration_label: duration_label.as_deref(),
+                },
+            )
+            .await
+        }
+    }
+}
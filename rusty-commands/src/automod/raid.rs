@@ -0,0 +1,205 @@
+//! Raid detection: watches member joins for waves of new members, or of
+//! young accounts, and locks the server down when one arrives.
+//!
+//! Guilds opt in by choosing a raid action. The members whose joins made up
+//! the wave can also be kicked or timed out.
+
+use tracing::{info, warn};
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    gateway::payload::incoming::MemberAdd,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+    },
+    util::Timestamp,
+};
+
+use crate::moderation::case_log::{bot_user_id, load_modlog_settings, log_case, publish_case};
+use crate::moderation::escalation::MAX_TIMEOUT_SECS;
+use crate::moderation::lockdown::engage_lockdown;
+use crate::moderation::targets::target_refusal;
+use crate::moderation::timeout::DEFAULT_TIMEOUT_SECS;
+use rusty_core::Context;
+use rusty_core::raid::{Raid, RaidLimits, RaidTrigger, TrackedJoin};
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_database::model::guild_settings::{GuildSettings, SpamLimit};
+use rusty_database::model::lockdowns::RaidAction;
+use rusty_utils::time::{format_duration_secs, now_unix_secs, snowflake_unix_secs};
+
+/// Members who may join within the window.
+pub(crate) const DEFAULT_JOIN_RATE: SpamLimit = SpamLimit {
+    count: 10,
+    window_secs: 10,
+};
+/// Members with young accounts who may join within the window.
+pub(crate) const DEFAULT_YOUNG_JOIN_RATE: SpamLimit = SpamLimit {
+    count: 4,
+    window_secs: 60,
+};
+/// Accounts younger than a week count as young.
+pub(crate) const DEFAULT_ACCOUNT_AGE_SECS: u64 = 7 * 86_400;
+
+/// The limits a guild enforces, with defaults filled in and disabled ones removed.
+pub(crate) fn raid_limits(settings: &GuildSettings) -> RaidLimits {
+    let effective = |limit: Option<SpamLimit>, default| {
+        Some(limit.unwrap_or(default)).filter(|limit| limit.count > 0)
+    };
+
+    RaidLimits {
+        join_rate: effective(settings.raid_join_rate, DEFAULT_JOIN_RATE),
+        young_join_rate: effective(settings.raid_young_join_rate, DEFAULT_YOUNG_JOIN_RATE),
+    }
+}
+
+/// Track a member join and answer the raid it completes, if any.
+pub(crate) async fn watch_join(ctx: &Context, member: &MemberAdd) -> anyhow::Result<()> {
+    let guild_id = member.guild_id;
+    let user = &member.member.user;
+    if user.bot {
+        return Ok(());
+    }
+
+    let settings = ctx.guild_settings(guild_id.get()).await?;
+    let Some(action) = settings.raid_action else {
+        return Ok(());
+    };
+
+    let min_age_secs = settings
+        .raid_account_age_secs
+        .unwrap_or(DEFAULT_ACCOUNT_AGE_SECS);
+    let age_secs = now_unix_secs().saturating_sub(snowflake_unix_secs(user.id.get()));
+    let join = TrackedJoin {
+        guild_id: guild_id.get(),
+        user_id: user.id.get(),
+        young: age_secs < min_age_secs,
+    };
+    let Some(raid) = ctx.raid.record(join, &raid_limits(&settings)) else {
+        return Ok(());
+    };
+
+    let reason = describe_raid(&raid, min_age_secs);
+    info!(
+        guild_id = guild_id.get(),
+        members = raid.user_ids.len(),
+        reason,
+        "raid detected"
+    );
+    let Some(bot_id) = bot_user_id(ctx).await else {
+        anyhow::bail!("bot user unavailable");
+    };
+
+    // A raid that keeps going during a lockdown only needs its new members handled
+    if let Some(outcome) = engage_lockdown(ctx, guild_id, bot_id, Some(&reason)).await? {
+        let headline = format!("Raid detected ({reason}). Server locked down.");
+        let alert = format!(
+            "{}\nUse `!unlock` to lift the lockdown.",
+            outcome.summary(&headline)
+        );
+        alert_moderators(ctx, guild_id, &alert).await;
+    }
+
+    if action == RaidAction::Lockdown {
+        return Ok(());
+    }
+    for user_id in raid.user_ids.iter().copied().map(Id::new) {
+        if let Err(source) = punish_raider(ctx, guild_id, bot_id, user_id, action, &reason).await {
+            warn!(?source, user_id = user_id.get(), "raid action failed");
+        }
+    }
+
+    Ok(())
+}
+
+fn describe_raid(raid: &Raid, min_age_secs: u64) -> String {
+    let SpamLimit { count, window_secs } = raid.limit;
+    let window = format_duration_secs(window_secs);
+    match raid.trigger {
+        RaidTrigger::Joins => format!("Raid: more than {count} joins within {window}"),
+        RaidTrigger::YoungJoins => format!(
+            "Raid: more than {count} joins from accounts younger than {} within {window}",
+            format_duration_secs(min_age_secs)
+        ),
+    }
+}
+
+/// Tell the mod-log channel, if there is one, what the lockdown changed.
+async fn alert_moderators(ctx: &Context, guild_id: Id<GuildMarker>, alert: &str) {
+    let settings = load_modlog_settings(ctx, guild_id.get()).await;
+    let Some(channel_id) = settings
+        .channel_id
+        .and_then(Id::<ChannelMarker>::new_checked)
+    else {
+        return;
+    };
+
+    if let Err(source) = ctx.http.create_message(channel_id).content(alert).await {
+        warn!(?source, channel_id = channel_id.get(), "raid alert failed");
+    }
+}
+
+async fn punish_raider(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    bot_id: u64,
+    user_id: Id<UserMarker>,
+    action: RaidAction,
+    reason: &str,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    if let Some(refusal) = target_refusal(ctx, guild_id, Id::new(bot_id), user_id).await? {
+        info!(
+            user_id = user_id.get(),
+            ?refusal,
+            reason,
+            "raid response skipped member"
+        );
+        return Ok(());
+    }
+
+    let case = match action {
+        RaidAction::Lockdown => return Ok(()),
+        RaidAction::Kick => {
+            http.remove_guild_member(guild_id, user_id)
+                .reason(reason)
+                .await?;
+            NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Kick,
+                user_id: Some(user_id.get()),
+                moderator_id: bot_id,
+                reason: Some(reason),
+                duration_secs: None,
+                expires_at: None,
+            }
+        }
+        RaidAction::Timeout => {
+            let duration = ctx
+                .guild_settings(guild_id.get())
+                .await?
+                .default_timeout_secs
+                .unwrap_or(DEFAULT_TIMEOUT_SECS)
+                .min(MAX_TIMEOUT_SECS);
+            let expires_at = now_unix_secs().saturating_add(duration);
+            http.update_guild_member(guild_id, user_id)
+                .communication_disabled_until(Some(Timestamp::from_secs(expires_at as i64)?))
+                .reason(reason)
+                .await?;
+            NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Timeout,
+                user_id: Some(user_id.get()),
+                moderator_id: bot_id,
+                reason: Some(reason),
+                duration_secs: Some(duration),
+                expires_at: Some(expires_at),
+            }
+        }
+    };
+
+    if let Some(entry) = log_case(ctx, case).await {
+        publish_case(ctx, &entry).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+//! Message-rate and near-duplicate detection for floods that keyword rules
+//! miss because every single message is short and harmless.
+//!
+//! Guilds opt in by choosing a spam action. A burst is purged, and each author
+//! in it who can't manage messages gets the action.
+
+use std::collections::BTreeMap;
+
+use tracing::warn;
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{GuildMarker, MessageMarker, UserMarker},
+    },
+};
+
+use crate::automod::{Violation, punish};
+use rusty_core::Context;
+use rusty_core::spam::{BurstMessage, SpamBurst, SpamLimits, SpamTrigger, TrackedMessage};
+use rusty_database::model::guild_settings::{GuildSettings, SpamLimit};
+use rusty_utils::permissions::{member_channel_permissions, resolve_message_author_permissions};
+use rusty_utils::time::format_duration_secs;
+
+/// Messages one member may send within the window.
+pub(crate) const DEFAULT_USER_RATE: SpamLimit = SpamLimit {
+    count: 5,
+    window_secs: 5,
+};
+/// Near-duplicate messages one member may send within the window.
+pub(crate) const DEFAULT_USER_DUPLICATES: SpamLimit = SpamLimit {
+    count: 3,
+    window_secs: 30,
+};
+/// Busy channels vary too much for a useful default, so this one starts off.
+pub(crate) const DEFAULT_CHANNEL_RATE: SpamLimit = SpamLimit {
+    count: 0,
+    window_secs: 10,
+};
+/// Near-duplicate messages a channel may receive from everyone within the window.
+pub(crate) const DEFAULT_CHANNEL_DUPLICATES: SpamLimit = SpamLimit {
+    count: 6,
+    window_secs: 20,
+};
+
+/// The limits a guild enforces, with defaults filled in and disabled ones removed.
+pub(crate) fn spam_limits(settings: &GuildSettings) -> SpamLimits {
+    let effective = |limit: Option<SpamLimit>, default| {
+        Some(limit.unwrap_or(default)).filter(|limit| limit.count > 0)
+    };
+
+    SpamLimits {
+        user_rate: effective(settings.spam_user_rate, DEFAULT_USER_RATE),
+        user_duplicates: effective(settings.spam_user_duplicates, DEFAULT_USER_DUPLICATES),
+        channel_rate: effective(settings.spam_channel_rate, DEFAULT_CHANNEL_RATE),
+        channel_duplicates: effective(settings.spam_channel_duplicates, DEFAULT_CHANNEL_DUPLICATES),
+    }
+}
+
+/// Track a guild message and act on the burst it completes, if any.
+///
+/// Returns `true` when the message itself was removed as part of a burst.
+pub(super) async fn check_spam(
+    ctx: &Context,
+    msg: &MessageCreate,
+    guild_id: Id<GuildMarker>,
+) -> anyhow::Result<bool> {
+    let settings = ctx.guild_settings(guild_id.get()).await?;
+    let Some(action) = settings.spam_action else {
+        return Ok(false);
+    };
+
+    let tracked = TrackedMessage {
+        guild_id: guild_id.get(),
+        channel_id: msg.channel_id.get(),
+        message_id: msg.id.get(),
+        author_id: msg.author.id.get(),
+        content: &msg.content,
+    };
+    let Some(burst) = ctx.spam.record(tracked, &spam_limits(&settings)) else {
+        return Ok(false);
+    };
+
+    let mut offenders: Vec<Id<UserMarker>> = Vec::new();
+    for message in &burst.messages {
+        let author_id = Id::new(message.author_id);
+        if !offenders.contains(&author_id) && !is_moderator(ctx, msg, guild_id, author_id).await {
+            offenders.push(author_id);
+        }
+    }
+    let messages: Vec<BurstMessage> = burst
+        .messages
+        .iter()
+        .filter(|message| offenders.contains(&Id::new(message.author_id)))
+        .copied()
+        .collect();
+    if messages.is_empty() {
+        return Ok(false);
+    }
+
+    let reason = describe_burst(&burst);
+    purge_burst(ctx, &messages, &reason).await;
+
+    let violation = Violation {
+        action,
+        duration_secs: None,
+        reason: &reason,
+        notice: "slow down, your recent messages were removed as spam.",
+    };
+    for offender in offenders {
+        if let Err(source) = punish(ctx, msg, guild_id, offender, &violation).await {
+            warn!(?source, user_id = offender.get(), "spam action failed");
+        }
+    }
+
+    Ok(messages
+        .iter()
+        .any(|message| message.message_id == msg.id.get()))
+}
+
+/// Members who can manage messages are never treated as spammers. Lookup
+/// failures count as not exempt, since the burst already broke a limit.
+async fn is_moderator(
+    ctx: &Context,
+    msg: &MessageCreate,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> bool {
+    let perms = if user_id == msg.author.id {
+        resolve_message_author_permissions(ctx, msg).await
+    } else {
+        member_channel_permissions(ctx, guild_id, msg.channel_id, user_id)
+            .await
+            .map(Some)
+    };
+
+    match perms {
+        Ok(perms) => perms.is_some_and(|perms| {
+            perms.intersects(Permissions::MANAGE_MESSAGES | Permissions::ADMINISTRATOR)
+        }),
+        Err(source) => {
+            warn!(
+                ?source,
+                user_id = user_id.get(),
+                "resolving spam exemption failed"
+            );
+            false
+        }
+    }
+}
+
+fn describe_burst(burst: &SpamBurst) -> String {
+    let SpamLimit { count, window_secs } = burst.limit;
+    let window = format_duration_secs(window_secs);
+    match burst.trigger {
+        SpamTrigger::UserRate => format!("Spam: more than {count} messages within {window}"),
+        SpamTrigger::UserDuplicates => {
+            format!("Spam: more than {count} near-duplicate messages within {window}")
+        }
+        SpamTrigger::ChannelRate => {
+            format!("Spam: channel flood of more than {count} messages within {window}")
+        }
+        SpamTrigger::ChannelDuplicates => format!(
+            "Spam: channel flood of more than {count} near-duplicate messages within {window}"
+        ),
+    }
+}
+
+/// Delete a burst, in bulk where a channel has several of its messages.
+///
+/// Bursts are seconds old, so they are always young enough for bulk deletion.
+async fn purge_burst(ctx: &Context, messages: &[BurstMessage], reason: &str) {
+    let mut by_channel: BTreeMap<u64, Vec<Id<MessageMarker>>> = BTreeMap::new();
+    for message in messages {
+        by_channel
+            .entry(message.channel_id)
+            .or_default()
+            .push(Id::new(message.message_id));
+    }
+
+    for (channel_id, message_ids) in by_channel {
+        let channel_id = Id::new(channel_id);
+        for chunk in message_ids.chunks(100) {
+            let result = match chunk {
+                [message_id] => ctx
+                    .http
+                    .delete_message(channel_id, *message_id)
+                    .reason(reason)
+                    .await
+                    .map(drop),
+                _ => ctx
+                    .http
+                    .delete_messages(channel_id, chunk)
+                    .reason(reason)
+                    .await
+                    .map(drop),
+            };
+            if let Err(source) = result {
+                warn!(
+                    ?source,
+                    channel_id = channel_id.get(),
+                    count = chunk.len(),
+                    "purging spam burst failed"
+                );
+            }
+        }
+    }
+}
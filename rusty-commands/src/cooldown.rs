@@ -0,0 +1,191 @@
+//! Command cooldowns declared on [`CommandMeta`].
+//!
+//! Uses are counted in Postgres so every process running shards shares the
+//! same limits. Members with administrator bypass cooldowns entirely.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::{debug, warn};
+use twilight_model::{
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+    },
+};
+
+use crate::CommandMeta;
+use rusty_core::Context;
+use rusty_database::impls::command_cooldowns::{prune_command_cooldowns, record_cooldown_use};
+use rusty_database::impls::warnings::now_unix_secs;
+use rusty_utils::permissions::resolve_message_author_permissions;
+use rusty_utils::time::format_duration_secs;
+
+/// Minimum time between sweeps of ended cooldown windows.
+const PRUNE_INTERVAL_SECS: u64 = 10 * 60;
+
+static LAST_PRUNE: AtomicU64 = AtomicU64::new(0);
+
+/// Who shares a cooldown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CooldownScope {
+    /// Every use of the command, everywhere.
+    Command,
+    /// Every use within a guild. Outside guilds this falls back to the channel.
+    Guild,
+    /// Every use within a channel.
+    Channel,
+    /// Every use by a single user.
+    User,
+}
+
+/// At most `uses` invocations per `per_secs` within a [`CooldownScope`].
+///
+/// The window is fixed rather than sliding: it starts at the first use and
+/// resets `per_secs` later, however the uses were spread within it. Refused
+/// uses are not counted.
+#[derive(Clone, Copy, Debug)]
+pub struct Cooldown {
+    pub scope: CooldownScope,
+    pub uses: u32,
+    pub per_secs: u64,
+}
+
+impl Cooldown {
+    pub const fn new(scope: CooldownScope, uses: u32, per_secs: u64) -> Self {
+        Self {
+            scope,
+            uses,
+            per_secs,
+        }
+    }
+
+    /// Human-readable limit, such as "3 uses per 15s per user".
+    pub fn describe(&self) -> String {
+        let scope = match self.scope {
+            CooldownScope::Command => "",
+            CooldownScope::Guild => " per server",
+            CooldownScope::Channel => " per channel",
+            CooldownScope::User => " per user",
+        };
+        let uses = if self.uses == 1 { "use" } else { "uses" };
+        format!(
+            "{} {uses} per {}{scope}",
+            self.uses,
+            format_duration_secs(self.per_secs)
+        )
+    }
+}
+
+/// Who is running a command, and where.
+struct CooldownCaller {
+    guild_id: Option<Id<GuildMarker>>,
+    channel_id: Id<ChannelMarker>,
+    user_id: Id<UserMarker>,
+}
+
+/// Record a message command use, returning the refusal reply when the
+/// author is on cooldown.
+pub(crate) async fn message_cooldown(
+    ctx: &Context,
+    meta: &CommandMeta,
+    msg: &MessageCreate,
+) -> Option<String> {
+    let cooldown = meta.cooldown?;
+
+    if msg.guild_id.is_some() {
+        match resolve_message_author_permissions(ctx, msg).await {
+            Ok(Some(perms)) if perms.contains(Permissions::ADMINISTRATOR) => return None,
+            Ok(_) => {}
+            Err(source) => warn!(?source, "resolving permissions for cooldown bypass failed"),
+        }
+    }
+
+    let caller = CooldownCaller {
+        guild_id: msg.guild_id,
+        channel_id: msg.channel_id,
+        user_id: msg.author.id,
+    };
+    check(ctx, meta.name, cooldown, &caller).await
+}
+
+/// Record an application command use, returning the refusal reply when the
+/// invoker is on cooldown.
+pub(crate) async fn interaction_cooldown(
+    ctx: &Context,
+    meta: &CommandMeta,
+    interaction: &InteractionCreate,
+) -> Option<String> {
+    let cooldown = meta.cooldown?;
+
+    let is_admin = interaction
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|perms| perms.contains(Permissions::ADMINISTRATOR));
+    if is_admin {
+        return None;
+    }
+
+    let caller = CooldownCaller {
+        guild_id: interaction.guild_id,
+        channel_id: interaction.channel.as_ref()?.id,
+        user_id: interaction.author_id()?,
+    };
+    check(ctx, meta.name, cooldown, &caller).await
+}
+
+/// Cooldowns fail open: a database error is logged and the command runs.
+async fn check(
+    ctx: &Context,
+    command: &str,
+    cooldown: Cooldown,
+    caller: &CooldownCaller,
+) -> Option<String> {
+    let bucket = match (cooldown.scope, caller.guild_id) {
+        (CooldownScope::Command, _) => command.to_owned(),
+        (CooldownScope::Guild, Some(guild_id)) => format!("{command}:guild:{guild_id}"),
+        (CooldownScope::Guild, None) | (CooldownScope::Channel, _) => {
+            format!("{command}:channel:{}", caller.channel_id)
+        }
+        (CooldownScope::User, _) => format!("{command}:user:{}", caller.user_id),
+    };
+
+    let now = now_unix_secs();
+    prune_if_due(ctx, now).await;
+
+    let window =
+        match record_cooldown_use(&ctx.db, &bucket, now, cooldown.uses, cooldown.per_secs).await {
+            Ok(window) => window,
+            Err(source) => {
+                warn!(?source, bucket, "recording cooldown use failed");
+                return None;
+            }
+        };
+    if window.allowed {
+        return None;
+    }
+
+    let remaining = window.ends_at.saturating_sub(now).max(1);
+    Some(format!(
+        "`{command}` is on cooldown. Try again in {}.",
+        format_duration_secs(remaining)
+    ))
+}
+
+async fn prune_if_due(ctx: &Context, now: u64) {
+    let last = LAST_PRUNE.load(Ordering::Relaxed);
+    if now < last + PRUNE_INTERVAL_SECS
+        || LAST_PRUNE
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+    {
+        return;
+    }
+
+    match prune_command_cooldowns(&ctx.db, now).await {
+        Ok(removed) => debug!(removed, "pruned ended cooldown windows"),
+        Err(source) => warn!(?source, "pruning cooldown windows failed"),
+    }
+}
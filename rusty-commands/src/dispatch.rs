@@ -0,0 +1,292 @@
+//! Runs gateway events on their own tasks so one failing or slow command
+//! cannot stall or crash the event loop, and drains them on shutdown.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::task::TaskTracker;
+use tracing::{error, warn};
+use twilight_http::Client;
+use twilight_model::{
+    application::interaction::InteractionType,
+    channel::message::MessageFlags,
+    gateway::payload::incoming::{InteractionCreate, MemberAdd, MessageCreate},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{
+        Id,
+        marker::{ApplicationMarker, ChannelMarker, GuildMarker, InteractionMarker},
+    },
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{handle_interaction, handle_member_add, handle_message};
+use rusty_core::Context;
+
+const COMMAND_FAILED_MESSAGE: &str =
+    "Something went wrong while running that command. The error has been logged.";
+const GUILD_BUSY_MESSAGE: &str =
+    "I'm still working on other commands in this server. Try again in a moment.";
+const SHUTTING_DOWN_MESSAGE: &str = "I'm restarting right now. Try again in a moment.";
+
+/// Spawns a task per event and optionally caps how many run at once per guild.
+#[derive(Clone)]
+pub struct Dispatcher {
+    ctx: Context,
+    guild_limit: Option<usize>,
+    guild_slots: Arc<GuildSlots>,
+    tasks: TaskTracker,
+}
+
+type GuildSlots = Mutex<HashMap<Id<GuildMarker>, Arc<Semaphore>>>;
+
+/// A held guild permit. Releasing the last one forgets the guild's semaphore
+/// so guilds the bot no longer hears from don't keep an entry.
+struct GuildSlot {
+    slots: Arc<GuildSlots>,
+    guild_id: Id<GuildMarker>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for GuildSlot {
+    fn drop(&mut self) {
+        drop(self.permit.take());
+
+        // Clones are only handed out under this lock, so nobody can pick the
+        // semaphore up between the check and the removal.
+        let mut slots = lock_slots(&self.slots);
+        if slots
+            .get(&self.guild_id)
+            .is_some_and(|semaphore| Arc::strong_count(semaphore) == 1)
+        {
+            slots.remove(&self.guild_id);
+        }
+    }
+}
+
+fn lock_slots(slots: &GuildSlots) -> MutexGuard<'_, HashMap<Id<GuildMarker>, Arc<Semaphore>>> {
+    slots
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Where to tell the user that their command failed.
+enum ErrorReply {
+    Channel(Id<ChannelMarker>),
+    Interaction {
+        application_id: Id<ApplicationMarker>,
+        interaction_id: Id<InteractionMarker>,
+        token: String,
+    },
+}
+
+impl Dispatcher {
+    /// Create a dispatcher; `guild_limit` of `None` leaves guilds unbounded.
+    pub fn new(ctx: Context, guild_limit: Option<usize>) -> Self {
+        Self {
+            ctx,
+            guild_limit: guild_limit.filter(|limit| *limit > 0),
+            guild_slots: Arc::default(),
+            tasks: TaskTracker::new(),
+        }
+    }
+
+    /// Stop accepting events and wait up to `timeout` for running handlers.
+    ///
+    /// Returns `false` if handlers were still running when the time ran out.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.tasks.close();
+        tokio::time::timeout(timeout, self.tasks.wait())
+            .await
+            .is_ok()
+    }
+
+    /// Handle a message on its own task.
+    ///
+    /// Automod and spam detection always run straight away; only commands over
+    /// the guild limit wait for a free slot. Messages arriving after
+    /// [`Dispatcher::drain`] started are dropped.
+    pub fn dispatch_message(&self, msg: Box<MessageCreate>) {
+        if self.tasks.is_closed() {
+            return;
+        }
+
+        let dispatcher = self.clone();
+        self.tasks.spawn(async move {
+            let reply = ErrorReply::Channel(msg.channel_id);
+            let ctx = dispatcher.ctx.clone();
+            let guild_id = msg.guild_id;
+            let slots = dispatcher.clone();
+            let wait_for_slot = move || async move { slots.wait_for_guild_slot(guild_id).await };
+            dispatcher
+                .run(
+                    "message",
+                    Some(reply),
+                    handle_message(ctx, msg, wait_for_slot),
+                )
+                .await;
+        });
+    }
+
+    /// Handle a member join on its own task.
+    ///
+    /// Joins are never held back by the guild limit, since raid detection
+    /// needs to see them as they happen. Failures are only logged.
+    pub fn dispatch_member_add(&self, member: Box<MemberAdd>) {
+        if self.tasks.is_closed() {
+            return;
+        }
+
+        let dispatcher = self.clone();
+        self.tasks.spawn(async move {
+            let ctx = dispatcher.ctx.clone();
+            dispatcher
+                .run("member add", None, handle_member_add(ctx, member))
+                .await;
+        });
+    }
+
+    /// Handle an interaction on its own task.
+    ///
+    /// Discord only waits three seconds for a response, so interactions over
+    /// the guild limit are turned away instead of queued. Autocomplete is
+    /// cheap and never limited. Once draining, interactions are answered with
+    /// a restart notice instead of being handled.
+    pub fn dispatch_interaction(&self, interaction: Box<InteractionCreate>) {
+        let reply = ErrorReply::Interaction {
+            application_id: interaction.application_id,
+            interaction_id: interaction.id,
+            token: interaction.token.clone(),
+        };
+
+        if self.tasks.is_closed() {
+            if interaction.kind != InteractionType::ApplicationCommandAutocomplete {
+                let http = Arc::clone(&self.ctx.http);
+                tokio::spawn(async move { send_reply(&http, &reply, SHUTTING_DOWN_MESSAGE).await });
+            }
+            return;
+        }
+
+        let dispatcher = self.clone();
+        self.tasks.spawn(async move {
+            let semaphore = if interaction.kind == InteractionType::ApplicationCommandAutocomplete {
+                None
+            } else {
+                dispatcher.guild_semaphore(interaction.guild_id)
+            };
+            let _slot = match semaphore
+                .map(|(guild_id, semaphore)| (guild_id, semaphore.try_acquire_owned()))
+            {
+                Some((guild_id, Ok(permit))) => Some(dispatcher.guild_slot(guild_id, permit)),
+                Some((_, Err(_))) => {
+                    warn!(guild_id = ?interaction.guild_id, "guild concurrency limit reached");
+                    send_reply(&dispatcher.ctx.http, &reply, GUILD_BUSY_MESSAGE).await;
+                    return;
+                }
+                None => None,
+            };
+
+            let ctx = dispatcher.ctx.clone();
+            dispatcher
+                .run(
+                    "interaction",
+                    Some(reply),
+                    handle_interaction(ctx, interaction),
+                )
+                .await;
+        });
+    }
+
+    fn guild_semaphore(
+        &self,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> Option<(Id<GuildMarker>, Arc<Semaphore>)> {
+        let (limit, guild_id) = (self.guild_limit?, guild_id?);
+        let mut slots = lock_slots(&self.guild_slots);
+
+        let semaphore = Arc::clone(
+            slots
+                .entry(guild_id)
+                .or_insert_with(|| Arc::new(Semaphore::new(limit))),
+        );
+        Some((guild_id, semaphore))
+    }
+
+    /// Wait for one of the guild's slots; `None` when guilds are unbounded.
+    async fn wait_for_guild_slot(&self, guild_id: Option<Id<GuildMarker>>) -> Option<GuildSlot> {
+        let (guild_id, semaphore) = self.guild_semaphore(guild_id)?;
+        let permit = semaphore.acquire_owned().await.ok()?;
+        Some(self.guild_slot(guild_id, permit))
+    }
+
+    fn guild_slot(&self, guild_id: Id<GuildMarker>, permit: OwnedSemaphorePermit) -> GuildSlot {
+        GuildSlot {
+            slots: Arc::clone(&self.guild_slots),
+            guild_id,
+            permit: Some(permit),
+        }
+    }
+
+    /// Run a handler, logging errors and panics and reporting them to `reply`.
+    async fn run<F>(&self, event: &'static str, reply: Option<ErrorReply>, handler: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        // The inner task turns a panicking handler into a `JoinError`.
+        match tokio::spawn(handler).await {
+            Ok(Ok(())) => return,
+            Ok(Err(source)) => error!(?source, event, "event handler failed"),
+            Err(source) => error!(?source, event, "event handler panicked"),
+        }
+
+        if let Some(reply) = reply {
+            send_reply(&self.ctx.http, &reply, COMMAND_FAILED_MESSAGE).await;
+        }
+    }
+}
+
+async fn send_reply(http: &Client, reply: &ErrorReply, content: &str) {
+    let result = match reply {
+        ErrorReply::Channel(channel_id) => http
+            .create_message(*channel_id)
+            .content(content)
+            .await
+            .map(drop),
+        ErrorReply::Interaction {
+            application_id,
+            interaction_id,
+            token,
+        } => {
+            let client = http.interaction(*application_id);
+            let response = InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .content(content)
+                        .flags(MessageFlags::EPHEMERAL)
+                        .build(),
+                ),
+            };
+
+            // The handler may have responded before failing; fall back to a follow-up.
+            match client
+                .create_response(*interaction_id, token, &response)
+                .await
+            {
+                Ok(_) => Ok(()),
+                Err(_) => client
+                    .create_followup(token)
+                    .content(content)
+                    .flags(MessageFlags::EPHEMERAL)
+                    .await
+                    .map(drop),
+            }
+        }
+    };
+
+    if let Err(source) = result {
+        warn!(?source, "failed to send error reply");
+    }
+}
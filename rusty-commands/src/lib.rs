@@ -1,40 +1,37 @@
+mod access;
+mod automod;
+pub mod cooldown;
+pub mod dispatch;
 pub mod moderation;
+pub mod registry;
 pub mod utility;
 
+use tracing::warn;
+use twilight_http::Client;
 use twilight_model::{
-    application::interaction::InteractionData,
-    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+    application::interaction::{InteractionData, InteractionType},
+    gateway::payload::incoming::{InteractionCreate, MemberAdd, MessageCreate},
+    id::{
+        Id,
+        marker::{ApplicationMarker, GuildMarker},
+    },
 };
 
+use crate::access::{Caller, CommandAccess, command_access, interaction_access};
+use crate::cooldown::{Cooldown, interaction_cooldown, message_cooldown};
 use rusty_core::Context;
-use rusty_utils::COMMAND_PREFIX;
-
-#[derive(Clone, Copy)]
-enum InteractionRoute {
-    PermissionsButtons,
-    HelpButtons,
-    PagetestButtons,
-    TerminateButtons,
-    PermissionsModal,
-    HelpModal,
-    PagetestModal,
-}
+use rusty_core::jobs::JobRegistry;
+use rusty_utils::DEFAULT_COMMAND_PREFIX;
+use rusty_utils::cleanup::run_delete_message;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::pagination::respond::{respond_ephemeral_message, run_component_cleanup};
+use rusty_utils::permissions::with_command_grant;
 
-fn route_interaction(custom_id: &str) -> Option<InteractionRoute> {
-    const ROUTES: [(&str, InteractionRoute); 7] = [
-        ("pg:permissions:", InteractionRoute::PermissionsButtons),
-        ("pg:help", InteractionRoute::HelpButtons),
-        ("pg:pagetest:", InteractionRoute::PagetestButtons),
-        ("terminate:", InteractionRoute::TerminateButtons),
-        ("pgm:permissions:", InteractionRoute::PermissionsModal),
-        ("pgm:help", InteractionRoute::HelpModal),
-        ("pgm:pagetest:", InteractionRoute::PagetestModal),
-    ];
-
-    ROUTES
-        .into_iter()
-        .find_map(|(prefix, route)| custom_id.starts_with(prefix).then_some(route))
-}
+pub use dispatch::Dispatcher;
+pub use registry::{
+    Command, CommandFuture, CommandInput, application_commands, command_metas, find_command,
+    find_component_owner, validate_registry,
+};
 
 // Global command meta data
 pub struct CommandMeta {
@@ -42,90 +39,171 @@ pub struct CommandMeta {
     pub desc: &'static str,
     pub category: &'static str,
     pub usage: &'static str,
+    /// Limit on how often the command may be used, if any.
+    pub cooldown: Option<Cooldown>,
 }
 
-pub const COMMANDS: &[CommandMeta] = &[
-    utility::ping::META,
-    utility::universe::META,
-    utility::help::META,
-    utility::usage::META,
-    utility::pagetest::META,
-    moderation::ban::META,
-    moderation::unban::META,
-    moderation::kick::META,
-    moderation::timeout::META,
-    moderation::untimeout::META,
-    moderation::warn::META,
-    moderation::warnings::META,
-    moderation::purge::META,
-    moderation::permissions::META,
-    moderation::terminate::META,
+/// Every registered command. Help, usage, dispatch and interaction routing
+/// are all derived from this list.
+pub static COMMANDS: &[&dyn Command] = &[
+    &utility::ping::PingCommand,
+    &utility::universe::UniverseCommand,
+    &utility::help::HelpCommand,
+    &utility::usage::UsageCommand,
+    &utility::pagetest::PagetestCommand,
+    &moderation::ban::BanCommand,
+    &moderation::tempban::TempbanCommand,
+    &moderation::unban::UnbanCommand,
+    &moderation::kick::KickCommand,
+    &moderation::timeout::TimeoutCommand,
+    &moderation::untimeout::UntimeoutCommand,
+    &moderation::warn::WarnCommand,
+    &moderation::warnings::WarningsCommand,
+    &moderation::delwarn::DelwarnCommand,
+    &moderation::editwarn::EditwarnCommand,
+    &moderation::clearwarns::ClearwarnsCommand,
+    &moderation::warnpolicy::WarnpolicyCommand,
+    &moderation::purge::PurgeCommand,
+    &moderation::permissions::PermissionsCommand,
+    &moderation::terminate::TerminateCommand,
+    &moderation::case::CaseCommand,
+    &moderation::cases::CasesCommand,
+    &moderation::reason::ReasonCommand,
+    &moderation::modlog::ModlogCommand,
+    &moderation::protectedroles::ProtectedrolesCommand,
+    &moderation::config::ConfigCommand,
+    &moderation::overrides::OverridesCommand,
+    &moderation::automod::AutomodCommand,
+    &moderation::lockdown::LockdownCommand,
+    &moderation::unlock::UnlockCommand,
+    &moderation::lock::LockCommand,
+    &moderation::slowmode::SlowmodeCommand,
+    &moderation::nuke_channel::NukeChannelCommand,
     // Add new commands here
 ];
 
-pub async fn handle_message(ctx: Context, msg: Box<MessageCreate>) -> anyhow::Result<()> {
+/// Handlers for every scheduled job kind the bot queues.
+pub fn job_registry() -> JobRegistry {
+    let mut registry = JobRegistry::new();
+    registry
+        .register(moderation::tempban::lift_tempban)
+        .register(moderation::lock::expire_channel_lock)
+        .register(run_component_cleanup)
+        .register(run_delete_message);
+    // Add new job handlers here
+    registry
+}
+
+/// Register all application commands globally, or for a single guild when given.
+///
+/// Guild registration applies instantly and is intended for development.
+pub async fn register_application_commands(
+    http: &Client,
+    application_id: Id<ApplicationMarker>,
+    guild_id: Option<Id<GuildMarker>>,
+) -> anyhow::Result<usize> {
+    let commands = application_commands();
+    let interaction_client = http.interaction(application_id);
+
+    match guild_id {
+        Some(guild_id) => {
+            interaction_client
+                .set_guild_commands(guild_id, &commands)
+                .await?;
+        }
+        None => {
+            interaction_client.set_global_commands(&commands).await?;
+        }
+    }
+
+    Ok(commands.len())
+}
+
+/// Moderate a message, then run the command it invokes, if any.
+///
+/// `wait_for_slot` is only awaited once a command is about to run, and what it
+/// returns is held until the command finishes.
+pub async fn handle_message<F, Fut, Slot>(
+    ctx: Context,
+    msg: Box<MessageCreate>,
+    wait_for_slot: F,
+) -> anyhow::Result<()>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Slot>,
+{
     if msg.author.bot {
         return Ok(());
     }
 
-    let content_owned = msg.content.clone();
-    let content = content_owned.trim();
-
-    if !content.starts_with(COMMAND_PREFIX) {
+    if msg.guild_id.is_some() && automod::moderate_message(&ctx, &msg).await {
         return Ok(());
     }
 
-    let content = content.trim_start_matches(COMMAND_PREFIX).trim();
+    let prefix = match msg.guild_id {
+        Some(guild_id) => match ctx.guild_settings(guild_id.get()).await {
+            Ok(settings) => settings.prefix,
+            Err(source) => {
+                warn!(?source, %guild_id, "loading guild prefix failed");
+                None
+            }
+        },
+        None => None,
+    };
+    let prefix = prefix.as_deref().unwrap_or(DEFAULT_COMMAND_PREFIX);
+
+    let content_owned = msg.content.clone();
+    let Some(content) = content_owned.trim().strip_prefix(prefix) else {
+        return Ok(());
+    };
+    let content = content.trim();
     let mut command_and_rest = content.splitn(2, char::is_whitespace);
     let cmd = command_and_rest.next().unwrap_or("").to_ascii_lowercase();
-    let rest = command_and_rest
-        .next()
-        .map(str::trim)
-        .filter(|value| !value.is_empty());
-
-    let (arg1, arg_tail): (Option<String>, Option<String>) = match rest {
-        Some(value) => {
-            let mut args = value.splitn(2, char::is_whitespace);
-            let first = args
-                .next()
-                .filter(|arg| !arg.is_empty())
-                .map(ToOwned::to_owned);
-            let tail = args
-                .next()
-                .map(str::trim)
-                .filter(|remaining| !remaining.is_empty())
-                .map(ToOwned::to_owned);
-
-            (first, tail)
-        }
-        None => (None, None),
+    let args = command_and_rest.next().unwrap_or("").trim().to_owned();
+
+    let Some(command) = find_command(&cmd) else {
+        return Ok(());
     };
 
-    let arg1 = arg1.as_deref();
-    let arg_tail = arg_tail.as_deref();
-
-    match cmd.as_str() {
-        "ping" => utility::ping::run(ctx.clone(), msg).await?,
-        "universe" => utility::universe::run(ctx.clone(), msg).await?,
-        "help" => utility::help::run(ctx.clone(), msg, arg1).await?,
-        "usage" => utility::usage::run(ctx.clone(), msg, arg1).await?,
-        "pagetest" => utility::pagetest::run(ctx.clone(), msg, arg1).await?,
-
-        "ban" => moderation::ban::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        "unban" => moderation::unban::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        "kick" => moderation::kick::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        "timeout" => moderation::timeout::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        "untimeout" => moderation::untimeout::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        "warn" => moderation::warn::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        "warnings" => moderation::warnings::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        "permissions" => moderation::permissions::run(ctx.clone(), msg, arg1).await?,
-        "purge" => moderation::purge::run(ctx.clone(), msg, arg1).await?,
-        "terminate" => moderation::terminate::run(ctx.clone(), msg, arg1, arg_tail).await?,
-        // Add new commands here
-        _ => {}
+    let access = match msg.guild_id {
+        Some(guild_id) => {
+            let roles = msg
+                .member
+                .as_ref()
+                .map_or(&[][..], |member| member.roles.as_slice());
+            let caller = Caller {
+                guild_id,
+                channel_id: msg.channel_id,
+                user_id: msg.author.id,
+                roles,
+            };
+            command_access(&ctx, command.meta().name, caller).await?
+        }
+        None => CommandAccess::Default,
+    };
+    let refusal = match access.refusal() {
+        Some(refusal) => Some(refusal.to_owned()),
+        None => message_cooldown(&ctx, command.meta(), &msg).await,
+    };
+    if let Some(refusal) = refusal {
+        ctx.http
+            .create_message(msg.channel_id)
+            .content(&refusal)
+            .await?;
+        return Ok(());
     }
 
-    Ok(())
+    let _slot = wait_for_slot().await;
+    with_command_grant(
+        access.is_granted(),
+        command.run(ctx, CommandInput::Message { msg, args }),
+    )
+    .await
+}
+
+/// Watch member joins for raids. Only sent with the `GUILD_MEMBERS` intent.
+pub async fn handle_member_add(ctx: Context, member: Box<MemberAdd>) -> anyhow::Result<()> {
+    automod::raid::watch_join(&ctx, &member).await
 }
 
 pub async fn handle_interaction(
@@ -133,51 +211,65 @@ pub async fn handle_interaction(
     interaction: Box<InteractionCreate>,
 ) -> anyhow::Result<()> {
     let custom_id = match interaction.data.as_ref() {
+        Some(InteractionData::ApplicationCommand(_)) => {
+            return handle_application_command(ctx, interaction).await;
+        }
         Some(InteractionData::MessageComponent(data)) => data.custom_id.clone(),
         Some(InteractionData::ModalSubmit(data)) => data.custom_id.clone(),
         _ => return Ok(()),
     };
 
-    let Some(route) = route_interaction(&custom_id) else {
+    let Some(command) = find_component_owner(&custom_id) else {
         return Ok(());
     };
 
-    match route {
-        InteractionRoute::PermissionsButtons => {
-            let _handled =
-                moderation::permissions::handle_pagination_interaction(ctx.clone(), interaction)
-                    .await?;
-        }
-        InteractionRoute::HelpButtons => {
-            let _handled =
-                utility::help::handle_pagination_interaction(ctx.clone(), interaction).await?;
-        }
-        InteractionRoute::PagetestButtons => {
-            let _handled =
-                utility::pagetest::handle_pagination_interaction(ctx.clone(), interaction).await?;
-        }
-        InteractionRoute::TerminateButtons => {
-            let _handled =
-                moderation::terminate::handle_interaction(ctx.clone(), interaction).await?;
-        }
-        InteractionRoute::PermissionsModal => {
-            let _handled = moderation::permissions::handle_pagination_modal_interaction(
-                ctx.clone(),
-                interaction,
-            )
-            .await?;
-        }
-        InteractionRoute::HelpModal => {
-            let _handled =
-                utility::help::handle_pagination_modal_interaction(ctx.clone(), interaction)
-                    .await?;
-        }
-        InteractionRoute::PagetestModal => {
-            let _handled =
-                utility::pagetest::handle_pagination_modal_interaction(ctx.clone(), interaction)
-                    .await?;
-        }
+    let access = interaction_access(&ctx, command.meta().name, &interaction).await?;
+    if let Some(refusal) = access.refusal() {
+        respond_ephemeral_message(&ctx.http, &interaction, refusal).await?;
+        return Ok(());
     }
 
+    let _handled = with_command_grant(
+        access.is_granted(),
+        command.handle_component(ctx, interaction),
+    )
+    .await?;
+
     Ok(())
 }
+
+async fn handle_application_command(
+    ctx: Context,
+    mut interaction: Box<InteractionCreate>,
+) -> anyhow::Result<()> {
+    let Some(InteractionData::ApplicationCommand(data)) = interaction.data.take() else {
+        return Ok(());
+    };
+
+    let Some(command) = find_command(&data.name) else {
+        return Ok(());
+    };
+
+    if interaction.kind == InteractionType::ApplicationCommandAutocomplete {
+        return command.autocomplete(ctx, interaction, data).await;
+    }
+
+    let access = interaction_access(&ctx, command.meta().name, &interaction).await?;
+    let refusal = match access.refusal() {
+        Some(refusal) => Some(refusal.to_owned()),
+        None => interaction_cooldown(&ctx, command.meta(), &interaction).await,
+    };
+    let Some(invocation) = Invocation::from_interaction(interaction) else {
+        return Ok(());
+    };
+    if let Some(refusal) = refusal {
+        invocation.reply_ephemeral(&ctx.http, &refusal).await?;
+        return Ok(());
+    }
+
+    with_command_grant(
+        access.is_granted(),
+        command.run(ctx, CommandInput::Slash { invocation, data }),
+    )
+    .await
+}
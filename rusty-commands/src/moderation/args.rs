@@ -0,0 +1,37 @@
+use twilight_model::{
+    application::interaction::application_command::CommandData,
+    id::{Id, marker::UserMarker},
+};
+
+use rusty_utils::options::{option_string, option_user};
+use rusty_utils::parse::{ArgError, ArgParser};
+
+/// A target user followed by an optional free-text reason.
+pub struct TargetReasonArgs {
+    pub target_user_id: Id<UserMarker>,
+    pub reason: Option<String>,
+}
+
+impl TargetReasonArgs {
+    /// Parse `<user> [reason]` message command arguments.
+    pub fn parse(raw: &str, usage: &'static str) -> Result<Self, ArgError> {
+        let mut args = ArgParser::new(raw, usage);
+        let target_user_id = args.required("user")?;
+
+        Ok(Self {
+            target_user_id,
+            reason: args.rest(),
+        })
+    }
+
+    /// Read the `user` and `reason` options of an application command.
+    pub fn from_options(data: &CommandData, usage: &'static str) -> Result<Self, ArgError> {
+        let target_user_id =
+            option_user(data, "user").ok_or_else(|| ArgError::missing(usage, "user"))?;
+
+        Ok(Self {
+            target_user_id,
+            reason: option_string(data, "reason").map(ToOwned::to_owned),
+        })
+    }
+}
@@ -0,0 +1,584 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::{Permissions, Role},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, RoleMarker},
+    },
+};
+use twilight_util::builder::command::{
+    ChannelBuilder, CommandBuilder, IntegerBuilder, RoleBuilder, StringBuilder,
+};
+
+use crate::automod::filters::default_threshold;
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::escalation::MAX_TIMEOUT_SECS;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_core::automod::compile_patterns;
+use rusty_database::impls::automod::{
+    add_automod_rule, automod_rules, remove_automod_rule, set_automod_exemption,
+};
+use rusty_database::model::automod::{
+    AutomodAction, AutomodExemption, AutomodKind, AutomodRule, NewAutomodRule,
+};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_channel, option_integer, option_role, option_string};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg, ParsedDuration};
+use rusty_utils::permissions::{guild_channel, guild_roles, has_invocation_permission};
+use rusty_utils::time::format_duration_secs;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "automod",
+    desc: "Configure rules that automatically remove unwanted messages.",
+    category: "moderation",
+    usage: "!automod [list|add <kind> <action> [--duration <time>] [value]|remove <id>|exempt <id> <role|channel>|unexempt <id> <role|channel>]",
+    cooldown: None,
+};
+
+/// Keeps the overview inside Discord's message length limit.
+const MAX_LIST_LEN: usize = 1800;
+/// Rules a single guild may configure; every one is checked on every message.
+const MAX_RULES: usize = 25;
+/// Longest pattern shown in the overview before it is cut short.
+const MAX_PATTERN_PREVIEW: usize = 40;
+
+pub struct AutomodCommand;
+
+impl Command for AutomodCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/automod`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .contexts([InteractionContextType::Guild])
+        .option(StringBuilder::new("action", "What to do.").choices([
+            ("List", "list"),
+            ("Add", "add"),
+            ("Remove", "remove"),
+            ("Exempt", "exempt"),
+            ("Unexempt", "unexempt"),
+        ]))
+        .option(
+            StringBuilder::new("kind", "What the new rule looks for.").choices([
+                ("Banned words", "words"),
+                ("Regular expressions", "regex"),
+                ("Invite links", "invites"),
+                ("Links outside an allowlist", "link-allowlist"),
+                ("Links on a denylist", "link-denylist"),
+                ("Excessive caps", "caps"),
+                ("Mass mentions", "mentions"),
+                ("Emoji spam", "emoji"),
+                ("Zalgo text", "zalgo"),
+            ]),
+        )
+        .option(
+            StringBuilder::new("punishment", "What happens to the author.").choices([
+                ("Delete", "delete"),
+                ("Warn", "warn"),
+                ("Timeout", "timeout"),
+                ("Ban", "ban"),
+            ]),
+        )
+        .option(StringBuilder::new(
+            "value",
+            "Words, a regex, domains, or a threshold, depending on the kind.",
+        ))
+        .option(StringBuilder::new(
+            "duration",
+            "Timeout length, or ban length for a temporary ban.",
+        ))
+        .option(IntegerBuilder::new("rule", "ID of the rule to change.").min_value(1))
+        .option(RoleBuilder::new("role", "Role to exempt."))
+        .option(ChannelBuilder::new("channel", "Channel to exempt."))
+        .build()
+}
+
+/// List or change automod rules.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// List or change automod rules from the `/automod` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct KindArg(AutomodKind);
+
+impl FromArg for KindArg {
+    const EXPECTED: &'static str = "`words`, `regex`, `invites`, `link-allowlist`, \
+         `link-denylist`, `caps`, `mentions`, `emoji` or `zalgo`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        raw.trim().to_ascii_lowercase().parse().ok().map(Self)
+    }
+}
+
+struct ActionArg(AutomodAction);
+
+impl FromArg for ActionArg {
+    const EXPECTED: &'static str = "`delete`, `warn`, `timeout` or `ban`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        raw.trim().to_ascii_lowercase().parse().ok().map(Self)
+    }
+}
+
+/// The role or channel a rule should ignore, before bare IDs are looked up.
+#[derive(Clone, Copy)]
+enum ExemptionArg {
+    Role(Id<RoleMarker>),
+    Channel(Id<ChannelMarker>),
+    /// A bare ID that may name a role or a channel.
+    Bare(u64),
+}
+
+impl FromArg for ExemptionArg {
+    const EXPECTED: &'static str = "a role or channel mention or ID";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.starts_with("<@&") {
+            Id::from_arg(raw).map(Self::Role)
+        } else if raw.starts_with("<#") {
+            Id::from_arg(raw).map(Self::Channel)
+        } else {
+            Id::<RoleMarker>::from_arg(raw).map(|id| Self::Bare(id.get()))
+        }
+    }
+}
+
+enum AutomodArgs {
+    List,
+    Add {
+        kind: AutomodKind,
+        action: AutomodAction,
+        duration: Option<ParsedDuration>,
+        value: Option<String>,
+    },
+    Remove {
+        id: u64,
+    },
+    Exempt {
+        id: u64,
+        target: ExemptionArg,
+        exempt: bool,
+    },
+}
+
+fn parse_args(raw: &str) -> Result<AutomodArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+
+    let exempt = if args.keyword("exempt") {
+        Some(true)
+    } else if args.keyword("unexempt") {
+        Some(false)
+    } else {
+        None
+    };
+
+    let parsed = if let Some(exempt) = exempt {
+        AutomodArgs::Exempt {
+            id: args.integer("id", 1..=i64::MAX)?.unsigned_abs(),
+            target: args.required("role|channel")?,
+            exempt,
+        }
+    } else if args.keyword("add") {
+        let duration = args.flag("duration")?;
+        let KindArg(kind) = args.required("kind")?;
+        let ActionArg(action) = args.required("action")?;
+        AutomodArgs::Add {
+            kind,
+            action,
+            duration,
+            value: args.rest(),
+        }
+    } else if args.keyword("remove") {
+        AutomodArgs::Remove {
+            id: args.integer("id", 1..=i64::MAX)?.unsigned_abs(),
+        }
+    } else {
+        args.keyword("list");
+        AutomodArgs::List
+    };
+    args.finish()?;
+
+    Ok(parsed)
+}
+
+fn slash_args(data: &CommandData) -> Result<AutomodArgs, ArgError> {
+    let required_id = || {
+        option_integer(data, "rule")
+            .map(i64::unsigned_abs)
+            .ok_or_else(|| ArgError::missing(META.usage, "rule"))
+    };
+    let required_target = || {
+        option_role(data, "role")
+            .map(ExemptionArg::Role)
+            .or_else(|| option_channel(data, "channel").map(ExemptionArg::Channel))
+            .ok_or_else(|| ArgError::missing(META.usage, "role|channel"))
+    };
+
+    Ok(match option_string(data, "action").unwrap_or("list") {
+        "add" => {
+            let KindArg(kind) = required_option(data, "kind")?;
+            let ActionArg(action) = required_option(data, "punishment")?;
+            let duration = option_string(data, "duration")
+                .map(|raw| {
+                    ParsedDuration::from_arg(raw).ok_or_else(|| {
+                        ArgError::invalid::<ParsedDuration>(META.usage, "duration", raw)
+                    })
+                })
+                .transpose()?;
+            AutomodArgs::Add {
+                kind,
+                action,
+                duration,
+                value: option_string(data, "value").map(str::to_owned),
+            }
+        }
+        "remove" => AutomodArgs::Remove { id: required_id()? },
+        action @ ("exempt" | "unexempt") => AutomodArgs::Exempt {
+            id: required_id()?,
+            target: required_target()?,
+            exempt: action == "exempt",
+        },
+        _ => AutomodArgs::List,
+    })
+}
+
+fn required_option<T: FromArg>(data: &CommandData, name: &str) -> Result<T, ArgError> {
+    let raw = option_string(data, name).ok_or_else(|| ArgError::missing(META.usage, name))?;
+    T::from_arg(raw).ok_or_else(|| ArgError::invalid::<T>(META.usage, name, raw))
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<AutomodArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_GUILD).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let args = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let guild = guild_id.get();
+    let roles = guild_roles(&ctx, guild_id).await?;
+
+    let reply = match args {
+        AutomodArgs::List => rules_overview(&roles, &automod_rules(&ctx.db, guild).await?),
+        AutomodArgs::Add {
+            kind,
+            action,
+            duration,
+            value,
+        } => {
+            let problem = match rule_value(kind, value.as_deref()) {
+                Ok(_) if automod_rules(&ctx.db, guild).await?.len() >= MAX_RULES => Err(format!(
+                    "This server already has {MAX_RULES} automod rules. Remove one first."
+                )),
+                Ok(value) => duration_problem(action, duration.as_ref())
+                    .map_or(Ok(value), |problem| Err(problem.to_owned())),
+                Err(problem) => Err(problem),
+            };
+            let (patterns, threshold) = match problem {
+                Ok(value) => value,
+                Err(problem) => {
+                    invocation.reply_content(http, &problem).await?;
+                    return Ok(());
+                }
+            };
+
+            let rule = add_automod_rule(
+                &ctx.db,
+                NewAutomodRule {
+                    guild_id: guild,
+                    kind,
+                    patterns,
+                    threshold,
+                    action,
+                    duration_secs: duration.map(|duration| duration.secs),
+                    created_by: invocation.author_id().get(),
+                },
+            )
+            .await?;
+            ctx.automod.invalidate(guild);
+            format!("Added automod rule {}", describe_rule(&roles, &rule))
+        }
+        AutomodArgs::Remove { id } => {
+            if remove_automod_rule(&ctx.db, guild, id).await? {
+                ctx.automod.invalidate(guild);
+                format!("Removed automod rule #{id}.")
+            } else {
+                format!("No automod rule #{id} exists in this server.")
+            }
+        }
+        AutomodArgs::Exempt { id, target, exempt } => {
+            let Some(exemption) = resolve_exemption(&ctx, guild_id, &roles, target).await else {
+                invocation
+                    .reply_content(http, "That isn't a role or channel in this server.")
+                    .await?;
+                return Ok(());
+            };
+
+            match set_automod_exemption(&ctx.db, guild, id, exemption, exempt).await? {
+                Some(rule) => {
+                    ctx.automod.invalidate(guild);
+                    let verb = if exempt {
+                        "now ignores"
+                    } else {
+                        "no longer ignores"
+                    };
+                    format!(
+                        "Automod rule #{} {verb} {}.",
+                        rule.id,
+                        describe_exemption(&roles, exemption)
+                    )
+                }
+                None => format!("No automod rule #{id} exists in this server."),
+            }
+        }
+    };
+
+    invocation.reply_content(http, &reply).await?;
+
+    Ok(())
+}
+
+/// Turn the free-text value of `add` into the patterns and threshold to store,
+/// or explain what is wrong with it.
+fn rule_value(
+    kind: AutomodKind,
+    value: Option<&str>,
+) -> Result<(Vec<String>, Option<u32>), String> {
+    let value = value.map(str::trim).filter(|value| !value.is_empty());
+
+    match kind {
+        AutomodKind::Words => {
+            let words: Vec<String> = value
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(str::to_owned)
+                .collect();
+            if words.is_empty() {
+                return Err("Give the words to ban, separated by commas.".to_owned());
+            }
+            Ok((words, None))
+        }
+        AutomodKind::Regex => {
+            let Some(pattern) = value else {
+                return Err("Give the regular expression to match.".to_owned());
+            };
+            let patterns = vec![pattern.to_owned()];
+            compile_patterns(kind, &patterns)
+                .map_err(|error| format!("That regular expression is invalid: {error}"))?;
+            Ok((patterns, None))
+        }
+        AutomodKind::LinkAllowlist | AutomodKind::LinkDenylist => {
+            let domains: Vec<String> = value
+                .unwrap_or_default()
+                .split([',', ' '])
+                .filter_map(normalize_domain)
+                .collect();
+            // An empty allowlist is valid and blocks every link
+            if domains.is_empty() && kind == AutomodKind::LinkDenylist {
+                return Err("Give the domains to block, such as `example.com`.".to_owned());
+            }
+            Ok((domains, None))
+        }
+        AutomodKind::Invites => match value {
+            Some(_) => Err("Invite rules don't take a value.".to_owned()),
+            None => Ok((Vec::new(), None)),
+        },
+        AutomodKind::Caps | AutomodKind::Mentions | AutomodKind::Emoji | AutomodKind::Zalgo => {
+            let max = if kind == AutomodKind::Caps { 100 } else { 1000 };
+            let Some(raw) = value else {
+                return Ok((Vec::new(), None));
+            };
+            match raw.parse::<u32>() {
+                Ok(threshold) if (1..=max).contains(&threshold) => {
+                    Ok((Vec::new(), Some(threshold)))
+                }
+                _ => Err(format!(
+                    "The {kind} threshold must be a whole number from 1 to {max}."
+                )),
+            }
+        }
+    }
+}
+
+/// Reduce `https://www.Example.com/path` style input to `www.example.com`.
+fn normalize_domain(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let raw = raw
+        .split_once("://")
+        .map_or(raw, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()?
+        .trim_matches('.');
+    (!raw.is_empty()).then(|| raw.to_ascii_lowercase())
+}
+
+fn duration_problem(
+    action: AutomodAction,
+    duration: Option<&ParsedDuration>,
+) -> Option<&'static str> {
+    match (action, duration) {
+        (AutomodAction::Timeout, Some(duration)) if duration.secs > MAX_TIMEOUT_SECS => {
+            Some("Timeouts can last at most 28 days.")
+        }
+        (AutomodAction::Delete | AutomodAction::Warn, Some(_)) => {
+            Some("Only timeout and ban rules take a duration.")
+        }
+        _ => None,
+    }
+}
+
+/// Look up what a bare ID refers to: a role, then a channel of this guild.
+async fn resolve_exemption(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    roles: &[Role],
+    target: ExemptionArg,
+) -> Option<AutomodExemption> {
+    match target {
+        ExemptionArg::Role(role_id) => Some(AutomodExemption::Role(role_id.get())),
+        ExemptionArg::Channel(channel_id) => Some(AutomodExemption::Channel(channel_id.get())),
+        ExemptionArg::Bare(id) => {
+            if roles.iter().any(|role| role.id.get() == id) {
+                return Some(AutomodExemption::Role(id));
+            }
+            let is_channel = guild_channel(ctx, Id::new(id))
+                .await
+                .is_ok_and(|channel| channel.guild_id == Some(guild_id));
+            is_channel.then_some(AutomodExemption::Channel(id))
+        }
+    }
+}
+
+fn describe_exemption(roles: &[Role], exemption: AutomodExemption) -> String {
+    match exemption {
+        AutomodExemption::Role(role_id) => roles
+            .iter()
+            .find(|role| role.id.get() == role_id)
+            .map_or_else(
+                || format!("deleted role ({role_id})"),
+                |role| format!("**{}**", role.name.replace('@', "@\u{200B}")),
+            ),
+        AutomodExemption::Channel(channel_id) => format!("<#{channel_id}>"),
+    }
+}
+
+fn describe_rule(roles: &[Role], rule: &AutomodRule) -> String {
+    let mut condition = rule.kind.to_string();
+    if !rule.patterns.is_empty() {
+        let patterns: Vec<String> = rule
+            .patterns
+            .iter()
+            .map(|pattern| {
+                let preview: String = pattern.chars().take(MAX_PATTERN_PREVIEW).collect();
+                let cut = if preview.len() < pattern.len() {
+                    "…"
+                } else {
+                    ""
+                };
+                format!("`{}{cut}`", preview.replace('`', "'"))
+            })
+            .collect();
+        condition.push_str(&format!(" {}", patterns.join(", ")));
+    }
+    if let Some(threshold) = rule.threshold.or_else(|| default_threshold(rule.kind)) {
+        let unit = if rule.kind == AutomodKind::Caps {
+            "%"
+        } else {
+            ""
+        };
+        condition.push_str(&format!(" (limit {threshold}{unit})"));
+    }
+
+    let punishment = match (rule.action, rule.duration_secs) {
+        (AutomodAction::Timeout, Some(secs)) => {
+            format!("timeout for {}", format_duration_secs(secs))
+        }
+        (AutomodAction::Ban, Some(secs)) => format!("ban for {}", format_duration_secs(secs)),
+        (action, _) => action.to_string(),
+    };
+
+    let mut line = format!("#{}: {condition} → {punishment}", rule.id);
+    let exemptions: Vec<String> = rule
+        .exempt_role_ids
+        .iter()
+        .map(|&id| describe_exemption(roles, AutomodExemption::Role(id)))
+        .chain(
+            rule.exempt_channel_ids
+                .iter()
+                .map(|&id| describe_exemption(roles, AutomodExemption::Channel(id))),
+        )
+        .collect();
+    if !exemptions.is_empty() {
+        line.push_str(&format!(", except {}", exemptions.join(", ")));
+    }
+    line
+}
+
+fn rules_overview(roles: &[Role], rules: &[AutomodRule]) -> String {
+    if rules.is_empty() {
+        return "No automod rules are configured.".to_owned();
+    }
+
+    let mut overview = "Automod rules:".to_owned();
+    for (shown, rule) in rules.iter().enumerate() {
+        let line = format!("\n{}", describe_rule(roles, rule));
+        if overview.len() + line.len() > MAX_LIST_LEN {
+            overview.push_str(&format!("\n…and {} more.", rules.len() - shown));
+            break;
+        }
+        overview.push_str(&line);
+    }
+    overview
+}
@@ -1,80 +1,245 @@
 use tracing::error;
 use twilight_http::request::AuditLogReason as _;
-use twilight_model::{gateway::payload::incoming::MessageCreate, guild::Permissions};
-
-use crate::CommandMeta;
-use crate::moderation::embeds::{
-    fetch_target_profile, guild_only_message, moderation_action_embed, permission_denied_message,
-    usage_message,
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::UserMarker},
 };
+use twilight_util::builder::command::{CommandBuilder, StringBuilder, UserBuilder};
+
+use crate::moderation::case_log::{ActionReport, report_action};
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::targets::target_refusal;
+use crate::moderation::tempban::{clear_tempban, store_tempban};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
-use rusty_utils::parse::parse_target_user_id;
-use rusty_utils::permissions::has_message_permission;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_string, option_user};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg, ParsedDuration, UnitDuration};
+use rusty_utils::permissions::has_invocation_permission;
+use rusty_utils::time::now_unix_secs;
 
 pub const META: CommandMeta = CommandMeta {
     name: "ban",
-    desc: "Ban a user from the server.",
+    desc: "Ban a user from the server, optionally for a limited time.",
     category: "moderation",
-    usage: "!ban <user> [reason]",
+    usage: "!ban <user> [duration] [reason]",
+    cooldown: None,
 };
 
+pub struct BanCommand;
+
+impl Command for BanCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/ban`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::BAN_MEMBERS)
+        .contexts([InteractionContextType::Guild])
+        .option(UserBuilder::new("user", "User to ban.").required(true))
+        .option(StringBuilder::new(
+            "duration",
+            "Lift the ban automatically after this long, such as 12h or 7d.",
+        ))
+        .option(StringBuilder::new("reason", "Reason for the ban."))
+        .build()
+}
+
 /// Ban a target user after permission and input validation.
-pub async fn run(
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Ban a target user from the `/ban` application command.
+pub async fn run_slash(
     ctx: Context,
-    msg: Box<MessageCreate>,
-    arg1: Option<&str>,
-    arg_tail: Option<&str>,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, BanArgs::from_options(data, META.usage)).await
+}
+
+/// A ban target with an optional expiry and reason.
+pub(crate) struct BanArgs {
+    pub target_user_id: Id<UserMarker>,
+    pub duration: Option<ParsedDuration>,
+    pub reason: Option<String>,
+}
+
+impl BanArgs {
+    /// Read the `user`, `duration` and `reason` options of a ban command.
+    pub fn from_options(data: &CommandData, usage: &'static str) -> Result<Self, ArgError> {
+        let target_user_id =
+            option_user(data, "user").ok_or_else(|| ArgError::missing(usage, "user"))?;
+        let duration = option_string(data, "duration")
+            .map(|raw| {
+                UnitDuration::from_arg(raw)
+                    .map(|UnitDuration(duration)| duration)
+                    .ok_or_else(|| ArgError::invalid::<UnitDuration>(usage, "duration", raw))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            target_user_id,
+            duration,
+            reason: option_string(data, "reason").map(ToOwned::to_owned),
+        })
+    }
+}
+
+fn parse_args(raw: &str) -> Result<BanArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let target_user_id = args.required("user")?;
+    // Only a token with a unit is a duration; `!ban @u 3 strikes` stays permanent
+    let duration = args
+        .optional::<UnitDuration>()
+        .map(|UnitDuration(duration)| duration);
+
+    Ok(BanArgs {
+        target_user_id,
+        duration,
+        reason: args.rest(),
+    })
+}
+
+/// Apply a permanent or temporary ban; shared with `!tempban`.
+pub(crate) async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<BanArgs, ArgError>,
 ) -> anyhow::Result<()> {
     let http = &ctx.http;
-    let Some(guild_id) = msg.guild_id else {
-        http.create_message(msg.channel_id)
-            .content(guild_only_message())
-            .await?;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
         return Ok(());
     };
 
-    if !has_message_permission(http, &msg, Permissions::BAN_MEMBERS).await? {
-        http.create_message(msg.channel_id)
-            .content(permission_denied_message())
+    if !has_invocation_permission(&ctx, invocation, Permissions::BAN_MEMBERS).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
             .await?;
         return Ok(());
     }
 
-    let Some(raw_target) = arg1 else {
-        let usage = usage_message(META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let BanArgs {
+        target_user_id,
+        duration,
+        reason,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
     };
+    let reason = reason.as_deref();
 
-    let Some(target_user_id) = parse_target_user_id(raw_target) else {
-        let usage = usage_message(META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
-    };
-
-    if target_user_id == msg.author.id {
-        http.create_message(msg.channel_id)
-            .content("You can't ban yourself.")
+    if let Some(refusal) =
+        target_refusal(&ctx, guild_id, invocation.author_id(), target_user_id).await?
+    {
+        invocation
+            .reply_content(http, &refusal.message("ban"))
             .await?;
         return Ok(());
     }
 
     let mut request = http.create_ban(guild_id, target_user_id);
-    if let Some(reason) = arg_tail {
+    if let Some(reason) = reason {
         request = request.reason(reason);
     }
 
     if let Err(source) = request.await {
         error!(?source, "ban request failed");
-        http.create_message(msg.channel_id)
-            .content("I couldn't ban that user. Check role hierarchy and permissions.")
+        invocation
+            .reply_content(
+                http,
+                "I couldn't ban that user. Check role hierarchy and permissions.",
+            )
             .await?;
         return Ok(());
     }
 
-    let target_profile = fetch_target_profile(http, target_user_id).await;
-    let embed = moderation_action_embed(&target_profile, target_user_id, "banned", arg_tail, None)?;
-    http.create_message(msg.channel_id).embeds(&[embed]).await?;
+    // A new ban always replaces any pending expiry, making permanent bans stick.
+    // The ban itself already happened, so a failed write is reported, not raised.
+    let requested_expiry = duration
+        .as_ref()
+        .map(|duration| now_unix_secs().saturating_add(duration.secs));
+    let expiry_warning = match requested_expiry {
+        Some(expires_at) => store_tempban(
+            &ctx,
+            guild_id.get(),
+            target_user_id.get(),
+            invocation.author_id().get(),
+            expires_at,
+        )
+        .await
+        .err()
+        .map(|source| {
+            error!(?source, "storing tempban expiry failed");
+            "The ban went through, but I couldn't schedule the unban, so it is permanent \
+             for now. Unban them manually when it should end."
+        }),
+        None => clear_tempban(&ctx, guild_id.get(), target_user_id.get())
+            .await
+            .err()
+            .map(|source| {
+                error!(?source, "clearing earlier tempban expiry failed");
+                "The ban went through, but I couldn't cancel an earlier scheduled unban, \
+                 so this user may still be unbanned when it was due. Ban them again to retry."
+            }),
+    };
+    // Without a stored expiry the ban is permanent, and the case says so
+    let (duration, expires_at) = match (requested_expiry, expiry_warning) {
+        (Some(_), Some(_)) => (None, None),
+        _ => (duration, requested_expiry),
+    };
+
+    report_action(
+        &ctx,
+        invocation,
+        ActionReport {
+            case: NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Ban,
+                user_id: Some(target_user_id.get()),
+                moderator_id: invocation.author_id().get(),
+                reason,
+                duration_secs: duration.as_ref().map(|duration| duration.secs),
+                expires_at,
+            },
+            target_user_id,
+            action_past_tense: "banned",
+            duration_label: duration.as_ref().map(|duration| duration.label.as_str()),
+        },
+    )
+    .await?;
+
+    if let Some(warning) = expiry_warning {
+        invocation.reply_ephemeral(http, warning).await?;
+    }
 
     Ok(())
 }
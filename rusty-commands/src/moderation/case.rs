@@ -0,0 +1,128 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+};
+use twilight_util::builder::command::{CommandBuilder, IntegerBuilder};
+
+use crate::moderation::case_log::load_embed_color;
+use crate::moderation::embeds::{
+    case_embed, case_not_found_message, guild_only_message, permission_denied_message,
+};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::cases::case_by_number;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::option_integer;
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "case",
+    desc: "Show a single moderation case.",
+    category: "moderation",
+    usage: "!case <number>",
+    cooldown: None,
+};
+
+pub struct CaseCommand;
+
+impl Command for CaseCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/case`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            IntegerBuilder::new("number", "Case number to show.")
+                .required(true)
+                .min_value(1),
+        )
+        .build()
+}
+
+/// Show a single case by its per-server number.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Show a single case from the `/case` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let case_number = option_integer(data, "number")
+        .and_then(|number| u64::try_from(number).ok())
+        .ok_or_else(|| ArgError::missing(META.usage, "number"));
+    execute(ctx, &invocation, case_number).await
+}
+
+fn parse_args(raw: &str) -> Result<u64, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let case_number = args.integer("number", 1..=i64::MAX)?;
+    args.finish()?;
+
+    Ok(case_number.unsigned_abs())
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    case_number: Result<u64, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let case_number = match case_number {
+        Ok(case_number) => case_number,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let Some(entry) = case_by_number(&ctx.db, guild_id.get(), case_number).await? else {
+        let not_found = case_not_found_message(case_number);
+        invocation.reply_content(http, &not_found).await?;
+        return Ok(());
+    };
+
+    let color = load_embed_color(&ctx, guild_id.get()).await;
+    let embed = case_embed(&entry, None, color)?;
+    invocation.reply_embeds(http, &[embed]).await?;
+
+    Ok(())
+}
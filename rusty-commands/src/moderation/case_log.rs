@@ -0,0 +1,244 @@
+use tracing::error;
+use twilight_model::{
+    channel::message::embed::Embed,
+    id::{
+        Id,
+        marker::{ChannelMarker, UserMarker},
+    },
+};
+
+use crate::moderation::embeds::{
+    TargetProfile, case_embed, channel_action_embed, fetch_target_profile, moderation_action_embed,
+    moderation_action_short_message, with_case_footer,
+};
+use rusty_core::Context;
+use rusty_database::impls::cases::record_case;
+use rusty_database::model::cases::{CaseEntry, NewCase};
+use rusty_database::model::modlog::ModLogSettings;
+use rusty_utils::embed::DEFAULT_EMBED_COLOR;
+use rusty_utils::invocation::Invocation;
+
+/// An applied moderation action, ready to be recorded and reported.
+pub struct ActionReport<'a> {
+    pub case: NewCase<'a>,
+    pub target_user_id: Id<UserMarker>,
+    pub action_past_tense: &'a str,
+    pub duration_label: Option<&'a str>,
+}
+
+/// An applied channel action, ready to be recorded and reported.
+pub struct ChannelActionReport<'a> {
+    /// Its reason names the channel, since cases have no channel of their own.
+    pub case: NewCase<'a>,
+    pub channel_id: Id<ChannelMarker>,
+    pub channel_name: &'a str,
+    pub action_past_tense: &'a str,
+    /// The moderator's reason as shown to the invoker.
+    pub reason: Option<&'a str>,
+    pub duration_label: Option<&'a str>,
+}
+
+/// The case reason for an action taken on a channel.
+pub fn channel_case_reason(channel_id: Id<ChannelMarker>, reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => format!("<#{channel_id}>: {reason}"),
+        None => format!("<#{channel_id}>"),
+    }
+}
+
+/// Record a case for an action that has already been applied.
+///
+/// Failures are logged rather than returned so a database outage never hides
+/// the outcome of an action Discord has already carried out.
+pub async fn log_case(ctx: &Context, case: NewCase<'_>) -> Option<CaseEntry> {
+    let action = case.action;
+    match record_case(&ctx.db, case).await {
+        Ok(entry) => Some(entry),
+        Err(source) => {
+            error!(?source, %action, "recording moderation case failed");
+            None
+        }
+    }
+}
+
+/// Resolve the bot's own user ID, used as the moderator of automatic actions.
+pub async fn bot_user_id(ctx: &Context) -> Option<u64> {
+    let result = async { anyhow::Ok(ctx.http.current_user().await?.model().await?.id.get()) };
+    match result.await {
+        Ok(user_id) => Some(user_id),
+        Err(source) => {
+            error!(?source, "resolving bot user failed");
+            None
+        }
+    }
+}
+
+/// Load a guild's mod-log settings, treating lookup failures as "not configured".
+pub async fn load_modlog_settings(ctx: &Context, guild_id: u64) -> ModLogSettings {
+    ctx.guild_settings(guild_id)
+        .await
+        .map(|settings| settings.modlog())
+        .unwrap_or_else(|source| {
+            error!(?source, guild_id, "loading mod-log settings failed");
+            ModLogSettings::default()
+        })
+}
+
+/// Color of a guild's moderation embeds, falling back to the bot default.
+pub async fn load_embed_color(ctx: &Context, guild_id: u64) -> u32 {
+    match ctx.guild_settings(guild_id).await {
+        Ok(settings) => settings.embed_color.unwrap_or(DEFAULT_EMBED_COLOR),
+        Err(source) => {
+            error!(?source, guild_id, "loading embed color failed");
+            DEFAULT_EMBED_COLOR
+        }
+    }
+}
+
+/// Post an embed to the guild's mod-log channel, if one is configured.
+pub async fn publish_to_modlog(ctx: &Context, settings: &ModLogSettings, embed: Embed) {
+    let Some(channel_id) = settings
+        .channel_id
+        .and_then(Id::<ChannelMarker>::new_checked)
+    else {
+        return;
+    };
+
+    if let Err(source) = ctx.http.create_message(channel_id).embeds(&[embed]).await {
+        error!(
+            ?source,
+            channel_id = channel_id.get(),
+            "mod-log publish failed"
+        );
+    }
+}
+
+/// Publish a recorded case to the guild's mod-log channel.
+pub async fn publish_case(ctx: &Context, entry: &CaseEntry) {
+    let settings = load_modlog_settings(ctx, entry.guild_id).await;
+    if settings.channel_id.is_none() {
+        return;
+    }
+
+    let target_profile = match entry.user_id.and_then(Id::<UserMarker>::new_checked) {
+        Some(user_id) => Some(fetch_target_profile(ctx, user_id).await),
+        None => None,
+    };
+    let color = load_embed_color(ctx, entry.guild_id).await;
+
+    match case_embed(entry, target_profile.as_ref(), color) {
+        Ok(embed) => publish_to_modlog(ctx, &settings, embed).await,
+        Err(source) => error!(?source, "building mod-log case embed failed"),
+    }
+}
+
+/// Record an applied action, publish it to the mod-log and answer the invoker.
+///
+/// Guilds with quiet replies get an ephemeral embed for slash commands and a
+/// single line for message commands instead of the full action embed.
+pub async fn report_action(
+    ctx: &Context,
+    invocation: &Invocation,
+    report: ActionReport<'_>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let guild_id = report.case.guild_id;
+    let reason = report.case.reason;
+
+    let settings = load_modlog_settings(ctx, guild_id).await;
+    let entry = log_case(ctx, report.case).await;
+    let target_profile = fetch_target_profile(ctx, report.target_user_id).await;
+    let color = load_embed_color(ctx, guild_id).await;
+
+    if let Some(entry) = &entry
+        && settings.channel_id.is_some()
+    {
+        match case_embed(entry, Some(&target_profile), color) {
+            Ok(embed) => publish_to_modlog(ctx, &settings, embed).await,
+            Err(source) => error!(?source, "building mod-log case embed failed"),
+        }
+    }
+
+    let case_number = entry.as_ref().map(|entry| entry.case_number);
+    if settings.quiet_replies && !invocation.is_interaction() {
+        let short =
+            moderation_action_short_message(&target_profile, report.action_past_tense, case_number);
+        invocation.reply_content(http, &short).await?;
+        return Ok(());
+    }
+
+    let mut embed = moderation_action_embed(
+        &target_profile,
+        report.target_user_id,
+        report.action_past_tense,
+        reason,
+        report.duration_label,
+        color,
+    )?;
+    if let Some(case_number) = case_number {
+        embed = with_case_footer(embed, case_number);
+    }
+
+    if settings.quiet_replies {
+        invocation.reply_ephemeral_embeds(http, &[embed]).await?;
+    } else {
+        invocation.reply_embeds(http, &[embed]).await?;
+    }
+
+    Ok(())
+}
+
+/// [`report_action`] for actions on a channel rather than a member.
+pub async fn report_channel_action(
+    ctx: &Context,
+    invocation: &Invocation,
+    report: ChannelActionReport<'_>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let guild_id = report.case.guild_id;
+
+    let settings = load_modlog_settings(ctx, guild_id).await;
+    let entry = log_case(ctx, report.case).await;
+    let color = load_embed_color(ctx, guild_id).await;
+
+    if let Some(entry) = &entry
+        && settings.channel_id.is_some()
+    {
+        match case_embed(entry, None, color) {
+            Ok(embed) => publish_to_modlog(ctx, &settings, embed).await,
+            Err(source) => error!(?source, "building mod-log case embed failed"),
+        }
+    }
+
+    let case_number = entry.as_ref().map(|entry| entry.case_number);
+    if settings.quiet_replies && !invocation.is_interaction() {
+        let profile = TargetProfile {
+            display_name: format!("#{}", report.channel_name),
+            avatar_url: None,
+        };
+        let short =
+            moderation_action_short_message(&profile, report.action_past_tense, case_number);
+        invocation.reply_content(http, &short).await?;
+        return Ok(());
+    }
+
+    let mut embed = channel_action_embed(
+        report.channel_name,
+        report.channel_id,
+        report.action_past_tense,
+        report.reason,
+        report.duration_label,
+        color,
+    )?;
+    if let Some(case_number) = case_number {
+        embed = with_case_footer(embed, case_number);
+    }
+
+    if settings.quiet_replies {
+        invocation.reply_ephemeral_embeds(http, &[embed]).await?;
+    } else {
+        invocation.reply_embeds(http, &[embed]).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::UserMarker},
+};
+use twilight_util::builder::command::{CommandBuilder, UserBuilder};
+
+use crate::moderation::case_log::load_embed_color;
+use crate::moderation::embeds::{
+    cases_overview_embed, fetch_target_profile, guild_only_message, permission_denied_message,
+};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::cases::cases_for_user;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::option_user;
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "cases",
+    desc: "Show every moderation case recorded against a user.",
+    category: "moderation",
+    usage: "!cases <user>",
+    cooldown: None,
+};
+
+pub struct CasesCommand;
+
+impl Command for CasesCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/cases`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(UserBuilder::new("user", "User whose cases to show.").required(true))
+        .build()
+}
+
+/// Show the case history of a target user.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Show the case history from the `/cases` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let target_user_id =
+        option_user(data, "user").ok_or_else(|| ArgError::missing(META.usage, "user"));
+    execute(ctx, &invocation, target_user_id).await
+}
+
+fn parse_args(raw: &str) -> Result<Id<UserMarker>, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let target_user_id = args.required("user")?;
+    args.finish()?;
+
+    Ok(target_user_id)
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    target_user_id: Result<Id<UserMarker>, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let target_user_id = match target_user_id {
+        Ok(target_user_id) => target_user_id,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let entries = cases_for_user(&ctx.db, guild_id.get(), target_user_id.get()).await?;
+    let target_profile = fetch_target_profile(&ctx, target_user_id).await;
+    let color = load_embed_color(&ctx, guild_id.get()).await;
+    let embed = cases_overview_embed(&target_profile, &entries, color)?;
+
+    invocation.reply_embeds(http, &[embed]).await?;
+
+    Ok(())
+}
@@ -0,0 +1,637 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::ChannelMarker},
+};
+use twilight_util::builder::command::{CommandBuilder, StringBuilder};
+
+use crate::automod::raid::{DEFAULT_ACCOUNT_AGE_SECS, DEFAULT_JOIN_RATE, DEFAULT_YOUNG_JOIN_RATE};
+use crate::automod::spam::{
+    DEFAULT_CHANNEL_DUPLICATES, DEFAULT_CHANNEL_RATE, DEFAULT_USER_DUPLICATES, DEFAULT_USER_RATE,
+};
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::escalation::MAX_TIMEOUT_SECS;
+use crate::moderation::timeout::DEFAULT_TIMEOUT_SECS;
+use crate::moderation::warnings::DEFAULT_DAYS;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_core::raid::MAX_RAID_WINDOW_SECS;
+use rusty_core::spam::MAX_SPAM_WINDOW_SECS;
+use rusty_database::model::automod::AutomodAction;
+use rusty_database::model::guild_settings::{GuildSettings, SpamLimit};
+use rusty_database::model::lockdowns::RaidAction;
+use rusty_utils::DEFAULT_COMMAND_PREFIX;
+use rusty_utils::embed::DEFAULT_EMBED_COLOR;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::option_string;
+use rusty_utils::parse::{ArgError, ArgErrorKind, ArgParser, FromArg, ParsedDuration};
+use rusty_utils::permissions::{guild_channel, has_invocation_permission};
+use rusty_utils::time::format_duration_secs;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "config",
+    desc: "Show or change this server's bot settings.",
+    category: "moderation",
+    usage: "!config [get [key]|set <key> <value>|reset <key>]",
+    cooldown: None,
+};
+
+const MAX_PREFIX_LEN: usize = 5;
+const MAX_WARNINGS_WINDOW_DAYS: u64 = 36_500;
+const MAX_SPAM_COUNT: u32 = 50;
+const MAX_ACCOUNT_AGE_SECS: u64 = 365 * 86_400;
+const MAX_LOCKDOWN_CHANNELS: usize = 25;
+
+pub struct ConfigCommand;
+
+impl Command for ConfigCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/config`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            StringBuilder::new("action", "What to do with the setting.").choices([
+                ("Get", "get"),
+                ("Set", "set"),
+                ("Reset", "reset"),
+            ]),
+        )
+        .option(
+            StringBuilder::new("key", "Setting to show or change.")
+                .choices(ConfigKey::ALL.map(|key| (key.name(), key.name()))),
+        )
+        .option(StringBuilder::new("value", "New value for `set`."))
+        .build()
+}
+
+/// Show or change guild settings.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Show or change guild settings from the `/config` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ConfigKey {
+    Prefix,
+    ModlogChannel,
+    DefaultTimeout,
+    WarningsWindow,
+    EmbedColor,
+    SpamAction,
+    SpamUserRate,
+    SpamUserDuplicates,
+    SpamChannelRate,
+    SpamChannelDuplicates,
+    RaidAction,
+    RaidJoinRate,
+    RaidYoungJoinRate,
+    RaidAccountAge,
+    LockdownChannels,
+}
+
+impl ConfigKey {
+    const ALL: [Self; 15] = [
+        Self::Prefix,
+        Self::ModlogChannel,
+        Self::DefaultTimeout,
+        Self::WarningsWindow,
+        Self::EmbedColor,
+        Self::SpamAction,
+        Self::SpamUserRate,
+        Self::SpamUserDuplicates,
+        Self::SpamChannelRate,
+        Self::SpamChannelDuplicates,
+        Self::RaidAction,
+        Self::RaidJoinRate,
+        Self::RaidYoungJoinRate,
+        Self::RaidAccountAge,
+        Self::LockdownChannels,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Prefix => "prefix",
+            Self::ModlogChannel => "modlog-channel",
+            Self::DefaultTimeout => "default-timeout",
+            Self::WarningsWindow => "warnings-window",
+            Self::EmbedColor => "embed-color",
+            Self::SpamAction => "spam-action",
+            Self::SpamUserRate => "spam-user-rate",
+            Self::SpamUserDuplicates => "spam-user-duplicates",
+            Self::SpamChannelRate => "spam-channel-rate",
+            Self::SpamChannelDuplicates => "spam-channel-duplicates",
+            Self::RaidAction => "raid-action",
+            Self::RaidJoinRate => "raid-join-rate",
+            Self::RaidYoungJoinRate => "raid-young-join-rate",
+            Self::RaidAccountAge => "raid-account-age",
+            Self::LockdownChannels => "lockdown-channels",
+        }
+    }
+
+    /// Effective value, and whether the guild has overridden the default.
+    fn describe(self, settings: &GuildSettings) -> (String, bool) {
+        match self {
+            Self::Prefix => match settings.prefix.as_deref() {
+                Some(prefix) => (format!("`{prefix}`"), true),
+                None => (format!("`{DEFAULT_COMMAND_PREFIX}`"), false),
+            },
+            Self::ModlogChannel => match settings.modlog_channel_id {
+                Some(channel_id) => (format!("<#{channel_id}>"), true),
+                None => ("not set".to_owned(), false),
+            },
+            Self::DefaultTimeout => match settings.default_timeout_secs {
+                Some(secs) => (format_duration_secs(secs), true),
+                None => (format_duration_secs(DEFAULT_TIMEOUT_SECS), false),
+            },
+            Self::WarningsWindow => match settings.warnings_window_days {
+                Some(days) => (format!("{days} days"), true),
+                None => (format!("{DEFAULT_DAYS} days"), false),
+            },
+            Self::EmbedColor => match settings.embed_color {
+                Some(color) => (format!("`#{color:06X}`"), true),
+                None => (format!("`#{DEFAULT_EMBED_COLOR:06X}`"), false),
+            },
+            Self::SpamAction => match settings.spam_action {
+                Some(action) => (format!("`{action}`"), true),
+                None => ("off".to_owned(), false),
+            },
+            Self::SpamUserRate => {
+                describe_limit(settings.spam_user_rate, DEFAULT_USER_RATE, "messages")
+            }
+            Self::SpamUserDuplicates => describe_limit(
+                settings.spam_user_duplicates,
+                DEFAULT_USER_DUPLICATES,
+                "messages",
+            ),
+            Self::SpamChannelRate => {
+                describe_limit(settings.spam_channel_rate, DEFAULT_CHANNEL_RATE, "messages")
+            }
+            Self::SpamChannelDuplicates => describe_limit(
+                settings.spam_channel_duplicates,
+                DEFAULT_CHANNEL_DUPLICATES,
+                "messages",
+            ),
+            Self::RaidAction => match settings.raid_action {
+                Some(action) => (format!("`{action}`"), true),
+                None => ("off".to_owned(), false),
+            },
+            Self::RaidJoinRate => {
+                describe_limit(settings.raid_join_rate, DEFAULT_JOIN_RATE, "joins")
+            }
+            Self::RaidYoungJoinRate => describe_limit(
+                settings.raid_young_join_rate,
+                DEFAULT_YOUNG_JOIN_RATE,
+                "joins",
+            ),
+            Self::RaidAccountAge => match settings.raid_account_age_secs {
+                Some(secs) => (format_duration_secs(secs), true),
+                None => (format_duration_secs(DEFAULT_ACCOUNT_AGE_SECS), false),
+            },
+            Self::LockdownChannels => match settings.lockdown_channel_ids.as_slice() {
+                [] => ("none".to_owned(), false),
+                channel_ids => (
+                    channel_ids
+                        .iter()
+                        .map(|channel_id| format!("<#{channel_id}>"))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    true,
+                ),
+            },
+        }
+    }
+
+    /// The setting behind a spam or raid limit key, or `None` for other keys.
+    fn limit_mut(self, settings: &mut GuildSettings) -> Option<&mut Option<SpamLimit>> {
+        match self {
+            Self::SpamUserRate => Some(&mut settings.spam_user_rate),
+            Self::SpamUserDuplicates => Some(&mut settings.spam_user_duplicates),
+            Self::SpamChannelRate => Some(&mut settings.spam_channel_rate),
+            Self::SpamChannelDuplicates => Some(&mut settings.spam_channel_duplicates),
+            Self::RaidJoinRate => Some(&mut settings.raid_join_rate),
+            Self::RaidYoungJoinRate => Some(&mut settings.raid_young_join_rate),
+            _ => None,
+        }
+    }
+}
+
+fn describe_limit(limit: Option<SpamLimit>, default: SpamLimit, unit: &str) -> (String, bool) {
+    let describe = |limit: SpamLimit| match limit.count {
+        0 => "off".to_owned(),
+        count => format!(
+            "more than {count} {unit} in {}",
+            format_duration_secs(limit.window_secs)
+        ),
+    };
+    match limit {
+        Some(limit) => (describe(limit), true),
+        None => (describe(default), false),
+    }
+}
+
+impl FromArg for ConfigKey {
+    const EXPECTED: &'static str = "`prefix`, `modlog-channel`, `default-timeout`, \
+        `warnings-window`, `embed-color`, `spam-action`, `spam-user-rate`, \
+        `spam-user-duplicates`, `spam-channel-rate`, `spam-channel-duplicates`, \
+        `raid-action`, `raid-join-rate`, `raid-young-join-rate`, `raid-account-age` or \
+        `lockdown-channels`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        Self::ALL
+            .into_iter()
+            .find(|key| key.name().eq_ignore_ascii_case(raw))
+    }
+}
+
+enum ConfigArgs {
+    Get(Option<ConfigKey>),
+    Set(ConfigValue),
+    Reset(ConfigKey),
+}
+
+enum ConfigValue {
+    Prefix(String),
+    ModlogChannel(Id<ChannelMarker>),
+    DefaultTimeout(u64),
+    WarningsWindow(u64),
+    EmbedColor(u32),
+    SpamAction(Option<AutomodAction>),
+    Limit(ConfigKey, SpamLimit),
+    RaidAction(Option<RaidAction>),
+    RaidAccountAge(u64),
+    LockdownChannels(Vec<Id<ChannelMarker>>),
+}
+
+impl ConfigValue {
+    fn parse(key: ConfigKey, raw: &str) -> Result<Self, ArgError> {
+        let invalid = |expected| {
+            ArgError::new(
+                META.usage,
+                "value",
+                ArgErrorKind::Invalid {
+                    value: raw.to_owned(),
+                    expected,
+                },
+            )
+        };
+
+        match key {
+            ConfigKey::Prefix => {
+                let prefix = raw.trim();
+                if prefix.is_empty()
+                    || prefix.chars().count() > MAX_PREFIX_LEN
+                    || prefix.chars().any(char::is_whitespace)
+                {
+                    return Err(invalid("1 to 5 characters without spaces"));
+                }
+                Ok(Self::Prefix(prefix.to_owned()))
+            }
+            ConfigKey::ModlogChannel => Id::<ChannelMarker>::from_arg(raw)
+                .map(Self::ModlogChannel)
+                .ok_or_else(|| ArgError::invalid::<Id<ChannelMarker>>(META.usage, "value", raw)),
+            ConfigKey::DefaultTimeout => ParsedDuration::from_arg(raw)
+                .filter(|duration| (1..=MAX_TIMEOUT_SECS).contains(&duration.secs))
+                .map(|duration| Self::DefaultTimeout(duration.secs))
+                .ok_or_else(|| invalid("a duration from `1s` to `28d`")),
+            ConfigKey::WarningsWindow => raw
+                .trim()
+                .trim_end_matches(['d', 'D'])
+                .parse::<u64>()
+                .ok()
+                .filter(|days| (1..=MAX_WARNINGS_WINDOW_DAYS).contains(days))
+                .map(Self::WarningsWindow)
+                .ok_or_else(|| invalid("a number of days from 1 to 36500")),
+            ConfigKey::EmbedColor => parse_color(raw)
+                .map(Self::EmbedColor)
+                .ok_or_else(|| invalid("a hex color like `#5865F2`")),
+            ConfigKey::SpamAction => match raw.trim().to_ascii_lowercase().as_str() {
+                "off" | "none" => Ok(Self::SpamAction(None)),
+                action => action
+                    .parse::<AutomodAction>()
+                    .map(|action| Self::SpamAction(Some(action)))
+                    .map_err(|_| invalid("`off`, `delete`, `warn`, `timeout` or `ban`")),
+            },
+            ConfigKey::SpamUserRate
+            | ConfigKey::SpamUserDuplicates
+            | ConfigKey::SpamChannelRate
+            | ConfigKey::SpamChannelDuplicates
+            | ConfigKey::RaidJoinRate
+            | ConfigKey::RaidYoungJoinRate => {
+                let max_window_secs = match key {
+                    ConfigKey::RaidJoinRate | ConfigKey::RaidYoungJoinRate => MAX_RAID_WINDOW_SECS,
+                    _ => MAX_SPAM_WINDOW_SECS,
+                };
+                parse_limit(raw, max_window_secs)
+                    .map(|limit| Self::Limit(key, limit))
+                    .ok_or_else(|| {
+                        invalid("`off` or `<count>/<duration>` like `5/10s`, up to `50/10m`")
+                    })
+            }
+            ConfigKey::RaidAction => match raw.trim().to_ascii_lowercase().as_str() {
+                "off" | "none" => Ok(Self::RaidAction(None)),
+                action => action
+                    .parse::<RaidAction>()
+                    .map(|action| Self::RaidAction(Some(action)))
+                    .map_err(|_| invalid("`off`, `lockdown`, `kick` or `timeout`")),
+            },
+            ConfigKey::RaidAccountAge => ParsedDuration::from_arg(raw)
+                .filter(|duration| (1..=MAX_ACCOUNT_AGE_SECS).contains(&duration.secs))
+                .map(|duration| Self::RaidAccountAge(duration.secs))
+                .ok_or_else(|| invalid("a duration from `1s` to `365d`")),
+            ConfigKey::LockdownChannels => parse_channel_list(raw)
+                .filter(|channel_ids| channel_ids.len() <= MAX_LOCKDOWN_CHANNELS)
+                .map(Self::LockdownChannels)
+                .ok_or_else(|| invalid("`none` or up to 25 channels")),
+        }
+    }
+
+    fn key(&self) -> ConfigKey {
+        match self {
+            Self::Prefix(_) => ConfigKey::Prefix,
+            Self::ModlogChannel(_) => ConfigKey::ModlogChannel,
+            Self::DefaultTimeout(_) => ConfigKey::DefaultTimeout,
+            Self::WarningsWindow(_) => ConfigKey::WarningsWindow,
+            Self::EmbedColor(_) => ConfigKey::EmbedColor,
+            Self::SpamAction(_) => ConfigKey::SpamAction,
+            Self::Limit(key, _) => *key,
+            Self::RaidAction(_) => ConfigKey::RaidAction,
+            Self::RaidAccountAge(_) => ConfigKey::RaidAccountAge,
+            Self::LockdownChannels(_) => ConfigKey::LockdownChannels,
+        }
+    }
+
+    fn apply(self, settings: &mut GuildSettings) {
+        match self {
+            Self::Prefix(prefix) => settings.prefix = Some(prefix),
+            Self::ModlogChannel(channel_id) => settings.modlog_channel_id = Some(channel_id.get()),
+            Self::DefaultTimeout(secs) => settings.default_timeout_secs = Some(secs),
+            Self::WarningsWindow(days) => settings.warnings_window_days = Some(days),
+            Self::EmbedColor(color) => settings.embed_color = Some(color),
+            Self::SpamAction(action) => settings.spam_action = action,
+            Self::Limit(key, limit) => {
+                if let Some(setting) = key.limit_mut(settings) {
+                    *setting = Some(limit);
+                }
+            }
+            Self::RaidAction(action) => settings.raid_action = action,
+            Self::RaidAccountAge(secs) => settings.raid_account_age_secs = Some(secs),
+            Self::LockdownChannels(channel_ids) => {
+                settings.lockdown_channel_ids = channel_ids.iter().map(|id| id.get()).collect();
+            }
+        }
+    }
+}
+
+fn parse_color(raw: &str) -> Option<u32> {
+    let raw = raw.trim();
+    let hex = raw
+        .strip_prefix('#')
+        .or_else(|| raw.strip_prefix("0x"))
+        .or_else(|| raw.strip_prefix("0X"))
+        .unwrap_or(raw);
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// `off`, or a count and window such as `5/10s`, meaning more than five
+/// messages, or joins, in ten seconds.
+fn parse_limit(raw: &str, max_window_secs: u64) -> Option<SpamLimit> {
+    let raw = raw.trim();
+    if raw.eq_ignore_ascii_case("off") {
+        return Some(SpamLimit {
+            count: 0,
+            window_secs: 0,
+        });
+    }
+
+    let (count, window) = raw.split_once('/')?;
+    let count = count
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|count| (1..=MAX_SPAM_COUNT).contains(count))?;
+    let window = ParsedDuration::from_arg(window)
+        .filter(|duration| (1..=max_window_secs).contains(&duration.secs))?;
+    Some(SpamLimit {
+        count,
+        window_secs: window.secs,
+    })
+}
+
+/// `none`, or channels separated by spaces or commas. Duplicates are dropped.
+fn parse_channel_list(raw: &str) -> Option<Vec<Id<ChannelMarker>>> {
+    let raw = raw.trim();
+    if raw.eq_ignore_ascii_case("none") || raw.eq_ignore_ascii_case("off") {
+        return Some(Vec::new());
+    }
+
+    let mut channel_ids = Vec::new();
+    for part in raw.split(|c: char| c == ',' || c.is_whitespace()) {
+        if part.is_empty() {
+            continue;
+        }
+        let channel_id = Id::<ChannelMarker>::from_arg(part)?;
+        if !channel_ids.contains(&channel_id) {
+            channel_ids.push(channel_id);
+        }
+    }
+    (!channel_ids.is_empty()).then_some(channel_ids)
+}
+
+fn reset(key: ConfigKey, settings: &mut GuildSettings) {
+    match key {
+        ConfigKey::Prefix => settings.prefix = None,
+        ConfigKey::ModlogChannel => settings.modlog_channel_id = None,
+        ConfigKey::DefaultTimeout => settings.default_timeout_secs = None,
+        ConfigKey::WarningsWindow => settings.warnings_window_days = None,
+        ConfigKey::EmbedColor => settings.embed_color = None,
+        ConfigKey::SpamAction => settings.spam_action = None,
+        ConfigKey::SpamUserRate
+        | ConfigKey::SpamUserDuplicates
+        | ConfigKey::SpamChannelRate
+        | ConfigKey::SpamChannelDuplicates
+        | ConfigKey::RaidJoinRate
+        | ConfigKey::RaidYoungJoinRate => {
+            if let Some(setting) = key.limit_mut(settings) {
+                *setting = None;
+            }
+        }
+        ConfigKey::RaidAction => settings.raid_action = None,
+        ConfigKey::RaidAccountAge => settings.raid_account_age_secs = None,
+        ConfigKey::LockdownChannels => settings.lockdown_channel_ids.clear(),
+    }
+}
+
+fn parse_args(raw: &str) -> Result<ConfigArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+
+    let parsed = if args.keyword("set") {
+        let key = args.required("key")?;
+        let value = args.required_rest("value")?;
+        return ConfigValue::parse(key, &value).map(ConfigArgs::Set);
+    } else if args.keyword("reset") {
+        ConfigArgs::Reset(args.required("key")?)
+    } else {
+        args.keyword("get");
+        ConfigArgs::Get(match args.peek() {
+            Some(_) => Some(args.required("key")?),
+            None => None,
+        })
+    };
+    args.finish()?;
+
+    Ok(parsed)
+}
+
+fn slash_args(data: &CommandData) -> Result<ConfigArgs, ArgError> {
+    let key = option_string(data, "key")
+        .map(|raw| {
+            ConfigKey::from_arg(raw)
+                .ok_or_else(|| ArgError::invalid::<ConfigKey>(META.usage, "key", raw))
+        })
+        .transpose()?;
+
+    match option_string(data, "action").unwrap_or("get") {
+        "set" => {
+            let key = key.ok_or_else(|| ArgError::missing(META.usage, "key"))?;
+            let value = option_string(data, "value")
+                .ok_or_else(|| ArgError::missing(META.usage, "value"))?;
+            ConfigValue::parse(key, value).map(ConfigArgs::Set)
+        }
+        "reset" => key
+            .map(ConfigArgs::Reset)
+            .ok_or_else(|| ArgError::missing(META.usage, "key")),
+        _ => Ok(ConfigArgs::Get(key)),
+    }
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<ConfigArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_GUILD).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let args = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let reply = match args {
+        ConfigArgs::Get(key) => {
+            let settings = ctx.guild_settings(guild_id.get()).await?;
+            match key {
+                Some(key) => describe_line(key, &settings),
+                None => ConfigKey::ALL
+                    .into_iter()
+                    .map(|key| describe_line(key, &settings))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            }
+        }
+        ConfigArgs::Set(value) => {
+            let channel_ids = match &value {
+                ConfigValue::ModlogChannel(channel_id) => vec![*channel_id],
+                ConfigValue::LockdownChannels(channel_ids) => channel_ids.clone(),
+                _ => Vec::new(),
+            };
+            for channel_id in channel_ids {
+                if !is_guild_channel(&ctx, guild_id.get(), channel_id).await {
+                    invocation
+                        .reply_content(http, &format!("<#{channel_id}> isn't in this server."))
+                        .await?;
+                    return Ok(());
+                }
+            }
+
+            let key = value.key();
+            let enables_raid_action = matches!(value, ConfigValue::RaidAction(Some(_)));
+            let settings = ctx
+                .update_guild_settings(guild_id.get(), |settings| value.apply(settings))
+                .await?;
+            let mut reply = format!("Updated {}", describe_line(key, &settings));
+            if enables_raid_action && !ctx.member_events {
+                reply.push_str(
+                    "\nThis bot isn't receiving member joins (`MEMBER_EVENTS` is off), so raids won't be detected until the bot's operator turns them on.",
+                );
+            }
+            reply
+        }
+        ConfigArgs::Reset(key) => {
+            let settings = ctx
+                .update_guild_settings(guild_id.get(), |settings| reset(key, settings))
+                .await?;
+            format!("Reset {}", describe_line(key, &settings))
+        }
+    };
+
+    invocation.reply_content(http, &reply).await?;
+
+    Ok(())
+}
+
+fn describe_line(key: ConfigKey, settings: &GuildSettings) -> String {
+    let (value, overridden) = key.describe(settings);
+    let value = value.replace('@', "@\u{200B}");
+    if overridden {
+        format!("`{}`: {value}", key.name())
+    } else {
+        format!("`{}`: {value} (default)", key.name())
+    }
+}
+
+/// Channels the bot can't see count as outside the guild; it couldn't post there anyway.
+async fn is_guild_channel(ctx: &Context, guild_id: u64, channel_id: Id<ChannelMarker>) -> bool {
+    guild_channel(ctx, channel_id)
+        .await
+        .is_ok_and(|channel| channel.guild_id.is_some_and(|id| id.get() == guild_id))
+}
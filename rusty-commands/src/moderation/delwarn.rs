@@ -0,0 +1,131 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+};
+use twilight_util::builder::command::{CommandBuilder, IntegerBuilder};
+
+use crate::moderation::embeds::{
+    guild_only_message, permission_denied_message, warning_not_found_message,
+};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::warnings::delete_warning;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::option_integer;
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "delwarn",
+    desc: "Delete a single warning.",
+    category: "moderation",
+    usage: "!delwarn <id>",
+    cooldown: None,
+};
+
+pub struct DelwarnCommand;
+
+impl Command for DelwarnCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/delwarn`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            IntegerBuilder::new("id", "ID of the warning to delete.")
+                .required(true)
+                .min_value(1),
+        )
+        .build()
+}
+
+/// Delete a warning so it no longer counts against the user.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Delete a warning from the `/delwarn` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let warning_id = option_integer(data, "id")
+        .and_then(|id| u64::try_from(id).ok())
+        .ok_or_else(|| ArgError::missing(META.usage, "id"));
+    execute(ctx, &invocation, warning_id).await
+}
+
+fn parse_args(raw: &str) -> Result<u64, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let warning_id = args.integer("id", 1..=i64::MAX)?.unsigned_abs();
+    args.finish()?;
+
+    Ok(warning_id)
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<u64, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let warning_id = match args {
+        Ok(warning_id) => warning_id,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let deleted = delete_warning(
+        &ctx.db,
+        guild_id.get(),
+        warning_id,
+        invocation.author_id().get(),
+    )
+    .await?;
+
+    let reply = match deleted {
+        Some(entry) => format!("Deleted warning #{warning_id} for <@{}>.", entry.user_id),
+        None => warning_not_found_message(warning_id),
+    };
+    invocation.reply_content(http, &reply).await?;
+
+    Ok(())
+}
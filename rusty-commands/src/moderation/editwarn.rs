@@ -0,0 +1,149 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+};
+use twilight_util::builder::command::{CommandBuilder, IntegerBuilder, StringBuilder};
+
+use crate::moderation::embeds::{
+    guild_only_message, permission_denied_message, warning_not_found_message,
+};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::warnings::edit_warning_reason;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_integer, option_string};
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "editwarn",
+    desc: "Replace the reason of a warning.",
+    category: "moderation",
+    usage: "!editwarn <id> <reason>",
+    cooldown: None,
+};
+
+pub struct EditwarnCommand;
+
+impl Command for EditwarnCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/editwarn`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            IntegerBuilder::new("id", "ID of the warning to update.")
+                .required(true)
+                .min_value(1),
+        )
+        .option(StringBuilder::new("reason", "New reason for the warning.").required(true))
+        .build()
+}
+
+/// Replace the reason recorded on a warning.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Replace a warning reason from the `/editwarn` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct EditwarnArgs {
+    warning_id: u64,
+    reason: String,
+}
+
+fn parse_args(raw: &str) -> Result<EditwarnArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let warning_id = args.integer("id", 1..=i64::MAX)?.unsigned_abs();
+    let reason = args.required_rest("reason")?;
+
+    Ok(EditwarnArgs { warning_id, reason })
+}
+
+fn slash_args(data: &CommandData) -> Result<EditwarnArgs, ArgError> {
+    let warning_id = option_integer(data, "id")
+        .and_then(|id| u64::try_from(id).ok())
+        .ok_or_else(|| ArgError::missing(META.usage, "id"))?;
+    let reason = option_string(data, "reason")
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| ArgError::missing(META.usage, "reason"))?;
+
+    Ok(EditwarnArgs { warning_id, reason })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<EditwarnArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let EditwarnArgs { warning_id, reason } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let updated = edit_warning_reason(
+        &ctx.db,
+        guild_id.get(),
+        warning_id,
+        invocation.author_id().get(),
+        &reason,
+    )
+    .await?;
+
+    let reply = match updated {
+        Some(entry) => format!(
+            "Updated the reason for warning #{warning_id} (<@{}>).",
+            entry.user_id
+        ),
+        None => warning_not_found_message(warning_id),
+    };
+    invocation.reply_content(http, &reply).await?;
+
+    Ok(())
+}
@@ -1,9 +1,16 @@
 use twilight_http::Client;
-use twilight_model::{channel::message::embed::Embed, id::Id, id::marker::UserMarker};
+use twilight_model::{
+    channel::message::embed::{Embed, EmbedFooter},
+    gateway::payload::incoming::InteractionCreate,
+    id::Id,
+    id::marker::{ChannelMarker, UserMarker},
+    user::User,
+};
 use twilight_util::builder::embed::{EmbedAuthorBuilder, EmbedBuilder, ImageSource};
 
-use rusty_database::model::warnings::WarningEntry;
-use rusty_utils::embed::DEFAULT_EMBED_COLOR;
+use rusty_core::{Context, cache::CachedUser};
+use rusty_database::model::{cases::CaseEntry, warnings::WarningEntry};
+use rusty_utils::time::format_duration_secs;
 
 /// Build a moderation action-result embed.
 ///
@@ -15,10 +22,16 @@ pub struct TargetProfile {
 }
 
 /// Resolve a moderation target profile for display in embeds.
-pub async fn fetch_target_profile(http: &Client, user_id: Id<UserMarker>) -> TargetProfile {
-    let user = match http.user(user_id).await {
-        Ok(response) => match response.model().await {
-            Ok(model) => model,
+///
+/// Uses the cached user when available and only fetches it over REST on a miss.
+pub async fn fetch_target_profile(ctx: &Context, user_id: Id<UserMarker>) -> TargetProfile {
+    let user = match ctx.cache.user(user_id) {
+        Some(user) => user,
+        None => match fetch_user(&ctx.http, user_id).await {
+            Ok(user) => {
+                ctx.cache.insert_user(&user);
+                CachedUser::from(&user)
+            }
             Err(_) => {
                 return TargetProfile {
                     display_name: format!("User {}", user_id.get()),
@@ -26,12 +39,6 @@ pub async fn fetch_target_profile(http: &Client, user_id: Id<UserMarker>) -> Tar
                 };
             }
         },
-        Err(_) => {
-            return TargetProfile {
-                display_name: format!("User {}", user_id.get()),
-                avatar_url: None,
-            };
-        }
     };
 
     let display_name = user.global_name.unwrap_or(user.name);
@@ -56,12 +63,17 @@ pub async fn fetch_target_profile(http: &Client, user_id: Id<UserMarker>) -> Tar
     }
 }
 
+async fn fetch_user(http: &Client, user_id: Id<UserMarker>) -> anyhow::Result<User> {
+    Ok(http.user(user_id).await?.model().await?)
+}
+
 pub fn moderation_action_embed(
     target_profile: &TargetProfile,
     target_user_id: Id<UserMarker>,
     action_past_tense: &str,
     reason: Option<&str>,
     duration: Option<&str>,
+    color: u32,
 ) -> anyhow::Result<Embed> {
     let reason = reason
         .unwrap_or("No reason provided")
@@ -77,9 +89,7 @@ pub fn moderation_action_embed(
         None => format!("Target: <@{}>\nReason: {}", target_user_id.get(), reason),
     };
 
-    let builder = EmbedBuilder::new()
-        .color(DEFAULT_EMBED_COLOR)
-        .description(description);
+    let builder = EmbedBuilder::new().color(color).description(description);
 
     let builder = match target_profile.avatar_url.as_deref() {
         Some(url) => {
@@ -101,8 +111,46 @@ pub fn moderation_action_embed(
     Ok(builder.validate()?.build())
 }
 
-pub fn usage_message(usage: &str) -> String {
-    format!("Usage: `{usage}`")
+/// [`moderation_action_embed`] for an action taken on a channel.
+pub fn channel_action_embed(
+    channel_name: &str,
+    channel_id: Id<ChannelMarker>,
+    action_past_tense: &str,
+    reason: Option<&str>,
+    duration: Option<&str>,
+    color: u32,
+) -> anyhow::Result<Embed> {
+    let reason = sanitize_reason(reason.unwrap_or("No reason provided"));
+    let mut description = format!("Channel: <#{}>\nReason: {}", channel_id.get(), reason);
+    if let Some(duration) = duration {
+        description.push_str(&format!("\nDuration: {duration}"));
+    }
+
+    let embed = EmbedBuilder::new()
+        .color(color)
+        .title(format!("#{channel_name} has been {action_past_tense}"))
+        .description(description)
+        .validate()?
+        .build();
+
+    Ok(embed)
+}
+
+/// Recover the reason shown on a pending confirmation embed.
+///
+/// Confirmation custom IDs have no room for free text, so the queued embed is
+/// the only place the reason survives until a moderator confirms.
+pub fn queued_reason(interaction: &InteractionCreate) -> Option<String> {
+    let description = interaction
+        .message
+        .as_ref()?
+        .embeds
+        .first()?
+        .description
+        .as_deref()?;
+    let (_, reason) = description.split_once("\nReason: ")?;
+
+    Some(reason.replace("@\u{200B}", "@")).filter(|reason| reason != "No reason provided")
 }
 
 pub fn guild_only_message() -> &'static str {
@@ -117,10 +165,6 @@ pub fn moderation_permission_combo_denied_message(required: &str) -> String {
     format!("You need {required} permissions to use this command.")
 }
 
-pub fn moderation_self_action_message(action: &str) -> String {
-    format!("You can't {action} yourself.")
-}
-
 pub fn moderation_invalid_interaction_message(action: &str) -> String {
     format!("Invalid {action} interaction.")
 }
@@ -129,34 +173,189 @@ pub fn warnings_window_label_days(days: u64) -> String {
     format!("last {} day(s)", days)
 }
 
-pub fn warnings_overview_embed(
-    target_profile: &TargetProfile,
+/// Description for one page of a `!warnings` view.
+///
+/// `total` counts every warning matching the view, not just the ones on this page.
+pub fn warnings_page_description(
     window_label: &str,
+    moderator_id: Option<u64>,
+    total: usize,
     entries: &[WarningEntry],
-) -> anyhow::Result<Embed> {
-    let count = entries.len();
-    let mut description = format!("Total warnings in {}: **{}**\n\n", window_label, count);
+) -> String {
+    let mut description = match moderator_id {
+        Some(moderator_id) => format!(
+            "Total warnings in {} by <@{}>: **{}**\n\n",
+            window_label, moderator_id, total
+        ),
+        None => format!("Total warnings in {}: **{}**\n\n", window_label, total),
+    };
 
     if entries.is_empty() {
         description.push_str("No warnings in this period.");
+    }
+
+    for entry in entries {
+        let line = format!(
+            "#{id} • <t:{ts}:F> • by <@{mod_id}>\nReason: {reason}\n\n",
+            id = entry.id,
+            ts = entry.warned_at,
+            mod_id = entry.moderator_id,
+            reason = sanitize_reason(&entry.reason)
+        );
+        description.push_str(&line);
+    }
+
+    description
+}
+
+/// Number of cases listed in a `!cases` overview before older ones are summarised.
+const CASES_OVERVIEW_LIMIT: usize = 10;
+
+pub fn case_not_found_message(case_number: u64) -> String {
+    format!("Case #{case_number} does not exist in this server.")
+}
+
+pub fn warning_not_found_message(warning_id: u64) -> String {
+    format!("Warning #{warning_id} does not exist in this server.")
+}
+
+/// Full case view used by `!case` and the mod-log channel.
+///
+/// The target profile, when known, is shown as the embed author.
+pub fn case_embed(
+    entry: &CaseEntry,
+    target_profile: Option<&TargetProfile>,
+    color: u32,
+) -> anyhow::Result<Embed> {
+    let target = entry.user_id.map_or_else(
+        || "None".to_owned(),
+        |user_id| format!("<@{user_id}> (`{user_id}`)"),
+    );
+    let mut description = format!(
+        "Target: {target}\nModerator: <@{mod_id}> (`{mod_id}`)",
+        mod_id = entry.moderator_id
+    );
+
+    if let Some(duration_secs) = entry.duration_secs {
+        description.push_str(&format!(
+            "\nDuration: {}",
+            format_duration_secs(duration_secs)
+        ));
+    }
+    if let Some(expires_at) = entry.expires_at {
+        description.push_str(&format!("\nExpires: <t:{expires_at}:R>"));
+    }
+    description.push_str(&format!(
+        "\nReason: {}\nDate: <t:{}:F>",
+        sanitize_reason(entry.reason.as_deref().unwrap_or("No reason provided")),
+        entry.created_at
+    ));
+
+    let title = format!("Case #{} • {}", entry.case_number, entry.action.label());
+    let builder = EmbedBuilder::new()
+        .color(color)
+        .title(title)
+        .description(description);
+
+    let builder = match target_profile {
+        Some(profile) => {
+            let author = EmbedAuthorBuilder::new(profile.display_name.clone());
+            let author = match profile.avatar_url.as_deref() {
+                Some(url) => author.icon_url(ImageSource::url(url.to_owned())?),
+                None => author,
+            };
+            builder.author(author.build())
+        }
+        None => builder,
+    };
+
+    Ok(builder.validate()?.build())
+}
+
+/// Mod-log entry for a queued termination that was declined.
+pub fn terminate_declined_embed(
+    target_profile: &TargetProfile,
+    target_user_id: Id<UserMarker>,
+    moderator_id: Id<UserMarker>,
+    color: u32,
+) -> anyhow::Result<Embed> {
+    let description = format!(
+        "Target: <@{target}> (`{target}`)\nModerator: <@{moderator}> (`{moderator}`)\nNo action was taken.",
+        target = target_user_id.get(),
+        moderator = moderator_id.get()
+    );
+
+    let embed = EmbedBuilder::new()
+        .color(color)
+        .title(format!(
+            "Termination declined • {}",
+            target_profile.display_name
+        ))
+        .description(description)
+        .validate()?
+        .build();
+
+    Ok(embed)
+}
+
+/// One-line reply used instead of an embed when a guild has quiet replies enabled.
+pub fn moderation_action_short_message(
+    target_profile: &TargetProfile,
+    action_past_tense: &str,
+    case_number: Option<u64>,
+) -> String {
+    let name = sanitize_reason(&target_profile.display_name);
+    match case_number {
+        Some(case_number) => {
+            format!("**{name}** has been {action_past_tense} (case #{case_number}).")
+        }
+        None => format!("**{name}** has been {action_past_tense}."),
+    }
+}
+
+/// Tag an action embed with the case number it was recorded under.
+pub fn with_case_footer(mut embed: Embed, case_number: u64) -> Embed {
+    embed.footer = Some(EmbedFooter {
+        icon_url: None,
+        proxy_icon_url: None,
+        text: format!("Case #{case_number}"),
+    });
+    embed
+}
+
+pub fn cases_overview_embed(
+    target_profile: &TargetProfile,
+    entries: &[CaseEntry],
+    color: u32,
+) -> anyhow::Result<Embed> {
+    let mut description = format!("Total cases: **{}**\n\n", entries.len());
+
+    if entries.is_empty() {
+        description.push_str("No cases recorded for this user.");
     } else {
-        let start = entries.len().saturating_sub(5);
-        for (index, entry) in entries.iter().enumerate().skip(start) {
+        let start = entries.len().saturating_sub(CASES_OVERVIEW_LIMIT);
+        if start > 0 {
+            description.push_str(&format!(
+                "Showing the {} most recent cases.\n\n",
+                CASES_OVERVIEW_LIMIT
+            ));
+        }
+
+        for entry in &entries[start..] {
             let line = format!(
-                "#{idx} • <t:{ts}:F> • by <@{mod_id}>\nReason: {reason}\n\n",
-                idx = index + 1,
-                ts = entry.warned_at,
+                "**#{number}** {action} • <t:{ts}:d> • by <@{mod_id}>\nReason: {reason}\n\n",
+                number = entry.case_number,
+                action = entry.action.label(),
+                ts = entry.created_at,
                 mod_id = entry.moderator_id,
-                reason = sanitize_reason(&entry.reason)
+                reason = reason_preview(entry.reason.as_deref())
             );
             description.push_str(&line);
         }
     }
 
-    let title = format!("Warnings for {}", target_profile.display_name);
-    let builder = EmbedBuilder::new()
-        .color(DEFAULT_EMBED_COLOR)
-        .description(description);
+    let title = format!("Cases for {}", target_profile.display_name);
+    let builder = EmbedBuilder::new().color(color).description(description);
 
     let builder = match target_profile.avatar_url.as_deref() {
         Some(url) => {
@@ -170,6 +369,19 @@ pub fn warnings_overview_embed(
     Ok(builder.validate()?.build())
 }
 
+/// Shorten a reason for list views so long reasons cannot overflow the embed.
+fn reason_preview(reason: Option<&str>) -> String {
+    const MAX_PREVIEW_CHARS: usize = 200;
+
+    let reason = reason.unwrap_or("No reason provided");
+    let mut preview: String = reason.chars().take(MAX_PREVIEW_CHARS).collect();
+    if reason.chars().count() > MAX_PREVIEW_CHARS {
+        preview.push('…');
+    }
+
+    sanitize_reason(&preview)
+}
+
 fn sanitize_reason(reason: &str) -> String {
     reason.replace('@', "@\u{200B}")
 }
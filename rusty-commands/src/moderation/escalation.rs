@@ -0,0 +1,190 @@
+use tracing::error;
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    id::{
+        Id,
+        marker::{GuildMarker, UserMarker},
+    },
+    util::Timestamp,
+};
+
+use crate::moderation::case_log::{ActionReport, bot_user_id, report_action};
+use crate::moderation::tempban::{clear_tempban, store_tempban};
+use rusty_core::Context;
+use rusty_database::impls::warn_policies::warn_policies;
+use rusty_database::impls::warnings::warnings_since;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_database::model::warn_policies::{WarnPolicy, WarnPolicyAction};
+use rusty_database::model::warnings::WarningRecord;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::time::{format_duration_secs, now_unix_secs};
+
+/// Longest timeout Discord accepts.
+pub(crate) const MAX_TIMEOUT_SECS: u64 = 28 * 24 * 60 * 60;
+
+/// Apply the most severe warning policy that the new warning triggers, if any.
+///
+/// A policy triggers when the warning brings the count inside its window to
+/// exactly its threshold, so further warnings do not repeat the punishment.
+pub(crate) async fn escalate_after_warning(
+    ctx: &Context,
+    invocation: &Invocation,
+    guild_id: Id<GuildMarker>,
+    target_user_id: Id<UserMarker>,
+    warning: WarningRecord,
+    warning_reason: &str,
+) -> anyhow::Result<()> {
+    let policies = warn_policies(&ctx.db, guild_id.get()).await?;
+    let Some(longest_window) = policies.iter().map(|policy| policy.window_secs).max() else {
+        return Ok(());
+    };
+
+    let now = now_unix_secs();
+    let warnings = warnings_since(
+        &ctx.db,
+        guild_id.get(),
+        target_user_id.get(),
+        now.saturating_sub(longest_window),
+    )
+    .await?;
+
+    let triggered = policies
+        .into_iter()
+        .filter(|policy| {
+            let since = now.saturating_sub(policy.window_secs);
+            let count = warnings
+                .iter()
+                .filter(|entry| entry.warned_at >= since)
+                .count();
+            count == policy.threshold as usize
+        })
+        .max_by_key(|policy| (policy.action, policy.duration_secs.unwrap_or(u64::MAX)));
+    let Some(policy) = triggered else {
+        return Ok(());
+    };
+
+    let policy_summary = format!(
+        "Warning policy #{}: {} warnings within {}",
+        policy.id,
+        policy.threshold,
+        format_duration_secs(policy.window_secs)
+    );
+    // The case keeps the triggering warning; the audit log only gets the short form.
+    let reason = format!(
+        "{policy_summary}, triggered by warning #{}: {warning_reason}",
+        warning.id
+    );
+
+    // Automatic actions are attributed to the bot rather than the warning moderator.
+    let moderator_id = bot_user_id(ctx)
+        .await
+        .unwrap_or(invocation.author_id().get());
+
+    // Timeouts always end; Discord caps them, so an open-ended policy uses the cap
+    let duration_secs = match policy.action {
+        WarnPolicyAction::Timeout => Some(
+            policy
+                .duration_secs
+                .unwrap_or(MAX_TIMEOUT_SECS)
+                .min(MAX_TIMEOUT_SECS),
+        ),
+        WarnPolicyAction::Kick | WarnPolicyAction::Ban => policy.duration_secs,
+    };
+
+    if let Err(source) = apply_policy(
+        ctx,
+        guild_id,
+        target_user_id,
+        moderator_id,
+        &policy,
+        duration_secs,
+        &policy_summary,
+    )
+    .await
+    {
+        error!(?source, policy_id = policy.id, "warning escalation failed");
+        let message = format!(
+            "Warning policy #{} matched, but I couldn't {} that user. Check role hierarchy and permissions.",
+            policy.id, policy.action
+        );
+        invocation.reply_content(&ctx.http, &message).await?;
+        return Ok(());
+    }
+
+    let duration_label = duration_secs.map(format_duration_secs);
+    let (action, action_past_tense) = match policy.action {
+        WarnPolicyAction::Timeout => (CaseAction::Timeout, "automatically timed out"),
+        WarnPolicyAction::Kick => (CaseAction::Kick, "automatically kicked"),
+        WarnPolicyAction::Ban => (CaseAction::Ban, "automatically banned"),
+    };
+
+    report_action(
+        ctx,
+        invocation,
+        ActionReport {
+            case: NewCase {
+                guild_id: guild_id.get(),
+                action,
+                user_id: Some(target_user_id.get()),
+                moderator_id,
+                reason: Some(&reason),
+                duration_secs,
+                expires_at: duration_secs.map(|duration| now.saturating_add(duration)),
+            },
+            target_user_id,
+            action_past_tense,
+            duration_label: duration_label.as_deref(),
+        },
+    )
+    .await
+}
+
+async fn apply_policy(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    target_user_id: Id<UserMarker>,
+    moderator_id: u64,
+    policy: &WarnPolicy,
+    duration_secs: Option<u64>,
+    audit_reason: &str,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let now = now_unix_secs();
+
+    match policy.action {
+        WarnPolicyAction::Timeout => {
+            let duration = duration_secs.unwrap_or(MAX_TIMEOUT_SECS);
+            let until = Timestamp::from_secs(now.saturating_add(duration) as i64)?;
+            http.update_guild_member(guild_id, target_user_id)
+                .communication_disabled_until(Some(until))
+                .reason(audit_reason)
+                .await?;
+        }
+        WarnPolicyAction::Kick => {
+            http.remove_guild_member(guild_id, target_user_id)
+                .reason(audit_reason)
+                .await?;
+        }
+        WarnPolicyAction::Ban => {
+            http.create_ban(guild_id, target_user_id)
+                .reason(audit_reason)
+                .await?;
+
+            match duration_secs {
+                Some(duration) => {
+                    store_tempban(
+                        ctx,
+                        guild_id.get(),
+                        target_user_id.get(),
+                        moderator_id,
+                        now.saturating_add(duration),
+                    )
+                    .await?;
+                }
+                None => clear_tempban(ctx, guild_id.get(), target_user_id.get()).await?,
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,434 @@
+//! Channel locks: stop `@everyone` from talking in one channel, optionally
+//! for a limited time.
+//!
+//! The channel's previous `@everyone` overwrite is stored before it is
+//! changed, so unlocking puts back exactly what was there.
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    channel::Channel,
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker},
+    },
+};
+use twilight_util::builder::command::{ChannelBuilder, CommandBuilder, StringBuilder};
+
+use crate::moderation::case_log::{
+    ChannelActionReport, bot_user_id, channel_case_reason, log_case, publish_case,
+    report_channel_action,
+};
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::lockdown::{audit_reason, everyone_bits, locked_overwrite, restore_channel};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_core::jobs::{JobPayload, cancel_scheduled_job, schedule_job};
+use rusty_database::impls::channel_locks::{
+    channel_lock, delete_channel_lock, insert_channel_lock,
+};
+use rusty_database::impls::lockdowns::active_lockdown;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_database::model::channel_locks::{ChannelLock, NewChannelLock};
+use rusty_database::model::lockdowns::LockedChannel;
+use rusty_utils::http_error::is_not_found;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_channel, option_string};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg, ParsedDuration, UnitDuration};
+use rusty_utils::permissions::has_invocation_permission;
+use rusty_utils::time::{format_duration_secs, now_unix_secs};
+
+pub const META: CommandMeta = CommandMeta {
+    name: "lock",
+    desc: "Stop everyone from talking in a channel, optionally for a while.",
+    category: "moderation",
+    usage: "!lock [channel] [duration] [reason]",
+    cooldown: None,
+};
+
+const EXPIRED_REASON: &str = "Lock expired";
+/// Longest a timed lock may last; anything longer should just be a lock.
+const MAX_LOCK_SECS: u64 = 365 * 86_400;
+/// The smallest ID with a timestamp; smaller bare numbers are durations.
+const MIN_SNOWFLAKE: u64 = 1 << 22;
+
+pub struct LockCommand;
+
+impl Command for LockCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/lock`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_CHANNELS)
+        .contexts([InteractionContextType::Guild])
+        .option(ChannelBuilder::new(
+            "channel",
+            "Channel to lock (default: this one).",
+        ))
+        .option(StringBuilder::new(
+            "duration",
+            "Unlock automatically after this long, such as 30m or 2h.",
+        ))
+        .option(StringBuilder::new("reason", "Reason for the lock."))
+        .build()
+}
+
+/// Lock a channel.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Lock a channel from the `/lock` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct LockArgs {
+    channel_id: Option<Id<ChannelMarker>>,
+    duration: Option<ParsedDuration>,
+    reason: Option<String>,
+}
+
+fn parse_args(raw: &str) -> Result<LockArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let channel_id = channel_arg(&mut args);
+    // Only a token with a unit is a duration; `!lock 3 raiders` is a reason
+    let duration = args
+        .optional::<UnitDuration>()
+        .map(|UnitDuration(duration)| duration);
+
+    Ok(LockArgs {
+        channel_id,
+        duration,
+        reason: args.rest(),
+    })
+}
+
+/// Consume a leading channel argument, if there is one.
+///
+/// A bare number too small to be an ID is never a channel, so it is left for
+/// the arguments that follow, such as the `30` in `!slowmode 30`.
+pub(crate) fn channel_arg(args: &mut ArgParser<'_>) -> Option<Id<ChannelMarker>> {
+    let bare_seconds = args
+        .peek()
+        .and_then(|raw| raw.parse::<u64>().ok())
+        .is_some_and(|value| value < MIN_SNOWFLAKE);
+    if bare_seconds {
+        return None;
+    }
+    args.optional()
+}
+
+fn slash_args(data: &CommandData) -> Result<LockArgs, ArgError> {
+    let duration = option_string(data, "duration")
+        .map(|raw| {
+            UnitDuration::from_arg(raw)
+                .map(|UnitDuration(duration)| duration)
+                .ok_or_else(|| ArgError::invalid::<UnitDuration>(META.usage, "duration", raw))
+        })
+        .transpose()?;
+
+    Ok(LockArgs {
+        channel_id: option_channel(data, "channel"),
+        duration,
+        reason: option_string(data, "reason").map(ToOwned::to_owned),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<LockArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_CHANNELS).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let LockArgs {
+        channel_id,
+        duration,
+        reason,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+    let channel_id = channel_id.unwrap_or_else(|| invocation.channel_id());
+    let reason = reason.as_deref();
+
+    if duration
+        .as_ref()
+        .is_some_and(|duration| duration.secs > MAX_LOCK_SECS)
+    {
+        invocation
+            .reply_content(
+                http,
+                &format!(
+                    "Timed locks can last at most {}. Leave the duration out to lock until `!unlock`.",
+                    format_duration_secs(MAX_LOCK_SECS)
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let lockdown = active_lockdown(&ctx.db, guild_id.get()).await?;
+    if lockdown.is_some_and(|lockdown| {
+        lockdown
+            .channels
+            .iter()
+            .any(|locked| locked.channel_id == channel_id.get())
+    }) {
+        invocation
+            .reply_content(
+                http,
+                &format!("<#{channel_id}> is already locked by the server lockdown."),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    // Fetched fresh so the stored overwrite is exactly what Discord has
+    let Some(channel) = fetch_guild_channel(&ctx, guild_id, channel_id).await? else {
+        invocation
+            .reply_content(http, &format!("<#{channel_id}> isn't in this server."))
+            .await?;
+        return Ok(());
+    };
+    let channel_name = display_name(&channel);
+
+    let moderator_id = invocation.author_id().get();
+    let expires_at = duration
+        .as_ref()
+        .map(|duration| now_unix_secs().saturating_add(duration.secs));
+    let previous = everyone_bits(&channel, guild_id);
+    let stored = insert_channel_lock(
+        &ctx.db,
+        NewChannelLock {
+            guild_id: guild_id.get(),
+            channel_id: channel_id.get(),
+            moderator_id,
+            reason,
+            previous,
+            expires_at,
+        },
+    )
+    .await?;
+    if !stored {
+        invocation
+            .reply_content(
+                http,
+                &format!("<#{channel_id}> is already locked. Use `!unlock` to lift it."),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let result = http
+        .update_channel_permission(channel_id, &locked_overwrite(guild_id, previous))
+        .reason(&audit_reason("Channel locked", reason))
+        .await;
+    if let Err(source) = result {
+        warn!(
+            ?source,
+            channel_id = channel_id.get(),
+            "locking channel failed"
+        );
+        delete_channel_lock(&ctx.db, guild_id.get(), channel_id.get()).await?;
+        invocation
+            .reply_content(
+                http,
+                &format!(
+                    "I couldn't lock <#{channel_id}>. I likely need the 'Manage Roles' permission there."
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if let Some(expires_at) = expires_at {
+        schedule_job(
+            &ctx.db,
+            &ChannelLockExpiry {
+                guild_id: guild_id.get(),
+                channel_id: channel_id.get(),
+            },
+            expires_at,
+            Some(&expiry_job_key(guild_id.get(), channel_id.get())),
+        )
+        .await?;
+    }
+
+    let case_reason = channel_case_reason(channel_id, reason);
+    report_channel_action(
+        &ctx,
+        invocation,
+        ChannelActionReport {
+            case: NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Lock,
+                user_id: None,
+                moderator_id,
+                reason: Some(&case_reason),
+                duration_secs: duration.as_ref().map(|duration| duration.secs),
+                expires_at,
+            },
+            channel_id,
+            channel_name: &channel_name,
+            action_past_tense: "locked",
+            reason,
+            duration_label: duration.as_ref().map(|duration| duration.label.as_str()),
+        },
+    )
+    .await
+}
+
+/// Fetch a channel over REST, or `None` if it doesn't exist in this guild.
+pub(crate) async fn fetch_guild_channel(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+) -> anyhow::Result<Option<Channel>> {
+    let channel = match ctx.http.channel(channel_id).await {
+        Ok(response) => response.model().await?,
+        Err(source) if is_not_found(&source) => return Ok(None),
+        Err(source) => return Err(source.into()),
+    };
+    Ok(Some(channel).filter(|channel| channel.guild_id == Some(guild_id)))
+}
+
+/// The channel's name, or its ID when Discord didn't send one.
+pub(crate) fn display_name(channel: &Channel) -> String {
+    channel
+        .name
+        .clone()
+        .unwrap_or_else(|| channel.id.to_string())
+}
+
+/// Job payload that unlocks a timed channel lock once it expires.
+#[derive(Serialize, Deserialize)]
+pub struct ChannelLockExpiry {
+    pub guild_id: u64,
+    pub channel_id: u64,
+}
+
+impl JobPayload for ChannelLockExpiry {
+    const KIND: &'static str = "channel-lock.expire";
+    const MAX_ATTEMPTS: u32 = 10;
+}
+
+fn expiry_job_key(guild_id: u64, channel_id: u64) -> String {
+    format!("channel-lock:{guild_id}:{channel_id}")
+}
+
+/// Forget a channel lock and its scheduled expiry without touching the channel.
+pub(crate) async fn forget_channel_lock(
+    ctx: &Context,
+    guild_id: u64,
+    channel_id: u64,
+) -> anyhow::Result<()> {
+    delete_channel_lock(&ctx.db, guild_id, channel_id).await?;
+    cancel_scheduled_job(&ctx.db, &expiry_job_key(guild_id, channel_id)).await?;
+    Ok(())
+}
+
+/// Put back a locked channel's saved overwrite and forget the lock.
+pub(crate) async fn unlock_channel(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    lock: &ChannelLock,
+    reason: Option<&str>,
+) -> anyhow::Result<()> {
+    let locked = LockedChannel {
+        channel_id: lock.channel_id,
+        previous: lock.previous,
+    };
+    restore_channel(
+        ctx,
+        guild_id,
+        &locked,
+        &audit_reason("Channel unlocked", reason),
+    )
+    .await?;
+    forget_channel_lock(ctx, guild_id.get(), lock.channel_id).await
+}
+
+/// Job handler for [`ChannelLockExpiry`].
+///
+/// Failed restores are retried by the job runner with backoff.
+pub async fn expire_channel_lock(ctx: Context, job: ChannelLockExpiry) -> anyhow::Result<()> {
+    let Some(lock) = channel_lock(&ctx.db, job.guild_id, job.channel_id).await? else {
+        // Unlocked by hand or taken over by a lockdown.
+        return Ok(());
+    };
+    let (Some(guild_id), Some(channel_id)) = (
+        Id::new_checked(lock.guild_id),
+        Id::<ChannelMarker>::new_checked(lock.channel_id),
+    ) else {
+        delete_channel_lock(&ctx.db, lock.guild_id, lock.channel_id).await?;
+        return Ok(());
+    };
+
+    unlock_channel(&ctx, guild_id, &lock, Some(EXPIRED_REASON)).await?;
+
+    let moderator_id = bot_user_id(&ctx).await;
+    let case_reason = channel_case_reason(channel_id, Some(EXPIRED_REASON));
+    let entry = log_case(
+        &ctx,
+        NewCase {
+            guild_id: lock.guild_id,
+            action: CaseAction::Unlock,
+            user_id: None,
+            moderator_id: moderator_id.unwrap_or(lock.moderator_id),
+            reason: Some(&case_reason),
+            duration_secs: None,
+            expires_at: None,
+        },
+    )
+    .await;
+    if let Some(entry) = entry {
+        publish_case(&ctx, &entry).await;
+    }
+
+    Ok(())
+}
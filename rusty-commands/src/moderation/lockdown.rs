@@ -0,0 +1,551 @@
+//! Server lockdown: raise the verification level and stop `@everyone` from
+//! talking in the guild's lockdown channels until a moderator lifts it.
+//!
+//! The overwrites and verification level in place beforehand are stored, so
+//! lifting the lockdown puts back exactly what was there.
+
+use tracing::{error, warn};
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    channel::{Channel, permission_overwrite::PermissionOverwriteType},
+    gateway::payload::incoming::MessageCreate,
+    guild::{Permissions, VerificationLevel},
+    http::permission_overwrite::{
+        PermissionOverwrite as HttpPermissionOverwrite,
+        PermissionOverwriteType as HttpPermissionOverwriteType,
+    },
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker},
+    },
+};
+use twilight_util::builder::command::{CommandBuilder, StringBuilder};
+
+use crate::moderation::case_log::{log_case, publish_case};
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::lock::forget_channel_lock;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::channel_locks::channel_lock;
+use rusty_database::impls::lockdowns::{
+    active_lockdown, add_lockdown_channel, end_lockdown, remove_lockdown_channel, start_lockdown,
+};
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_database::model::lockdowns::{Lockdown, LockedChannel, NewLockdown, OverwriteBits};
+use rusty_utils::http_error::is_not_found;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::option_string;
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "lockdown",
+    desc: "Lock the server down: raise verification and silence the lockdown channels.",
+    category: "moderation",
+    usage: "!lockdown [reason]",
+    cooldown: None,
+};
+
+/// What `@everyone` loses in a locked channel.
+pub(crate) const LOCKED_PERMISSIONS: Permissions =
+    Permissions::SEND_MESSAGES.union(Permissions::SEND_MESSAGES_IN_THREADS);
+/// Members must have been in the server for ten minutes before talking.
+const LOCKDOWN_VERIFICATION_LEVEL: VerificationLevel = VerificationLevel::High;
+
+pub struct LockdownCommand;
+
+impl Command for LockdownCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/lockdown`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .contexts([InteractionContextType::Guild])
+        .option(StringBuilder::new("reason", "Reason for the lockdown."))
+        .build()
+}
+
+/// Start a lockdown by hand.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Start a lockdown from the `/lockdown` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let reason = option_string(data, "reason").map(str::to_owned);
+    execute(ctx, &invocation, Ok(reason)).await
+}
+
+fn parse_args(raw: &str) -> Result<Option<String>, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    Ok(args.rest())
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    reason: Result<Option<String>, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_GUILD).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let reason = match reason {
+        Ok(reason) => reason,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let moderator_id = invocation.author_id().get();
+    let Some(outcome) = engage_lockdown(&ctx, guild_id, moderator_id, reason.as_deref()).await?
+    else {
+        let lockdown = active_lockdown(&ctx.db, guild_id.get()).await?;
+        let since = lockdown.map_or_else(String::new, |lockdown| {
+            format!(" since <t:{}:R>", lockdown.created_at)
+        });
+        invocation
+            .reply_content(
+                http,
+                &format!("This server has been in lockdown{since}. Use `!unlock` to lift it."),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    invocation
+        .reply_content(http, &outcome.summary("Server locked down."))
+        .await?;
+
+    Ok(())
+}
+
+/// What starting a lockdown managed to change.
+pub(crate) struct LockdownOutcome {
+    pub verification_raised: bool,
+    pub locked: Vec<Id<ChannelMarker>>,
+    pub failed: Vec<Id<ChannelMarker>>,
+    pub no_channels_configured: bool,
+}
+
+impl LockdownOutcome {
+    pub(crate) fn summary(&self, headline: &str) -> String {
+        let mut lines = vec![headline.to_owned()];
+        if self.verification_raised {
+            lines.push("Verification level raised to high.".to_owned());
+        }
+        if !self.locked.is_empty() {
+            lines.push(format!("Locked: {}", channel_list(&self.locked)));
+        }
+        if !self.failed.is_empty() {
+            lines.push(format!(
+                "Couldn't lock: {}. I likely need the 'Manage Roles' permission there.",
+                channel_list(&self.failed)
+            ));
+        }
+        if self.no_channels_configured {
+            lines.push(
+                "No lockdown channels are configured; set them with \
+                 `!config set lockdown-channels <channels>`."
+                    .to_owned(),
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+fn channel_list(channels: &[Id<ChannelMarker>]) -> String {
+    channels
+        .iter()
+        .map(|channel_id| format!("<#{channel_id}>"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Start a lockdown, returning `None` if the guild is already in one.
+///
+/// Each channel's previous `@everyone` overwrite is stored before it is
+/// changed, so a crash part-way through never loses what has to be restored.
+pub(crate) async fn engage_lockdown(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    moderator_id: u64,
+    reason: Option<&str>,
+) -> anyhow::Result<Option<LockdownOutcome>> {
+    let http = &ctx.http;
+    let settings = ctx.guild_settings(guild_id.get()).await?;
+    let guild = http.guild(guild_id).await?.model().await?;
+    let previous_level = u8::from(guild.verification_level);
+    let raise_verification = previous_level < u8::from(LOCKDOWN_VERIFICATION_LEVEL);
+
+    let started = start_lockdown(
+        &ctx.db,
+        NewLockdown {
+            guild_id: guild_id.get(),
+            moderator_id,
+            reason,
+            previous_verification_level: raise_verification.then_some(previous_level),
+        },
+    )
+    .await?;
+    if !started {
+        return Ok(None);
+    }
+
+    let audit_reason = audit_reason("Lockdown", reason);
+    let mut outcome = LockdownOutcome {
+        verification_raised: false,
+        locked: Vec::new(),
+        failed: Vec::new(),
+        no_channels_configured: settings.lockdown_channel_ids.is_empty(),
+    };
+
+    if raise_verification {
+        match http
+            .update_guild(guild_id)
+            .verification_level(Some(LOCKDOWN_VERIFICATION_LEVEL))
+            .reason(&audit_reason)
+            .await
+        {
+            Ok(_) => outcome.verification_raised = true,
+            Err(source) => warn!(
+                ?source,
+                guild_id = guild_id.get(),
+                "raising verification failed"
+            ),
+        }
+    }
+
+    for channel_id in settings.lockdown_channel_ids.iter().copied().map(Id::new) {
+        match lock_channel(ctx, guild_id, channel_id, &audit_reason).await {
+            Ok(()) => outcome.locked.push(channel_id),
+            Err(source) => {
+                warn!(
+                    ?source,
+                    channel_id = channel_id.get(),
+                    "locking channel failed"
+                );
+                outcome.failed.push(channel_id);
+            }
+        }
+    }
+
+    let entry = log_case(
+        ctx,
+        NewCase {
+            guild_id: guild_id.get(),
+            action: CaseAction::Lockdown,
+            user_id: None,
+            moderator_id,
+            reason,
+            duration_secs: None,
+            expires_at: None,
+        },
+    )
+    .await;
+    if let Some(entry) = entry {
+        publish_case(ctx, &entry).await;
+    }
+
+    Ok(Some(outcome))
+}
+
+async fn lock_channel(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    audit_reason: &str,
+) -> anyhow::Result<()> {
+    // A channel locked by hand hands its saved overwrite over to the lockdown,
+    // so lifting the lockdown restores what it had before either lock
+    let held = channel_lock(&ctx.db, guild_id.get(), channel_id.get()).await?;
+    let previous = match &held {
+        Some(lock) => lock.previous,
+        None => {
+            // Fetched fresh so the stored overwrite is exactly what Discord has
+            let channel = ctx.http.channel(channel_id).await?.model().await?;
+            if channel.guild_id != Some(guild_id) {
+                anyhow::bail!("channel is not in this guild");
+            }
+            everyone_bits(&channel, guild_id)
+        }
+    };
+    add_lockdown_channel(
+        &ctx.db,
+        guild_id.get(),
+        LockedChannel {
+            channel_id: channel_id.get(),
+            previous,
+        },
+    )
+    .await?;
+
+    let result = ctx
+        .http
+        .update_channel_permission(channel_id, &locked_overwrite(guild_id, previous))
+        .reason(audit_reason)
+        .await;
+    if let Err(source) = result {
+        remove_lockdown_channel(&ctx.db, guild_id.get(), channel_id.get()).await?;
+        return Err(source.into());
+    }
+
+    if held.is_some() {
+        forget_channel_lock(ctx, guild_id.get(), channel_id.get()).await?;
+    }
+
+    Ok(())
+}
+
+/// The `@everyone` overwrite a channel currently has, if any.
+pub(crate) fn everyone_bits(channel: &Channel, guild_id: Id<GuildMarker>) -> Option<OverwriteBits> {
+    channel
+        .permission_overwrites
+        .iter()
+        .flatten()
+        .find(|overwrite| {
+            overwrite.kind == PermissionOverwriteType::Role && overwrite.id == guild_id.cast()
+        })
+        .map(|overwrite| OverwriteBits {
+            allow: overwrite.allow.bits(),
+            deny: overwrite.deny.bits(),
+        })
+}
+
+/// The `@everyone` overwrite of a locked channel: what it had before, minus
+/// the right to talk.
+pub(crate) fn locked_overwrite(
+    guild_id: Id<GuildMarker>,
+    previous: Option<OverwriteBits>,
+) -> HttpPermissionOverwrite {
+    let (allow, deny) = previous.map_or((Permissions::empty(), Permissions::empty()), |bits| {
+        (
+            Permissions::from_bits_retain(bits.allow),
+            Permissions::from_bits_retain(bits.deny),
+        )
+    });
+    everyone_overwrite(
+        guild_id,
+        allow - LOCKED_PERMISSIONS,
+        deny | LOCKED_PERMISSIONS,
+    )
+}
+
+/// What lifting a lockdown managed to restore.
+pub(crate) struct LiftOutcome {
+    pub verification_restored: bool,
+    pub restored: Vec<Id<ChannelMarker>>,
+    pub failed: Vec<Id<ChannelMarker>>,
+}
+
+impl LiftOutcome {
+    pub(crate) fn summary(&self) -> String {
+        let mut lines = vec![if self.failed.is_empty() {
+            "Lockdown lifted.".to_owned()
+        } else {
+            "Lockdown partly lifted.".to_owned()
+        }];
+        if self.verification_restored {
+            lines.push("Verification level restored.".to_owned());
+        }
+        if !self.restored.is_empty() {
+            lines.push(format!("Unlocked: {}", channel_list(&self.restored)));
+        }
+        if !self.failed.is_empty() {
+            lines.push(format!(
+                "Couldn't unlock: {}. Fix my permissions there and run `!unlock` again.",
+                channel_list(&self.failed)
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Lift a guild's lockdown, returning `None` if it has none.
+///
+/// Channels that can't be restored stay recorded and keep the lockdown
+/// active, so running this again retries just those.
+pub(crate) async fn lift_lockdown(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    moderator_id: u64,
+    reason: Option<&str>,
+) -> anyhow::Result<Option<LiftOutcome>> {
+    let Some(lockdown) = active_lockdown(&ctx.db, guild_id.get()).await? else {
+        return Ok(None);
+    };
+
+    let audit_reason = audit_reason("Lockdown lifted", reason);
+    let mut outcome = LiftOutcome {
+        verification_restored: false,
+        restored: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for locked in &lockdown.channels {
+        let channel_id = Id::new(locked.channel_id);
+        match restore_channel(ctx, guild_id, locked, &audit_reason).await {
+            Ok(()) => {
+                remove_lockdown_channel(&ctx.db, guild_id.get(), locked.channel_id).await?;
+                outcome.restored.push(channel_id);
+            }
+            Err(source) => {
+                warn!(
+                    ?source,
+                    channel_id = locked.channel_id,
+                    "unlocking channel failed"
+                );
+                outcome.failed.push(channel_id);
+            }
+        }
+    }
+
+    let verification_ok = restore_verification(ctx, guild_id, &lockdown, &audit_reason).await;
+    outcome.verification_restored =
+        verification_ok && lockdown.previous_verification_level.is_some();
+
+    if outcome.failed.is_empty() && verification_ok {
+        end_lockdown(&ctx.db, guild_id.get()).await?;
+
+        let entry = log_case(
+            ctx,
+            NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::LiftLockdown,
+                user_id: None,
+                moderator_id,
+                reason,
+                duration_secs: None,
+                expires_at: None,
+            },
+        )
+        .await;
+        if let Some(entry) = entry {
+            publish_case(ctx, &entry).await;
+        }
+    }
+
+    Ok(Some(outcome))
+}
+
+/// Put a channel's `@everyone` overwrite back as it was, removing it if the
+/// channel had none. Deleted channels have nothing left to restore.
+pub(crate) async fn restore_channel(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    locked: &LockedChannel,
+    audit_reason: &str,
+) -> anyhow::Result<()> {
+    let channel_id = Id::new(locked.channel_id);
+    let result = match locked.previous {
+        Some(bits) => {
+            let overwrite = everyone_overwrite(
+                guild_id,
+                Permissions::from_bits_retain(bits.allow),
+                Permissions::from_bits_retain(bits.deny),
+            );
+            ctx.http
+                .update_channel_permission(channel_id, &overwrite)
+                .reason(audit_reason)
+                .await
+                .map(drop)
+        }
+        None => ctx
+            .http
+            .delete_channel_permission(channel_id)
+            .role(guild_id.cast())
+            .reason(audit_reason)
+            .await
+            .map(drop),
+    };
+
+    match result {
+        Err(source) if !is_not_found(&source) => Err(source.into()),
+        _ => Ok(()),
+    }
+}
+
+async fn restore_verification(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    lockdown: &Lockdown,
+    audit_reason: &str,
+) -> bool {
+    let Some(level) = lockdown.previous_verification_level else {
+        return true;
+    };
+
+    match ctx
+        .http
+        .update_guild(guild_id)
+        .verification_level(Some(VerificationLevel::from(level)))
+        .reason(audit_reason)
+        .await
+    {
+        Ok(_) => true,
+        Err(source) => {
+            error!(
+                ?source,
+                guild_id = guild_id.get(),
+                "restoring verification failed"
+            );
+            false
+        }
+    }
+}
+
+pub(crate) fn everyone_overwrite(
+    guild_id: Id<GuildMarker>,
+    allow: Permissions,
+    deny: Permissions,
+) -> HttpPermissionOverwrite {
+    HttpPermissionOverwrite {
+        allow: Some(allow),
+        deny: Some(deny),
+        id: guild_id.cast(),
+        kind: HttpPermissionOverwriteType::Role,
+    }
+}
+
+pub(crate) fn audit_reason(action: &str, reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => format!("{action}: {reason}"),
+        None => action.to_owned(),
+    }
+}
@@ -1,11 +1,33 @@
+mod args;
+pub mod automod;
 pub mod ban;
+pub mod case;
+pub(crate) mod case_log;
+pub mod cases;
+pub mod clearwarns;
+pub mod config;
+pub mod delwarn;
+pub mod editwarn;
 mod embeds;
+pub(crate) mod escalation;
 pub mod kick;
+pub mod lock;
+pub mod lockdown;
+pub mod modlog;
+pub mod nuke_channel;
+pub mod overrides;
 pub mod permissions;
+pub mod protectedroles;
 pub mod purge;
+pub mod reason;
+pub mod slowmode;
+pub(crate) mod targets;
+pub mod tempban;
 pub mod terminate;
 pub mod timeout;
 pub mod unban;
+pub mod unlock;
 pub mod untimeout;
 pub mod warn;
 pub mod warnings;
+pub mod warnpolicy;
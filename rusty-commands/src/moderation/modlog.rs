@@ -0,0 +1,181 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    channel::ChannelType,
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::ChannelMarker},
+};
+use twilight_util::builder::command::{BooleanBuilder, ChannelBuilder, CommandBuilder};
+
+use crate::moderation::case_log::load_modlog_settings;
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_bool, option_channel};
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "modlog",
+    desc: "Show or configure the moderation log channel.",
+    category: "moderation",
+    usage: "!modlog [channel|off] [--quiet on|off]",
+    cooldown: None,
+};
+
+pub struct ModlogCommand;
+
+impl Command for ModlogCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/modlog`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            ChannelBuilder::new("channel", "Channel to publish moderation actions to.")
+                .channel_types([ChannelType::GuildText]),
+        )
+        .option(BooleanBuilder::new(
+            "disable",
+            "Stop publishing moderation actions.",
+        ))
+        .option(BooleanBuilder::new(
+            "quiet",
+            "Keep replies in the invoking channel short.",
+        ))
+        .build()
+}
+
+/// Show or update mod-log settings.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Show or update mod-log settings from the `/modlog` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let channel = if option_bool(data, "disable").unwrap_or(false) {
+        Some(ChannelChange::Disable)
+    } else {
+        option_channel(data, "channel").map(ChannelChange::Set)
+    };
+    let args = ModlogArgs {
+        channel,
+        quiet_replies: option_bool(data, "quiet"),
+    };
+
+    execute(ctx, &invocation, Ok(args)).await
+}
+
+enum ChannelChange {
+    Set(Id<ChannelMarker>),
+    Disable,
+}
+
+struct ModlogArgs {
+    channel: Option<ChannelChange>,
+    quiet_replies: Option<bool>,
+}
+
+fn parse_args(raw: &str) -> Result<ModlogArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let quiet_replies = args.flag("quiet")?;
+
+    let channel = if args.keyword("off") {
+        Some(ChannelChange::Disable)
+    } else if args.is_empty() {
+        None
+    } else {
+        Some(ChannelChange::Set(args.required("channel")?))
+    };
+    args.finish()?;
+
+    Ok(ModlogArgs {
+        channel,
+        quiet_replies,
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<ModlogArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_GUILD).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let ModlogArgs {
+        channel,
+        quiet_replies,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    if channel.is_some() || quiet_replies.is_some() {
+        ctx.update_guild_settings(guild_id.get(), |settings| {
+            match channel {
+                Some(ChannelChange::Set(channel_id)) => {
+                    settings.modlog_channel_id = Some(channel_id.get());
+                }
+                Some(ChannelChange::Disable) => settings.modlog_channel_id = None,
+                None => {}
+            }
+            if let Some(quiet_replies) = quiet_replies {
+                settings.modlog_quiet_replies = quiet_replies;
+            }
+        })
+        .await?;
+    }
+
+    let settings = load_modlog_settings(&ctx, guild_id.get()).await;
+    let channel_text = settings.channel_id.map_or_else(
+        || "not set".to_owned(),
+        |channel_id| format!("<#{channel_id}>"),
+    );
+    let quiet_text = if settings.quiet_replies { "on" } else { "off" };
+    let summary = format!("Mod-log channel: {channel_text}\nQuiet replies: {quiet_text}");
+
+    invocation.reply_content(http, &summary).await?;
+
+    Ok(())
+}
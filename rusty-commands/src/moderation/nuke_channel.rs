@@ -0,0 +1,552 @@
+//! `!nuke-channel`: replace a channel with a fresh copy, wiping its history.
+//!
+//! The copy keeps the channel's name, place, topic and permissions. Like
+//! `!terminate`, nothing happens until the moderator confirms the prompt.
+
+use tracing::error;
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, InteractionData, application_command::CommandData},
+    },
+    channel::{Channel, ChannelType},
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker},
+    },
+};
+use twilight_util::builder::command::{ChannelBuilder, CommandBuilder, StringBuilder};
+
+use crate::moderation::case_log::{channel_case_reason, load_embed_color, log_case, publish_case};
+use crate::moderation::embeds::{
+    channel_action_embed, guild_only_message, moderation_invalid_interaction_message,
+    permission_denied_message, queued_reason, with_case_footer,
+};
+use crate::moderation::lock::{channel_arg, display_name, fetch_guild_channel};
+use crate::moderation::lockdown::audit_reason;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::automod::move_automod_channel_exemptions;
+use rusty_database::impls::channel_locks::channel_lock;
+use rusty_database::impls::command_overrides::move_channel_overrides;
+use rusty_database::impls::lockdowns::active_lockdown;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::interaction::{
+    ConfirmationAction, build_confirmation_components, build_confirmation_custom_ids,
+    edit_original_response_content_embed_without_components, parse_confirmation_custom_id,
+    respond_ephemeral_notice, respond_update_content_embed_without_components,
+    respond_update_without_components,
+};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_channel, option_string};
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::{check_interaction_permissions, has_invocation_permission};
+
+pub const META: CommandMeta = CommandMeta {
+    name: "nuke-channel",
+    desc: "Delete every message in a channel by replacing it with a fresh copy.",
+    category: "moderation",
+    usage: "!nuke-channel [channel] [reason]",
+    cooldown: None,
+};
+
+const CUSTOM_ID_PREFIX: &str = "nuke-channel:";
+
+pub struct NukeChannelCommand;
+
+impl Command for NukeChannelCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+
+    fn component_prefixes(&self) -> &'static [&'static str] {
+        &[CUSTOM_ID_PREFIX]
+    }
+
+    fn handle_component(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+    ) -> CommandFuture<bool> {
+        Box::pin(handle_interaction(ctx, interaction))
+    }
+}
+
+/// Application command definition for `/nuke-channel`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_CHANNELS)
+        .contexts([InteractionContextType::Guild])
+        .option(ChannelBuilder::new(
+            "channel",
+            "Channel to nuke (default: this one).",
+        ))
+        .option(StringBuilder::new("reason", "Reason for the nuke."))
+        .build()
+}
+
+/// Queue a channel nuke behind a moderator confirmation prompt.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Queue a channel nuke from the `/nuke-channel` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let args = NukeArgs {
+        channel_id: option_channel(data, "channel"),
+        reason: option_string(data, "reason").map(ToOwned::to_owned),
+    };
+    execute(ctx, &invocation, Ok(args)).await
+}
+
+struct NukeArgs {
+    channel_id: Option<Id<ChannelMarker>>,
+    reason: Option<String>,
+}
+
+fn parse_args(raw: &str) -> Result<NukeArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let channel_id = channel_arg(&mut args);
+
+    Ok(NukeArgs {
+        channel_id,
+        reason: args.rest(),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<NukeArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_CHANNELS).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let NukeArgs { channel_id, reason } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+    let channel_id = channel_id.unwrap_or_else(|| invocation.channel_id());
+
+    let Some(channel) = fetch_guild_channel(&ctx, guild_id, channel_id).await? else {
+        invocation
+            .reply_content(http, &format!("<#{channel_id}> isn't in this server."))
+            .await?;
+        return Ok(());
+    };
+    if let Some(refusal) = nuke_refusal(&ctx, guild_id, &channel).await? {
+        invocation.reply_content(http, &refusal).await?;
+        return Ok(());
+    }
+
+    let (confirm_custom_id, decline_custom_id) = build_confirmation_custom_ids(
+        CUSTOM_ID_PREFIX,
+        invocation.author_id().get(),
+        channel_id.get(),
+        None,
+    );
+
+    let components = build_confirmation_components(confirm_custom_id, decline_custom_id);
+    let color = load_embed_color(&ctx, guild_id.get()).await;
+    let confirmation = channel_action_embed(
+        &display_name(&channel),
+        channel_id,
+        "queued for nuking",
+        reason.as_deref(),
+        None,
+        color,
+    )?;
+    let confirmation_text = format!(
+        "Clone and delete pending moderator confirmation.\nEvery message in <#{channel_id}> will be lost."
+    );
+
+    invocation
+        .reply_message(http, Some(&confirmation_text), &[confirmation], &components)
+        .await?;
+
+    Ok(())
+}
+
+/// Why a channel can't be nuked, if it can't.
+async fn nuke_refusal(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel: &Channel,
+) -> anyhow::Result<Option<String>> {
+    let channel_id = channel.id;
+    if !matches!(
+        channel.kind,
+        ChannelType::GuildText
+            | ChannelType::GuildAnnouncement
+            | ChannelType::GuildVoice
+            | ChannelType::GuildStageVoice
+    ) {
+        return Ok(Some(
+            "Only text, announcement, voice and stage channels can be nuked.".to_owned(),
+        ));
+    }
+
+    // The copy would inherit the locked overwrite with nothing left to restore it
+    let in_lockdown = active_lockdown(&ctx.db, guild_id.get())
+        .await?
+        .is_some_and(|lockdown| {
+            lockdown
+                .channels
+                .iter()
+                .any(|locked| locked.channel_id == channel_id.get())
+        });
+    let locked = channel_lock(&ctx.db, guild_id.get(), channel_id.get())
+        .await?
+        .is_some();
+    if in_lockdown || locked {
+        return Ok(Some(format!(
+            "<#{channel_id}> is locked. Unlock it before nuking it."
+        )));
+    }
+
+    Ok(None)
+}
+
+pub async fn handle_interaction(
+    ctx: Context,
+    interaction: Box<InteractionCreate>,
+) -> anyhow::Result<bool> {
+    let http = &ctx.http;
+
+    let Some(InteractionData::MessageComponent(component_data)) = interaction.data.as_ref() else {
+        return Ok(false);
+    };
+
+    if !component_data.custom_id.starts_with(CUSTOM_ID_PREFIX) {
+        return Ok(false);
+    }
+
+    let Some(parsed) = parse_confirmation_custom_id(&component_data.custom_id, CUSTOM_ID_PREFIX)
+    else {
+        let invalid = moderation_invalid_interaction_message("nuke-channel");
+        respond_update_without_components(http, &interaction, &invalid).await?;
+        return Ok(true);
+    };
+
+    let Some(actor_id) = interaction.author_id().map(|id| id.get()) else {
+        respond_ephemeral_notice(http, &interaction, "Unable to determine interaction user.")
+            .await?;
+        return Ok(true);
+    };
+
+    if actor_id != parsed.requester_id {
+        respond_ephemeral_notice(
+            http,
+            &interaction,
+            "Only the user who initiated this nuke can confirm it.",
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    let Some(guild_id) = interaction.guild_id else {
+        respond_ephemeral_notice(http, &interaction, guild_only_message()).await?;
+        return Ok(true);
+    };
+
+    if !check_interaction_permissions(&interaction, Permissions::MANAGE_CHANNELS) {
+        respond_ephemeral_notice(
+            http,
+            &interaction,
+            "You no longer have the required permissions for this action.",
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    let channel_id = Id::<ChannelMarker>::new(parsed.target_id);
+    let Some(channel) = fetch_guild_channel(&ctx, guild_id, channel_id).await? else {
+        respond_update_without_components(http, &interaction, "That channel no longer exists.")
+            .await?;
+        return Ok(true);
+    };
+    let channel_name = display_name(&channel);
+    let reason = queued_reason(&interaction);
+    let color = load_embed_color(&ctx, guild_id.get()).await;
+
+    match parsed.action {
+        ConfirmationAction::Decline => {
+            let cancelled_embed = channel_action_embed(
+                &channel_name,
+                channel_id,
+                "left unchanged",
+                Some("Nuke cancelled."),
+                None,
+                color,
+            )?;
+            respond_update_content_embed_without_components(
+                http,
+                &interaction,
+                "Nuke cancelled.",
+                &cancelled_embed,
+            )
+            .await?;
+            return Ok(true);
+        }
+        ConfirmationAction::Confirm => {
+            // A lock may have been placed since the prompt was posted
+            if let Some(refusal) = nuke_refusal(&ctx, guild_id, &channel).await? {
+                respond_update_without_components(http, &interaction, &refusal).await?;
+                return Ok(true);
+            }
+
+            let loading_embed = channel_action_embed(
+                &channel_name,
+                channel_id,
+                "queued for nuking",
+                Some("Nuke in progress."),
+                None,
+                color,
+            )?;
+            respond_update_content_embed_without_components(
+                http,
+                &interaction,
+                "Nuking...",
+                &loading_embed,
+            )
+            .await?;
+        }
+    }
+
+    let audit_reason = audit_reason("Channel nuked", reason.as_deref());
+    let replacement = match clone_channel(&ctx, guild_id, &channel, &audit_reason).await {
+        Ok(replacement) => replacement,
+        Err(source) => {
+            error!(?source, "nuke clone failed");
+            let failed_embed = channel_action_embed(
+                &channel_name,
+                channel_id,
+                "left unchanged",
+                Some("Cloning failed. Check my permissions."),
+                None,
+                color,
+            )?;
+            edit_original_response_content_embed_without_components(
+                http,
+                &interaction,
+                "I couldn't clone the channel. I likely need the 'Manage Channels' permission.",
+                &failed_embed,
+            )
+            .await?;
+            return Ok(true);
+        }
+    };
+
+    if let Err(source) = http.delete_channel(channel_id).reason(&audit_reason).await {
+        error!(?source, "nuke delete failed");
+        // Don't leave a duplicate behind
+        if let Err(source) = http
+            .delete_channel(replacement.id)
+            .reason(&audit_reason)
+            .await
+        {
+            error!(?source, "removing nuke clone failed");
+        }
+        let failed_embed = channel_action_embed(
+            &channel_name,
+            channel_id,
+            "left unchanged",
+            Some("Deleting the channel failed. Check my permissions."),
+            None,
+            color,
+        )?;
+        edit_original_response_content_embed_without_components(
+            http,
+            &interaction,
+            "I couldn't delete the channel. I likely need the 'Manage Channels' permission.",
+            &failed_embed,
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    repoint_channel_references(&ctx, guild_id, channel_id, replacement.id).await;
+
+    let case_reason = channel_case_reason(replacement.id, reason.as_deref());
+    let entry = log_case(
+        &ctx,
+        NewCase {
+            guild_id: guild_id.get(),
+            action: CaseAction::Nuke,
+            user_id: None,
+            moderator_id: actor_id,
+            reason: Some(&case_reason),
+            duration_secs: None,
+            expires_at: None,
+        },
+    )
+    .await;
+    if let Some(entry) = &entry {
+        publish_case(&ctx, entry).await;
+    }
+
+    let mut success_embed = channel_action_embed(
+        &channel_name,
+        replacement.id,
+        "nuked",
+        reason.as_deref(),
+        None,
+        color,
+    )?;
+    if let Some(entry) = &entry {
+        success_embed = with_case_footer(success_embed, entry.case_number);
+    }
+
+    // The prompt went down with the channel, so report in the replacement
+    let prompt_channel_id = interaction.channel.as_ref().map(|channel| channel.id);
+    if prompt_channel_id == Some(channel_id) {
+        http.create_message(replacement.id)
+            .embeds(&[success_embed])
+            .await?;
+    } else {
+        edit_original_response_content_embed_without_components(
+            http,
+            &interaction,
+            &format!("Channel recreated as <#{}>.", replacement.id),
+            &success_embed,
+        )
+        .await?;
+    }
+
+    Ok(true)
+}
+
+/// Create a copy of `channel` with its settings but none of its messages.
+async fn clone_channel(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel: &Channel,
+    audit_reason: &str,
+) -> anyhow::Result<Channel> {
+    let name = display_name(channel);
+    let mut request = ctx
+        .http
+        .create_guild_channel(guild_id, &name)
+        .kind(channel.kind)
+        .permission_overwrites(channel.permission_overwrites.as_deref().unwrap_or_default());
+    if let Some(parent_id) = channel.parent_id {
+        request = request.parent_id(parent_id);
+    }
+    if let Some(position) = channel
+        .position
+        .and_then(|position| u64::try_from(position).ok())
+    {
+        request = request.position(position);
+    }
+    if let Some(topic) = channel.topic.as_deref() {
+        request = request.topic(topic);
+    }
+    if let Some(nsfw) = channel.nsfw {
+        request = request.nsfw(nsfw);
+    }
+    if let Some(rate_limit) = channel.rate_limit_per_user {
+        request = request.rate_limit_per_user(rate_limit);
+    }
+    if let Some(duration) = channel.default_auto_archive_duration {
+        request = request.default_auto_archive_duration(duration);
+    }
+    if let Some(bitrate) = channel.bitrate {
+        request = request.bitrate(bitrate);
+    }
+    if let Some(user_limit) = channel
+        .user_limit
+        .and_then(|limit| u16::try_from(limit).ok())
+    {
+        request = request.user_limit(user_limit);
+    }
+    if let Some(region) = channel.rtc_region.as_deref() {
+        request = request.rtc_region(region);
+    }
+    if let Some(quality) = channel.video_quality_mode {
+        request = request.video_quality_mode(quality);
+    }
+
+    Ok(request.reason(audit_reason).await?.model().await?)
+}
+
+/// Point settings, channel command overrides and automod exemptions that named
+/// the old channel at its replacement.
+async fn repoint_channel_references(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    old_id: Id<ChannelMarker>,
+    new_id: Id<ChannelMarker>,
+) {
+    let (old_id, new_id) = (old_id.get(), new_id.get());
+    if let Err(source) = move_channel_overrides(&ctx.db, guild_id.get(), old_id, new_id).await {
+        error!(?source, "moving command overrides after nuke failed");
+    }
+    match move_automod_channel_exemptions(&ctx.db, guild_id.get(), old_id, new_id).await {
+        Ok(0) => {}
+        Ok(_) => ctx.automod.invalidate(guild_id.get()),
+        Err(source) => error!(?source, "moving automod exemptions after nuke failed"),
+    }
+
+    let settings = match ctx.guild_settings(guild_id.get()).await {
+        Ok(settings) => settings,
+        Err(source) => {
+            error!(?source, "loading settings after nuke failed");
+            return;
+        }
+    };
+    if settings.modlog_channel_id != Some(old_id)
+        && !settings.lockdown_channel_ids.contains(&old_id)
+    {
+        return;
+    }
+
+    let result = ctx
+        .update_guild_settings(guild_id.get(), |settings| {
+            if settings.modlog_channel_id == Some(old_id) {
+                settings.modlog_channel_id = Some(new_id);
+            }
+            for channel_id in &mut settings.lockdown_channel_ids {
+                if *channel_id == old_id {
+                    *channel_id = new_id;
+                }
+            }
+        })
+        .await;
+    if let Err(source) = result {
+        error!(?source, "updating settings after nuke failed");
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::BTreeMap;
+
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::{Permissions, Role},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+    },
+};
+use twilight_util::builder::command::{
+    ChannelBuilder, CommandBuilder, RoleBuilder, StringBuilder, UserBuilder,
+};
+
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta, find_command};
+use rusty_core::Context;
+use rusty_database::impls::command_overrides::{
+    clear_command_overrides, command_overrides, remove_command_override, set_command_override,
+};
+use rusty_database::model::command_overrides::{CommandOverride, OverrideMode, OverrideTarget};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_channel, option_role, option_string, option_user};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg};
+use rusty_utils::permissions::{guild_channel, guild_roles, has_invocation_permission};
+
+pub const META: CommandMeta = CommandMeta {
+    name: "overrides",
+    desc: "Grant, deny or disable commands for roles, users and channels.",
+    category: "moderation",
+    usage: "!overrides [list [command]|allow <command> <target>|deny <command> <target>|clear <command> [target]|disable <command>|enable <command>]",
+    cooldown: None,
+};
+
+/// Keeps the overview inside Discord's message length limit.
+const MAX_LIST_LEN: usize = 1800;
+
+pub struct OverridesCommand;
+
+impl Command for OverridesCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/overrides`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .contexts([InteractionContextType::Guild])
+        .option(StringBuilder::new("action", "What to do.").choices([
+            ("List", "list"),
+            ("Allow", "allow"),
+            ("Deny", "deny"),
+            ("Clear", "clear"),
+            ("Disable", "disable"),
+            ("Enable", "enable"),
+        ]))
+        .option(StringBuilder::new(
+            "command",
+            "Name of the command to change.",
+        ))
+        .option(RoleBuilder::new("role", "Role the override applies to."))
+        .option(UserBuilder::new("user", "Member the override applies to."))
+        .option(ChannelBuilder::new(
+            "channel",
+            "Channel the override applies to.",
+        ))
+        .build()
+}
+
+/// List or change command overrides.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// List or change command overrides from the `/overrides` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+/// A registered command, by its canonical name.
+struct CommandName(&'static str);
+
+impl FromArg for CommandName {
+    const EXPECTED: &'static str = "the name of a command";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        let name = raw
+            .trim()
+            .trim_start_matches(['!', '/'])
+            .to_ascii_lowercase();
+        find_command(&name).map(|command| Self(command.meta().name))
+    }
+}
+
+/// Who an override applies to, before bare IDs are looked up.
+#[derive(Clone, Copy)]
+enum TargetArg {
+    Role(Id<RoleMarker>),
+    User(Id<UserMarker>),
+    Channel(Id<ChannelMarker>),
+    /// A bare ID that may name a role, channel or user.
+    Bare(u64),
+}
+
+impl FromArg for TargetArg {
+    const EXPECTED: &'static str = "a role, user or channel mention or ID";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.starts_with("<@&") {
+            Id::from_arg(raw).map(Self::Role)
+        } else if raw.starts_with("<#") {
+            Id::from_arg(raw).map(Self::Channel)
+        } else if raw.starts_with("<@") {
+            Id::from_arg(raw).map(Self::User)
+        } else {
+            Id::<UserMarker>::from_arg(raw).map(|id| Self::Bare(id.get()))
+        }
+    }
+}
+
+enum OverridesArgs {
+    List(Option<&'static str>),
+    Set {
+        command: &'static str,
+        target: TargetArg,
+        mode: OverrideMode,
+    },
+    Clear {
+        command: &'static str,
+        target: Option<TargetArg>,
+    },
+    Disable(&'static str),
+    Enable(&'static str),
+}
+
+fn parse_args(raw: &str) -> Result<OverridesArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+
+    let mode = if args.keyword("allow") {
+        Some(OverrideMode::Allow)
+    } else if args.keyword("deny") {
+        Some(OverrideMode::Deny)
+    } else {
+        None
+    };
+
+    let parsed = if let Some(mode) = mode {
+        let CommandName(command) = args.required("command")?;
+        OverridesArgs::Set {
+            command,
+            target: args.required("target")?,
+            mode,
+        }
+    } else if args.keyword("clear") {
+        let CommandName(command) = args.required("command")?;
+        OverridesArgs::Clear {
+            command,
+            target: match args.peek() {
+                Some(_) => Some(args.required("target")?),
+                None => None,
+            },
+        }
+    } else if args.keyword("disable") {
+        let CommandName(command) = args.required("command")?;
+        OverridesArgs::Disable(command)
+    } else if args.keyword("enable") {
+        let CommandName(command) = args.required("command")?;
+        OverridesArgs::Enable(command)
+    } else {
+        args.keyword("list");
+        match args.peek() {
+            Some(_) => {
+                let CommandName(command) = args.required("command")?;
+                OverridesArgs::List(Some(command))
+            }
+            None => OverridesArgs::List(None),
+        }
+    };
+    args.finish()?;
+
+    Ok(parsed)
+}
+
+fn slash_args(data: &CommandData) -> Result<OverridesArgs, ArgError> {
+    let command = option_string(data, "command")
+        .map(|raw| {
+            CommandName::from_arg(raw)
+                .map(|CommandName(name)| name)
+                .ok_or_else(|| ArgError::invalid::<CommandName>(META.usage, "command", raw))
+        })
+        .transpose()?;
+    let target = option_role(data, "role")
+        .map(TargetArg::Role)
+        .or_else(|| option_user(data, "user").map(TargetArg::User))
+        .or_else(|| option_channel(data, "channel").map(TargetArg::Channel));
+    let required_command = || command.ok_or_else(|| ArgError::missing(META.usage, "command"));
+    let required_target = || target.ok_or_else(|| ArgError::missing(META.usage, "target"));
+
+    Ok(match option_string(data, "action").unwrap_or("list") {
+        "allow" => OverridesArgs::Set {
+            command: required_command()?,
+            target: required_target()?,
+            mode: OverrideMode::Allow,
+        },
+        "deny" => OverridesArgs::Set {
+            command: required_command()?,
+            target: required_target()?,
+            mode: OverrideMode::Deny,
+        },
+        "clear" => OverridesArgs::Clear {
+            command: required_command()?,
+            target,
+        },
+        "disable" => OverridesArgs::Disable(required_command()?),
+        "enable" => OverridesArgs::Enable(required_command()?),
+        _ => OverridesArgs::List(command),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<OverridesArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_GUILD).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let args = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let changed_command = match &args {
+        OverridesArgs::List(_) => None,
+        OverridesArgs::Set { command, .. }
+        | OverridesArgs::Clear { command, .. }
+        | OverridesArgs::Disable(command)
+        | OverridesArgs::Enable(command) => Some(*command),
+    };
+    if changed_command == Some(META.name) {
+        invocation
+            .reply_content(http, "The overrides command can't be overridden.")
+            .await?;
+        return Ok(());
+    }
+
+    let roles = guild_roles(&ctx, guild_id).await?;
+    let guild = guild_id.get();
+
+    let reply = match args {
+        OverridesArgs::List(command) => {
+            let overrides = command_overrides(&ctx.db, guild, command).await?;
+            overrides_overview(&ctx, &roles, &overrides)
+        }
+        OverridesArgs::Set {
+            command,
+            target,
+            mode,
+        } => {
+            let target = resolve_target(&ctx, guild_id, &roles, target).await;
+            let entry = CommandOverride {
+                command: command.to_owned(),
+                target,
+                mode,
+            };
+            set_command_override(&ctx.db, guild, &entry, invocation.author_id().get()).await?;
+
+            let verb = match mode {
+                OverrideMode::Allow => "Allowed",
+                OverrideMode::Deny => "Denied",
+            };
+            format!(
+                "{verb} `{command}` {}.",
+                describe_target(&ctx, &roles, target)
+            )
+        }
+        OverridesArgs::Clear {
+            command,
+            target: Some(target),
+        } => {
+            let target = resolve_target(&ctx, guild_id, &roles, target).await;
+            let described = describe_target(&ctx, &roles, target);
+            if remove_command_override(&ctx.db, guild, command, target).await? {
+                format!("Removed the override of `{command}` {described}.")
+            } else {
+                format!("`{command}` had no override {described}.")
+            }
+        }
+        OverridesArgs::Clear {
+            command,
+            target: None,
+        } => {
+            let removed = clear_command_overrides(&ctx.db, guild, command).await?;
+            format!("Removed {removed} override(s) of `{command}`.")
+        }
+        OverridesArgs::Disable(command) => {
+            let entry = CommandOverride {
+                command: command.to_owned(),
+                target: OverrideTarget::Guild,
+                mode: OverrideMode::Deny,
+            };
+            set_command_override(&ctx.db, guild, &entry, invocation.author_id().get()).await?;
+            format!("Disabled `{command}` in this server.")
+        }
+        OverridesArgs::Enable(command) => {
+            if remove_command_override(&ctx.db, guild, command, OverrideTarget::Guild).await? {
+                format!("Enabled `{command}` in this server.")
+            } else {
+                format!("`{command}` is not disabled.")
+            }
+        }
+    };
+
+    invocation.reply_content(http, &reply).await?;
+
+    Ok(())
+}
+
+/// Look up what a bare ID refers to: a role, then a channel of this guild,
+/// and otherwise a user.
+async fn resolve_target(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    roles: &[Role],
+    target: TargetArg,
+) -> OverrideTarget {
+    match target {
+        TargetArg::Role(role_id) => OverrideTarget::Role(role_id.get()),
+        TargetArg::User(user_id) => OverrideTarget::User(user_id.get()),
+        TargetArg::Channel(channel_id) => OverrideTarget::Channel(channel_id.get()),
+        TargetArg::Bare(id) => {
+            if roles.iter().any(|role| role.id.get() == id) {
+                return OverrideTarget::Role(id);
+            }
+            let is_channel = guild_channel(ctx, Id::new(id))
+                .await
+                .is_ok_and(|channel| channel.guild_id == Some(guild_id));
+            if is_channel {
+                OverrideTarget::Channel(id)
+            } else {
+                OverrideTarget::User(id)
+            }
+        }
+    }
+}
+
+fn describe_target(ctx: &Context, roles: &[Role], target: OverrideTarget) -> String {
+    match target {
+        OverrideTarget::Guild => "in this server".to_owned(),
+        OverrideTarget::Role(role_id) => roles
+            .iter()
+            .find(|role| role.id.get() == role_id)
+            .map_or_else(
+                || format!("for deleted role ({role_id})"),
+                |role| format!("for **{}**", role.name.replace('@', "@\u{200B}")),
+            ),
+        OverrideTarget::User(user_id) => ctx.cache.user(Id::new(user_id)).map_or_else(
+            || format!("for user `{user_id}`"),
+            |user| format!("for **{}**", user.name.replace('@', "@\u{200B}")),
+        ),
+        OverrideTarget::Channel(channel_id) => format!("in <#{channel_id}>"),
+    }
+}
+
+fn overrides_overview(ctx: &Context, roles: &[Role], overrides: &[CommandOverride]) -> String {
+    if overrides.is_empty() {
+        return "No command overrides are configured.".to_owned();
+    }
+
+    let mut by_command: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for entry in overrides {
+        let line = match entry.target {
+            OverrideTarget::Guild => "disabled".to_owned(),
+            target => format!("{} {}", entry.mode, describe_target(ctx, roles, target)),
+        };
+        by_command.entry(&entry.command).or_default().push(line);
+    }
+
+    let mut overview = "Command overrides:".to_owned();
+    let total = by_command.len();
+    for (shown, (command, lines)) in by_command.into_iter().enumerate() {
+        let line = format!("\n`{command}`: {}", lines.join(", "));
+        if overview.len() + line.len() > MAX_LIST_LEN {
+            overview.push_str(&format!(
+                "\n…and {} more. Use `!overrides list <command>` to see one command.",
+                total - shown
+            ));
+            break;
+        }
+        overview.push_str(&line);
+    }
+    overview
+}
@@ -1,89 +1,253 @@
-use std::sync::Arc;
-use twilight_model::gateway::payload::incoming::{InteractionCreate, MessageCreate};
+use tracing::warn;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    channel::message::{Embed, component::Component},
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+    },
+};
+use twilight_util::builder::command::{
+    ChannelBuilder, CommandBuilder, IntegerBuilder, UserBuilder,
+};
 
-use crate::CommandMeta;
+use crate::cooldown::{Cooldown, CooldownScope};
+use crate::moderation::embeds::guild_only_message;
+use crate::registry::is_modal_submit;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_channel, option_integer, option_user};
+use rusty_utils::pagination::token::{from_base36, to_base36};
 use rusty_utils::pagination::{
     DEFAULT_TIMEOUT_SECS, PaginationInteractionValidation, PaginationModalSubmitValidation,
-    build_paginated_list_view, clamp_page, open_jump_modal_from_token, parse_one_based_page,
-    resolve_modal_target_page, respond_ephemeral_message, send_paginated_message, total_pages,
-    update_paginated_interaction_message, validate_interaction_for_command,
-    validate_jump_modal_for_command,
+    build_paginated_view, clamp_page, open_jump_modal_from_token, paginated_bulleted_description,
+    resolve_modal_target_page, respond_ephemeral_message, send_paginated_reply, total_pages,
+    update_paginated_interaction_message, validate_interaction_for_command_prefix,
+    validate_jump_modal_for_command_prefix,
 };
-use rusty_utils::permissions::{permission_names, resolve_message_author_permissions};
+use rusty_utils::parse::{ArgError, ArgParser, MemberArg};
+use rusty_utils::permissions::{guild_channel, member_channel_permissions, permission_names};
 
 pub const META: CommandMeta = CommandMeta {
     name: "permissions",
-    desc: "Display your server permissions.",
+    desc: "Display effective permissions in a channel, including overwrites.",
     category: "moderation",
-    usage: "!permissions [page]",
+    usage: "!permissions [channel] [member] [page]",
+    cooldown: Some(Cooldown::new(CooldownScope::User, 3, 15)),
 };
 
 const PERMISSIONS_PER_PAGE: usize = 10;
 
-/// Display invoking member permissions in a paginated embed.
-pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> anyhow::Result<()> {
-    let http = &ctx.http;
-    let perms = match resolve_message_author_permissions(http, &msg).await? {
-        Some(perms) => perms,
-        None => {
-            http.create_message(msg.channel_id)
-                .content("This command only works in servers.")
-                .await?;
-            return Ok(());
+/// Snowflakes are at least 17 digits long, so shorter numbers are page numbers.
+const MIN_SNOWFLAKE_DIGITS: usize = 17;
+
+/// Whose permissions to show, and where.
+///
+/// Carried in the pagination command key as `permissions|<channel>|<member>`
+/// with both IDs in base 36.
+#[derive(Clone, Copy)]
+struct PermissionsView {
+    channel_id: Id<ChannelMarker>,
+    user_id: Id<UserMarker>,
+}
+
+impl PermissionsView {
+    fn pagination_command(&self) -> String {
+        format!(
+            "{}|{}|{}",
+            META.name,
+            to_base36(self.channel_id.get()),
+            to_base36(self.user_id.get())
+        )
+    }
+
+    fn from_pagination_command(command: &str) -> Option<Self> {
+        let mut parts = command.split('|');
+        if parts.next()? != META.name {
+            return None;
         }
-    };
 
-    if perms.is_empty() {
-        http.create_message(msg.channel_id)
-            .content("No permissions found for your member record.")
-            .await?;
-        return Ok(());
+        let channel_id = Id::new_checked(from_base36(parts.next()?)?)?;
+        let user_id = Id::new_checked(from_base36(parts.next()?)?)?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            channel_id,
+            user_id,
+        })
     }
+}
 
-    let names = permission_names(perms);
+pub struct PermissionsCommand;
 
-    if names.is_empty() {
-        http.create_message(msg.channel_id)
-            .content("You have no permissions set.")
-            .await?;
-        return Ok(());
+impl Command for PermissionsCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+
+    fn component_prefixes(&self) -> &'static [&'static str] {
+        &["pg:permissions", "pgm:permissions"]
+    }
+
+    fn handle_component(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+    ) -> CommandFuture<bool> {
+        Box::pin(async move {
+            if is_modal_submit(&interaction) {
+                handle_pagination_modal_interaction(ctx, interaction).await
+            } else {
+                handle_pagination_interaction(ctx, interaction).await
+            }
+        })
     }
+}
+
+/// Application command definition for `/permissions`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .contexts([InteractionContextType::Guild])
+        .option(ChannelBuilder::new(
+            "channel",
+            "Channel to resolve permissions in (default: this one).",
+        ))
+        .option(UserBuilder::new(
+            "member",
+            "Member to resolve permissions for (default: you).",
+        ))
+        .option(IntegerBuilder::new("page", "Page to show (starts at 1).").min_value(1))
+        .build()
+}
+
+/// Display a member's channel permissions in a paginated embed.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Display channel permissions from the `/permissions` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let args = PermissionsArgs {
+        channel_id: option_channel(data, "channel"),
+        user_id: option_user(data, "member"),
+        page: option_integer(data, "page")
+            .and_then(|page| usize::try_from(page).ok())
+            .unwrap_or(1),
+    };
+
+    execute(ctx, &invocation, Ok(args)).await
+}
+
+struct PermissionsArgs {
+    channel_id: Option<Id<ChannelMarker>>,
+    user_id: Option<Id<UserMarker>>,
+    page: usize,
+}
+
+fn parse_args(raw: &str) -> Result<PermissionsArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
 
-    let total_pages = total_pages(names.len(), PERMISSIONS_PER_PAGE);
-    let Some(requested_page) = parse_one_based_page(arg1) else {
-        let usage = format!("Usage: `{}` (page starts at 1)", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
+    let is_page = |token: &str| {
+        token.len() < MIN_SNOWFLAKE_DIGITS && token.bytes().all(|byte| byte.is_ascii_digit())
+    };
+    let channel_id = if args.peek().is_some_and(is_page) {
+        None
+    } else {
+        args.optional::<Id<ChannelMarker>>()
+    };
+    let user_id = if args.peek().is_some_and(is_page) {
+        None
+    } else {
+        args.optional::<MemberArg>().map(|member| member.0)
+    };
+    let page = args.optional_integer("page", 1..=i64::from(u32::MAX))?;
+    args.finish()?;
+
+    Ok(PermissionsArgs {
+        channel_id,
+        user_id,
+        page: page
+            .and_then(|page| usize::try_from(page).ok())
+            .unwrap_or(1),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<PermissionsArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
         return Ok(());
     };
 
-    if requested_page > total_pages {
-        let msg_out = format!(
-            "Page {} does not exist. Available pages: 1-{}.",
-            requested_page, total_pages
-        );
-        http.create_message(msg.channel_id)
-            .content(&msg_out)
+    let PermissionsArgs {
+        channel_id,
+        user_id,
+        page,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+    let view = PermissionsView {
+        channel_id: channel_id.unwrap_or_else(|| invocation.channel_id()),
+        user_id: user_id.unwrap_or_else(|| invocation.author_id()),
+    };
+
+    let Some(names) = resolve_names(&ctx, guild_id, &view).await else {
+        invocation
+            .reply_content(
+                http,
+                "I couldn't resolve permissions for that member in that channel.",
+            )
             .await?;
         return Ok(());
-    }
+    };
 
-    let (embed, components) = build_paginated_list_view(
-        "permissions",
-        "Your Server Permissions",
-        &names,
-        requested_page,
-        PERMISSIONS_PER_PAGE,
-        msg.author.id.get(),
-        DEFAULT_TIMEOUT_SECS,
-    )?;
+    let total = total_pages(names.len(), PERMISSIONS_PER_PAGE);
+    if page > total {
+        let msg_out = format!("Page {page} does not exist. Available pages: 1-{total}.");
+        invocation.reply_content(http, &msg_out).await?;
+        return Ok(());
+    }
 
-    send_paginated_message(
-        Arc::clone(&ctx.http),
-        msg.channel_id,
+    let (embed, components) = build_view(&view, &names, page, invocation.author_id().get())?;
+    send_paginated_reply(
+        &ctx,
+        invocation,
         embed,
         components,
-        total_pages,
+        total,
         DEFAULT_TIMEOUT_SECS,
     )
     .await?;
@@ -91,6 +255,56 @@ pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> a
     Ok(())
 }
 
+/// Resolve permission names for a view, or `None` when the channel is not in
+/// the guild or the user is not a member.
+async fn resolve_names(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    view: &PermissionsView,
+) -> Option<Vec<String>> {
+    let in_guild = guild_channel(ctx, view.channel_id)
+        .await
+        .is_ok_and(|channel| channel.guild_id == Some(guild_id));
+    if !in_guild {
+        return None;
+    }
+
+    match member_channel_permissions(ctx, guild_id, view.channel_id, view.user_id).await {
+        Ok(perms) => Some(permission_names(perms)),
+        Err(source) => {
+            warn!(?source, "failed to resolve channel permissions");
+            None
+        }
+    }
+}
+
+fn build_view(
+    view: &PermissionsView,
+    names: &[String],
+    page: usize,
+    owner_user_id: u64,
+) -> anyhow::Result<(Embed, Vec<Component>)> {
+    let list = if names.is_empty() {
+        "No permissions.".to_owned()
+    } else {
+        paginated_bulleted_description(names, PERMISSIONS_PER_PAGE, page)
+    };
+    let description = format!(
+        "Effective permissions for <@{}> in <#{}>\n\n{list}",
+        view.user_id, view.channel_id
+    );
+
+    build_paginated_view(
+        &view.pagination_command(),
+        "Channel Permissions",
+        description,
+        page,
+        total_pages(names.len(), PERMISSIONS_PER_PAGE),
+        owner_user_id,
+        DEFAULT_TIMEOUT_SECS,
+    )
+}
+
 /// Handle pagination button presses for the `permissions` command.
 pub async fn handle_pagination_interaction(
     ctx: Context,
@@ -98,7 +312,7 @@ pub async fn handle_pagination_interaction(
 ) -> anyhow::Result<bool> {
     let http = &ctx.http;
     let (actor_id, token) =
-        match validate_interaction_for_command(http, &interaction, "permissions").await? {
+        match validate_interaction_for_command_prefix(http, &interaction, META.name).await? {
             PaginationInteractionValidation::NotForCommand => return Ok(false),
             PaginationInteractionValidation::HandledInvalid => return Ok(true),
             PaginationInteractionValidation::Valid {
@@ -107,51 +321,26 @@ pub async fn handle_pagination_interaction(
             } => (actor_user_id, token),
         };
 
-    let Some(perms) = interaction
-        .member
-        .as_ref()
-        .and_then(|member| member.permissions)
+    let Some((view, names)) = resolve_interaction_view(&ctx, &interaction, &token.command).await?
     else {
-        respond_ephemeral_message(
-            http,
-            &interaction,
-            "Unable to resolve member permissions for this interaction.",
-        )
-        .await?;
         return Ok(true);
     };
-
-    let names = permission_names(perms);
-    if names.is_empty() {
-        respond_ephemeral_message(http, &interaction, "No permissions available for display.")
-            .await?;
-        return Ok(true);
-    }
-
-    let total_pages = total_pages(names.len(), PERMISSIONS_PER_PAGE);
+    let total = total_pages(names.len(), PERMISSIONS_PER_PAGE);
 
     if token.action == "jump" {
-        open_jump_modal_from_token(http, &interaction, &token, total_pages).await?;
+        open_jump_modal_from_token(http, &interaction, &token, total).await?;
         return Ok(true);
     }
 
-    let target_page = clamp_page(token.page, total_pages);
-    let (embed, components) = build_paginated_list_view(
-        "permissions",
-        "Your Server Permissions",
-        &names,
-        target_page,
-        PERMISSIONS_PER_PAGE,
-        actor_id,
-        DEFAULT_TIMEOUT_SECS,
-    )?;
+    let target_page = clamp_page(token.page, total);
+    let (embed, components) = build_view(&view, &names, target_page, actor_id)?;
 
     update_paginated_interaction_message(
-        Arc::clone(&ctx.http),
+        &ctx,
         &interaction,
         embed,
         components,
-        total_pages,
+        total,
         DEFAULT_TIMEOUT_SECS,
     )
     .await?;
@@ -165,61 +354,67 @@ pub async fn handle_pagination_modal_interaction(
     interaction: Box<InteractionCreate>,
 ) -> anyhow::Result<bool> {
     let http = &ctx.http;
-    let (actor_id, entered_page, total_pages_hint) =
-        match validate_jump_modal_for_command(http, &interaction, "permissions").await? {
+    let (actor_id, command, entered_page, total_pages_hint) =
+        match validate_jump_modal_for_command_prefix(http, &interaction, META.name).await? {
             PaginationModalSubmitValidation::NotForCommand => return Ok(false),
             PaginationModalSubmitValidation::HandledInvalid => return Ok(true),
             PaginationModalSubmitValidation::Valid {
                 actor_user_id,
+                command,
                 requested_page,
                 total_pages_hint,
-                ..
-            } => (actor_user_id, requested_page, total_pages_hint),
+            } => (actor_user_id, command, requested_page, total_pages_hint),
         };
 
-    let Some(perms) = interaction
-        .member
-        .as_ref()
-        .and_then(|member| member.permissions)
-    else {
-        respond_ephemeral_message(
-            http,
-            &interaction,
-            "Unable to resolve member permissions for this interaction.",
-        )
-        .await?;
+    let Some((view, names)) = resolve_interaction_view(&ctx, &interaction, &command).await? else {
         return Ok(true);
     };
-
-    let names = permission_names(perms);
-    if names.is_empty() {
-        respond_ephemeral_message(http, &interaction, "No permissions available for display.")
-            .await?;
-        return Ok(true);
-    }
-
-    let total_pages: usize = total_pages(names.len(), PERMISSIONS_PER_PAGE);
-    let target_page = resolve_modal_target_page(entered_page, total_pages, total_pages_hint);
-
-    let (embed, components) = build_paginated_list_view(
-        "permissions",
-        "Your Server Permissions",
-        &names,
-        target_page,
-        PERMISSIONS_PER_PAGE,
-        actor_id,
-        DEFAULT_TIMEOUT_SECS,
-    )?;
+    let total = total_pages(names.len(), PERMISSIONS_PER_PAGE);
+    let target_page = resolve_modal_target_page(entered_page, total, total_pages_hint);
+    let (embed, components) = build_view(&view, &names, target_page, actor_id)?;
 
     update_paginated_interaction_message(
-        Arc::clone(&ctx.http),
+        &ctx,
         &interaction,
         embed,
         components,
-        total_pages,
+        total,
         DEFAULT_TIMEOUT_SECS,
     )
     .await?;
 
     Ok(true)
 }
+
+/// Decode the view from a pagination command key and resolve it again, since
+/// roles or overwrites may have changed since the list was opened.
+///
+/// Returns `None` after replying when the interaction cannot be served.
+async fn resolve_interaction_view(
+    ctx: &Context,
+    interaction: &InteractionCreate,
+    command: &str,
+) -> anyhow::Result<Option<(PermissionsView, Vec<String>)>> {
+    let http = &ctx.http;
+    let Some(guild_id) = interaction.guild_id else {
+        respond_ephemeral_message(http, interaction, guild_only_message()).await?;
+        return Ok(None);
+    };
+
+    let Some(view) = PermissionsView::from_pagination_command(command) else {
+        respond_ephemeral_message(http, interaction, "Invalid permissions interaction.").await?;
+        return Ok(None);
+    };
+
+    let Some(names) = resolve_names(ctx, guild_id, &view).await else {
+        respond_ephemeral_message(
+            http,
+            interaction,
+            "Unable to resolve permissions for this view anymore.",
+        )
+        .await?;
+        return Ok(None);
+    };
+
+    Ok(Some((view, names)))
+}
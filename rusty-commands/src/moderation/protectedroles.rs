@@ -0,0 +1,188 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::RoleMarker},
+};
+use twilight_util::builder::command::{CommandBuilder, RoleBuilder};
+
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::protected_roles::{
+    add_protected_role, protected_roles, remove_protected_role,
+};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::option_role;
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::{guild_roles, has_invocation_permission};
+
+pub const META: CommandMeta = CommandMeta {
+    name: "protectedroles",
+    desc: "Show or change the roles moderation commands refuse to act on.",
+    category: "moderation",
+    usage: "!protectedroles [add|remove <role>]",
+    cooldown: None,
+};
+
+pub struct ProtectedrolesCommand;
+
+impl Command for ProtectedrolesCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/protectedroles`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .contexts([InteractionContextType::Guild])
+        .option(RoleBuilder::new(
+            "add",
+            "Role to protect from moderation actions.",
+        ))
+        .option(RoleBuilder::new("remove", "Role to stop protecting."))
+        .build()
+}
+
+/// Show or update the guild's protected roles.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Show or update protected roles from the `/protectedroles` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let change = match (option_role(data, "add"), option_role(data, "remove")) {
+        (Some(role_id), _) => Some(RoleChange::Add(role_id)),
+        (None, Some(role_id)) => Some(RoleChange::Remove(role_id)),
+        (None, None) => None,
+    };
+
+    execute(ctx, &invocation, Ok(change)).await
+}
+
+enum RoleChange {
+    Add(Id<RoleMarker>),
+    Remove(Id<RoleMarker>),
+}
+
+fn parse_args(raw: &str) -> Result<Option<RoleChange>, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+
+    let change = if args.keyword("add") {
+        Some(RoleChange::Add(args.required("role")?))
+    } else if args.keyword("remove") {
+        Some(RoleChange::Remove(args.required("role")?))
+    } else {
+        None
+    };
+    args.finish()?;
+
+    Ok(change)
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<Option<RoleChange>, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_GUILD).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let change = match args {
+        Ok(change) => change,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let roles = guild_roles(&ctx, guild_id).await?;
+    let role_name = |role_id: u64| {
+        roles
+            .iter()
+            .find(|role| role.id.get() == role_id)
+            .map_or_else(
+                || format!("deleted role ({role_id})"),
+                |role| format!("**{}**", role.name.replace('@', "@\u{200B}")),
+            )
+    };
+
+    let notice = match change {
+        Some(RoleChange::Add(role_id)) if role_id == guild_id.cast() => {
+            invocation
+                .reply_content(http, "The @\u{200B}everyone role can't be protected.")
+                .await?;
+            return Ok(());
+        }
+        Some(RoleChange::Add(role_id)) => {
+            if add_protected_role(&ctx.db, guild_id.get(), role_id.get()).await? {
+                Some(format!("Protected {}.", role_name(role_id.get())))
+            } else {
+                Some(format!(
+                    "{} is already protected.",
+                    role_name(role_id.get())
+                ))
+            }
+        }
+        Some(RoleChange::Remove(role_id)) => {
+            if remove_protected_role(&ctx.db, guild_id.get(), role_id.get()).await? {
+                Some(format!("Stopped protecting {}.", role_name(role_id.get())))
+            } else {
+                Some(format!("{} was not protected.", role_name(role_id.get())))
+            }
+        }
+        None => None,
+    };
+
+    let protected = protected_roles(&ctx.db, guild_id.get()).await?;
+    let list = if protected.is_empty() {
+        "none".to_owned()
+    } else {
+        protected
+            .into_iter()
+            .map(role_name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let summary = match notice {
+        Some(notice) => format!("{notice}\nProtected roles: {list}"),
+        None => format!("Protected roles: {list}"),
+    };
+    invocation.reply_content(http, &summary).await?;
+
+    Ok(())
+}
@@ -1,99 +1,605 @@
-use tokio::time::{Duration, sleep};
+use regex::{Regex, RegexBuilder};
 use tracing::error;
 use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    channel::Message,
     gateway::payload::incoming::MessageCreate,
     guild::Permissions,
-    id::{Id, marker::MessageMarker},
+    id::{
+        Id,
+        marker::{MessageMarker, UserMarker},
+    },
+};
+use twilight_util::builder::command::{
+    BooleanBuilder, CommandBuilder, IntegerBuilder, StringBuilder, UserBuilder,
 };
 
-use crate::CommandMeta;
+use crate::automod::filters::contains_link;
+use crate::cooldown::{Cooldown, CooldownScope};
+use crate::moderation::case_log::{log_case, publish_case};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
-use rusty_utils::permissions::has_message_permission;
+use rusty_core::automod::PATTERN_SIZE_LIMIT;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::cleanup::{bulk_delete_cutoff, delete_channel_messages, schedule_message_delete};
+use rusty_utils::http_error::is_not_found;
+use rusty_utils::invocation::{Invocation, InvocationSource};
+use rusty_utils::options::{option_bool, option_integer, option_string, option_user};
+use rusty_utils::parse::{
+    ArgError, ArgErrorKind, ArgParser, FromArg, ParsedDuration, parse_message_link,
+};
+use rusty_utils::permissions::has_invocation_permission;
+use rusty_utils::time::now_unix_secs;
 
 pub const META: CommandMeta = CommandMeta {
     name: "purge",
-    desc: "Delete the latest messages in this channel.",
+    desc: "Delete recent messages in this channel, optionally only those matching filters.",
     category: "moderation",
-    usage: "!purge <amount>",
+    usage: "!purge <amount> [user:<user>] [bots] [contains:<text>] [regex:<pattern>] \
+            [attachments] [links] [embeds] [before:<message>] [after:<message>] \
+            [since:<duration>]",
+    cooldown: Some(Cooldown::new(CooldownScope::Channel, 1, 10)),
 };
 
-const MAX_PURGE: u16 = 100;
+const MAX_PURGE: u16 = 1000;
+/// Messages looked through before giving up on finding enough matches.
+const MAX_SCANNED: usize = 5000;
+/// Messages too old to bulk-delete cost a request each, so only this many are tried.
+const MAX_SINGLE_DELETES: usize = 100;
+/// How long the message-command confirmation stays before it is removed.
+const CONFIRMATION_LIFETIME_SECS: u64 = 3;
+/// Confirmations that explain skipped messages stay long enough to be read.
+const SKIP_REPORT_LIFETIME_SECS: u64 = 15;
+
+pub struct PurgeCommand;
+
+impl Command for PurgeCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/purge`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            IntegerBuilder::new("amount", "Number of messages to delete.")
+                .required(true)
+                .min_value(1)
+                .max_value(i64::from(MAX_PURGE)),
+        )
+        .option(UserBuilder::new("user", "Only messages from this user."))
+        .option(BooleanBuilder::new("bots", "Only messages from bots."))
+        .option(StringBuilder::new(
+            "contains",
+            "Only messages containing this text (ignoring case).",
+        ))
+        .option(StringBuilder::new(
+            "regex",
+            "Only messages matching this regular expression.",
+        ))
+        .option(BooleanBuilder::new(
+            "attachments",
+            "Only messages with attachments.",
+        ))
+        .option(BooleanBuilder::new("links", "Only messages with links."))
+        .option(BooleanBuilder::new("embeds", "Only messages with embeds."))
+        .option(StringBuilder::new(
+            "before",
+            "Only messages before this message ID or link.",
+        ))
+        .option(StringBuilder::new(
+            "after",
+            "Only messages after this message ID or link.",
+        ))
+        .option(StringBuilder::new(
+            "since",
+            "Only messages from this period, such as 30m or 2h.",
+        ))
+        .build()
+}
+
+/// Delete recent channel messages, optionally filtered.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Delete recent channel messages from the `/purge` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct PurgeArgs {
+    amount: u16,
+    filters: PurgeFilters,
+}
+
+/// Which messages a purge may delete. Every filter given has to match.
+#[derive(Default)]
+struct PurgeFilters {
+    user_ids: Vec<Id<UserMarker>>,
+    bots: bool,
+    /// Lowercased.
+    contains: Option<String>,
+    regex: Option<Regex>,
+    attachments: bool,
+    links: bool,
+    embeds: bool,
+    before: Option<Id<MessageMarker>>,
+    after: Option<Id<MessageMarker>>,
+    since: Option<ParsedDuration>,
+}
+
+impl PurgeFilters {
+    /// Apply one `key:value` filter; switches like `bots` take no value.
+    fn set(&mut self, key: &str, value: Option<&str>) -> Result<(), ArgError> {
+        let takes_value = matches!(
+            key,
+            "user" | "contains" | "regex" | "before" | "after" | "since"
+        );
+        let value = match (takes_value, value) {
+            (true, Some(value)) => value,
+            (true, None) => return Err(ArgError::missing(META.usage, key)),
+            (false, Some(value)) => {
+                return Err(unexpected(&format!("{key}:{value}")));
+            }
+            (false, None) => "",
+        };
+
+        match key {
+            "user" => self.user_ids.push(
+                Id::<UserMarker>::from_arg(value)
+                    .ok_or_else(|| ArgError::invalid::<Id<UserMarker>>(META.usage, key, value))?,
+            ),
+            "bots" => self.bots = true,
+            "contains" => self.contains = Some(value.to_lowercase()),
+            "regex" => {
+                let regex = RegexBuilder::new(value)
+                    .size_limit(PATTERN_SIZE_LIMIT)
+                    .build()
+                    .map_err(|_| invalid(key, value, "a valid regular expression"))?;
+                self.regex = Some(regex);
+            }
+            "attachments" => self.attachments = true,
+            "links" => self.links = true,
+            "embeds" => self.embeds = true,
+            "before" | "after" => {
+                let message_id = parse_message_id(value)
+                    .ok_or_else(|| invalid(key, value, "a message ID or link"))?;
+                if key == "before" {
+                    self.before = Some(message_id);
+                } else {
+                    self.after = Some(message_id);
+                }
+            }
+            "since" => {
+                self.since =
+                    Some(ParsedDuration::from_arg(value).ok_or_else(|| {
+                        ArgError::invalid::<ParsedDuration>(META.usage, key, value)
+                    })?);
+            }
+            _ => return Err(unexpected(key)),
+        }
 
-/// Delete a bounded number of recent channel messages.
-pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn matches(&self, message: &Message) -> bool {
+        (self.user_ids.is_empty() || self.user_ids.contains(&message.author.id))
+            && (!self.bots || message.author.bot)
+            && self
+                .contains
+                .as_ref()
+                .is_none_or(|needle| message.content.to_lowercase().contains(needle))
+            && self
+                .regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&message.content))
+            && (!self.attachments || !message.attachments.is_empty())
+            && (!self.links || contains_link(&message.content))
+            && (!self.embeds || !message.embeds.is_empty())
+    }
+
+    /// Whether anything narrows the purge beyond the latest messages.
+    fn is_filtered(&self) -> bool {
+        !self.user_ids.is_empty()
+            || self.bots
+            || self.contains.is_some()
+            || self.regex.is_some()
+            || self.attachments
+            || self.links
+            || self.embeds
+    }
+
+    /// The filters as written, for the case log.
+    fn describe(&self) -> Vec<String> {
+        let mut parts: Vec<String> = self
+            .user_ids
+            .iter()
+            .map(|user_id| format!("user:<@{user_id}>"))
+            .collect();
+        if self.bots {
+            parts.push("bots".to_owned());
+        }
+        if let Some(contains) = &self.contains {
+            parts.push(format!("contains:\"{contains}\""));
+        }
+        if let Some(regex) = &self.regex {
+            parts.push(format!("regex:`{}`", regex.as_str()));
+        }
+        for (set, name) in [
+            (self.attachments, "attachments"),
+            (self.links, "links"),
+            (self.embeds, "embeds"),
+        ] {
+            if set {
+                parts.push(name.to_owned());
+            }
+        }
+        if let Some(before) = self.before {
+            parts.push(format!("before:{before}"));
+        }
+        if let Some(after) = self.after {
+            parts.push(format!("after:{after}"));
+        }
+        if let Some(since) = &self.since {
+            parts.push(format!("since:{}", since.label));
+        }
+        parts
+    }
+}
+
+fn invalid(key: &str, value: &str, expected: &'static str) -> ArgError {
+    ArgError::new(
+        META.usage,
+        key,
+        ArgErrorKind::Invalid {
+            value: value.to_owned(),
+            expected,
+        },
+    )
+}
+
+fn unexpected(value: &str) -> ArgError {
+    ArgError::new(
+        META.usage,
+        value,
+        ArgErrorKind::Unexpected {
+            value: value.to_owned(),
+        },
+    )
+}
+
+/// A message ID, or the message a link points to.
+fn parse_message_id(raw: &str) -> Option<Id<MessageMarker>> {
+    parse_message_link(raw)
+        .map(|link| link.message_id)
+        .or_else(|| Id::new_checked(raw.trim().parse().ok()?))
+}
+
+fn parse_args(raw: &str) -> Result<PurgeArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let amount = args.integer("amount", 1..=i64::from(MAX_PURGE))?;
+
+    let mut filters = PurgeFilters::default();
+    while let Some(token) = args.optional::<String>() {
+        match token.split_once(':') {
+            // `contains: "two words"` puts the value in the next argument
+            Some((key, "")) => {
+                let key = key.to_ascii_lowercase();
+                let value = args.required::<String>(&key)?;
+                filters.set(&key, Some(&value))?;
+            }
+            Some((key, value)) => filters.set(&key.to_ascii_lowercase(), Some(value))?,
+            None => filters.set(&token.to_ascii_lowercase(), None)?,
+        }
+    }
+
+    Ok(PurgeArgs {
+        amount: u16::try_from(amount).unwrap_or(MAX_PURGE),
+        filters,
+    })
+}
+
+fn slash_args(data: &CommandData) -> Result<PurgeArgs, ArgError> {
+    let amount = option_integer(data, "amount")
+        .ok_or_else(|| ArgError::missing(META.usage, "amount"))
+        .and_then(|amount| {
+            u16::try_from(amount)
+                .map_err(|_| ArgError::invalid::<i64>(META.usage, "amount", &amount.to_string()))
+        })?;
+
+    let mut filters = PurgeFilters::default();
+    if let Some(user_id) = option_user(data, "user") {
+        filters.user_ids.push(user_id);
+    }
+    for key in ["bots", "attachments", "links", "embeds"] {
+        if option_bool(data, key) == Some(true) {
+            filters.set(key, None)?;
+        }
+    }
+    for key in ["contains", "regex", "before", "after", "since"] {
+        if let Some(value) = option_string(data, key) {
+            filters.set(key, Some(value))?;
+        }
+    }
+
+    Ok(PurgeArgs { amount, filters })
+}
+
+/// Messages a purge looked at but left alone, by reason.
+#[derive(Default)]
+struct Skipped {
+    pinned: u64,
+    unmatched: u64,
+    too_old: u64,
+    failed: u64,
+}
+
+impl Skipped {
+    fn summary(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            (self.pinned, "pinned"),
+            (self.unmatched, "not matching the filters"),
+            (
+                self.too_old,
+                "older than 14 days, past the one-by-one delete limit",
+            ),
+            (self.failed, "that couldn't be deleted"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, reason)| format!("{count} {reason}"))
+        .collect();
+
+        (!parts.is_empty()).then(|| format!("Skipped {}.", parts.join(", ")))
+    }
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<PurgeArgs, ArgError>,
+) -> anyhow::Result<()> {
     let http = &ctx.http;
-    let Some(requested_raw) = arg1 else {
-        let usage = format!("Usage: `{}`", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let PurgeArgs { amount, filters } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
     };
 
-    let Ok(requested) = requested_raw.parse::<u16>() else {
-        http.create_message(msg.channel_id)
-            .content("Amount must be a number between 1 and 100.")
+    if amount == 0 {
+        invocation
+            .reply_content(http, "Amount must be at least 1.")
             .await?;
         return Ok(());
     };
+    let amount = usize::from(amount.min(MAX_PURGE));
 
-    if requested == 0 {
-        http.create_message(msg.channel_id)
-            .content("Amount must be at least 1.")
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, "You are not permitted to use this command.")
             .await?;
         return Ok(());
-    };
+    }
 
-    let amount = requested.min(MAX_PURGE);
-    let delete_count = amount.saturating_add(1).min(MAX_PURGE);
+    // Paging through history can outlast the interaction deadline
+    invocation.defer(http, true).await?;
 
-    if !has_message_permission(http, &msg, Permissions::MANAGE_MESSAGES).await? {
-        http.create_message(msg.channel_id)
-            .content("You are not permitted to use this command.")
-            .await?;
-        return Ok(());
+    let channel_id = invocation.channel_id();
+    // Message commands also remove the invoking `!purge` message.
+    let mut before = filters.before;
+    if let InvocationSource::Message(msg) = invocation.source() {
+        match http.delete_message(channel_id, msg.id).await {
+            Err(source) if !is_not_found(&source) => {
+                error!(?source, "deleting purge command message failed");
+            }
+            _ => {}
+        }
+        before = Some(before.map_or(msg.id, |before| before.min(msg.id)));
     }
 
-    let messages = http
-        .channel_messages(msg.channel_id)
-        .limit(delete_count)
-        .await?
-        .model()
-        .await?;
+    let since_cutoff = filters
+        .since
+        .as_ref()
+        .map(|since| now_unix_secs().saturating_sub(since.secs) as i64);
+    let mut skipped = Skipped::default();
+    let mut targets: Vec<(Id<MessageMarker>, i64)> = Vec::new();
+    let mut scanned = 0_usize;
+    let mut history_left = true;
 
-    let ids: Vec<Id<MessageMarker>> = messages.into_iter().map(|m| m.id).collect();
+    'pages: while targets.len() < amount && scanned < MAX_SCANNED {
+        let request = http.channel_messages(channel_id).limit(100);
+        let response = match before {
+            Some(before_id) => request.before(before_id).await,
+            None => request.await,
+        };
+        let messages = match response {
+            Ok(response) => response.model().await?,
+            Err(source) => {
+                error!(?source, "purge history fetch failed");
+                invocation
+                    .reply_ephemeral(
+                        http,
+                        "I couldn't read this channel's history. I likely need the 'Read Message History' permission.",
+                    )
+                    .await?;
+                return Ok(());
+            }
+        };
+        if messages.is_empty() {
+            history_left = false;
+            break;
+        }
+        before = messages.last().map(|message| message.id);
 
-    if ids.is_empty() {
-        http.create_message(msg.channel_id)
-            .content("No messages found to delete.")
-            .await?;
-        return Ok(());
+        for message in messages {
+            let created_at = message.timestamp.as_secs();
+            let past_after = filters.after.is_some_and(|after| message.id <= after);
+            let past_since = since_cutoff.is_some_and(|cutoff| created_at < cutoff);
+            if past_after || past_since {
+                history_left = false;
+                break 'pages;
+            }
+
+            scanned += 1;
+            if message.pinned {
+                skipped.pinned += 1;
+            } else if !filters.matches(&message) {
+                skipped.unmatched += 1;
+            } else {
+                targets.push((message.id, created_at));
+                if targets.len() == amount {
+                    break 'pages;
+                }
+            }
+        }
     }
 
-    let delete_result = if ids.len() == 1 {
-        http.delete_message(msg.channel_id, ids[0]).await
+    // Anything older than the bulk limit costs a request of its own
+    let bulk_cutoff = bulk_delete_cutoff();
+    let mut single_deletes = 0_usize;
+    targets.retain(|&(_, created_at)| {
+        if created_at >= bulk_cutoff {
+            return true;
+        }
+        single_deletes += 1;
+        let keep = single_deletes <= MAX_SINGLE_DELETES;
+        if !keep {
+            skipped.too_old += 1;
+        }
+        keep
+    });
+
+    let outcome = delete_channel_messages(http, channel_id, &targets).await;
+    skipped.failed = outcome.failed;
+    let deleted = outcome.deleted;
+
+    if let Some(guild_id) = invocation.guild_id()
+        && deleted > 0
+    {
+        let mut summary = format!("Purged {} message(s) in <#{}>.", deleted, channel_id.get());
+        let described = filters.describe();
+        if !described.is_empty() {
+            summary.push_str(&format!(" Filters: {}", described.join(" ")));
+        }
+        let entry = log_case(
+            &ctx,
+            NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Purge,
+                user_id: None,
+                moderator_id: invocation.author_id().get(),
+                reason: Some(&summary),
+                duration_secs: None,
+                expires_at: None,
+            },
+        )
+        .await;
+        if let Some(entry) = entry {
+            publish_case(&ctx, &entry).await;
+        }
+    }
+
+    let mut lines = vec![if deleted > 0 {
+        format!("Purged {} message(s).", deleted)
     } else {
-        http.delete_messages(msg.channel_id, &ids).await
-    };
+        "No messages found to delete.".to_owned()
+    }];
+    // Unmatched messages are only worth mentioning when filters were asked for
+    if !filters.is_filtered() {
+        skipped.unmatched = 0;
+    }
+    let skip_report = skipped.summary();
+    lines.extend(skip_report.clone());
+    if history_left && scanned >= MAX_SCANNED && targets.len() < amount {
+        lines.push(format!(
+            "Stopped after looking through {MAX_SCANNED} messages."
+        ));
+    }
+    let confirmation = lines.join("\n");
 
-    if let Err(source) = delete_result {
-        error!(?source, "purge delete request failed");
-        http.create_message(msg.channel_id)
-            .content("I couldn't delete messages. I likely need the 'Manage Messages' permission.")
-            .await?;
+    if invocation.is_interaction() {
+        invocation.reply_ephemeral(http, &confirmation).await?;
         return Ok(());
     }
 
-    let confirmation = format!("Purged {} message(s).", amount);
-    let confirmation_message = http
-        .create_message(msg.channel_id)
-        .content(&confirmation)
-        .await?
-        .model()
+    let confirmation_message = invocation
+        .reply_message(http, Some(&confirmation), &[], &[])
         .await?;
 
-    sleep(Duration::from_secs(3)).await;
-    let _ = http
-        .delete_message(msg.channel_id, confirmation_message.id)
-        .await;
+    let lifetime = if skip_report.is_some() {
+        SKIP_REPORT_LIFETIME_SECS
+    } else {
+        CONFIRMATION_LIFETIME_SECS
+    };
+    if let Err(source) =
+        schedule_message_delete(&ctx, channel_id, confirmation_message.id, lifetime).await
+    {
+        error!(?source, "failed to schedule purge confirmation delete");
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contains(raw: &str) -> Option<String> {
+        match parse_args(raw) {
+            Ok(args) => args.filters.contains,
+            Err(error) => panic!("`{raw}` failed to parse: {error}"),
+        }
+    }
+
+    #[test]
+    fn contains_takes_a_quoted_value_after_the_colon() {
+        assert_eq!(
+            contains(r#"50 contains:"Two Words""#).as_deref(),
+            Some("two words")
+        );
+    }
+
+    #[test]
+    fn contains_takes_a_quoted_value_after_a_space() {
+        assert_eq!(
+            contains(r#"50 contains: "two words""#).as_deref(),
+            Some("two words")
+        );
+    }
+
+    #[test]
+    fn quoted_value_leaves_later_filters_alone() {
+        let Ok(args) = parse_args(r#"50 contains:"two words" bots"#) else {
+            panic!("failed to parse");
+        };
+        assert_eq!(args.filters.contains.as_deref(), Some("two words"));
+        assert!(args.filters.bots);
+    }
+}
@@ -0,0 +1,159 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+};
+use twilight_util::builder::command::{CommandBuilder, IntegerBuilder, StringBuilder};
+
+use crate::moderation::case_log::load_embed_color;
+use crate::moderation::embeds::{
+    case_embed, case_not_found_message, guild_only_message, permission_denied_message,
+};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::cases::update_case_reason;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_integer, option_string};
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "reason",
+    desc: "Replace the reason of a moderation case.",
+    category: "moderation",
+    usage: "!reason <number> <text>",
+    cooldown: None,
+};
+
+pub struct ReasonCommand;
+
+impl Command for ReasonCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/reason`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            IntegerBuilder::new("number", "Case number to update.")
+                .required(true)
+                .min_value(1),
+        )
+        .option(StringBuilder::new("text", "New reason for the case.").required(true))
+        .build()
+}
+
+/// Replace the reason recorded on a case.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Replace a case reason from the `/reason` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct ReasonArgs {
+    case_number: u64,
+    reason: String,
+}
+
+fn parse_args(raw: &str) -> Result<ReasonArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let case_number = args.integer("number", 1..=i64::MAX)?.unsigned_abs();
+    let reason = args.required_rest("text")?;
+
+    Ok(ReasonArgs {
+        case_number,
+        reason,
+    })
+}
+
+fn slash_args(data: &CommandData) -> Result<ReasonArgs, ArgError> {
+    let case_number = option_integer(data, "number")
+        .and_then(|number| u64::try_from(number).ok())
+        .ok_or_else(|| ArgError::missing(META.usage, "number"))?;
+    let reason = option_string(data, "text")
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| ArgError::missing(META.usage, "text"))?;
+
+    Ok(ReasonArgs {
+        case_number,
+        reason,
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<ReasonArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let ReasonArgs {
+        case_number,
+        reason,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let Some(entry) = update_case_reason(&ctx.db, guild_id.get(), case_number, &reason).await?
+    else {
+        let not_found = case_not_found_message(case_number);
+        invocation.reply_content(http, &not_found).await?;
+        return Ok(());
+    };
+
+    let color = load_embed_color(&ctx, guild_id.get()).await;
+    let embed = case_embed(&entry, None, color)?;
+    invocation
+        .reply_message(
+            http,
+            Some(&format!("Updated the reason for case #{case_number}.")),
+            &[embed],
+            &[],
+        )
+        .await?;
+
+    Ok(())
+}
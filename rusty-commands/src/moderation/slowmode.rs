@@ -0,0 +1,245 @@
+use tracing::warn;
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::ChannelMarker},
+};
+use twilight_util::builder::command::{ChannelBuilder, CommandBuilder, StringBuilder};
+
+use crate::moderation::case_log::{
+    ChannelActionReport, channel_case_reason, report_channel_action,
+};
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::lock::{channel_arg, display_name, fetch_guild_channel};
+use crate::moderation::lockdown::audit_reason;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_channel, option_string};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg, ParsedDuration};
+use rusty_utils::permissions::has_invocation_permission;
+use rusty_utils::time::format_duration_secs;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "slowmode",
+    desc: "Set or turn off a channel's slowmode.",
+    category: "moderation",
+    usage: "!slowmode [channel] <duration|off> [reason]",
+    cooldown: None,
+};
+
+/// Longest slowmode Discord allows.
+const MAX_SLOWMODE_SECS: u64 = 6 * 60 * 60;
+
+pub struct SlowmodeCommand;
+
+impl Command for SlowmodeCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/slowmode`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_CHANNELS)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            StringBuilder::new(
+                "duration",
+                "Time between each member's messages, such as 10s or 5m, or `off`.",
+            )
+            .required(true),
+        )
+        .option(ChannelBuilder::new(
+            "channel",
+            "Channel to change (default: this one).",
+        ))
+        .option(StringBuilder::new("reason", "Reason for the change."))
+        .build()
+}
+
+/// Change a channel's slowmode.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Change a channel's slowmode from the `/slowmode` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct SlowmodeArgs {
+    channel_id: Option<Id<ChannelMarker>>,
+    /// `None` turns slowmode off.
+    duration: Option<ParsedDuration>,
+    reason: Option<String>,
+}
+
+fn parse_args(raw: &str) -> Result<SlowmodeArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let channel_id = channel_arg(&mut args);
+    let duration = if args.keyword("off") {
+        None
+    } else {
+        Some(args.required("duration")?)
+    };
+
+    Ok(SlowmodeArgs {
+        channel_id,
+        duration,
+        reason: args.rest(),
+    })
+}
+
+fn slash_args(data: &CommandData) -> Result<SlowmodeArgs, ArgError> {
+    let raw =
+        option_string(data, "duration").ok_or_else(|| ArgError::missing(META.usage, "duration"))?;
+    let duration = if raw.trim().eq_ignore_ascii_case("off") {
+        None
+    } else {
+        Some(
+            ParsedDuration::from_arg(raw)
+                .ok_or_else(|| ArgError::invalid::<ParsedDuration>(META.usage, "duration", raw))?,
+        )
+    };
+
+    Ok(SlowmodeArgs {
+        channel_id: option_channel(data, "channel"),
+        duration,
+        reason: option_string(data, "reason").map(ToOwned::to_owned),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<SlowmodeArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_CHANNELS).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let SlowmodeArgs {
+        channel_id,
+        duration,
+        reason,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+    let channel_id = channel_id.unwrap_or_else(|| invocation.channel_id());
+    let reason = reason.as_deref();
+
+    let secs = duration.as_ref().map_or(0, |duration| duration.secs);
+    if secs > MAX_SLOWMODE_SECS {
+        invocation
+            .reply_content(
+                http,
+                &format!(
+                    "Slowmode can be at most {}.",
+                    format_duration_secs(MAX_SLOWMODE_SECS)
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let Some(channel) = fetch_guild_channel(&ctx, guild_id, channel_id).await? else {
+        invocation
+            .reply_content(http, &format!("<#{channel_id}> isn't in this server."))
+            .await?;
+        return Ok(());
+    };
+
+    let action = if secs > 0 {
+        "Slowmode set"
+    } else {
+        "Slowmode off"
+    };
+    let result = http
+        .update_channel(channel_id)
+        .rate_limit_per_user(u16::try_from(secs).unwrap_or(u16::MAX))
+        .reason(&audit_reason(action, reason))
+        .await;
+    if let Err(source) = result {
+        warn!(
+            ?source,
+            channel_id = channel_id.get(),
+            "changing slowmode failed"
+        );
+        invocation
+            .reply_content(
+                http,
+                &format!(
+                    "I couldn't change slowmode in <#{channel_id}>. I likely need the 'Manage Channels' permission there."
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let channel_name = display_name(&channel);
+    let case_reason = channel_case_reason(channel_id, reason);
+    report_channel_action(
+        &ctx,
+        invocation,
+        ChannelActionReport {
+            case: NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Slowmode,
+                user_id: None,
+                moderator_id: invocation.author_id().get(),
+                reason: Some(&case_reason),
+                duration_secs: (secs > 0).then_some(secs),
+                expires_at: None,
+            },
+            channel_id,
+            channel_name: &channel_name,
+            action_past_tense: if secs > 0 {
+                "slowed down"
+            } else {
+                "taken out of slowmode"
+            },
+            reason,
+            duration_label: duration.as_ref().map(|duration| duration.label.as_str()),
+        },
+    )
+    .await
+}
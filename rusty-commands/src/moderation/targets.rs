@@ -0,0 +1,30 @@
+//! Pre-flight checks on who a moderation action may target.
+
+use anyhow::Context as _;
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
+
+use rusty_core::Context;
+use rusty_database::impls::protected_roles::protected_roles;
+use rusty_utils::permissions::{TargetRefusal, check_moderation_target};
+
+/// Check a target against the guild's hierarchy and protected roles.
+///
+/// Returns the refusal to explain to the moderator, or `None` when the action
+/// may go ahead.
+pub(crate) async fn target_refusal(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    moderator_id: Id<UserMarker>,
+    target_user_id: Id<UserMarker>,
+) -> anyhow::Result<Option<TargetRefusal>> {
+    let protected = protected_roles(&ctx.db, guild_id.get())
+        .await?
+        .into_iter()
+        .map(|role_id| Id::new_checked(role_id).context("protected role_id is zero"))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    check_moderation_target(ctx, guild_id, moderator_id, target_user_id, &protected).await
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use twilight_http::request::AuditLogReason as _;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::Id,
+};
+use twilight_util::builder::command::{CommandBuilder, StringBuilder, UserBuilder};
+
+use crate::moderation::ban::{self, BanArgs};
+use crate::moderation::case_log::{bot_user_id, log_case, publish_case};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_core::jobs::{JobPayload, cancel_scheduled_job, schedule_job};
+use rusty_database::impls::tempbans::{delete_tempban, tempban_entry, upsert_tempban};
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::http_error::is_not_found;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::parse::{ArgError, ArgParser, UnitDuration};
+
+pub const META: CommandMeta = CommandMeta {
+    name: "tempban",
+    desc: "Ban a user for a limited time.",
+    category: "moderation",
+    usage: "!tempban <user> <duration> [reason]",
+    cooldown: None,
+};
+
+const EXPIRED_REASON: &str = "Temporary ban expired";
+
+pub struct TempbanCommand;
+
+impl Command for TempbanCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/tempban`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::BAN_MEMBERS)
+        .contexts([InteractionContextType::Guild])
+        .option(UserBuilder::new("user", "User to ban.").required(true))
+        .option(StringBuilder::new("duration", "Ban length such as 12h or 7d.").required(true))
+        .option(StringBuilder::new("reason", "Reason for the ban."))
+        .build()
+}
+
+/// Ban a target user until the given duration elapses.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    ban::execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Temporarily ban a target user from the `/tempban` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let args = BanArgs::from_options(data, META.usage).and_then(|args| match args.duration {
+        Some(_) => Ok(args),
+        None => Err(ArgError::missing(META.usage, "duration")),
+    });
+    ban::execute(ctx, &invocation, args).await
+}
+
+fn parse_args(raw: &str) -> Result<BanArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let target_user_id = args.required("user")?;
+    let UnitDuration(duration) = args.required("duration")?;
+
+    Ok(BanArgs {
+        target_user_id,
+        duration: Some(duration),
+        reason: args.rest(),
+    })
+}
+
+/// Job payload that lifts a temporary ban once it expires.
+#[derive(Serialize, Deserialize)]
+pub struct TempbanExpiry {
+    pub guild_id: u64,
+    pub user_id: u64,
+}
+
+impl JobPayload for TempbanExpiry {
+    const KIND: &'static str = "tempban.expire";
+    const MAX_ATTEMPTS: u32 = 10;
+}
+
+fn expiry_job_key(guild_id: u64, user_id: u64) -> String {
+    format!("tempban:{guild_id}:{user_id}")
+}
+
+/// Record a tempban and schedule its expiry, replacing any earlier one.
+pub(crate) async fn store_tempban(
+    ctx: &Context,
+    guild_id: u64,
+    user_id: u64,
+    moderator_id: u64,
+    expires_at: u64,
+) -> anyhow::Result<()> {
+    upsert_tempban(&ctx.db, guild_id, user_id, moderator_id, expires_at).await?;
+    schedule_job(
+        &ctx.db,
+        &TempbanExpiry { guild_id, user_id },
+        expires_at,
+        Some(&expiry_job_key(guild_id, user_id)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Forget a pending tempban and its scheduled expiry.
+pub(crate) async fn clear_tempban(
+    ctx: &Context,
+    guild_id: u64,
+    user_id: u64,
+) -> anyhow::Result<()> {
+    delete_tempban(&ctx.db, guild_id, user_id).await?;
+    cancel_scheduled_job(&ctx.db, &expiry_job_key(guild_id, user_id)).await?;
+    Ok(())
+}
+
+/// Job handler for [`TempbanExpiry`].
+///
+/// Failed unbans are retried by the job runner with backoff.
+pub async fn lift_tempban(ctx: Context, job: TempbanExpiry) -> anyhow::Result<()> {
+    let Some(entry) = tempban_entry(&ctx.db, job.guild_id, job.user_id).await? else {
+        // Cleared by a manual unban or a permanent re-ban.
+        return Ok(());
+    };
+
+    let (Some(guild_id), Some(user_id)) = (
+        Id::new_checked(entry.guild_id),
+        Id::new_checked(entry.user_id),
+    ) else {
+        delete_tempban(&ctx.db, entry.guild_id, entry.user_id).await?;
+        return Ok(());
+    };
+
+    match ctx
+        .http
+        .delete_ban(guild_id, user_id)
+        .reason(EXPIRED_REASON)
+        .await
+    {
+        Ok(_) => {}
+        Err(source) if is_not_found(&source) => {
+            // Someone already lifted the ban by hand; nothing left to record.
+            delete_tempban(&ctx.db, entry.guild_id, entry.user_id).await?;
+            return Ok(());
+        }
+        Err(source) => return Err(source.into()),
+    }
+
+    delete_tempban(&ctx.db, entry.guild_id, entry.user_id).await?;
+
+    let moderator_id = bot_user_id(&ctx).await;
+    let case = log_case(
+        &ctx,
+        NewCase {
+            guild_id: entry.guild_id,
+            action: CaseAction::Unban,
+            user_id: Some(entry.user_id),
+            moderator_id: moderator_id.unwrap_or(entry.moderator_id),
+            reason: Some(EXPIRED_REASON),
+            duration_secs: None,
+            expires_at: None,
+        },
+    )
+    .await;
+    if let Some(case) = case {
+        publish_case(&ctx, &case).await;
+    }
+
+    Ok(())
+}
@@ -1,17 +1,29 @@
 use tracing::error;
+use twilight_http::request::AuditLogReason as _;
 use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
     gateway::payload::incoming::{InteractionCreate, MessageCreate},
     guild::Permissions,
     id::{Id, marker::UserMarker},
 };
+use twilight_util::builder::command::{CommandBuilder, StringBuilder, UserBuilder};
 
-use crate::CommandMeta;
+use crate::moderation::case_log::{
+    load_embed_color, load_modlog_settings, log_case, publish_case, publish_to_modlog,
+};
 use crate::moderation::embeds::{
     fetch_target_profile, guild_only_message, moderation_action_embed,
     moderation_invalid_interaction_message, moderation_permission_combo_denied_message,
-    moderation_self_action_message, usage_message,
+    queued_reason, terminate_declined_embed,
 };
+use crate::moderation::targets::target_refusal;
+use crate::moderation::tempban::clear_tempban;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
+use rusty_database::model::cases::{CaseAction, NewCase};
 use rusty_utils::cleanup::purge_user_globally;
 use rusty_utils::interaction::{
     ConfirmationAction, build_confirmation_components, build_confirmation_custom_ids,
@@ -19,8 +31,10 @@ use rusty_utils::interaction::{
     respond_ephemeral_notice, respond_update_content_embed_without_components,
     respond_update_without_components,
 };
-use rusty_utils::parse::{parse_duration_seconds, parse_target_user_id};
-use rusty_utils::permissions::{check_interaction_permissions, has_message_permission};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_string, option_user};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg, ParsedDuration};
+use rusty_utils::permissions::{check_interaction_permissions, has_invocation_permission};
 use rusty_utils::time::now_unix_secs;
 
 pub const META: CommandMeta = CommandMeta {
@@ -28,99 +42,177 @@ pub const META: CommandMeta = CommandMeta {
     desc: "Ban a user and purge their messages (optionally by period and reason).",
     category: "moderation",
     usage: "!terminate <user> [period] [reason]",
+    cooldown: None,
 };
 
 const CUSTOM_ID_PREFIX: &str = "terminate:";
 
-pub async fn run(
+pub struct TerminateCommand;
+
+impl Command for TerminateCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+
+    fn component_prefixes(&self) -> &'static [&'static str] {
+        &[CUSTOM_ID_PREFIX]
+    }
+
+    fn handle_component(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+    ) -> CommandFuture<bool> {
+        Box::pin(handle_interaction(ctx, interaction))
+    }
+}
+
+/// Application command definition for `/terminate`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::BAN_MEMBERS | Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(UserBuilder::new("user", "User to ban and purge.").required(true))
+        .option(StringBuilder::new(
+            "period",
+            "Only purge messages from this period, such as 1h or 7d (default: all-time).",
+        ))
+        .option(StringBuilder::new("reason", "Reason for the termination."))
+        .build()
+}
+
+/// Queue a termination behind a moderator confirmation prompt.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Queue a termination from the `/terminate` application command.
+pub async fn run_slash(
     ctx: Context,
-    msg: Box<MessageCreate>,
-    arg1: Option<&str>,
-    arg_tail: Option<&str>,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct TerminateArgs {
+    target_user_id: Id<UserMarker>,
+    period: Option<ParsedDuration>,
+    reason: Option<String>,
+}
+
+fn parse_args(raw: &str) -> Result<TerminateArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let target_user_id = args.required("user")?;
+    let period = args.optional();
+
+    Ok(TerminateArgs {
+        target_user_id,
+        period,
+        reason: args.rest(),
+    })
+}
+
+fn slash_args(data: &CommandData) -> Result<TerminateArgs, ArgError> {
+    let target_user_id =
+        option_user(data, "user").ok_or_else(|| ArgError::missing(META.usage, "user"))?;
+    let period = option_string(data, "period")
+        .map(|raw| {
+            ParsedDuration::from_arg(raw)
+                .ok_or_else(|| ArgError::invalid::<ParsedDuration>(META.usage, "period", raw))
+        })
+        .transpose()?;
+
+    Ok(TerminateArgs {
+        target_user_id,
+        period,
+        reason: option_string(data, "reason").map(ToOwned::to_owned),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<TerminateArgs, ArgError>,
 ) -> anyhow::Result<()> {
     let http = &ctx.http;
-    if msg.guild_id.is_none() {
-        http.create_message(msg.channel_id)
-            .content(guild_only_message())
-            .await?;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
         return Ok(());
-    }
+    };
 
     let required_permissions = Permissions::BAN_MEMBERS | Permissions::MANAGE_MESSAGES;
-    if !has_message_permission(http, &msg, required_permissions).await? {
+    if !has_invocation_permission(&ctx, invocation, required_permissions).await? {
         let denied = moderation_permission_combo_denied_message("Ban Members and Manage Messages");
-        http.create_message(msg.channel_id).content(&denied).await?;
+        invocation.reply_ephemeral(http, &denied).await?;
         return Ok(());
     }
 
-    let Some(raw_target) = arg1 else {
-        let usage = usage_message(META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
-    };
-
-    let Some(target_user_id) = parse_target_user_id(raw_target) else {
-        let usage = usage_message(META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let TerminateArgs {
+        target_user_id,
+        period,
+        reason,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
     };
+    let reason = reason.as_deref();
+    let cutoff_secs = period
+        .as_ref()
+        .map(|period| now_unix_secs().saturating_sub(period.secs));
+    let cutoff_display = period.map_or_else(|| "all-time".to_owned(), |period| period.label);
 
-    if target_user_id == msg.author.id {
-        let self_message = moderation_self_action_message("terminate");
-        http.create_message(msg.channel_id)
-            .content(&self_message)
+    if let Some(refusal) =
+        target_refusal(&ctx, guild_id, invocation.author_id(), target_user_id).await?
+    {
+        invocation
+            .reply_content(http, &refusal.message("terminate"))
             .await?;
         return Ok(());
     }
 
-    let (cutoff_secs, cutoff_display, reason) = match arg_tail {
-        Some(tail) => {
-            let mut parts = tail.splitn(2, char::is_whitespace);
-            let first = parts.next().unwrap_or("");
-
-            if let Some(duration_secs) = parse_duration_seconds(first) {
-                let parsed_reason = parts
-                    .next()
-                    .map(str::trim)
-                    .filter(|value| !value.is_empty())
-                    .map(ToOwned::to_owned);
-                (
-                    Some(now_unix_secs().saturating_sub(duration_secs)),
-                    first.to_owned(),
-                    parsed_reason,
-                )
-            } else {
-                (None, "all-time".to_owned(), Some(tail.to_owned()))
-            }
-        }
-        None => (None, "all-time".to_owned(), None),
-    };
-
     let (confirm_custom_id, decline_custom_id) = build_confirmation_custom_ids(
         CUSTOM_ID_PREFIX,
-        msg.author.id.get(),
+        invocation.author_id().get(),
         target_user_id.get(),
         cutoff_secs,
     );
 
     let components = build_confirmation_components(confirm_custom_id, decline_custom_id);
-    let target_profile = fetch_target_profile(http, target_user_id).await;
+    let target_profile = fetch_target_profile(&ctx, target_user_id).await;
+    let color = load_embed_color(&ctx, guild_id.get()).await;
     let confirmation = moderation_action_embed(
         &target_profile,
         target_user_id,
         "queued for termination",
-        reason.as_deref(),
+        reason,
         None,
+        color,
     )?;
     let confirmation_text = format!(
         "Ban and purge pending moderator confirmation.\nPeriod: {}",
         cutoff_display
     );
 
-    http.create_message(msg.channel_id)
-        .content(&confirmation_text)
-        .embeds(&[confirmation])
-        .components(&components)
+    invocation
+        .reply_message(http, Some(&confirmation_text), &[confirmation], &components)
         .await?;
 
     Ok(())
@@ -189,7 +281,19 @@ pub async fn handle_interaction(
     }
 
     let target_user_id = Id::<UserMarker>::new(parsed.target_id);
-    let target_profile = fetch_target_profile(http, target_user_id).await;
+
+    // Roles may have moved since the prompt was posted
+    if parsed.action == ConfirmationAction::Confirm
+        && let Some(refusal) =
+            target_refusal(&ctx, guild_id, Id::new(actor_id), target_user_id).await?
+    {
+        respond_update_without_components(http, &interaction, &refusal.message("terminate"))
+            .await?;
+        return Ok(true);
+    }
+
+    let target_profile = fetch_target_profile(&ctx, target_user_id).await;
+    let color = load_embed_color(&ctx, guild_id.get()).await;
 
     match parsed.action {
         ConfirmationAction::Decline => {
@@ -199,6 +303,7 @@ pub async fn handle_interaction(
                 "left unchanged",
                 Some("Termination cancelled."),
                 None,
+                color,
             )?;
             respond_update_content_embed_without_components(
                 http,
@@ -207,6 +312,17 @@ pub async fn handle_interaction(
                 &cancelled_embed,
             )
             .await?;
+
+            let settings = load_modlog_settings(&ctx, guild_id.get()).await;
+            if settings.channel_id.is_some() {
+                let declined_embed = terminate_declined_embed(
+                    &target_profile,
+                    target_user_id,
+                    Id::new(actor_id),
+                    color,
+                )?;
+                publish_to_modlog(&ctx, &settings, declined_embed).await;
+            }
             return Ok(true);
         }
         ConfirmationAction::Confirm => {
@@ -216,6 +332,7 @@ pub async fn handle_interaction(
                 "queued for termination",
                 Some("Termination in progress."),
                 None,
+                color,
             )?;
             respond_update_content_embed_without_components(
                 http,
@@ -227,7 +344,13 @@ pub async fn handle_interaction(
         }
     }
 
-    if let Err(source) = http.create_ban(guild_id, target_user_id).await {
+    let reason = queued_reason(&interaction);
+    let mut ban_request = http.create_ban(guild_id, target_user_id);
+    if let Some(reason) = reason.as_deref() {
+        ban_request = ban_request.reason(reason);
+    }
+
+    if let Err(source) = ban_request.await {
         error!(?source, "terminate ban failed");
         let failed_embed = moderation_action_embed(
             &target_profile,
@@ -235,6 +358,7 @@ pub async fn handle_interaction(
             "not terminated",
             Some("Ban failed. Check hierarchy and permissions."),
             None,
+            color,
         )?;
         edit_original_response_content_embed_without_components(
             http,
@@ -245,6 +369,7 @@ pub async fn handle_interaction(
         .await?;
         return Ok(true);
     }
+    clear_tempban(&ctx, guild_id.get(), target_user_id.get()).await?;
 
     let deleted_count = purge_user_globally(http, guild_id, target_user_id, parsed.context_value)
         .await
@@ -253,6 +378,23 @@ pub async fn handle_interaction(
             0
         });
 
+    let entry = log_case(
+        &ctx,
+        NewCase {
+            guild_id: guild_id.get(),
+            action: CaseAction::Terminate,
+            user_id: Some(target_user_id.get()),
+            moderator_id: actor_id,
+            reason: reason.as_deref(),
+            duration_secs: None,
+            expires_at: None,
+        },
+    )
+    .await;
+    if let Some(entry) = entry {
+        publish_case(&ctx, &entry).await;
+    }
+
     let window = parsed
         .context_value
         .map(|cutoff| format!("since <t:{}:R>", cutoff))
@@ -267,6 +409,7 @@ pub async fn handle_interaction(
         "terminated",
         Some("Ban applied and purge completed."),
         None,
+        color,
     )?;
     edit_original_response_content_embed_without_components(
         http,
@@ -3,90 +3,189 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::error;
 use twilight_http::request::AuditLogReason as _;
 use twilight_model::{
-    gateway::payload::incoming::MessageCreate, guild::Permissions, util::Timestamp,
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::UserMarker},
+    util::Timestamp,
 };
+use twilight_util::builder::command::{CommandBuilder, StringBuilder, UserBuilder};
 
-use crate::CommandMeta;
-use crate::moderation::embeds::{fetch_target_profile, moderation_action_embed};
+use crate::moderation::case_log::{ActionReport, report_action};
+use crate::moderation::targets::target_refusal;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
-use rusty_utils::parse::{parse_duration_seconds, parse_target_user_id};
-use rusty_utils::permissions::has_message_permission;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_string, option_user};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg, ParsedDuration};
+use rusty_utils::permissions::has_invocation_permission;
+use rusty_utils::time::format_duration_secs;
 
 pub const META: CommandMeta = CommandMeta {
     name: "timeout",
     desc: "Timeout a user for a duration (default: 10m).",
     category: "moderation",
     usage: "!timeout <user> [duration] [reason]",
+    cooldown: None,
 };
 
-const DEFAULT_TIMEOUT_SECS: u64 = 10 * 60;
+/// Used when neither the invocation nor the guild settings give a length.
+pub(crate) const DEFAULT_TIMEOUT_SECS: u64 = 10 * 60;
+
+pub struct TimeoutCommand;
+
+impl Command for TimeoutCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/timeout`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MODERATE_MEMBERS)
+        .contexts([InteractionContextType::Guild])
+        .option(UserBuilder::new("user", "User to timeout.").required(true))
+        .option(StringBuilder::new(
+            "duration",
+            "Timeout length such as 30s, 10m, 2h or 1d (default: 10m unless configured).",
+        ))
+        .option(StringBuilder::new("reason", "Reason for the timeout."))
+        .build()
+}
 
 /// Apply a temporary communication timeout to a target user.
-pub async fn run(
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Apply a timeout from the `/timeout` application command.
+pub async fn run_slash(
     ctx: Context,
-    msg: Box<MessageCreate>,
-    arg1: Option<&str>,
-    arg_tail: Option<&str>,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+struct TimeoutArgs {
+    target_user_id: Id<UserMarker>,
+    /// `None` uses the guild's configured default.
+    duration: Option<ParsedDuration>,
+    reason: Option<String>,
+}
+
+fn parse_args(raw: &str) -> Result<TimeoutArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let target_user_id = args.required("user")?;
+    let duration = args.optional();
+
+    Ok(TimeoutArgs {
+        target_user_id,
+        duration,
+        reason: args.rest(),
+    })
+}
+
+fn slash_args(data: &CommandData) -> Result<TimeoutArgs, ArgError> {
+    let target_user_id =
+        option_user(data, "user").ok_or_else(|| ArgError::missing(META.usage, "user"))?;
+    let duration = option_string(data, "duration")
+        .map(|raw| {
+            ParsedDuration::from_arg(raw)
+                .ok_or_else(|| ArgError::invalid::<ParsedDuration>(META.usage, "duration", raw))
+        })
+        .transpose()?;
+
+    Ok(TimeoutArgs {
+        target_user_id,
+        duration,
+        reason: option_string(data, "reason").map(ToOwned::to_owned),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<TimeoutArgs, ArgError>,
 ) -> anyhow::Result<()> {
     let http = &ctx.http;
-    let Some(guild_id) = msg.guild_id else {
-        http.create_message(msg.channel_id)
-            .content("This command only works in servers.")
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation
+            .reply_content(http, "This command only works in servers.")
             .await?;
         return Ok(());
     };
 
-    if !has_message_permission(http, &msg, Permissions::MODERATE_MEMBERS).await? {
-        http.create_message(msg.channel_id)
-            .content("You are not permitted to use this command.")
+    if !has_invocation_permission(&ctx, invocation, Permissions::MODERATE_MEMBERS).await? {
+        invocation
+            .reply_ephemeral(http, "You are not permitted to use this command.")
             .await?;
         return Ok(());
     }
 
-    let Some(raw_target) = arg1 else {
-        let usage = format!("Usage: `{}`", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let TimeoutArgs {
+        target_user_id,
+        duration,
+        reason,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
     };
-
-    let Some(target_user_id) = parse_target_user_id(raw_target) else {
-        let usage = format!("Usage: `{}`", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let reason = reason.as_deref();
+    let duration = match duration {
+        Some(duration) => duration,
+        None => {
+            let secs = ctx
+                .guild_settings(guild_id.get())
+                .await?
+                .default_timeout_secs
+                .unwrap_or(DEFAULT_TIMEOUT_SECS);
+            ParsedDuration {
+                secs,
+                label: format_duration_secs(secs),
+            }
+        }
     };
 
-    if target_user_id == msg.author.id {
-        http.create_message(msg.channel_id)
-            .content("You can't timeout yourself.")
+    if let Some(refusal) =
+        target_refusal(&ctx, guild_id, invocation.author_id(), target_user_id).await?
+    {
+        invocation
+            .reply_content(http, &refusal.message("timeout"))
             .await?;
         return Ok(());
     }
 
-    let (duration_secs, duration_label, reason) = match arg_tail {
-        Some(tail) => {
-            let mut parts = tail.splitn(2, char::is_whitespace);
-            let first = parts.next().unwrap_or("");
-            if let Some(parsed_duration) = parse_duration_seconds(first) {
-                let parsed_reason = parts
-                    .next()
-                    .map(str::trim)
-                    .filter(|value| !value.is_empty());
-                (parsed_duration, first.to_owned(), parsed_reason)
-            } else {
-                (DEFAULT_TIMEOUT_SECS, "10m".to_owned(), Some(tail))
-            }
-        }
-        None => (DEFAULT_TIMEOUT_SECS, "10m".to_owned(), None),
-    };
-
     let expires_at_secs = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map_or(0, |duration| duration.as_secs())
-        .saturating_add(duration_secs);
+        .map_or(0, |elapsed| elapsed.as_secs())
+        .saturating_add(duration.secs);
 
     let Ok(expires_at) = Timestamp::from_secs(expires_at_secs as i64) else {
-        http.create_message(msg.channel_id)
-            .content("Unable to compute timeout expiration timestamp.")
+        invocation
+            .reply_content(http, "Unable to compute timeout expiration timestamp.")
             .await?;
         return Ok(());
     };
@@ -101,21 +200,32 @@ pub async fn run(
 
     if let Err(source) = request.await {
         error!(?source, "timeout request failed");
-        http.create_message(msg.channel_id)
-            .content("I couldn't timeout that user. Check role hierarchy and permissions.")
+        invocation
+            .reply_content(
+                http,
+                "I couldn't timeout that user. Check role hierarchy and permissions.",
+            )
             .await?;
         return Ok(());
     }
 
-    let target_profile = fetch_target_profile(http, target_user_id).await;
-    let embed = moderation_action_embed(
-        &target_profile,
-        target_user_id,
-        "timed out",
-        reason,
-        Some(&duration_label),
-    )?;
-    http.create_message(msg.channel_id).embeds(&[embed]).await?;
-
-    Ok(())
+    report_action(
+        &ctx,
+        invocation,
+        ActionReport {
+            case: NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Timeout,
+                user_id: Some(target_user_id.get()),
+                moderator_id: invocation.author_id().get(),
+                reason,
+                duration_secs: Some(duration.secs),
+                expires_at: Some(expires_at_secs),
+            },
+            target_user_id,
+            action_past_tense: "timed out",
+            duration_label: Some(&duration.label),
+        },
+    )
+    .await
 }
@@ -0,0 +1,206 @@
+use tracing::warn;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+    id::{Id, marker::ChannelMarker},
+};
+use twilight_util::builder::command::{ChannelBuilder, CommandBuilder, StringBuilder};
+
+use crate::moderation::case_log::{
+    ChannelActionReport, channel_case_reason, report_channel_action,
+};
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::lock::{channel_arg, display_name, fetch_guild_channel, unlock_channel};
+use crate::moderation::lockdown::lift_lockdown;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::channel_locks::channel_lock;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_channel, option_string};
+use rusty_utils::parse::{ArgError, ArgParser};
+use rusty_utils::permissions::has_invocation_permission;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "unlock",
+    desc: "Unlock a channel, or lift the server lockdown, restoring the previous permissions.",
+    category: "moderation",
+    usage: "!unlock [channel] [reason]",
+    cooldown: None,
+};
+
+pub struct UnlockCommand;
+
+impl Command for UnlockCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/unlock`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_CHANNELS)
+        .contexts([InteractionContextType::Guild])
+        .option(ChannelBuilder::new(
+            "channel",
+            "Locked channel to unlock (default: this one, or else the server lockdown).",
+        ))
+        .option(StringBuilder::new("reason", "Reason for unlocking."))
+        .build()
+}
+
+/// Unlock a channel or lift the server lockdown.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Unlock from the `/unlock` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let args = UnlockArgs {
+        channel_id: option_channel(data, "channel"),
+        reason: option_string(data, "reason").map(str::to_owned),
+    };
+    execute(ctx, &invocation, Ok(args)).await
+}
+
+struct UnlockArgs {
+    /// Set when the invoker named a channel, which rules out the lockdown.
+    channel_id: Option<Id<ChannelMarker>>,
+    reason: Option<String>,
+}
+
+fn parse_args(raw: &str) -> Result<UnlockArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let channel_id = channel_arg(&mut args);
+
+    Ok(UnlockArgs {
+        channel_id,
+        reason: args.rest(),
+    })
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<UnlockArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    let UnlockArgs { channel_id, reason } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+    let reason = reason.as_deref();
+    let moderator_id = invocation.author_id().get();
+
+    // Without a channel argument a locked current channel comes first, and
+    // only otherwise does this lift the server lockdown
+    let target_id = channel_id.unwrap_or_else(|| invocation.channel_id());
+    let lock = channel_lock(&ctx.db, guild_id.get(), target_id.get()).await?;
+    let required = if lock.is_some() || channel_id.is_some() {
+        Permissions::MANAGE_CHANNELS
+    } else {
+        Permissions::MANAGE_GUILD
+    };
+    if !has_invocation_permission(&ctx, invocation, required).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    if let Some(lock) = lock {
+        if let Err(source) = unlock_channel(&ctx, guild_id, &lock, reason).await {
+            warn!(
+                ?source,
+                channel_id = target_id.get(),
+                "unlocking channel failed"
+            );
+            invocation
+                .reply_content(
+                    http,
+                    &format!(
+                        "I couldn't unlock <#{target_id}>. I likely need the 'Manage Roles' permission there."
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+
+        let channel_name = fetch_guild_channel(&ctx, guild_id, target_id)
+            .await?
+            .map_or_else(|| target_id.to_string(), |channel| display_name(&channel));
+        let case_reason = channel_case_reason(target_id, reason);
+        return report_channel_action(
+            &ctx,
+            invocation,
+            ChannelActionReport {
+                case: NewCase {
+                    guild_id: guild_id.get(),
+                    action: CaseAction::Unlock,
+                    user_id: None,
+                    moderator_id,
+                    reason: Some(&case_reason),
+                    duration_secs: None,
+                    expires_at: None,
+                },
+                channel_id: target_id,
+                channel_name: &channel_name,
+                action_past_tense: "unlocked",
+                reason,
+                duration_label: None,
+            },
+        )
+        .await;
+    }
+
+    if channel_id.is_some() {
+        invocation
+            .reply_content(
+                http,
+                &format!(
+                    "<#{target_id}> isn't locked. Use `!unlock` without a channel to lift a server lockdown."
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let reply = match lift_lockdown(&ctx, guild_id, moderator_id, reason).await? {
+        Some(outcome) => outcome.summary(),
+        None => "This channel isn't locked and the server isn't in lockdown.".to_owned(),
+    };
+    invocation.reply_content(http, &reply).await?;
+
+    Ok(())
+}
@@ -1,68 +1,142 @@
-use twilight_model::{gateway::payload::incoming::MessageCreate, guild::Permissions};
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+};
+use twilight_util::builder::command::{CommandBuilder, StringBuilder, UserBuilder};
 
-use crate::CommandMeta;
-use crate::moderation::embeds::{fetch_target_profile, moderation_action_embed};
+use crate::moderation::args::TargetReasonArgs;
+use crate::moderation::case_log::{ActionReport, report_action};
+use crate::moderation::escalation::escalate_after_warning;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
 use rusty_database::impls::warnings::record_warning;
-use rusty_utils::parse::parse_target_user_id;
-use rusty_utils::permissions::has_message_permission;
+use rusty_database::model::cases::{CaseAction, NewCase};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::parse::ArgError;
+use rusty_utils::permissions::has_invocation_permission;
 
 pub const META: CommandMeta = CommandMeta {
     name: "warn",
     desc: "Issue a warning to a user.",
     category: "moderation",
     usage: "!warn <user> [reason]",
+    cooldown: None,
 };
 
+pub struct WarnCommand;
+
+impl Command for WarnCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/warn`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(UserBuilder::new("user", "User to warn.").required(true))
+        .option(StringBuilder::new("reason", "Reason for the warning."))
+        .build()
+}
+
 /// Record a warning for a target user and report it back to the channel.
-pub async fn run(
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    let args = TargetReasonArgs::parse(args, META.usage);
+    execute(ctx, &invocation, args).await
+}
+
+/// Record a warning from the `/warn` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let args = TargetReasonArgs::from_options(data, META.usage);
+    execute(ctx, &invocation, args).await
+}
+
+async fn execute(
     ctx: Context,
-    msg: Box<MessageCreate>,
-    arg1: Option<&str>,
-    arg_tail: Option<&str>,
+    invocation: &Invocation,
+    args: Result<TargetReasonArgs, ArgError>,
 ) -> anyhow::Result<()> {
     let http = &ctx.http;
-    let Some(guild_id) = msg.guild_id else {
-        http.create_message(msg.channel_id)
-            .content("This command only works in servers.")
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation
+            .reply_content(http, "This command only works in servers.")
             .await?;
         return Ok(());
     };
 
-    if !has_message_permission(http, &msg, Permissions::MANAGE_MESSAGES).await? {
-        http.create_message(msg.channel_id)
-            .content("You are not permitted to use this command.")
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, "You are not permitted to use this command.")
             .await?;
         return Ok(());
     }
 
-    let Some(raw_target) = arg1 else {
-        let usage = format!("Usage: `{}`", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
-    };
-
-    let Some(target_user_id) = parse_target_user_id(raw_target) else {
-        let usage = format!("Usage: `{}`", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let TargetReasonArgs {
+        target_user_id,
+        reason,
+    } = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
     };
+    let reason = reason.as_deref();
 
-    let reason = arg_tail.unwrap_or("No reason provided");
+    let reason = reason.unwrap_or("No reason provided");
     let warning = record_warning(
         &ctx.db,
         guild_id.get(),
         target_user_id.get(),
-        msg.author.id.get(),
+        invocation.author_id().get(),
         reason,
     )
     .await?;
-    let action = format!("warned #{}", warning.warn_number);
+    let action = format!("warned (warning #{})", warning.id);
 
-    let target_profile = fetch_target_profile(http, target_user_id).await;
-    let embed =
-        moderation_action_embed(&target_profile, target_user_id, &action, Some(reason), None)?;
-    http.create_message(msg.channel_id).embeds(&[embed]).await?;
+    report_action(
+        &ctx,
+        invocation,
+        ActionReport {
+            case: NewCase {
+                guild_id: guild_id.get(),
+                action: CaseAction::Warn,
+                user_id: Some(target_user_id.get()),
+                moderator_id: invocation.author_id().get(),
+                reason: Some(reason),
+                duration_secs: None,
+                expires_at: None,
+            },
+            target_user_id,
+            action_past_tense: &action,
+            duration_label: None,
+        },
+    )
+    .await?;
 
-    Ok(())
+    escalate_after_warning(&ctx, invocation, guild_id, target_user_id, warning, reason).await
 }
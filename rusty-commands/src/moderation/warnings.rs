@@ -1,93 +1,550 @@
-use twilight_model::{gateway::payload::incoming::MessageCreate, guild::Permissions};
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    channel::message::{Embed, component::Component},
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{GuildMarker, UserMarker},
+    },
+};
+use twilight_util::builder::command::{
+    BooleanBuilder, CommandBuilder, IntegerBuilder, StringBuilder, UserBuilder,
+};
 
-use crate::CommandMeta;
+use crate::cooldown::{Cooldown, CooldownScope};
 use crate::moderation::embeds::{
-    fetch_target_profile, guild_only_message, permission_denied_message, usage_message,
-    warnings_overview_embed, warnings_window_label_days,
+    fetch_target_profile, guild_only_message, moderation_invalid_interaction_message,
+    permission_denied_message, warnings_page_description, warnings_window_label_days,
 };
+use crate::registry::is_modal_submit;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
 use rusty_database::impls::warnings::{now_unix_secs, warnings_since};
-use rusty_utils::parse::parse_target_user_id;
-use rusty_utils::permissions::has_message_permission;
+use rusty_database::model::warnings::WarningEntry;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_bool, option_integer, option_string, option_user};
+use rusty_utils::pagination::token::{from_base36, to_base36};
+use rusty_utils::pagination::{
+    DEFAULT_TIMEOUT_SECS, PaginationInteractionValidation, PaginationModalSubmitValidation,
+    build_paginated_view_with_footer_note, clamp_page, open_jump_modal_from_token, page_window,
+    resolve_modal_target_page, respond_ephemeral_message, send_paginated_reply, total_pages,
+    update_paginated_interaction_message, validate_interaction_for_command_prefix,
+    validate_jump_modal_for_command_prefix,
+};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg};
+use rusty_utils::permissions::{check_interaction_permissions, has_invocation_permission};
 
 pub const META: CommandMeta = CommandMeta {
     name: "warnings",
     desc: "Show warning history for a user in a time window.",
     category: "moderation",
-    usage: "!warnings <user> [days|all]",
+    usage: "!warnings <user> [days|all] [--sort newest|oldest|moderator] [--moderator <user>]",
+    cooldown: Some(Cooldown::new(CooldownScope::User, 3, 15)),
 };
 
-const DEFAULT_DAYS: u64 = 30;
+/// Used when neither the invocation nor the guild settings give a window.
+pub(crate) const DEFAULT_DAYS: u64 = 30;
+/// Upper bound for the look-back window; anything longer is effectively `all`.
+const MAX_DAYS: i64 = 36_500;
+const WARNINGS_PER_PAGE: usize = 5;
 
+#[derive(Clone, Copy)]
 enum WarningWindow {
     Days(u64),
     All,
 }
 
+#[derive(Clone, Copy, Default)]
+enum WarningSort {
+    #[default]
+    Newest,
+    Oldest,
+    Moderator,
+}
+
+impl WarningSort {
+    /// Single-character form stored in pagination custom IDs.
+    fn code(self) -> char {
+        match self {
+            Self::Newest => 'n',
+            Self::Oldest => 'o',
+            Self::Moderator => 'm',
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "n" => Some(Self::Newest),
+            "o" => Some(Self::Oldest),
+            "m" => Some(Self::Moderator),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Newest => "Newest first",
+            Self::Oldest => "Oldest first",
+            Self::Moderator => "Grouped by moderator",
+        }
+    }
+}
+
+impl FromArg for WarningSort {
+    const EXPECTED: &'static str = "`newest`, `oldest` or `moderator`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "newest" => Some(Self::Newest),
+            "oldest" => Some(Self::Oldest),
+            "moderator" => Some(Self::Moderator),
+            _ => None,
+        }
+    }
+}
+
+/// Everything needed to re-render a warnings view from a button press.
+///
+/// The view is carried in the pagination command key as
+/// `warnings|<target>|<days or a>|<sort>|<moderator or ->`, with numbers in
+/// base 36 so the custom ID stays under Discord's 100-character limit.
+#[derive(Clone, Copy)]
+struct WarningsView {
+    target_user_id: Id<UserMarker>,
+    window: WarningWindow,
+    sort: WarningSort,
+    moderator_id: Option<Id<UserMarker>>,
+}
+
+impl WarningsView {
+    fn pagination_command(&self) -> String {
+        let window = match self.window {
+            WarningWindow::Days(days) => to_base36(days),
+            WarningWindow::All => "a".to_owned(),
+        };
+        let moderator = self
+            .moderator_id
+            .map_or_else(|| "-".to_owned(), |id| to_base36(id.get()));
+
+        format!(
+            "{}|{}|{}|{}|{}",
+            META.name,
+            to_base36(self.target_user_id.get()),
+            window,
+            self.sort.code(),
+            moderator
+        )
+    }
+
+    fn from_pagination_command(command: &str) -> Option<Self> {
+        let mut parts = command.split('|');
+        if parts.next()? != META.name {
+            return None;
+        }
+
+        let target_user_id = Id::new_checked(from_base36(parts.next()?)?)?;
+        let window = match parts.next()? {
+            "a" => WarningWindow::All,
+            days => WarningWindow::Days(from_base36(days)?),
+        };
+        let sort = WarningSort::from_code(parts.next()?)?;
+        let moderator_id = match parts.next()? {
+            "-" => None,
+            raw => Some(Id::new_checked(from_base36(raw)?)?),
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            target_user_id,
+            window,
+            sort,
+            moderator_id,
+        })
+    }
+}
+
+pub struct WarningsCommand;
+
+impl Command for WarningsCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+
+    fn component_prefixes(&self) -> &'static [&'static str] {
+        &["pg:warnings", "pgm:warnings"]
+    }
+
+    fn handle_component(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+    ) -> CommandFuture<bool> {
+        Box::pin(async move {
+            if is_modal_submit(&interaction) {
+                handle_pagination_modal_interaction(ctx, interaction).await
+            } else {
+                handle_pagination_interaction(ctx, interaction).await
+            }
+        })
+    }
+}
+
+/// Application command definition for `/warnings`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .contexts([InteractionContextType::Guild])
+        .option(UserBuilder::new("user", "User whose warnings to show.").required(true))
+        .option(
+            IntegerBuilder::new(
+                "days",
+                "Number of days to look back (default: 30 unless configured).",
+            )
+            .min_value(1)
+            .max_value(MAX_DAYS),
+        )
+        .option(BooleanBuilder::new("all", "Show warnings from all time."))
+        .option(
+            StringBuilder::new("sort", "Order of the listed warnings (default: newest).").choices(
+                [
+                    ("Newest first", "newest"),
+                    ("Oldest first", "oldest"),
+                    ("By moderator", "moderator"),
+                ],
+            ),
+        )
+        .option(UserBuilder::new(
+            "moderator",
+            "Only show warnings issued by this moderator.",
+        ))
+        .build()
+}
+
 /// Show warning history for a target user within a selected time window.
-pub async fn run(
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// Show warning history from the `/warnings` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+/// A parsed invocation; the window is `None` when the guild default applies.
+struct WarningsArgs {
+    target_user_id: Id<UserMarker>,
+    window: Option<WarningWindow>,
+    sort: WarningSort,
+    moderator_id: Option<Id<UserMarker>>,
+}
+
+fn parse_args(raw: &str) -> Result<WarningsArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+    let sort = args.flag("sort")?.unwrap_or_default();
+    let moderator_id = args.flag("moderator")?;
+    let target_user_id = args.required("user")?;
+
+    let window = if args.keyword("all") {
+        Some(WarningWindow::All)
+    } else {
+        args.optional_integer("days", 1..=MAX_DAYS)?
+            .map(|days| WarningWindow::Days(days.unsigned_abs()))
+    };
+    args.finish()?;
+
+    Ok(WarningsArgs {
+        target_user_id,
+        window,
+        sort,
+        moderator_id,
+    })
+}
+
+fn slash_args(data: &CommandData) -> Result<WarningsArgs, ArgError> {
+    let target_user_id =
+        option_user(data, "user").ok_or_else(|| ArgError::missing(META.usage, "user"))?;
+    let window = if option_bool(data, "all").unwrap_or(false) {
+        Some(WarningWindow::All)
+    } else {
+        option_integer(data, "days")
+            .and_then(|days| u64::try_from(days).ok())
+            .filter(|days| *days > 0)
+            .map(WarningWindow::Days)
+    };
+    let sort = match option_string(data, "sort") {
+        Some(raw) => WarningSort::from_arg(raw)
+            .ok_or_else(|| ArgError::invalid::<WarningSort>(META.usage, "sort", raw))?,
+        None => WarningSort::default(),
+    };
+
+    Ok(WarningsArgs {
+        target_user_id,
+        window,
+        sort,
+        moderator_id: option_user(data, "moderator"),
+    })
+}
+
+async fn execute(
     ctx: Context,
-    msg: Box<MessageCreate>,
-    arg1: Option<&str>,
-    arg_tail: Option<&str>,
+    invocation: &Invocation,
+    args: Result<WarningsArgs, ArgError>,
 ) -> anyhow::Result<()> {
     let http = &ctx.http;
-    let Some(guild_id) = msg.guild_id else {
-        http.create_message(msg.channel_id)
-            .content(guild_only_message())
-            .await?;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
         return Ok(());
     };
 
-    if !has_message_permission(http, &msg, Permissions::MANAGE_MESSAGES).await? {
-        http.create_message(msg.channel_id)
-            .content(permission_denied_message())
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_MESSAGES).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
             .await?;
         return Ok(());
     }
 
-    let Some(raw_target) = arg1 else {
-        let usage = usage_message(META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let args = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+    let window = match args.window {
+        Some(window) => window,
+        None => WarningWindow::Days(
+            ctx.guild_settings(guild_id.get())
+                .await?
+                .warnings_window_days
+                .unwrap_or(DEFAULT_DAYS),
+        ),
+    };
+    let view = WarningsView {
+        target_user_id: args.target_user_id,
+        window,
+        sort: args.sort,
+        moderator_id: args.moderator_id,
     };
 
-    let Some(target_user_id) = parse_target_user_id(raw_target) else {
-        let usage = usage_message(META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
-        return Ok(());
+    let entries = load_entries(&ctx, guild_id, &view).await?;
+    let total = total_pages(entries.len(), WARNINGS_PER_PAGE);
+    let (embed, components) =
+        build_view(&ctx, &view, &entries, 1, invocation.author_id().get()).await?;
+
+    send_paginated_reply(
+        &ctx,
+        invocation,
+        embed,
+        components,
+        total,
+        DEFAULT_TIMEOUT_SECS,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Handle pagination button presses for the `warnings` command.
+pub async fn handle_pagination_interaction(
+    ctx: Context,
+    interaction: Box<InteractionCreate>,
+) -> anyhow::Result<bool> {
+    let http = &ctx.http;
+    let (actor_id, token) =
+        match validate_interaction_for_command_prefix(http, &interaction, META.name).await? {
+            PaginationInteractionValidation::NotForCommand => return Ok(false),
+            PaginationInteractionValidation::HandledInvalid => return Ok(true),
+            PaginationInteractionValidation::Valid {
+                actor_user_id,
+                token,
+            } => (actor_user_id, token),
+        };
+
+    let Some((guild_id, view)) =
+        resolve_interaction_view(&ctx, &interaction, &token.command).await?
+    else {
+        return Ok(true);
     };
 
-    let window = parse_window(arg_tail);
-    let (since, window_label) = match window {
-        WarningWindow::Days(days) => (
-            now_unix_secs().saturating_sub(days.saturating_mul(86_400)),
-            warnings_window_label_days(days),
-        ),
-        WarningWindow::All => (0, "all time".to_owned()),
+    let entries = load_entries(&ctx, guild_id, &view).await?;
+    let total = total_pages(entries.len(), WARNINGS_PER_PAGE);
+
+    if token.action == "jump" {
+        open_jump_modal_from_token(http, &interaction, &token, total).await?;
+        return Ok(true);
+    }
+
+    let target_page = clamp_page(token.page, total);
+    let (embed, components) = build_view(&ctx, &view, &entries, target_page, actor_id).await?;
+
+    update_paginated_interaction_message(
+        &ctx,
+        &interaction,
+        embed,
+        components,
+        total,
+        DEFAULT_TIMEOUT_SECS,
+    )
+    .await?;
+
+    Ok(true)
+}
+
+/// Handle jump-modal submit interactions for the `warnings` command.
+pub async fn handle_pagination_modal_interaction(
+    ctx: Context,
+    interaction: Box<InteractionCreate>,
+) -> anyhow::Result<bool> {
+    let http = &ctx.http;
+    let (actor_id, command, entered_page, total_pages_hint) =
+        match validate_jump_modal_for_command_prefix(http, &interaction, META.name).await? {
+            PaginationModalSubmitValidation::NotForCommand => return Ok(false),
+            PaginationModalSubmitValidation::HandledInvalid => return Ok(true),
+            PaginationModalSubmitValidation::Valid {
+                actor_user_id,
+                command,
+                requested_page,
+                total_pages_hint,
+            } => (actor_user_id, command, requested_page, total_pages_hint),
+        };
+
+    let Some((guild_id, view)) = resolve_interaction_view(&ctx, &interaction, &command).await?
+    else {
+        return Ok(true);
     };
 
-    let entries = warnings_since(&ctx.db, guild_id.get(), target_user_id.get(), since).await?;
-    let target_profile = fetch_target_profile(http, target_user_id).await;
-    let embed = warnings_overview_embed(&target_profile, &window_label, &entries)?;
+    let entries = load_entries(&ctx, guild_id, &view).await?;
+    let total = total_pages(entries.len(), WARNINGS_PER_PAGE);
+    let target_page = resolve_modal_target_page(entered_page, total, total_pages_hint);
+    let (embed, components) = build_view(&ctx, &view, &entries, target_page, actor_id).await?;
 
-    http.create_message(msg.channel_id).embeds(&[embed]).await?;
+    update_paginated_interaction_message(
+        &ctx,
+        &interaction,
+        embed,
+        components,
+        total,
+        DEFAULT_TIMEOUT_SECS,
+    )
+    .await?;
 
-    Ok(())
+    Ok(true)
 }
 
-fn parse_window(arg_tail: Option<&str>) -> WarningWindow {
-    let Some(raw) = arg_tail.and_then(|value| value.split_whitespace().next()) else {
-        return WarningWindow::Days(DEFAULT_DAYS);
+/// Decode the view from a pagination command key and re-check access.
+///
+/// Returns `None` after replying when the interaction cannot be served; the
+/// permission check repeats because the moderator may have lost the role since
+/// the list was opened.
+async fn resolve_interaction_view(
+    ctx: &Context,
+    interaction: &InteractionCreate,
+    command: &str,
+) -> anyhow::Result<Option<(Id<GuildMarker>, WarningsView)>> {
+    let http = &ctx.http;
+    let Some(guild_id) = interaction.guild_id else {
+        respond_ephemeral_message(http, interaction, guild_only_message()).await?;
+        return Ok(None);
     };
 
-    if raw.eq_ignore_ascii_case("all") {
-        return WarningWindow::All;
+    if !check_interaction_permissions(interaction, Permissions::MANAGE_MESSAGES) {
+        respond_ephemeral_message(http, interaction, permission_denied_message()).await?;
+        return Ok(None);
     }
 
-    let Some(days) = raw.parse::<u64>().ok().filter(|value| *value > 0) else {
-        return WarningWindow::Days(DEFAULT_DAYS);
+    let Some(view) = WarningsView::from_pagination_command(command) else {
+        let invalid = moderation_invalid_interaction_message(META.name);
+        respond_ephemeral_message(http, interaction, &invalid).await?;
+        return Ok(None);
     };
 
-    WarningWindow::Days(days)
+    Ok(Some((guild_id, view)))
+}
+
+/// Load every active warning matching `view`, filtered and in display order.
+async fn load_entries(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    view: &WarningsView,
+) -> anyhow::Result<Vec<WarningEntry>> {
+    let since = match view.window {
+        WarningWindow::Days(days) => now_unix_secs().saturating_sub(days.saturating_mul(86_400)),
+        WarningWindow::All => 0,
+    };
+
+    let mut entries =
+        warnings_since(&ctx.db, guild_id.get(), view.target_user_id.get(), since).await?;
+    if let Some(moderator_id) = view.moderator_id {
+        entries.retain(|entry| entry.moderator_id == moderator_id.get());
+    }
+
+    match view.sort {
+        WarningSort::Newest => entries.sort_by_key(|entry| std::cmp::Reverse(entry.id)),
+        WarningSort::Oldest => entries.sort_by_key(|entry| entry.id),
+        WarningSort::Moderator => entries.sort_by_key(|entry| (entry.moderator_id, entry.id)),
+    }
+
+    Ok(entries)
+}
+
+async fn build_view(
+    ctx: &Context,
+    view: &WarningsView,
+    entries: &[WarningEntry],
+    page: usize,
+    owner_user_id: u64,
+) -> anyhow::Result<(Embed, Vec<Component>)> {
+    let total = total_pages(entries.len(), WARNINGS_PER_PAGE);
+    let page = clamp_page(page, total);
+    let (start, end) = page_window(entries.len(), WARNINGS_PER_PAGE, page);
+
+    let window_label = match view.window {
+        WarningWindow::Days(days) => warnings_window_label_days(days),
+        WarningWindow::All => "all time".to_owned(),
+    };
+    let description = warnings_page_description(
+        &window_label,
+        view.moderator_id.map(Id::get),
+        entries.len(),
+        &entries[start..end],
+    );
+
+    let target_profile = fetch_target_profile(ctx, view.target_user_id).await;
+    let title = format!("Warnings for {}", target_profile.display_name);
+
+    build_paginated_view_with_footer_note(
+        &view.pagination_command(),
+        &title,
+        description,
+        page,
+        total,
+        owner_user_id,
+        DEFAULT_TIMEOUT_SECS,
+        Some(view.sort.label()),
+    )
 }
@@ -0,0 +1,340 @@
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::{InteractionContextType, application_command::CommandData},
+    },
+    gateway::payload::incoming::MessageCreate,
+    guild::Permissions,
+};
+use twilight_util::builder::command::{CommandBuilder, IntegerBuilder, StringBuilder};
+
+use crate::moderation::embeds::{guild_only_message, permission_denied_message};
+use crate::moderation::escalation::MAX_TIMEOUT_SECS;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
+use rusty_core::Context;
+use rusty_database::impls::warn_policies::{delete_warn_policy, upsert_warn_policy, warn_policies};
+use rusty_database::impls::warnings::{set_warning_expiry, warning_settings};
+use rusty_database::model::warn_policies::{NewWarnPolicy, WarnPolicy, WarnPolicyAction};
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{option_integer, option_string};
+use rusty_utils::parse::{ArgError, ArgParser, FromArg, ParsedDuration};
+use rusty_utils::permissions::has_invocation_permission;
+use rusty_utils::time::format_duration_secs;
+
+pub const META: CommandMeta = CommandMeta {
+    name: "warnpolicy",
+    desc: "Configure automatic punishments for repeated warnings.",
+    category: "moderation",
+    usage: "!warnpolicy [list|add <count> <window> <punishment> [duration]|remove <id>|expiry <age|off>]",
+    cooldown: None,
+};
+
+const MAX_THRESHOLD: i64 = 100;
+
+pub struct WarnpolicyCommand;
+
+impl Command for WarnpolicyCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/warnpolicy`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .contexts([InteractionContextType::Guild])
+        .option(
+            IntegerBuilder::new("count", "Warnings needed to trigger the policy.")
+                .min_value(1)
+                .max_value(MAX_THRESHOLD),
+        )
+        .option(StringBuilder::new(
+            "window",
+            "Period the warnings must fall in, such as 7d.",
+        ))
+        .option(
+            StringBuilder::new("punishment", "Action applied when the policy triggers.").choices([
+                ("Timeout", "timeout"),
+                ("Kick", "kick"),
+                ("Ban", "ban"),
+            ]),
+        )
+        .option(StringBuilder::new(
+            "duration",
+            "Timeout length, or ban length for a temporary ban.",
+        ))
+        .option(IntegerBuilder::new("remove", "ID of a policy to remove.").min_value(1))
+        .option(StringBuilder::new(
+            "expiry",
+            "Age after which warnings stop counting, such as 90d, or off.",
+        ))
+        .build()
+}
+
+/// List, add or remove warning policies.
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let invocation = Invocation::from_message(msg);
+    execute(ctx, &invocation, parse_args(args)).await
+}
+
+/// List, add or remove warning policies from the `/warnpolicy` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, slash_args(data)).await
+}
+
+enum WarnpolicyArgs {
+    List,
+    Add {
+        threshold: u32,
+        window: ParsedDuration,
+        action: WarnPolicyAction,
+        duration: Option<ParsedDuration>,
+    },
+    Remove {
+        id: u64,
+    },
+    Expiry(ExpiryChange),
+}
+
+enum ExpiryChange {
+    Set(ParsedDuration),
+    Disable,
+}
+
+impl FromArg for ExpiryChange {
+    const EXPECTED: &'static str = "a duration like `90d`, or `off`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        if raw.trim().eq_ignore_ascii_case("off") {
+            return Some(Self::Disable);
+        }
+        ParsedDuration::from_arg(raw).map(Self::Set)
+    }
+}
+
+struct PunishmentArg(WarnPolicyAction);
+
+impl FromArg for PunishmentArg {
+    const EXPECTED: &'static str = "`timeout`, `kick` or `ban`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        raw.trim().to_ascii_lowercase().parse().ok().map(Self)
+    }
+}
+
+fn parse_args(raw: &str) -> Result<WarnpolicyArgs, ArgError> {
+    let mut args = ArgParser::new(raw, META.usage);
+
+    let parsed = if args.keyword("add") {
+        let threshold = args.integer("count", 1..=MAX_THRESHOLD)? as u32;
+        let window = args.required("window")?;
+        let PunishmentArg(action) = args.required("punishment")?;
+        WarnpolicyArgs::Add {
+            threshold,
+            window,
+            action,
+            duration: args.optional(),
+        }
+    } else if args.keyword("remove") {
+        WarnpolicyArgs::Remove {
+            id: args.integer("id", 1..=i64::MAX)?.unsigned_abs(),
+        }
+    } else if args.keyword("expiry") {
+        WarnpolicyArgs::Expiry(args.required("age")?)
+    } else {
+        args.keyword("list");
+        WarnpolicyArgs::List
+    };
+    args.finish()?;
+
+    Ok(parsed)
+}
+
+fn slash_args(data: &CommandData) -> Result<WarnpolicyArgs, ArgError> {
+    if let Some(id) = option_integer(data, "remove") {
+        return Ok(WarnpolicyArgs::Remove {
+            id: id.unsigned_abs(),
+        });
+    }
+    if option_string(data, "expiry").is_some() {
+        return required_option(data, "expiry").map(WarnpolicyArgs::Expiry);
+    }
+    let Some(threshold) = option_integer(data, "count") else {
+        return Ok(WarnpolicyArgs::List);
+    };
+
+    let window = required_option::<ParsedDuration>(data, "window")?;
+    let PunishmentArg(action) = required_option(data, "punishment")?;
+    let duration = option_string(data, "duration")
+        .map(|raw| {
+            ParsedDuration::from_arg(raw)
+                .ok_or_else(|| ArgError::invalid::<ParsedDuration>(META.usage, "duration", raw))
+        })
+        .transpose()?;
+
+    Ok(WarnpolicyArgs::Add {
+        threshold: threshold as u32,
+        window,
+        action,
+        duration,
+    })
+}
+
+fn required_option<T: FromArg>(data: &CommandData, name: &str) -> Result<T, ArgError> {
+    let raw = option_string(data, name).ok_or_else(|| ArgError::missing(META.usage, name))?;
+    T::from_arg(raw).ok_or_else(|| ArgError::invalid::<T>(META.usage, name, raw))
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    args: Result<WarnpolicyArgs, ArgError>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let Some(guild_id) = invocation.guild_id() else {
+        invocation.reply_content(http, guild_only_message()).await?;
+        return Ok(());
+    };
+
+    if !has_invocation_permission(&ctx, invocation, Permissions::MANAGE_GUILD).await? {
+        invocation
+            .reply_ephemeral(http, permission_denied_message())
+            .await?;
+        return Ok(());
+    }
+
+    let args = match args {
+        Ok(args) => args,
+        Err(error) => {
+            invocation.reply_content(http, &error.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let reply = match args {
+        WarnpolicyArgs::List => {
+            let policies = warn_policies(&ctx.db, guild_id.get()).await?;
+            let settings = warning_settings(&ctx.db, guild_id.get()).await?;
+            format!(
+                "{}\n{}",
+                policies_overview(&policies),
+                expiry_summary(settings.expiry_secs)
+            )
+        }
+        WarnpolicyArgs::Add {
+            threshold,
+            window,
+            action,
+            duration,
+        } => {
+            if let Some(problem) = duration_problem(action, duration.as_ref()) {
+                invocation.reply_content(http, problem).await?;
+                return Ok(());
+            }
+
+            let policy = upsert_warn_policy(
+                &ctx.db,
+                NewWarnPolicy {
+                    guild_id: guild_id.get(),
+                    threshold,
+                    window_secs: window.secs,
+                    action,
+                    duration_secs: duration.map(|duration| duration.secs),
+                    created_by: invocation.author_id().get(),
+                },
+            )
+            .await?;
+            format!("Saved warning policy {}", describe_policy(&policy))
+        }
+        WarnpolicyArgs::Remove { id } => {
+            if delete_warn_policy(&ctx.db, guild_id.get(), id).await? {
+                format!("Removed warning policy #{id}.")
+            } else {
+                format!("No warning policy #{id} exists in this server.")
+            }
+        }
+        WarnpolicyArgs::Expiry(change) => {
+            let expiry_secs = match change {
+                ExpiryChange::Set(age) => Some(age.secs),
+                ExpiryChange::Disable => None,
+            };
+            set_warning_expiry(&ctx.db, guild_id.get(), expiry_secs).await?;
+            expiry_summary(expiry_secs)
+        }
+    };
+
+    invocation.reply_content(http, &reply).await?;
+
+    Ok(())
+}
+
+fn duration_problem(
+    action: WarnPolicyAction,
+    duration: Option<&ParsedDuration>,
+) -> Option<&'static str> {
+    match (action, duration) {
+        (WarnPolicyAction::Timeout, None) => {
+            Some("Timeout policies need a duration, such as `1h`.")
+        }
+        (WarnPolicyAction::Timeout, Some(duration)) if duration.secs > MAX_TIMEOUT_SECS => {
+            Some("Timeouts can last at most 28 days.")
+        }
+        (WarnPolicyAction::Kick, Some(_)) => Some("Kick policies don't take a duration."),
+        _ => None,
+    }
+}
+
+fn describe_policy(policy: &WarnPolicy) -> String {
+    let punishment = match (policy.action, policy.duration_secs) {
+        (WarnPolicyAction::Timeout, Some(secs)) => {
+            format!("timeout for {}", format_duration_secs(secs))
+        }
+        (WarnPolicyAction::Ban, Some(secs)) => format!("ban for {}", format_duration_secs(secs)),
+        (action, _) => action.to_string(),
+    };
+
+    format!(
+        "#{}: {} warnings within {} → {}",
+        policy.id,
+        policy.threshold,
+        format_duration_secs(policy.window_secs),
+        punishment
+    )
+}
+
+fn policies_overview(policies: &[WarnPolicy]) -> String {
+    if policies.is_empty() {
+        return "No warning policies are configured.".to_owned();
+    }
+
+    let lines: Vec<String> = policies.iter().map(describe_policy).collect();
+    format!("Warning policies:\n{}", lines.join("\n"))
+}
+
+fn expiry_summary(expiry_secs: Option<u64>) -> String {
+    match expiry_secs {
+        Some(secs) => format!(
+            "Warnings stop counting after {}.",
+            format_duration_secs(secs)
+        ),
+        None => "Warnings never expire.".to_owned(),
+    }
+}
@@ -0,0 +1,170 @@
+//! Command trait and registry-driven dispatch helpers.
+
+use std::{future::Future, pin::Pin};
+
+use twilight_model::{
+    application::{
+        command::Command as ApplicationCommand,
+        interaction::{InteractionData, application_command::CommandData},
+    },
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+};
+
+use crate::{COMMANDS, CommandMeta};
+use rusty_core::Context;
+use rusty_utils::invocation::Invocation;
+
+/// Boxed future returned by [`Command`] entry points.
+pub type CommandFuture<T> = Pin<Box<dyn Future<Output = anyhow::Result<T>> + Send>>;
+
+/// Input handed to a command's run entry point.
+pub enum CommandInput {
+    /// A prefixed message command with the raw text following its name.
+    ///
+    /// Commands parse `args` with [`rusty_utils::parse::ArgParser`].
+    Message {
+        msg: Box<MessageCreate>,
+        args: String,
+    },
+    /// An application (slash) command with its resolved option data.
+    Slash {
+        invocation: Invocation,
+        data: Box<CommandData>,
+    },
+}
+
+/// A bot command: metadata, entry points and the interactions it owns.
+pub trait Command: Send + Sync {
+    /// Static metadata used for help, usage and dispatch.
+    fn meta(&self) -> &'static CommandMeta;
+
+    /// Application command definition registered with Discord.
+    fn application_command(&self) -> ApplicationCommand;
+
+    /// Run the command for a message or slash invocation.
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()>;
+
+    /// Custom ID prefixes of the components and modals this command owns.
+    fn component_prefixes(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Handle a component or modal interaction matching [`Self::component_prefixes`].
+    ///
+    /// Returns whether the interaction was handled.
+    fn handle_component(
+        &self,
+        _ctx: Context,
+        _interaction: Box<InteractionCreate>,
+    ) -> CommandFuture<bool> {
+        Box::pin(async { Ok(false) })
+    }
+
+    /// Answer an autocomplete request for one of this command's options.
+    fn autocomplete(
+        &self,
+        _ctx: Context,
+        _interaction: Box<InteractionCreate>,
+        _data: Box<CommandData>,
+    ) -> CommandFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Look up a registered command by name.
+pub fn find_command(name: &str) -> Option<&'static dyn Command> {
+    COMMANDS
+        .iter()
+        .copied()
+        .find(|command| command.meta().name == name)
+}
+
+/// Look up the registered command owning a component or modal custom ID.
+pub fn find_component_owner(custom_id: &str) -> Option<&'static dyn Command> {
+    COMMANDS.iter().copied().find(|command| {
+        command
+            .component_prefixes()
+            .iter()
+            .any(|prefix| custom_id.starts_with(prefix))
+    })
+}
+
+/// Metadata for every registered command, in registration order.
+pub fn command_metas() -> impl Iterator<Item = &'static CommandMeta> {
+    COMMANDS.iter().map(|command| command.meta())
+}
+
+/// Build the application (slash) command definitions for every command.
+pub fn application_commands() -> Vec<ApplicationCommand> {
+    COMMANDS
+        .iter()
+        .map(|command| command.application_command())
+        .collect()
+}
+
+/// Check the registry for duplicate names and overlapping custom ID prefixes.
+pub fn validate_registry() -> anyhow::Result<()> {
+    for (index, command) in COMMANDS.iter().enumerate() {
+        let name = command.meta().name;
+
+        for other in &COMMANDS[index + 1..] {
+            if other.meta().name == name {
+                anyhow::bail!("command `{name}` is registered more than once");
+            }
+
+            for prefix in command.component_prefixes() {
+                for other_prefix in other.component_prefixes() {
+                    if prefix.starts_with(other_prefix) || other_prefix.starts_with(prefix) {
+                        anyhow::bail!(
+                            "custom ID prefix `{prefix}` of `{name}` overlaps `{other_prefix}` of `{}`",
+                            other.meta().name
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether an interaction is a modal submit rather than a component press.
+pub fn is_modal_submit(interaction: &InteractionCreate) -> bool {
+    matches!(
+        interaction.data.as_ref(),
+        Some(InteractionData::ModalSubmit(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_valid() {
+        validate_registry().unwrap();
+    }
+
+    #[test]
+    fn application_commands_use_their_meta_names() {
+        for command in COMMANDS {
+            assert_eq!(command.application_command().name, command.meta().name);
+        }
+    }
+
+    #[test]
+    fn commands_and_components_resolve_to_their_owner() {
+        for command in COMMANDS {
+            let name = command.meta().name;
+            assert_eq!(
+                find_command(name).map(|found| found.meta().name),
+                Some(name)
+            );
+
+            for prefix in command.component_prefixes() {
+                let owner = find_component_owner(&format!("{prefix}1"));
+                assert_eq!(owner.map(|owner| owner.meta().name), Some(name));
+            }
+        }
+    }
+}
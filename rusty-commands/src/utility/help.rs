@@ -1,16 +1,27 @@
-use std::sync::Arc;
-use twilight_model::gateway::payload::incoming::{InteractionCreate, MessageCreate};
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::application_command::CommandData,
+    },
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+};
+use twilight_util::builder::command::{CommandBuilder, IntegerBuilder, StringBuilder};
 
+use crate::cooldown::{Cooldown, CooldownScope};
+use crate::registry::is_modal_submit;
 use crate::utility::embeds::{
     grouped_help_description, no_commands_message, page_out_of_range_message,
     unknown_category_message,
 };
-use crate::{COMMANDS, CommandMeta};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta, command_metas};
 use rusty_core::Context;
+use rusty_utils::interaction::respond_autocomplete_choices;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{focused_option, option_integer, option_string};
 use rusty_utils::pagination::{
     DEFAULT_TIMEOUT_SECS, PaginationInteractionValidation, PaginationModalSubmitValidation,
     build_paginated_view, build_paginated_view_with_footer_note, clamp_page,
-    open_jump_modal_from_token, page_window, resolve_modal_target_page, send_paginated_message,
+    open_jump_modal_from_token, page_window, resolve_modal_target_page, send_paginated_reply,
     total_pages, update_paginated_interaction_message, validate_interaction_for_command_prefix,
     validate_jump_modal_for_command_prefix,
 };
@@ -20,35 +31,137 @@ pub const META: CommandMeta = CommandMeta {
     desc: "Lists out all available commands.",
     category: "utility",
     usage: "!help [page|category]",
+    cooldown: Some(Cooldown::new(CooldownScope::User, 3, 15)),
 };
 
 const HELP_COMMANDS_PER_PAGE: usize = 20;
 
+pub struct HelpCommand;
+
+impl Command for HelpCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+
+    fn component_prefixes(&self) -> &'static [&'static str] {
+        &["pg:help", "pgm:help"]
+    }
+
+    fn handle_component(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+    ) -> CommandFuture<bool> {
+        Box::pin(async move {
+            if is_modal_submit(&interaction) {
+                handle_pagination_modal_interaction(ctx, interaction).await
+            } else {
+                handle_pagination_interaction(ctx, interaction).await
+            }
+        })
+    }
+
+    fn autocomplete(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+        data: Box<CommandData>,
+    ) -> CommandFuture<()> {
+        Box::pin(async move { autocomplete(ctx, &interaction, &data).await })
+    }
+}
+
+/// Application command definition for `/help`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .option(IntegerBuilder::new("page", "Page to show (starts at 1).").min_value(1))
+        .option(
+            StringBuilder::new("category", "Only list commands from this category.")
+                .autocomplete(true),
+        )
+        .build()
+}
+
 /// Render the command catalog, optionally filtered by category or page.
-pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> anyhow::Result<()> {
-    let http = &ctx.http;
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let arg1 = args.split_whitespace().next();
+    let invocation = Invocation::from_message(msg);
     let parsed_page = arg1.and_then(|raw| raw.parse::<usize>().ok().filter(|page| *page >= 1));
     let category = match (arg1, parsed_page) {
         (Some(raw), None) => Some(raw),
         _ => None,
     };
 
-    let mut categories: Vec<&str> = COMMANDS.iter().map(|c| c.category).collect();
-    categories.sort_unstable();
-    categories.dedup();
+    execute(ctx, &invocation, parsed_page, category).await
+}
+
+/// Render the command catalog from the `/help` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let parsed_page = option_integer(data, "page")
+        .and_then(|page| usize::try_from(page).ok())
+        .filter(|page| *page >= 1);
+    let category = option_string(data, "category");
+
+    execute(ctx, &invocation, parsed_page, category).await
+}
+
+/// Suggest command categories for the `/help` category option.
+pub async fn autocomplete(
+    ctx: Context,
+    interaction: &InteractionCreate,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let typed = focused_option(data)
+        .map(|(_, value)| value.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let choices: Vec<String> = command_categories()
+        .into_iter()
+        .filter(|category| category.starts_with(&typed))
+        .map(ToOwned::to_owned)
+        .collect();
+
+    respond_autocomplete_choices(&ctx.http, interaction, &choices).await
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    parsed_page: Option<usize>,
+    category: Option<&str>,
+) -> anyhow::Result<()> {
+    let http = &ctx.http;
+    let categories = command_categories();
 
     if let Some(wanted_category) = category
         && !categories.contains(&wanted_category)
     {
         let out = unknown_category_message(wanted_category, &categories);
-        http.create_message(msg.channel_id).content(&out).await?;
+        invocation.reply_content(http, &out).await?;
         return Ok(());
     }
 
     let commands = sorted_commands(category);
     if commands.is_empty() {
         let out = no_commands_message(category);
-        http.create_message(msg.channel_id).content(&out).await?;
+        invocation.reply_content(http, &out).await?;
         return Ok(());
     }
 
@@ -57,7 +170,7 @@ pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> a
 
     if requested_page > total {
         let out = page_out_of_range_message(requested_page, total);
-        http.create_message(msg.channel_id).content(&out).await?;
+        invocation.reply_content(http, &out).await?;
         return Ok(());
     }
 
@@ -74,7 +187,7 @@ pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> a
             description,
             requested_page,
             total,
-            msg.author.id.get(),
+            invocation.author_id().get(),
             DEFAULT_TIMEOUT_SECS,
             Some(note),
         )?,
@@ -84,14 +197,14 @@ pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> a
             description,
             requested_page,
             total,
-            msg.author.id.get(),
+            invocation.author_id().get(),
             DEFAULT_TIMEOUT_SECS,
         )?,
     };
 
-    send_paginated_message(
-        Arc::clone(&ctx.http),
-        msg.channel_id,
+    send_paginated_reply(
+        &ctx,
+        invocation,
         embed,
         components,
         total,
@@ -161,7 +274,7 @@ pub async fn handle_pagination_interaction(
     };
 
     update_paginated_interaction_message(
-        Arc::clone(&ctx.http),
+        &ctx,
         &interaction,
         embed,
         components,
@@ -228,7 +341,7 @@ pub async fn handle_pagination_modal_interaction(
     };
 
     update_paginated_interaction_message(
-        Arc::clone(&ctx.http),
+        &ctx,
         &interaction,
         embed,
         components,
@@ -259,9 +372,15 @@ fn help_footer_note() -> Option<String> {
     None
 }
 
+fn command_categories() -> Vec<&'static str> {
+    let mut categories: Vec<&'static str> = command_metas().map(|c| c.category).collect();
+    categories.sort_unstable();
+    categories.dedup();
+    categories
+}
+
 fn sorted_commands(category: Option<&str>) -> Vec<&'static CommandMeta> {
-    let mut filtered: Vec<&'static CommandMeta> = COMMANDS
-        .iter()
+    let mut filtered: Vec<&'static CommandMeta> = command_metas()
         .filter(|cmd| match category {
             Some(wanted) => cmd.category == wanted,
             None => true,
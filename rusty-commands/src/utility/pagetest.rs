@@ -1,13 +1,22 @@
-use std::sync::Arc;
-
-use twilight_model::gateway::payload::incoming::{InteractionCreate, MessageCreate};
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::application_command::CommandData,
+    },
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+};
+use twilight_util::builder::command::{CommandBuilder, IntegerBuilder};
 
-use crate::CommandMeta;
+use crate::cooldown::{Cooldown, CooldownScope};
+use crate::registry::is_modal_submit;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::option_integer;
 use rusty_utils::pagination::{
     DEFAULT_TIMEOUT_SECS, PaginationInteractionValidation, PaginationModalSubmitValidation,
     build_paginated_list_view, clamp_page, open_jump_modal_from_token, parse_one_based_page,
-    resolve_modal_target_page, send_paginated_message, total_pages,
+    resolve_modal_target_page, send_paginated_reply, total_pages,
     update_paginated_interaction_message, validate_interaction_for_command,
     validate_jump_modal_for_command,
 };
@@ -17,12 +26,59 @@ pub const META: CommandMeta = CommandMeta {
     desc: "Test embed pagination behavior.",
     category: "utility",
     usage: "!pagetest [page]",
+    cooldown: Some(Cooldown::new(CooldownScope::User, 1, 30)),
 };
 
 // TODO: Remove this temporary command after pagination verification is complete.
 
 const ITEMS_PER_PAGE: usize = 5;
 
+pub struct PagetestCommand;
+
+impl Command for PagetestCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+
+    fn component_prefixes(&self) -> &'static [&'static str] {
+        &["pg:pagetest:", "pgm:pagetest:"]
+    }
+
+    fn handle_component(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+    ) -> CommandFuture<bool> {
+        Box::pin(async move {
+            if is_modal_submit(&interaction) {
+                handle_pagination_modal_interaction(ctx, interaction).await
+            } else {
+                handle_pagination_interaction(ctx, interaction).await
+            }
+        })
+    }
+}
+
+/// Application command definition for `/pagetest`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .option(IntegerBuilder::new("page", "Page to show (starts at 1).").min_value(1))
+        .build()
+}
+
 /// Temporary pagination test command.
 ///
 /// Purpose:
@@ -34,14 +90,41 @@ const ITEMS_PER_PAGE: usize = 5;
 /// Error behavior:
 /// - returns usage text on invalid page input.
 /// - returns bounds text when the requested page is out of range.
-pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> anyhow::Result<()> {
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let arg1 = args.split_whitespace().next();
+    execute(
+        ctx,
+        &Invocation::from_message(msg),
+        parse_one_based_page(arg1),
+    )
+    .await
+}
+
+/// Temporary pagination test entry point for the `/pagetest` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let requested_page = match option_integer(data, "page") {
+        Some(page) => usize::try_from(page).ok().filter(|page| *page >= 1),
+        None => Some(1),
+    };
+    execute(ctx, &invocation, requested_page).await
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    requested_page: Option<usize>,
+) -> anyhow::Result<()> {
     let http = &ctx.http;
     let items = build_test_items();
     let total = total_pages(items.len(), ITEMS_PER_PAGE);
 
-    let Some(requested_page) = parse_one_based_page(arg1) else {
+    let Some(requested_page) = requested_page else {
         let usage = format!("Usage: `{}` (page starts at 1)", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
+        invocation.reply_content(http, &usage).await?;
         return Ok(());
     };
 
@@ -50,7 +133,7 @@ pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> a
             "Page {} does not exist. Available pages: 1-{}.",
             requested_page, total
         );
-        http.create_message(msg.channel_id).content(&out).await?;
+        invocation.reply_content(http, &out).await?;
         return Ok(());
     }
 
@@ -60,13 +143,13 @@ pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> a
         &items,
         requested_page,
         ITEMS_PER_PAGE,
-        msg.author.id.get(),
+        invocation.author_id().get(),
         DEFAULT_TIMEOUT_SECS,
     )?;
 
-    send_paginated_message(
-        Arc::clone(&ctx.http),
-        msg.channel_id,
+    send_paginated_reply(
+        &ctx,
+        invocation,
         embed,
         components,
         total,
@@ -114,7 +197,7 @@ pub async fn handle_pagination_interaction(
     )?;
 
     update_paginated_interaction_message(
-        Arc::clone(&ctx.http),
+        &ctx,
         &interaction,
         embed,
         components,
@@ -159,7 +242,7 @@ pub async fn handle_pagination_modal_interaction(
     )?;
 
     update_paginated_interaction_message(
-        Arc::clone(&ctx.http),
+        &ctx,
         &interaction,
         embed,
         components,
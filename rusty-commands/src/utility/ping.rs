@@ -1,19 +1,68 @@
-use twilight_model::gateway::payload::incoming::MessageCreate;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::application_command::CommandData,
+    },
+    gateway::payload::incoming::MessageCreate,
+};
+use twilight_util::builder::command::CommandBuilder;
 
-use crate::CommandMeta;
+use crate::cooldown::{Cooldown, CooldownScope};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
+use rusty_utils::invocation::Invocation;
 
 pub const META: CommandMeta = CommandMeta {
     name: "ping",
     desc: "Replies with Pong!",
     category: "utility",
     usage: "!ping",
+    cooldown: Some(Cooldown::new(CooldownScope::User, 3, 10)),
 };
 
+pub struct PingCommand;
+
+impl Command for PingCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, .. } => run(ctx, msg).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/ping`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput).build()
+}
+
 /// Send a simple connectivity response.
 pub async fn run(ctx: Context, msg: Box<MessageCreate>) -> anyhow::Result<()> {
+    execute(ctx, &Invocation::from_message(msg)).await
+}
+
+/// Send a simple connectivity response to the `/ping` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    _data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation).await
+}
+
+async fn execute(ctx: Context, invocation: &Invocation) -> anyhow::Result<()> {
     let http = &ctx.http;
-    http.create_message(msg.channel_id).content("Pong!").await?;
+    invocation.reply_content(http, "Pong!").await?;
 
     Ok(())
 }
@@ -1,20 +1,68 @@
-use twilight_model::gateway::payload::incoming::MessageCreate;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::application_command::CommandData,
+    },
+    gateway::payload::incoming::MessageCreate,
+};
+use twilight_util::builder::command::CommandBuilder;
 
-use crate::CommandMeta;
+use crate::{Command, CommandFuture, CommandInput, CommandMeta};
 use rusty_core::Context;
+use rusty_utils::invocation::Invocation;
 
 pub const META: CommandMeta = CommandMeta {
     name: "universe",
     desc: "The answer to the universe.",
     category: "utility",
     usage: "!universe",
+    cooldown: None,
 };
 
+pub struct UniverseCommand;
+
+impl Command for UniverseCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, .. } => run(ctx, msg).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+}
+
+/// Application command definition for `/universe`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput).build()
+}
+
 /// Send the universe easter-egg response.
 pub async fn run(ctx: Context, msg: Box<MessageCreate>) -> anyhow::Result<()> {
+    execute(ctx, &Invocation::from_message(msg)).await
+}
+
+/// Send the universe easter-egg response to the `/universe` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    _data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation).await
+}
+
+async fn execute(ctx: Context, invocation: &Invocation) -> anyhow::Result<()> {
     let http = &ctx.http;
-    http.create_message(msg.channel_id)
-        .content("The answer to the universe is 67 😹")
+    invocation
+        .reply_content(http, "The answer to the universe is 67 😹")
         .await?;
 
     Ok(())
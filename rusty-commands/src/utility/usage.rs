@@ -1,15 +1,68 @@
-use twilight_model::gateway::payload::incoming::MessageCreate;
+use twilight_model::{
+    application::{
+        command::{Command as ApplicationCommand, CommandType},
+        interaction::application_command::CommandData,
+    },
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+};
+use twilight_util::builder::command::{CommandBuilder, StringBuilder};
 
-use crate::{COMMANDS, CommandMeta};
+use crate::cooldown::{Cooldown, CooldownScope};
+use crate::{Command, CommandFuture, CommandInput, CommandMeta, command_metas};
 use rusty_core::Context;
+use rusty_utils::interaction::respond_autocomplete_choices;
+use rusty_utils::invocation::Invocation;
+use rusty_utils::options::{focused_option, option_string};
 
 pub const META: CommandMeta = CommandMeta {
     name: "usage",
     desc: "Show usage syntax for a specific command.",
     category: "utility",
     usage: "!usage <command>",
+    cooldown: Some(Cooldown::new(CooldownScope::User, 5, 15)),
 };
 
+pub struct UsageCommand;
+
+impl Command for UsageCommand {
+    fn meta(&self) -> &'static CommandMeta {
+        &META
+    }
+
+    fn application_command(&self) -> ApplicationCommand {
+        slash_command()
+    }
+
+    fn run(&self, ctx: Context, input: CommandInput) -> CommandFuture<()> {
+        Box::pin(async move {
+            match input {
+                CommandInput::Message { msg, args } => run(ctx, msg, &args).await,
+                CommandInput::Slash { invocation, data } => run_slash(ctx, invocation, &data).await,
+            }
+        })
+    }
+
+    fn autocomplete(
+        &self,
+        ctx: Context,
+        interaction: Box<InteractionCreate>,
+        data: Box<CommandData>,
+    ) -> CommandFuture<()> {
+        Box::pin(async move { autocomplete(ctx, &interaction, &data).await })
+    }
+}
+
+/// Application command definition for `/usage`.
+pub fn slash_command() -> ApplicationCommand {
+    CommandBuilder::new(META.name, META.desc, CommandType::ChatInput)
+        .option(
+            StringBuilder::new("command", "Command to show usage for.")
+                .required(true)
+                .autocomplete(true),
+        )
+        .build()
+}
+
 /// Show usage for a specific command.
 ///
 /// Purpose:
@@ -21,24 +74,64 @@ pub const META: CommandMeta = CommandMeta {
 /// Error behavior:
 /// - missing argument returns this command's usage.
 /// - unknown command returns a short not-found message.
-pub async fn run(ctx: Context, msg: Box<MessageCreate>, arg1: Option<&str>) -> anyhow::Result<()> {
+pub async fn run(ctx: Context, msg: Box<MessageCreate>, args: &str) -> anyhow::Result<()> {
+    let arg1 = args.split_whitespace().next();
+    execute(ctx, &Invocation::from_message(msg), arg1).await
+}
+
+/// Show usage for a specific command from the `/usage` application command.
+pub async fn run_slash(
+    ctx: Context,
+    invocation: Invocation,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    execute(ctx, &invocation, option_string(data, "command")).await
+}
+
+/// Suggest command names for the `/usage` command option.
+pub async fn autocomplete(
+    ctx: Context,
+    interaction: &InteractionCreate,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let typed = focused_option(data)
+        .map(|(_, value)| value.trim().trim_start_matches('!').to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let mut choices: Vec<String> = command_metas()
+        .filter(|command| command.name.starts_with(&typed))
+        .map(|command| command.name.to_owned())
+        .collect();
+    choices.sort_unstable();
+
+    respond_autocomplete_choices(&ctx.http, interaction, &choices).await
+}
+
+async fn execute(
+    ctx: Context,
+    invocation: &Invocation,
+    raw_name: Option<&str>,
+) -> anyhow::Result<()> {
     let http = &ctx.http;
-    let Some(raw_name) = arg1 else {
+    let Some(raw_name) = raw_name else {
         let usage = format!("Usage: `{}`", META.usage);
-        http.create_message(msg.channel_id).content(&usage).await?;
+        invocation.reply_content(http, &usage).await?;
         return Ok(());
     };
 
     let lookup = raw_name.trim().trim_start_matches('!').to_ascii_lowercase();
 
-    let Some(command) = COMMANDS.iter().find(|command| command.name == lookup) else {
+    let Some(command) = command_metas().find(|command| command.name == lookup) else {
         let out = format!("Unknown command: `{}`", lookup);
-        http.create_message(msg.channel_id).content(&out).await?;
+        invocation.reply_content(http, &out).await?;
         return Ok(());
     };
 
-    let out = format!("Usage: `{}`", command.usage);
-    http.create_message(msg.channel_id).content(&out).await?;
+    let mut out = format!("Usage: `{}`", command.usage);
+    if let Some(cooldown) = command.cooldown {
+        out.push_str(&format!("\nCooldown: {}", cooldown.describe()));
+    }
+    invocation.reply_content(http, &out).await?;
 
     Ok(())
 }
@@ -0,0 +1,106 @@
+//! Compiled automod rules kept in memory in front of the `automod_rules` table.
+//!
+//! Every guild message is checked against its guild's rules, so they are
+//! loaded and their patterns compiled once per [`RULES_TTL`] rather than per
+//! message.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use regex::{Regex, RegexBuilder};
+use tracing::warn;
+
+use rusty_database::Database;
+use rusty_database::impls::automod::automod_rules;
+use rusty_database::model::automod::{AutomodKind, AutomodRule};
+
+/// How long loaded rules are trusted before they are reloaded, which also
+/// bounds how long other processes take to see a change.
+const RULES_TTL: Duration = Duration::from_secs(60);
+/// Upper bound on the compiled size of a pattern supplied by a moderator.
+pub const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+/// A rule ready to be evaluated.
+#[derive(Debug)]
+pub struct CompiledRule {
+    pub rule: AutomodRule,
+    /// Combined matcher for the words and regex kinds.
+    pub matcher: Option<Regex>,
+}
+
+type GuildRules = Arc<[CompiledRule]>;
+
+/// Cache of compiled automod rules. Cheap to clone; clones share entries.
+#[derive(Clone, Default)]
+pub struct AutomodCache {
+    entries: Arc<Mutex<HashMap<u64, (Instant, GuildRules)>>>,
+}
+
+impl AutomodCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules for a guild, loading and compiling them when missing or stale.
+    ///
+    /// Rules whose patterns no longer compile are logged and skipped.
+    pub async fn get(&self, db: &Database, guild_id: u64) -> anyhow::Result<GuildRules> {
+        if let Some((loaded_at, rules)) = self.lock().get(&guild_id)
+            && loaded_at.elapsed() < RULES_TTL
+        {
+            return Ok(Arc::clone(rules));
+        }
+
+        let rules: GuildRules = automod_rules(db, guild_id)
+            .await?
+            .into_iter()
+            .filter_map(|rule| match compile_patterns(rule.kind, &rule.patterns) {
+                Ok(matcher) => Some(CompiledRule { rule, matcher }),
+                Err(source) => {
+                    warn!(?source, rule_id = rule.id, "skipping automod rule");
+                    None
+                }
+            })
+            .collect();
+
+        self.lock()
+            .insert(guild_id, (Instant::now(), Arc::clone(&rules)));
+        Ok(rules)
+    }
+
+    /// Drop a guild's cached rules so the next lookup reloads them.
+    pub fn invalidate(&self, guild_id: u64) {
+        self.lock().remove(&guild_id);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<u64, (Instant, GuildRules)>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Build the matcher for a words or regex rule; other kinds have none.
+///
+/// Words match case-insensitively and only as whole words, so `ass` does not
+/// match `class`.
+pub fn compile_patterns(kind: AutomodKind, patterns: &[String]) -> anyhow::Result<Option<Regex>> {
+    let pattern = match kind {
+        AutomodKind::Words => {
+            let words: Vec<String> = patterns.iter().map(|word| regex::escape(word)).collect();
+            format!(r"(?i)(?:^|\W)(?:{})(?:$|\W)", words.join("|"))
+        }
+        AutomodKind::Regex => patterns
+            .iter()
+            .map(|pattern| format!("(?:{pattern})"))
+            .collect::<Vec<_>>()
+            .join("|"),
+        _ => return Ok(None),
+    };
+
+    let matcher = RegexBuilder::new(&pattern)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()?;
+    Ok(Some(matcher))
+}
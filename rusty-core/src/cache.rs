@@ -0,0 +1,394 @@
+//! In-memory view of the guilds the bot is in.
+//!
+//! The gateway loop feeds every event to [`Cache::update`]. Readers get cloned
+//! snapshots and fall back to REST when something is missing, storing what
+//! they fetched with the `insert_*` methods so the next lookup hits.
+//!
+//! Members and users are only trusted for [`ENTRY_TTL`]: without the
+//! GUILD_MEMBERS intent nothing reports role changes for members who stay
+//! quiet, and those entries would otherwise pile up forever.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+
+use twilight_model::{
+    channel::Channel,
+    gateway::{event::Event, payload::incoming::GuildCreate},
+    guild::{Member, PartialMember, Role},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+    },
+    user::User,
+    util::{ImageHash, Timestamp},
+};
+
+/// How long a cached member or user is served before it is fetched again.
+pub const ENTRY_TTL: Duration = Duration::from_secs(5 * 60);
+/// How often expired members and users are dropped.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug)]
+pub struct CachedGuild {
+    pub id: Id<GuildMarker>,
+    pub name: String,
+    pub owner_id: Id<UserMarker>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CachedMember {
+    pub guild_id: Id<GuildMarker>,
+    pub user_id: Id<UserMarker>,
+    pub nick: Option<String>,
+    pub roles: Vec<Id<RoleMarker>>,
+    pub joined_at: Option<Timestamp>,
+    pub communication_disabled_until: Option<Timestamp>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CachedUser {
+    pub id: Id<UserMarker>,
+    pub name: String,
+    pub global_name: Option<String>,
+    pub avatar: Option<ImageHash>,
+    pub bot: bool,
+}
+
+impl From<&User> for CachedUser {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            name: user.name.clone(),
+            global_name: user.global_name.clone(),
+            avatar: user.avatar,
+            bot: user.bot,
+        }
+    }
+}
+
+/// A cached value and when it was stored.
+struct Stamped<T> {
+    value: T,
+    at: Instant,
+}
+
+impl<T: Clone> Stamped<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            at: Instant::now(),
+        }
+    }
+
+    fn fresh(&self) -> Option<T> {
+        (self.at.elapsed() < ENTRY_TTL).then(|| self.value.clone())
+    }
+}
+
+#[derive(Default)]
+struct CacheState {
+    guilds: HashMap<Id<GuildMarker>, CachedGuild>,
+    roles: HashMap<Id<GuildMarker>, HashMap<Id<RoleMarker>, Role>>,
+    channels: HashMap<Id<ChannelMarker>, Channel>,
+    members: HashMap<(Id<GuildMarker>, Id<UserMarker>), Stamped<CachedMember>>,
+    users: HashMap<Id<UserMarker>, Stamped<CachedUser>>,
+    current_user_id: Option<Id<UserMarker>>,
+    last_sweep: Option<Instant>,
+}
+
+/// Shared guild, role, channel, member and user cache.
+///
+/// Cheap to clone; every clone reads and writes the same state.
+#[derive(Clone, Default)]
+pub struct Cache {
+    state: Arc<RwLock<CacheState>>,
+}
+
+impl Cache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a gateway event. Events the cache does not track are ignored.
+    pub fn update(&self, event: &Event) {
+        match event {
+            Event::Ready(ready) => {
+                let mut state = self.write();
+                state.users.insert(
+                    ready.user.id,
+                    Stamped::new(CachedUser {
+                        id: ready.user.id,
+                        name: ready.user.name.clone(),
+                        global_name: None,
+                        avatar: ready.user.avatar,
+                        bot: ready.user.bot,
+                    }),
+                );
+                state.current_user_id = Some(ready.user.id);
+            }
+            Event::GuildCreate(guild_create) => {
+                if let GuildCreate::Available(guild) = guild_create.as_ref() {
+                    let mut state = self.write();
+                    state.remove_guild(guild.id);
+                    state.guilds.insert(
+                        guild.id,
+                        CachedGuild {
+                            id: guild.id,
+                            name: guild.name.clone(),
+                            owner_id: guild.owner_id,
+                        },
+                    );
+                    state.set_roles(guild.id, &guild.roles);
+                    for channel in &guild.channels {
+                        state.insert_channel(channel, Some(guild.id));
+                    }
+                    for member in &guild.members {
+                        state.insert_member(guild.id, member);
+                    }
+                }
+            }
+            Event::GuildUpdate(guild) => {
+                let mut state = self.write();
+                state.guilds.insert(
+                    guild.id,
+                    CachedGuild {
+                        id: guild.id,
+                        name: guild.name.clone(),
+                        owner_id: guild.owner_id,
+                    },
+                );
+                state.set_roles(guild.id, &guild.roles);
+            }
+            Event::GuildDelete(guild) => self.write().remove_guild(guild.id),
+            Event::RoleCreate(role) => self.write().insert_role(role.guild_id, &role.role),
+            Event::RoleUpdate(role) => self.write().insert_role(role.guild_id, &role.role),
+            Event::RoleDelete(role) => {
+                if let Some(roles) = self.write().roles.get_mut(&role.guild_id) {
+                    roles.remove(&role.role_id);
+                }
+            }
+            Event::ChannelCreate(channel) => self.write().insert_channel(&channel.0, None),
+            Event::ChannelUpdate(channel) => self.write().insert_channel(&channel.0, None),
+            Event::ChannelDelete(channel) => {
+                self.write().channels.remove(&channel.0.id);
+            }
+            Event::MemberAdd(member) => self.write().insert_member(member.guild_id, &member.member),
+            Event::MemberUpdate(member) => {
+                let mut state = self.write();
+                state.insert_user(&member.user);
+                state.members.insert(
+                    (member.guild_id, member.user.id),
+                    Stamped::new(CachedMember {
+                        guild_id: member.guild_id,
+                        user_id: member.user.id,
+                        nick: member.nick.clone(),
+                        roles: member.roles.clone(),
+                        joined_at: member.joined_at,
+                        communication_disabled_until: member.communication_disabled_until,
+                    }),
+                );
+            }
+            Event::MemberRemove(member) => {
+                self.write()
+                    .members
+                    .remove(&(member.guild_id, member.user.id));
+            }
+            Event::MemberChunk(chunk) => {
+                let mut state = self.write();
+                for member in &chunk.members {
+                    state.insert_member(chunk.guild_id, member);
+                }
+            }
+            Event::MessageCreate(msg) => {
+                let mut state = self.write();
+                state.insert_user(&msg.author);
+                if let (Some(guild_id), Some(member)) = (msg.guild_id, msg.member.as_ref()) {
+                    state.insert_partial_member(guild_id, &msg.author, member);
+                }
+            }
+            Event::InteractionCreate(interaction) => {
+                let mut state = self.write();
+                if let (Some(guild_id), Some(member)) =
+                    (interaction.guild_id, interaction.member.as_ref())
+                    && let Some(user) = member.user.as_ref()
+                {
+                    state.insert_user(user);
+                    state.insert_partial_member(guild_id, user, member);
+                } else if let Some(user) = interaction.user.as_ref() {
+                    state.insert_user(user);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn guild(&self, guild_id: Id<GuildMarker>) -> Option<CachedGuild> {
+        self.read().guilds.get(&guild_id).cloned()
+    }
+
+    /// Every role of a guild, or `None` if the guild's roles are not cached.
+    pub fn roles(&self, guild_id: Id<GuildMarker>) -> Option<Vec<Role>> {
+        self.read()
+            .roles
+            .get(&guild_id)
+            .map(|roles| roles.values().cloned().collect())
+    }
+
+    pub fn channel(&self, channel_id: Id<ChannelMarker>) -> Option<Channel> {
+        self.read().channels.get(&channel_id).cloned()
+    }
+
+    /// A member stored within the last [`ENTRY_TTL`].
+    pub fn member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Option<CachedMember> {
+        self.read()
+            .members
+            .get(&(guild_id, user_id))
+            .and_then(Stamped::fresh)
+    }
+
+    /// The bot's own user ID, known once a shard has received READY.
+    pub fn current_user_id(&self) -> Option<Id<UserMarker>> {
+        self.read().current_user_id
+    }
+
+    /// A user stored within the last [`ENTRY_TTL`].
+    pub fn user(&self, user_id: Id<UserMarker>) -> Option<CachedUser> {
+        self.read().users.get(&user_id).and_then(Stamped::fresh)
+    }
+
+    /// Store a guild fetched over REST.
+    pub fn insert_guild(&self, guild: CachedGuild) {
+        self.write().guilds.insert(guild.id, guild);
+    }
+
+    /// Store a guild's full role list fetched over REST.
+    pub fn insert_roles(&self, guild_id: Id<GuildMarker>, roles: &[Role]) {
+        self.write().set_roles(guild_id, roles);
+    }
+
+    /// Store a channel fetched over REST.
+    pub fn insert_channel(&self, channel: &Channel) {
+        self.write().insert_channel(channel, None);
+    }
+
+    /// Store a member fetched over REST.
+    pub fn insert_member(&self, guild_id: Id<GuildMarker>, member: &Member) {
+        self.write().insert_member(guild_id, member);
+    }
+
+    /// Store a user fetched over REST.
+    pub fn insert_user(&self, user: &User) {
+        self.write().insert_user(user);
+    }
+
+    // A panic while holding the lock leaves the maps consistent enough to keep
+    // serving, so poisoning is ignored.
+    fn read(&self) -> RwLockReadGuard<'_, CacheState> {
+        self.state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, CacheState> {
+        let mut state = self
+            .state
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.sweep();
+        state
+    }
+}
+
+impl CacheState {
+    /// Drop expired members and users, at most once per [`SWEEP_INTERVAL`].
+    fn sweep(&mut self) {
+        let now = Instant::now();
+        if self
+            .last_sweep
+            .is_some_and(|last| now.duration_since(last) < SWEEP_INTERVAL)
+        {
+            return;
+        }
+        self.last_sweep = Some(now);
+
+        self.members
+            .retain(|_, member| now.duration_since(member.at) < ENTRY_TTL);
+        self.users
+            .retain(|_, user| now.duration_since(user.at) < ENTRY_TTL);
+    }
+
+    fn remove_guild(&mut self, guild_id: Id<GuildMarker>) {
+        self.guilds.remove(&guild_id);
+        self.roles.remove(&guild_id);
+        self.channels
+            .retain(|_, channel| channel.guild_id != Some(guild_id));
+        self.members
+            .retain(|(member_guild_id, _), _| *member_guild_id != guild_id);
+    }
+
+    fn set_roles(&mut self, guild_id: Id<GuildMarker>, roles: &[Role]) {
+        self.roles.insert(
+            guild_id,
+            roles.iter().map(|role| (role.id, role.clone())).collect(),
+        );
+    }
+
+    fn insert_role(&mut self, guild_id: Id<GuildMarker>, role: &Role) {
+        self.roles
+            .entry(guild_id)
+            .or_default()
+            .insert(role.id, role.clone());
+    }
+
+    /// Channels inside a GUILD_CREATE payload carry no guild ID of their own.
+    fn insert_channel(&mut self, channel: &Channel, guild_id: Option<Id<GuildMarker>>) {
+        let mut channel = channel.clone();
+        channel.guild_id = channel.guild_id.or(guild_id);
+        self.channels.insert(channel.id, channel);
+    }
+
+    fn insert_member(&mut self, guild_id: Id<GuildMarker>, member: &Member) {
+        self.insert_user(&member.user);
+        self.members.insert(
+            (guild_id, member.user.id),
+            Stamped::new(CachedMember {
+                guild_id,
+                user_id: member.user.id,
+                nick: member.nick.clone(),
+                roles: member.roles.clone(),
+                joined_at: member.joined_at,
+                communication_disabled_until: member.communication_disabled_until,
+            }),
+        );
+    }
+
+    fn insert_partial_member(
+        &mut self,
+        guild_id: Id<GuildMarker>,
+        user: &User,
+        member: &PartialMember,
+    ) {
+        self.members.insert(
+            (guild_id, user.id),
+            Stamped::new(CachedMember {
+                guild_id,
+                user_id: user.id,
+                nick: member.nick.clone(),
+                roles: member.roles.clone(),
+                joined_at: member.joined_at,
+                communication_disabled_until: member.communication_disabled_until,
+            }),
+        );
+    }
+
+    fn insert_user(&mut self, user: &User) {
+        self.users
+            .insert(user.id, Stamped::new(CachedUser::from(user)));
+    }
+}
@@ -0,0 +1,310 @@
+//! Delayed work that survives restarts.
+//!
+//! Jobs are rows in the `jobs` table with a kind, a JSON payload and a run-at
+//! timestamp. Features define a [`JobPayload`] type, register a handler for it
+//! in a [`JobRegistry`], and queue work with [`schedule_job`]. The runner
+//! started by [`spawn_job_runner`] leases due jobs, runs their handlers and
+//! retries failures with exponential backoff until it is told to shut down.
+
+use std::{collections::HashMap, future::Future, pin::Pin, sync::OnceLock, time::Duration};
+
+use anyhow::Context as _;
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{sync::Notify, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::Context;
+use rusty_database::Database;
+use rusty_database::impls::jobs::{
+    cancel_job, claim_due_jobs, claim_jobs_of_kind, complete_job, enqueue_job, fail_job,
+    next_job_run_at, pending_job_count, retry_job,
+};
+use rusty_database::impls::warnings::now_unix_secs;
+use rusty_database::model::jobs::{JobEntry, NewJob};
+
+/// How long a claimed job is leased before another runner may pick it up.
+const LEASE_SECS: u64 = 5 * 60;
+/// Jobs claimed per database round trip.
+const BATCH_SIZE: u32 = 25;
+/// Longest the runner sleeps without checking the queue, which also bounds the
+/// delay for jobs queued by other processes.
+const MAX_IDLE: Duration = Duration::from_secs(30);
+/// Delay after the first failed attempt; doubles on every further failure.
+const RETRY_BASE_SECS: u64 = 30;
+const RETRY_MAX_SECS: u64 = 6 * 60 * 60;
+
+/// A typed job payload stored as JSON.
+pub trait JobPayload: Serialize + DeserializeOwned + Send + 'static {
+    /// Stable identifier stored with each job; never rename once jobs are queued.
+    const KIND: &'static str;
+    /// Attempts before the job is parked as failed.
+    const MAX_ATTEMPTS: u32 = 5;
+}
+
+type JobFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+type JobHandler = Box<dyn Fn(Context, &str) -> anyhow::Result<JobFuture> + Send + Sync>;
+
+/// Handlers for every job kind the runner knows how to execute.
+#[derive(Default)]
+pub struct JobRegistry {
+    handlers: HashMap<&'static str, JobHandler>,
+}
+
+impl JobRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the handler for jobs carrying payload `P`.
+    ///
+    /// A handler error schedules a retry until `P::MAX_ATTEMPTS` is reached.
+    pub fn register<P, F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        P: JobPayload,
+        F: Fn(Context, P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let erased: JobHandler = Box::new(move |ctx, raw| {
+            let payload: P = serde_json::from_str(raw)
+                .with_context(|| format!("invalid {} payload", P::KIND))?;
+            Ok(Box::pin(handler(ctx, payload)))
+        });
+
+        if self.handlers.insert(P::KIND, erased).is_some() {
+            warn!(
+                kind = P::KIND,
+                "job handler registered twice, keeping the last one"
+            );
+        }
+        self
+    }
+}
+
+fn wakeup() -> &'static Notify {
+    static WAKEUP: OnceLock<Notify> = OnceLock::new();
+    WAKEUP.get_or_init(Notify::new)
+}
+
+/// Queue `payload` to run at `run_at` (unix seconds).
+///
+/// Scheduling again with the same idempotency key replaces the pending job, so
+/// callers can use it to move or refresh work without creating duplicates.
+pub async fn schedule_job<P: JobPayload>(
+    db: &Database,
+    payload: &P,
+    run_at: u64,
+    idempotency_key: Option<&str>,
+) -> anyhow::Result<u64> {
+    let payload = serde_json::to_string(payload)
+        .with_context(|| format!("failed to serialize {} payload", P::KIND))?;
+
+    let id = enqueue_job(
+        db,
+        NewJob {
+            kind: P::KIND,
+            payload: &payload,
+            idempotency_key,
+            run_at,
+            max_attempts: P::MAX_ATTEMPTS,
+        },
+    )
+    .await?;
+
+    wakeup().notify_one();
+    Ok(id)
+}
+
+/// Cancel a pending job by idempotency key, returning whether one existed.
+pub async fn cancel_scheduled_job(db: &Database, idempotency_key: &str) -> anyhow::Result<bool> {
+    cancel_job(db, idempotency_key).await
+}
+
+/// Spawn the background task that executes due jobs.
+///
+/// Jobs queued before a restart are picked up as soon as it starts. Once
+/// `shutdown` is cancelled the runner finishes the batch it is working on and
+/// exits; await the handle to drain it.
+pub fn spawn_job_runner(
+    ctx: Context,
+    registry: JobRegistry,
+    shutdown: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(run_jobs(ctx, registry, shutdown))
+}
+
+async fn run_jobs(ctx: Context, registry: JobRegistry, shutdown: CancellationToken) {
+    match pending_job_count(&ctx.db).await {
+        Ok(pending) => info!(pending, "job runner started"),
+        Err(source) => error!(?source, "failed to count pending jobs"),
+    }
+
+    while !shutdown.is_cancelled() {
+        let now = now_unix_secs();
+        let claimed = match claim_due_jobs(&ctx.db, now, now + LEASE_SECS, BATCH_SIZE).await {
+            Ok(jobs) => jobs,
+            Err(source) => {
+                error!(?source, "failed to claim due jobs");
+                Vec::new()
+            }
+        };
+
+        let batch_full = claimed.len() == BATCH_SIZE as usize;
+        for job in claimed {
+            run_job(&ctx, &registry, job).await;
+        }
+        if batch_full {
+            continue;
+        }
+
+        let delay = match next_job_run_at(&ctx.db).await {
+            Ok(Some(run_at)) => {
+                Duration::from_secs(run_at.saturating_sub(now_unix_secs())).min(MAX_IDLE)
+            }
+            Ok(None) => MAX_IDLE,
+            Err(source) => {
+                error!(?source, "failed to look up next job");
+                MAX_IDLE
+            }
+        };
+        if delay.is_zero() {
+            continue;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = wakeup().notified() => {}
+            _ = shutdown.cancelled() => {}
+        }
+    }
+
+    info!("job runner stopped");
+}
+
+/// Run every pending job carrying payload `P` whose idempotency key starts with
+/// `key_prefix` now, whether due or not.
+///
+/// Failed jobs are handed back to the regular runner at their original time.
+/// Returns how many jobs completed.
+pub async fn run_jobs_now<P, F, Fut>(
+    ctx: &Context,
+    key_prefix: &str,
+    handler: F,
+) -> anyhow::Result<usize>
+where
+    P: JobPayload,
+    F: Fn(Context, P) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let mut completed = 0;
+    loop {
+        let now = now_unix_secs();
+        let claimed = claim_jobs_of_kind(
+            &ctx.db,
+            P::KIND,
+            key_prefix,
+            now,
+            now + LEASE_SECS,
+            BATCH_SIZE,
+        )
+        .await?;
+        let batch_full = claimed.len() == BATCH_SIZE as usize;
+        let mut any_failed = false;
+
+        for job in claimed {
+            let outcome = match serde_json::from_str::<P>(&job.payload) {
+                Ok(payload) => handler(ctx.clone(), payload).await,
+                Err(source) => Err(source.into()),
+            };
+
+            let result = match outcome {
+                Ok(()) => {
+                    completed += 1;
+                    complete_job(&ctx.db, &job).await
+                }
+                Err(source) => {
+                    any_failed = true;
+                    warn!(?source, id = job.id, kind = %job.kind, "early job run failed");
+                    retry_job(&ctx.db, &job, job.run_at, &format!("{source:#}")).await
+                }
+            };
+            if let Err(source) = result {
+                error!(?source, id = job.id, kind = %job.kind, "failed to record job outcome");
+            }
+        }
+
+        // Released failures would be claimed again, so stop rather than spin on them.
+        if !batch_full || any_failed {
+            return Ok(completed);
+        }
+    }
+}
+
+async fn run_job(ctx: &Context, registry: &JobRegistry, job: JobEntry) {
+    let Some(handler) = registry.handlers.get(job.kind.as_str()) else {
+        warn!(id = job.id, kind = %job.kind, "no handler registered for job kind");
+        park_job(ctx, &job, "no handler registered for job kind").await;
+        return;
+    };
+
+    // Attempts are counted on claim, so a job that keeps crashing the runner
+    // still runs out of attempts.
+    if job.attempts > job.max_attempts {
+        park_job(ctx, &job, "attempts exhausted").await;
+        return;
+    }
+
+    let outcome = match handler(ctx.clone(), &job.payload) {
+        Ok(future) => future.await,
+        Err(source) => {
+            error!(?source, id = job.id, kind = %job.kind, "job payload rejected");
+            park_job(ctx, &job, &format!("{source:#}")).await;
+            return;
+        }
+    };
+
+    let result = match outcome {
+        Ok(()) => complete_job(&ctx.db, &job).await,
+        Err(source) if job.attempts >= job.max_attempts => {
+            error!(
+                ?source,
+                id = job.id,
+                kind = %job.kind,
+                attempts = job.attempts,
+                "job failed permanently"
+            );
+            fail_job(&ctx.db, &job, &format!("{source:#}")).await
+        }
+        Err(source) => {
+            let delay_secs = retry_delay_secs(job.attempts);
+            warn!(
+                ?source,
+                id = job.id,
+                kind = %job.kind,
+                attempts = job.attempts,
+                delay_secs,
+                "job failed, retrying"
+            );
+            let run_at = now_unix_secs().saturating_add(delay_secs);
+            retry_job(&ctx.db, &job, run_at, &format!("{source:#}")).await
+        }
+    };
+
+    if let Err(source) = result {
+        error!(?source, id = job.id, kind = %job.kind, "failed to record job outcome");
+    }
+}
+
+async fn park_job(ctx: &Context, job: &JobEntry, reason: &str) {
+    if let Err(source) = fail_job(&ctx.db, job, reason).await {
+        error!(?source, id = job.id, kind = %job.kind, "failed to park job");
+    }
+}
+
+fn retry_delay_secs(attempts: u32) -> u64 {
+    let exponent = attempts.saturating_sub(1).min(20);
+    RETRY_BASE_SECS
+        .saturating_mul(1_u64 << exponent)
+        .min(RETRY_MAX_SECS)
+}
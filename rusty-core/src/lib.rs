@@ -1,8 +1,28 @@
 use std::sync::Arc;
 
 use rusty_database::Database;
+use rusty_database::model::guild_settings::GuildSettings;
 use twilight_http::Client;
 
+use crate::automod::{AutomodCache, CompiledRule};
+use crate::cache::Cache;
+use crate::raid::JoinTracker;
+use crate::settings::SettingsCache;
+use crate::spam::SpamTracker;
+
+/// Compiled automod rules backed by the `automod_rules` table.
+pub mod automod;
+/// In-memory guild, member, role and channel cache fed by the gateway.
+pub mod cache;
+/// Durable scheduled jobs backed by the `jobs` table.
+pub mod jobs;
+/// Sliding-window join histories used for raid detection.
+pub mod raid;
+/// Cached per-guild settings backed by the `guild_settings` table.
+pub mod settings;
+/// Sliding-window message histories used for spam detection.
+pub mod spam;
+
 /// Shared application context passed into command handlers.
 ///
 /// Cheap to clone because it only stores reference-counted shared state.
@@ -10,11 +30,54 @@ use twilight_http::Client;
 pub struct Context {
     pub db: Database,
     pub http: Arc<Client>,
+    pub cache: Cache,
+    pub settings: SettingsCache,
+    pub automod: AutomodCache,
+    pub spam: SpamTracker,
+    pub raid: JoinTracker,
+    /// Tags work owned by this process, such as its shard range, in tables
+    /// shared with other processes.
+    pub instance: Arc<str>,
+    /// Whether the gateway sends member joins and updates; raid detection
+    /// needs them.
+    pub member_events: bool,
 }
 
 impl Context {
     /// Create a new application context.
-    pub fn new(http: Arc<Client>, db: Database) -> Self {
-        Self { http, db }
+    pub fn new(http: Arc<Client>, db: Database, instance: &str, member_events: bool) -> Self {
+        Self {
+            http,
+            db,
+            cache: Cache::new(),
+            settings: SettingsCache::new(),
+            automod: AutomodCache::new(),
+            spam: SpamTracker::new(),
+            raid: JoinTracker::new(),
+            instance: Arc::from(instance),
+            member_events,
+        }
+    }
+
+    /// Settings for a guild, served from memory when recently loaded.
+    pub async fn guild_settings(&self, guild_id: u64) -> anyhow::Result<GuildSettings> {
+        self.settings.get(&self.db, guild_id).await
+    }
+
+    /// Change and save a guild's settings, returning the new values.
+    pub async fn update_guild_settings<F>(
+        &self,
+        guild_id: u64,
+        change: F,
+    ) -> anyhow::Result<GuildSettings>
+    where
+        F: FnOnce(&mut GuildSettings),
+    {
+        self.settings.update(&self.db, guild_id, change).await
+    }
+
+    /// Compiled automod rules for a guild, served from memory when recently loaded.
+    pub async fn automod_rules(&self, guild_id: u64) -> anyhow::Result<Arc<[CompiledRule]>> {
+        self.automod.get(&self.db, guild_id).await
     }
 }
@@ -0,0 +1,396 @@
+//! Sliding windows of recent member joins, used to spot raids.
+//!
+//! Joins are remembered per guild for as long as the longest configured
+//! window. A join that pushes a guild over a limit yields every member in the
+//! window so the response can cover the whole wave.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use rusty_database::model::guild_settings::SpamLimit;
+
+/// Longest window a limit may use; also how long an idle history is kept.
+pub const MAX_RAID_WINDOW_SECS: u64 = 10 * 60;
+/// How often histories nobody has joined recently are dropped.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The limits one guild enforces. `None` turns a limit off.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RaidLimits {
+    pub join_rate: Option<SpamLimit>,
+    pub young_join_rate: Option<SpamLimit>,
+}
+
+impl RaidLimits {
+    fn retention(&self) -> Duration {
+        let longest = [self.join_rate, self.young_join_rate]
+            .into_iter()
+            .flatten()
+            .map(|limit| limit.window_secs)
+            .max()
+            .unwrap_or(0);
+        Duration::from_secs(longest)
+    }
+}
+
+/// Which limit a raid broke.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaidTrigger {
+    Joins,
+    YoungJoins,
+}
+
+/// A member join as seen by the tracker.
+#[derive(Clone, Copy, Debug)]
+pub struct TrackedJoin {
+    pub guild_id: u64,
+    pub user_id: u64,
+    /// Whether the account is younger than the guild's minimum account age.
+    pub young: bool,
+}
+
+/// Members whose joins together broke a limit, oldest first.
+#[derive(Clone, Debug)]
+pub struct Raid {
+    pub trigger: RaidTrigger,
+    pub limit: SpamLimit,
+    pub user_ids: Vec<u64>,
+}
+
+#[derive(Clone, Copy)]
+struct Join {
+    at: Instant,
+    user_id: u64,
+    young: bool,
+}
+
+#[derive(Default)]
+struct Histories {
+    guilds: HashMap<u64, VecDeque<Join>>,
+    last_sweep: Option<Instant>,
+}
+
+/// Per-process join histories. Cheap to clone; clones share state.
+///
+/// Like message floods, a guild's joins all arrive on the shard that owns it.
+#[derive(Clone, Default)]
+pub struct JoinTracker {
+    histories: Arc<Mutex<Histories>>,
+}
+
+impl JoinTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember a join and report the raid it completes, if any.
+    ///
+    /// Joins of a reported raid are forgotten, so a continuing raid is
+    /// reported again once enough new members pile up.
+    pub fn record(&self, join: TrackedJoin, limits: &RaidLimits) -> Option<Raid> {
+        self.record_at(join, limits, Instant::now())
+    }
+
+    fn record_at(&self, join: TrackedJoin, limits: &RaidLimits, now: Instant) -> Option<Raid> {
+        let retention = limits.retention();
+        if retention.is_zero() {
+            return None;
+        }
+
+        let mut histories = self.lock();
+        histories.sweep(now);
+
+        let history = histories.guilds.entry(join.guild_id).or_default();
+        while history
+            .front()
+            .is_some_and(|oldest| now.duration_since(oldest.at) > retention)
+        {
+            history.pop_front();
+        }
+        history.push_back(Join {
+            at: now,
+            user_id: join.user_id,
+            young: join.young,
+        });
+
+        // Young accounts are the stronger signal, so they are checked first
+        let raid = detect(
+            history,
+            now,
+            limits.young_join_rate,
+            RaidTrigger::YoungJoins,
+            |join| join.young,
+        )
+        .or_else(|| detect(history, now, limits.join_rate, RaidTrigger::Joins, |_| true))?;
+
+        let raiders: HashSet<u64> = raid.user_ids.iter().copied().collect();
+        history.retain(|join| !raiders.contains(&join.user_id));
+        Some(raid)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Histories> {
+        self.histories
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Histories {
+    /// Drop histories whose newest join has aged out of every window.
+    fn sweep(&mut self, now: Instant) {
+        if self
+            .last_sweep
+            .is_some_and(|last| now.duration_since(last) < SWEEP_INTERVAL)
+        {
+            return;
+        }
+        self.last_sweep = Some(now);
+
+        let max_window = Duration::from_secs(MAX_RAID_WINDOW_SECS);
+        self.guilds.retain(|_, history| {
+            history
+                .back()
+                .is_some_and(|join| now.duration_since(join.at) <= max_window)
+        });
+    }
+}
+
+fn detect(
+    history: &VecDeque<Join>,
+    now: Instant,
+    limit: Option<SpamLimit>,
+    trigger: RaidTrigger,
+    counts: impl Fn(&Join) -> bool,
+) -> Option<Raid> {
+    let limit = limit.filter(|limit| limit.count > 0)?;
+    let window = Duration::from_secs(limit.window_secs);
+    let user_ids: Vec<u64> = history
+        .iter()
+        .filter(|join| now.duration_since(join.at) <= window && counts(join))
+        .map(|join| join.user_id)
+        .collect();
+    (user_ids.len() > limit.count as usize).then_some(Raid {
+        trigger,
+        limit,
+        user_ids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: u64 = 1;
+
+    fn join(guild_id: u64, user_id: u64, young: bool) -> TrackedJoin {
+        TrackedJoin {
+            guild_id,
+            user_id,
+            young,
+        }
+    }
+
+    fn join_rate(count: u32, window_secs: u64) -> RaidLimits {
+        RaidLimits {
+            join_rate: Some(SpamLimit { count, window_secs }),
+            ..RaidLimits::default()
+        }
+    }
+
+    #[test]
+    fn join_rate_triggers_past_the_count() {
+        let tracker = JoinTracker::new();
+        let limits = join_rate(3, 10);
+        let start = Instant::now();
+
+        for user_id in 0..3 {
+            let at = start + Duration::from_secs(user_id);
+            assert!(
+                tracker
+                    .record_at(join(GUILD, user_id, false), &limits, at)
+                    .is_none()
+            );
+        }
+        let raid = tracker
+            .record_at(
+                join(GUILD, 3, false),
+                &limits,
+                start + Duration::from_secs(3),
+            )
+            .expect("fourth join breaks the limit");
+        assert_eq!(raid.trigger, RaidTrigger::Joins);
+        assert_eq!(raid.user_ids, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn reported_joins_are_forgotten() {
+        let tracker = JoinTracker::new();
+        let limits = join_rate(1, 10);
+        let start = Instant::now();
+
+        assert!(
+            tracker
+                .record_at(join(GUILD, 1, false), &limits, start)
+                .is_none()
+        );
+        assert!(
+            tracker
+                .record_at(join(GUILD, 2, false), &limits, start)
+                .is_some()
+        );
+        assert!(
+            tracker
+                .record_at(join(GUILD, 3, false), &limits, start)
+                .is_none()
+        );
+        let raid = tracker.record_at(join(GUILD, 4, false), &limits, start);
+        assert_eq!(raid.map(|raid| raid.user_ids), Some(vec![3, 4]));
+    }
+
+    #[test]
+    fn joins_outside_the_window_do_not_count() {
+        let tracker = JoinTracker::new();
+        let limits = join_rate(2, 10);
+        let start = Instant::now();
+
+        assert!(
+            tracker
+                .record_at(join(GUILD, 1, false), &limits, start)
+                .is_none()
+        );
+        assert!(
+            tracker
+                .record_at(join(GUILD, 2, false), &limits, start)
+                .is_none()
+        );
+        let later = start + Duration::from_secs(11);
+        assert!(
+            tracker
+                .record_at(join(GUILD, 3, false), &limits, later)
+                .is_none()
+        );
+        assert!(
+            tracker
+                .record_at(join(GUILD, 4, false), &limits, later)
+                .is_none()
+        );
+        let raid = tracker.record_at(join(GUILD, 5, false), &limits, later);
+        assert_eq!(raid.map(|raid| raid.user_ids), Some(vec![3, 4, 5]));
+    }
+
+    #[test]
+    fn window_edge_is_inclusive() {
+        let tracker = JoinTracker::new();
+        let limits = join_rate(1, 10);
+        let start = Instant::now();
+
+        assert!(
+            tracker
+                .record_at(join(GUILD, 1, false), &limits, start)
+                .is_none()
+        );
+        let edge = start + Duration::from_secs(10);
+        assert!(
+            tracker
+                .record_at(join(GUILD, 2, false), &limits, edge)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn guilds_are_tracked_separately() {
+        let tracker = JoinTracker::new();
+        let limits = join_rate(2, 10);
+        let start = Instant::now();
+
+        for user_id in 0..2 {
+            assert!(
+                tracker
+                    .record_at(join(GUILD, user_id, false), &limits, start)
+                    .is_none()
+            );
+            assert!(
+                tracker
+                    .record_at(join(GUILD + 1, user_id, false), &limits, start)
+                    .is_none()
+            );
+        }
+        let raid = tracker
+            .record_at(join(GUILD + 1, 2, false), &limits, start)
+            .expect("second guild breaks its own limit");
+        assert_eq!(raid.user_ids, [0, 1, 2]);
+        assert!(
+            tracker
+                .record_at(join(GUILD, 3, false), &limits, start)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn young_joins_are_checked_first_and_alone() {
+        let tracker = JoinTracker::new();
+        let limits = RaidLimits {
+            join_rate: Some(SpamLimit {
+                count: 3,
+                window_secs: 10,
+            }),
+            young_join_rate: Some(SpamLimit {
+                count: 1,
+                window_secs: 10,
+            }),
+        };
+        let start = Instant::now();
+
+        assert!(
+            tracker
+                .record_at(join(GUILD, 1, true), &limits, start)
+                .is_none()
+        );
+        assert!(
+            tracker
+                .record_at(join(GUILD, 2, false), &limits, start)
+                .is_none()
+        );
+        let raid = tracker
+            .record_at(join(GUILD, 3, true), &limits, start)
+            .expect("second young join breaks the young limit");
+        assert_eq!(raid.trigger, RaidTrigger::YoungJoins);
+        assert_eq!(raid.user_ids, [1, 3]);
+    }
+
+    #[test]
+    fn no_limits_tracks_nothing() {
+        let tracker = JoinTracker::new();
+        let start = Instant::now();
+
+        for user_id in 0..10 {
+            let raid = tracker.record_at(join(GUILD, user_id, true), &RaidLimits::default(), start);
+            assert!(raid.is_none());
+        }
+        assert!(tracker.lock().guilds.is_empty());
+    }
+
+    #[test]
+    fn sweep_drops_idle_guilds() {
+        let tracker = JoinTracker::new();
+        let limits = join_rate(5, 10);
+        let start = Instant::now();
+
+        assert!(
+            tracker
+                .record_at(join(GUILD, 1, false), &limits, start)
+                .is_none()
+        );
+        let later = start + Duration::from_secs(MAX_RAID_WINDOW_SECS + 1);
+        assert!(
+            tracker
+                .record_at(join(GUILD + 1, 2, false), &limits, later)
+                .is_none()
+        );
+
+        let histories = tracker.lock();
+        assert!(!histories.guilds.contains_key(&GUILD));
+        assert!(histories.guilds.contains_key(&(GUILD + 1)));
+    }
+}
@@ -0,0 +1,67 @@
+//! Per-guild settings kept in memory in front of the `guild_settings` table.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use rusty_database::Database;
+use rusty_database::impls::guild_settings::{guild_settings, save_guild_settings};
+use rusty_database::model::guild_settings::GuildSettings;
+
+/// How long loaded settings are trusted. Other processes running the bot's
+/// other shards may change them, so entries are reloaded after this long.
+const SETTINGS_TTL: Duration = Duration::from_secs(60);
+
+/// Cache of loaded guild settings. Cheap to clone; clones share entries.
+#[derive(Clone, Default)]
+pub struct SettingsCache {
+    entries: Arc<Mutex<HashMap<u64, (Instant, GuildSettings)>>>,
+}
+
+impl SettingsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Settings for a guild, loading them from the database when missing or stale.
+    pub async fn get(&self, db: &Database, guild_id: u64) -> anyhow::Result<GuildSettings> {
+        if let Some((loaded_at, settings)) = self.lock().get(&guild_id)
+            && loaded_at.elapsed() < SETTINGS_TTL
+        {
+            return Ok(settings.clone());
+        }
+
+        let settings = guild_settings(db, guild_id).await?;
+        self.lock()
+            .insert(guild_id, (Instant::now(), settings.clone()));
+        Ok(settings)
+    }
+
+    /// Change a guild's settings, save them and return the result.
+    ///
+    /// Always starts from the stored row so a stale cached copy cannot
+    /// overwrite a change made elsewhere.
+    pub async fn update<F>(
+        &self,
+        db: &Database,
+        guild_id: u64,
+        change: F,
+    ) -> anyhow::Result<GuildSettings>
+    where
+        F: FnOnce(&mut GuildSettings),
+    {
+        let mut settings = guild_settings(db, guild_id).await?;
+        change(&mut settings);
+        save_guild_settings(db, guild_id, &settings).await?;
+
+        self.lock()
+            .insert(guild_id, (Instant::now(), settings.clone()));
+        Ok(settings)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<u64, (Instant, GuildSettings)>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,505 @@
+//! Sliding windows of recent guild messages, used to spot message floods.
+//!
+//! Every tracked message is remembered per author and per channel for as long
+//! as the longest configured window. A message that pushes either history over
+//! a limit yields the whole burst so it can be cleaned up together.
+//!
+//! Windows are timed by when Discord says a message was sent, taken from its
+//! ID, so messages that queue up behind a slow command and are processed
+//! together do not look like a flood.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use rusty_database::model::guild_settings::SpamLimit;
+
+/// Longest window a limit may use; also how long an idle history is kept.
+pub const MAX_SPAM_WINDOW_SECS: u64 = 10 * 60;
+/// How often histories nobody has posted to recently are dropped.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// Bigram overlap at which two longer messages count as near-duplicates.
+const DUPLICATE_SIMILARITY: f64 = 0.8;
+/// Normalized messages shorter than this must match exactly to count as duplicates.
+const MIN_FUZZY_LEN: usize = 10;
+/// Unix time in milliseconds of the first second of 2015, where snowflakes start.
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// When a snowflake ID was created, in Unix milliseconds.
+fn snowflake_ms(id: u64) -> u64 {
+    (id >> 22).saturating_add(DISCORD_EPOCH_MS)
+}
+
+/// The limits one guild enforces. `None` turns a limit off.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpamLimits {
+    pub user_rate: Option<SpamLimit>,
+    pub user_duplicates: Option<SpamLimit>,
+    pub channel_rate: Option<SpamLimit>,
+    pub channel_duplicates: Option<SpamLimit>,
+}
+
+impl SpamLimits {
+    fn retention(&self) -> Duration {
+        let longest = [
+            self.user_rate,
+            self.user_duplicates,
+            self.channel_rate,
+            self.channel_duplicates,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|limit| limit.window_secs)
+        .max()
+        .unwrap_or(0);
+        Duration::from_secs(longest)
+    }
+}
+
+/// Which limit a burst broke.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpamTrigger {
+    UserRate,
+    UserDuplicates,
+    ChannelRate,
+    ChannelDuplicates,
+}
+
+/// A guild message as seen by the tracker.
+#[derive(Clone, Copy, Debug)]
+pub struct TrackedMessage<'a> {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    /// Also tells the tracker when the message was sent.
+    pub message_id: u64,
+    pub author_id: u64,
+    pub content: &'a str,
+}
+
+/// A message that belongs to a detected burst.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BurstMessage {
+    pub channel_id: u64,
+    pub message_id: u64,
+    pub author_id: u64,
+}
+
+/// Messages that together broke a limit, oldest first.
+#[derive(Clone, Debug)]
+pub struct SpamBurst {
+    pub trigger: SpamTrigger,
+    pub limit: SpamLimit,
+    pub messages: Vec<BurstMessage>,
+}
+
+#[derive(Clone)]
+struct Seen {
+    /// Unix milliseconds the message was sent at.
+    at: u64,
+    message: BurstMessage,
+    fingerprint: Option<Arc<Fingerprint>>,
+}
+
+#[derive(Default)]
+struct Histories {
+    users: HashMap<(u64, u64), VecDeque<Seen>>,
+    channels: HashMap<u64, VecDeque<Seen>>,
+    last_sweep: Option<u64>,
+}
+
+/// Per-process message histories. Cheap to clone; clones share state.
+///
+/// Each process only sees the guilds on its own shards, which is every message
+/// a guild sends, so no state needs to be shared between processes.
+#[derive(Clone, Default)]
+pub struct SpamTracker {
+    histories: Arc<Mutex<Histories>>,
+}
+
+impl SpamTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember a message and report the burst it completes, if any.
+    ///
+    /// Messages of a reported burst are forgotten, so the next burst has to
+    /// build up from scratch rather than re-triggering on every message.
+    pub fn record(&self, message: TrackedMessage<'_>, limits: &SpamLimits) -> Option<SpamBurst> {
+        let retention = limits.retention();
+        if retention.is_zero() {
+            return None;
+        }
+
+        let now = snowflake_ms(message.message_id);
+        let seen = Seen {
+            at: now,
+            message: BurstMessage {
+                channel_id: message.channel_id,
+                message_id: message.message_id,
+                author_id: message.author_id,
+            },
+            fingerprint: Fingerprint::new(message.content).map(Arc::new),
+        };
+
+        let mut guard = self.lock();
+        let histories = &mut *guard;
+        histories.sweep(now);
+
+        let user_key = (message.guild_id, message.author_id);
+        let user = histories.users.entry(user_key).or_default();
+        push(user, seen.clone(), now, retention);
+        let burst = detect(user, now, limits.user_rate, SpamTrigger::UserRate).or_else(|| {
+            detect_duplicates(
+                user,
+                &seen,
+                now,
+                limits.user_duplicates,
+                SpamTrigger::UserDuplicates,
+            )
+        });
+
+        let channel = histories.channels.entry(message.channel_id).or_default();
+        push(channel, seen.clone(), now, retention);
+        let burst = burst
+            .or_else(|| detect(channel, now, limits.channel_rate, SpamTrigger::ChannelRate))
+            .or_else(|| {
+                detect_duplicates(
+                    channel,
+                    &seen,
+                    now,
+                    limits.channel_duplicates,
+                    SpamTrigger::ChannelDuplicates,
+                )
+            })?;
+
+        histories.forget(message.guild_id, &burst.messages);
+        Some(burst)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Histories> {
+        self.histories
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Histories {
+    /// Drop histories whose newest message has aged out of every window.
+    fn sweep(&mut self, now: u64) {
+        if self
+            .last_sweep
+            .is_some_and(|last| elapsed(last, now) < SWEEP_INTERVAL)
+        {
+            return;
+        }
+        self.last_sweep = Some(now);
+
+        // Guilds keep different windows, so only drop histories that even the
+        // longest allowed window would have emptied.
+        let max_window = Duration::from_secs(MAX_SPAM_WINDOW_SECS);
+        let stale = |history: &VecDeque<Seen>| {
+            history
+                .back()
+                .is_none_or(|seen| elapsed(seen.at, now) > max_window)
+        };
+        self.users.retain(|_, history| !stale(history));
+        self.channels.retain(|_, history| !stale(history));
+    }
+
+    fn forget(&mut self, guild_id: u64, messages: &[BurstMessage]) {
+        let ids: HashSet<u64> = messages.iter().map(|message| message.message_id).collect();
+        for message in messages {
+            if let Some(history) = self.users.get_mut(&(guild_id, message.author_id)) {
+                history.retain(|seen| !ids.contains(&seen.message.message_id));
+            }
+            if let Some(history) = self.channels.get_mut(&message.channel_id) {
+                history.retain(|seen| !ids.contains(&seen.message.message_id));
+            }
+        }
+    }
+}
+
+/// Time from `earlier` to `now`; zero when messages arrive out of order.
+fn elapsed(earlier: u64, now: u64) -> Duration {
+    Duration::from_millis(now.saturating_sub(earlier))
+}
+
+fn push(history: &mut VecDeque<Seen>, seen: Seen, now: u64, retention: Duration) {
+    while history
+        .front()
+        .is_some_and(|oldest| elapsed(oldest.at, now) > retention)
+    {
+        history.pop_front();
+    }
+    history.push_back(seen);
+}
+
+fn within(seen: &Seen, now: u64, limit: SpamLimit) -> bool {
+    elapsed(seen.at, now) <= Duration::from_secs(limit.window_secs)
+}
+
+fn detect(
+    history: &VecDeque<Seen>,
+    now: u64,
+    limit: Option<SpamLimit>,
+    trigger: SpamTrigger,
+) -> Option<SpamBurst> {
+    let limit = limit.filter(|limit| limit.count > 0)?;
+    let messages: Vec<BurstMessage> = history
+        .iter()
+        .filter(|seen| within(seen, now, limit))
+        .map(|seen| seen.message)
+        .collect();
+    (messages.len() > limit.count as usize).then_some(SpamBurst {
+        trigger,
+        limit,
+        messages,
+    })
+}
+
+fn detect_duplicates(
+    history: &VecDeque<Seen>,
+    latest: &Seen,
+    now: u64,
+    limit: Option<SpamLimit>,
+    trigger: SpamTrigger,
+) -> Option<SpamBurst> {
+    let limit = limit.filter(|limit| limit.count > 0)?;
+    let fingerprint = latest.fingerprint.as_deref()?;
+    let messages: Vec<BurstMessage> = history
+        .iter()
+        .filter(|seen| within(seen, now, limit))
+        .filter(|seen| {
+            seen.fingerprint
+                .as_deref()
+                .is_some_and(|other| fingerprint.is_near_duplicate(other))
+        })
+        .map(|seen| seen.message)
+        .collect();
+    (messages.len() > limit.count as usize).then_some(SpamBurst {
+        trigger,
+        limit,
+        messages,
+    })
+}
+
+/// Message content reduced to what matters for duplicate detection.
+struct Fingerprint {
+    normalized: String,
+    bigrams: HashSet<(char, char)>,
+}
+
+impl Fingerprint {
+    /// Lowercased letters and digits with whitespace collapsed, so `Hello!!`
+    /// and `hello` match. Content without any, such as a row of emoji, is
+    /// compared as written. Empty messages have no fingerprint.
+    fn new(content: &str) -> Option<Self> {
+        let mut normalized = String::new();
+        for word in content.split_whitespace() {
+            let word: String = word
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect();
+            if word.is_empty() {
+                continue;
+            }
+            if !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            normalized.push_str(&word);
+        }
+        if normalized.is_empty() {
+            normalized = content.trim().to_owned();
+        }
+        if normalized.is_empty() {
+            return None;
+        }
+
+        let chars: Vec<char> = normalized.chars().collect();
+        let bigrams = chars.windows(2).map(|pair| (pair[0], pair[1])).collect();
+        Some(Self {
+            normalized,
+            bigrams,
+        })
+    }
+
+    /// Exact matches always count; longer messages also count when most of
+    /// their character pairs are shared, which catches floods that vary a
+    /// word or append a counter.
+    fn is_near_duplicate(&self, other: &Self) -> bool {
+        if self.normalized == other.normalized {
+            return true;
+        }
+        if self.normalized.len() < MIN_FUZZY_LEN || other.normalized.len() < MIN_FUZZY_LEN {
+            return false;
+        }
+
+        let shared = self.bigrams.intersection(&other.bigrams).count();
+        let total = self.bigrams.len() + other.bigrams.len();
+        total > 0 && (2 * shared) as f64 / total as f64 >= DUPLICATE_SIMILARITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: u64 = 1;
+    const CHANNEL: u64 = 2;
+    const START_MS: u64 = DISCORD_EPOCH_MS + 1_000_000_000;
+
+    /// A message ID created `ms` milliseconds into the test, unique per `n`.
+    fn message_id(ms: u64, n: u64) -> u64 {
+        ((START_MS + ms - DISCORD_EPOCH_MS) << 22) | n
+    }
+
+    fn message(ms: u64, n: u64, author_id: u64, content: &str) -> TrackedMessage<'_> {
+        TrackedMessage {
+            guild_id: GUILD,
+            channel_id: CHANNEL,
+            message_id: message_id(ms, n),
+            author_id,
+            content,
+        }
+    }
+
+    fn user_rate(count: u32, window_secs: u64) -> SpamLimits {
+        SpamLimits {
+            user_rate: Some(SpamLimit { count, window_secs }),
+            ..SpamLimits::default()
+        }
+    }
+
+    #[test]
+    fn snowflake_time_round_trips() {
+        assert_eq!(snowflake_ms(message_id(1_500, 7)), START_MS + 1_500);
+    }
+
+    #[test]
+    fn user_rate_triggers_past_the_count() {
+        let tracker = SpamTracker::new();
+        let limits = user_rate(3, 5);
+
+        for n in 0..3 {
+            assert!(
+                tracker
+                    .record(message(n * 100, n, 10, "hi"), &limits)
+                    .is_none()
+            );
+        }
+        let burst = tracker
+            .record(message(300, 3, 10, "hi"), &limits)
+            .expect("fourth message within the window");
+        assert_eq!(burst.trigger, SpamTrigger::UserRate);
+        assert_eq!(burst.messages.len(), 4);
+    }
+
+    #[test]
+    fn messages_outside_the_window_do_not_count() {
+        let tracker = SpamTracker::new();
+        let limits = user_rate(3, 5);
+
+        for n in 0..4 {
+            let sent_ms = n * 2_000;
+            assert!(
+                tracker
+                    .record(message(sent_ms, n, 10, "hi"), &limits)
+                    .is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn window_uses_send_time_not_processing_time() {
+        // Processed back to back, but sent far apart
+        let tracker = SpamTracker::new();
+        let limits = user_rate(1, 5);
+
+        assert!(tracker.record(message(0, 0, 10, "a"), &limits).is_none());
+        assert!(
+            tracker
+                .record(message(60_000, 1, 10, "b"), &limits)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn reported_burst_is_forgotten() {
+        let tracker = SpamTracker::new();
+        let limits = user_rate(1, 5);
+
+        assert!(tracker.record(message(0, 0, 10, "a"), &limits).is_none());
+        assert!(tracker.record(message(100, 1, 10, "b"), &limits).is_some());
+        assert!(tracker.record(message(200, 2, 10, "c"), &limits).is_none());
+    }
+
+    #[test]
+    fn authors_are_tracked_separately() {
+        let tracker = SpamTracker::new();
+        let limits = user_rate(1, 5);
+
+        assert!(tracker.record(message(0, 0, 10, "a"), &limits).is_none());
+        assert!(tracker.record(message(100, 1, 11, "b"), &limits).is_none());
+    }
+
+    #[test]
+    fn duplicates_trigger_on_near_identical_content() {
+        let tracker = SpamTracker::new();
+        let limits = SpamLimits {
+            user_duplicates: Some(SpamLimit {
+                count: 1,
+                window_secs: 5,
+            }),
+            ..SpamLimits::default()
+        };
+
+        assert!(
+            tracker
+                .record(message(0, 0, 10, "hello there"), &limits)
+                .is_none()
+        );
+        assert!(
+            tracker
+                .record(message(100, 1, 10, "something else"), &limits)
+                .is_none()
+        );
+        let burst = tracker
+            .record(message(200, 2, 10, "Hello there!!"), &limits)
+            .expect("second duplicate");
+        assert_eq!(burst.trigger, SpamTrigger::UserDuplicates);
+        assert_eq!(burst.messages.len(), 2);
+    }
+
+    fn fingerprint(content: &str) -> Fingerprint {
+        Fingerprint::new(content).expect("content has a fingerprint")
+    }
+
+    #[test]
+    fn fingerprint_ignores_case_punctuation_and_spacing() {
+        assert_eq!(fingerprint("  Hello,   WORLD!! ").normalized, "hello world");
+        assert!(fingerprint("Hello!!").is_near_duplicate(&fingerprint("hello")));
+    }
+
+    #[test]
+    fn fingerprint_keeps_symbol_only_content() {
+        assert_eq!(fingerprint(" 🎉🎉 ").normalized, "🎉🎉");
+        assert!(Fingerprint::new("   ").is_none());
+    }
+
+    #[test]
+    fn long_messages_match_when_mostly_shared() {
+        let first = fingerprint("buy cheap followers now at example");
+        let second = fingerprint("buy cheap followers now at example 2");
+        assert!(first.is_near_duplicate(&second));
+
+        let unrelated = fingerprint("what time is the meeting tomorrow");
+        assert!(!first.is_near_duplicate(&unrelated));
+    }
+
+    #[test]
+    fn short_messages_must_match_exactly() {
+        assert!(!fingerprint("hey you").is_near_duplicate(&fingerprint("hey yo")));
+    }
+}
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS cases_guild_user_created_at_idx;
+DROP TABLE IF EXISTS cases;
+DROP TABLE IF EXISTS case_counters;
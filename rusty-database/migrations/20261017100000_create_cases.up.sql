@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS case_counters (
+    guild_id BIGINT PRIMARY KEY,
+    last_case_number BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS cases (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    case_number BIGINT NOT NULL,
+    action TEXT NOT NULL,
+    user_id BIGINT,
+    moderator_id BIGINT NOT NULL,
+    reason TEXT,
+    duration_secs BIGINT,
+    created_at BIGINT NOT NULL,
+    expires_at BIGINT,
+    UNIQUE (guild_id, case_number)
+);
+
+CREATE INDEX IF NOT EXISTS cases_guild_user_created_at_idx
+    ON cases (guild_id, user_id, created_at DESC);
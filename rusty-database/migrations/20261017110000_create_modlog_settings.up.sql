@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS modlog_settings (
+    guild_id BIGINT PRIMARY KEY,
+    channel_id BIGINT,
+    quiet_replies BOOLEAN NOT NULL DEFAULT FALSE
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS tempbans_expires_at_idx;
+DROP TABLE IF EXISTS tempbans;
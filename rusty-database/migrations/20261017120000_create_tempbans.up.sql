@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS tempbans (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS tempbans_expires_at_idx
+    ON tempbans (expires_at);
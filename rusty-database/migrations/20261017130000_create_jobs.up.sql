@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS jobs (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    idempotency_key TEXT UNIQUE,
+    run_at BIGINT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    locked_until BIGINT,
+    last_error TEXT,
+    failed_at BIGINT,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS jobs_pending_run_at_idx
+    ON jobs (run_at)
+    WHERE failed_at IS NULL;
+
+-- Tempbans created before the job scheduler existed still need lifting.
+INSERT INTO jobs (kind, payload, idempotency_key, run_at, max_attempts, created_at)
+SELECT
+    'tempban.expire',
+    json_build_object('guild_id', guild_id, 'user_id', user_id)::TEXT,
+    'tempban:' || guild_id || ':' || user_id,
+    expires_at,
+    10,
+    created_at
+FROM tempbans
+ON CONFLICT (idempotency_key) DO NOTHING;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS warn_policies (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    threshold INTEGER NOT NULL,
+    window_secs BIGINT NOT NULL,
+    action TEXT NOT NULL,
+    duration_secs BIGINT,
+    created_by BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    UNIQUE (guild_id, threshold, window_secs)
+);
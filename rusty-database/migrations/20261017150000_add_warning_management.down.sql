@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS warning_settings;
+DROP INDEX IF EXISTS warning_revisions_warning_id_idx;
+DROP TABLE IF EXISTS warning_revisions;
+
+ALTER TABLE warnings
+    DROP COLUMN IF EXISTS deleted_by,
+    DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,23 @@
+ALTER TABLE warnings
+    ADD COLUMN IF NOT EXISTS deleted_at BIGINT,
+    ADD COLUMN IF NOT EXISTS deleted_by BIGINT;
+
+-- Every edit or removal keeps the previous reason for auditing.
+CREATE TABLE IF NOT EXISTS warning_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    warning_id BIGINT NOT NULL REFERENCES warnings (id),
+    guild_id BIGINT NOT NULL,
+    change TEXT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    old_reason TEXT NOT NULL,
+    new_reason TEXT,
+    changed_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS warning_revisions_warning_id_idx
+    ON warning_revisions (warning_id);
+
+CREATE TABLE IF NOT EXISTS warning_settings (
+    guild_id BIGINT PRIMARY KEY,
+    expiry_secs BIGINT
+);
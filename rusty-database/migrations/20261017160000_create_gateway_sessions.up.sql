@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS gateway_sessions (
+    shard_id INTEGER NOT NULL,
+    shard_total INTEGER NOT NULL,
+    session_id TEXT NOT NULL,
+    sequence BIGINT NOT NULL,
+    resume_url TEXT,
+    saved_at BIGINT NOT NULL,
+    PRIMARY KEY (shard_id, shard_total)
+);
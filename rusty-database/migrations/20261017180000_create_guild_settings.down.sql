@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS modlog_settings (
+    guild_id BIGINT PRIMARY KEY,
+    channel_id BIGINT,
+    quiet_replies BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO modlog_settings (guild_id, channel_id, quiet_replies)
+SELECT guild_id, modlog_channel_id, modlog_quiet_replies FROM guild_settings
+WHERE modlog_channel_id IS NOT NULL OR modlog_quiet_replies
+ON CONFLICT (guild_id) DO NOTHING;
+
+DROP TABLE IF EXISTS guild_settings;
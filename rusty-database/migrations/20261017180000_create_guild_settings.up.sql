@@ -0,0 +1,16 @@
+-- One row of per-guild configuration; NULL columns fall back to the bot's defaults.
+CREATE TABLE IF NOT EXISTS guild_settings (
+    guild_id BIGINT PRIMARY KEY,
+    prefix TEXT,
+    modlog_channel_id BIGINT,
+    modlog_quiet_replies BOOLEAN NOT NULL DEFAULT FALSE,
+    default_timeout_secs BIGINT,
+    warnings_window_days BIGINT,
+    embed_color INTEGER
+);
+
+INSERT INTO guild_settings (guild_id, modlog_channel_id, modlog_quiet_replies)
+SELECT guild_id, channel_id, quiet_replies FROM modlog_settings
+ON CONFLICT (guild_id) DO NOTHING;
+
+DROP TABLE IF EXISTS modlog_settings;
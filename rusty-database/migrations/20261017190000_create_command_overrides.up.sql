@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS command_overrides (
+    guild_id BIGINT NOT NULL,
+    command TEXT NOT NULL,
+    target_kind TEXT NOT NULL,
+    target_id BIGINT NOT NULL,
+    allow BOOLEAN NOT NULL,
+    created_by BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    PRIMARY KEY (guild_id, command, target_kind, target_id)
+);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS command_cooldowns (
+    bucket TEXT PRIMARY KEY,
+    uses INTEGER NOT NULL,
+    window_ends_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS command_cooldowns_window_ends_at_idx
+    ON command_cooldowns (window_ends_at);
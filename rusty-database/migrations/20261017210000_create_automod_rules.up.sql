@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS automod_rules (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    patterns TEXT[] NOT NULL DEFAULT '{}',
+    threshold INTEGER,
+    action TEXT NOT NULL,
+    duration_secs BIGINT,
+    exempt_role_ids BIGINT[] NOT NULL DEFAULT '{}',
+    exempt_channel_ids BIGINT[] NOT NULL DEFAULT '{}',
+    created_by BIGINT NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS automod_rules_guild_id_idx ON automod_rules (guild_id);
@@ -0,0 +1,10 @@
+ALTER TABLE guild_settings
+    DROP COLUMN IF EXISTS spam_action,
+    DROP COLUMN IF EXISTS spam_user_rate,
+    DROP COLUMN IF EXISTS spam_user_rate_secs,
+    DROP COLUMN IF EXISTS spam_user_duplicates,
+    DROP COLUMN IF EXISTS spam_user_duplicates_secs,
+    DROP COLUMN IF EXISTS spam_channel_rate,
+    DROP COLUMN IF EXISTS spam_channel_rate_secs,
+    DROP COLUMN IF EXISTS spam_channel_duplicates,
+    DROP COLUMN IF EXISTS spam_channel_duplicates_secs;
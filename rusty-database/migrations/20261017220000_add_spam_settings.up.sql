@@ -0,0 +1,12 @@
+-- Spam detection settings; NULL limits fall back to the bot's defaults and a
+-- count of zero turns a limit off. A NULL action leaves detection disabled.
+ALTER TABLE guild_settings
+    ADD COLUMN IF NOT EXISTS spam_action TEXT,
+    ADD COLUMN IF NOT EXISTS spam_user_rate INTEGER,
+    ADD COLUMN IF NOT EXISTS spam_user_rate_secs BIGINT,
+    ADD COLUMN IF NOT EXISTS spam_user_duplicates INTEGER,
+    ADD COLUMN IF NOT EXISTS spam_user_duplicates_secs BIGINT,
+    ADD COLUMN IF NOT EXISTS spam_channel_rate INTEGER,
+    ADD COLUMN IF NOT EXISTS spam_channel_rate_secs BIGINT,
+    ADD COLUMN IF NOT EXISTS spam_channel_duplicates INTEGER,
+    ADD COLUMN IF NOT EXISTS spam_channel_duplicates_secs BIGINT;
@@ -0,0 +1,11 @@
+DROP TABLE IF EXISTS lockdown_channels;
+DROP TABLE IF EXISTS lockdowns;
+
+ALTER TABLE guild_settings
+    DROP COLUMN IF EXISTS raid_action,
+    DROP COLUMN IF EXISTS raid_join_rate,
+    DROP COLUMN IF EXISTS raid_join_rate_secs,
+    DROP COLUMN IF EXISTS raid_young_join_rate,
+    DROP COLUMN IF EXISTS raid_young_join_rate_secs,
+    DROP COLUMN IF EXISTS raid_account_age_secs,
+    DROP COLUMN IF EXISTS lockdown_channel_ids;
@@ -0,0 +1,31 @@
+-- Raid detection settings; NULL limits fall back to the bot's defaults and a
+-- count of zero turns a limit off. A NULL action leaves detection disabled.
+ALTER TABLE guild_settings
+    ADD COLUMN IF NOT EXISTS raid_action TEXT,
+    ADD COLUMN IF NOT EXISTS raid_join_rate INTEGER,
+    ADD COLUMN IF NOT EXISTS raid_join_rate_secs BIGINT,
+    ADD COLUMN IF NOT EXISTS raid_young_join_rate INTEGER,
+    ADD COLUMN IF NOT EXISTS raid_young_join_rate_secs BIGINT,
+    ADD COLUMN IF NOT EXISTS raid_account_age_secs BIGINT,
+    ADD COLUMN IF NOT EXISTS lockdown_channel_ids BIGINT[] NOT NULL DEFAULT '{}';
+
+-- One row per guild currently in lockdown. The previous verification level is
+-- NULL when the lockdown did not have to raise it.
+CREATE TABLE IF NOT EXISTS lockdowns (
+    guild_id BIGINT PRIMARY KEY,
+    moderator_id BIGINT NOT NULL,
+    reason TEXT,
+    previous_verification_level SMALLINT,
+    created_at BIGINT NOT NULL
+);
+
+-- The @everyone overwrite each locked channel had before the lockdown, so
+-- lifting it can put back exactly what was there. NULL bits mean the channel
+-- had no @everyone overwrite.
+CREATE TABLE IF NOT EXISTS lockdown_channels (
+    guild_id BIGINT NOT NULL REFERENCES lockdowns (guild_id) ON DELETE CASCADE,
+    channel_id BIGINT NOT NULL,
+    previous_allow BIGINT,
+    previous_deny BIGINT,
+    PRIMARY KEY (guild_id, channel_id)
+);
@@ -0,0 +1,14 @@
+-- Channels locked with `!lock`, and the @everyone overwrite each had before so
+-- unlocking can put back exactly what was there. NULL bits mean the channel
+-- had no @everyone overwrite; a NULL expiry means the lock is indefinite.
+CREATE TABLE IF NOT EXISTS channel_locks (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    reason TEXT,
+    previous_allow BIGINT,
+    previous_deny BIGINT,
+    created_at BIGINT NOT NULL,
+    expires_at BIGINT,
+    PRIMARY KEY (guild_id, channel_id)
+);
@@ -0,0 +1,186 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    impls::warnings::now_unix_secs,
+    model::automod::{AutomodExemption, AutomodRule, NewAutomodRule},
+};
+
+#[derive(sqlx::FromRow)]
+struct AutomodRuleRow {
+    id: i64,
+    guild_id: i64,
+    kind: String,
+    patterns: Vec<String>,
+    threshold: Option<i32>,
+    action: String,
+    duration_secs: Option<i64>,
+    exempt_role_ids: Vec<i64>,
+    exempt_channel_ids: Vec<i64>,
+}
+
+impl TryFrom<AutomodRuleRow> for AutomodRule {
+    type Error = anyhow::Error;
+
+    fn try_from(row: AutomodRuleRow) -> anyhow::Result<Self> {
+        let ids = |ids: Vec<i64>, name: &str| {
+            ids.into_iter()
+                .map(|id| u64::try_from(id).with_context(|| format!("{name} row out of u64 range")))
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+
+        Ok(Self {
+            id: u64::try_from(row.id).context("id row out of u64 range")?,
+            guild_id: u64::try_from(row.guild_id).context("guild_id row out of u64 range")?,
+            kind: row.kind.parse()?,
+            patterns: row.patterns,
+            threshold: row
+                .threshold
+                .map(u32::try_from)
+                .transpose()
+                .context("threshold row out of u32 range")?,
+            action: row.action.parse()?,
+            duration_secs: row
+                .duration_secs
+                .map(u64::try_from)
+                .transpose()
+                .context("duration_secs row out of u64 range")?,
+            exempt_role_ids: ids(row.exempt_role_ids, "exempt_role_ids")?,
+            exempt_channel_ids: ids(row.exempt_channel_ids, "exempt_channel_ids")?,
+        })
+    }
+}
+
+const RULE_COLUMNS: &str = "id, guild_id, kind, patterns, threshold, action, duration_secs, \
+     exempt_role_ids, exempt_channel_ids";
+
+/// List a guild's automod rules, oldest first.
+pub async fn automod_rules(db: &Database, guild_id: u64) -> anyhow::Result<Vec<AutomodRule>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let rows: Vec<AutomodRuleRow> = sqlx::query_as(&format!(
+        "SELECT {RULE_COLUMNS} FROM automod_rules WHERE guild_id = $1 ORDER BY id"
+    ))
+    .bind(guild_id_i64)
+    .fetch_all(db.pool())
+    .await?;
+
+    rows.into_iter().map(AutomodRule::try_from).collect()
+}
+
+/// Store a new automod rule.
+pub async fn add_automod_rule(db: &Database, rule: NewAutomodRule) -> anyhow::Result<AutomodRule> {
+    let guild_id_i64 = i64::try_from(rule.guild_id).context("guild_id out of i64 range")?;
+    let threshold_i32 = rule
+        .threshold
+        .map(i32::try_from)
+        .transpose()
+        .context("threshold out of i32 range")?;
+    let duration_secs_i64 = rule
+        .duration_secs
+        .map(i64::try_from)
+        .transpose()
+        .context("duration_secs out of i64 range")?;
+    let created_by_i64 = i64::try_from(rule.created_by).context("created_by out of i64 range")?;
+    let created_at_i64 = i64::try_from(now_unix_secs()).context("created_at out of i64 range")?;
+
+    let row: AutomodRuleRow = sqlx::query_as(&format!(
+        "INSERT INTO automod_rules
+            (guild_id, kind, patterns, threshold, action, duration_secs, created_by, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         RETURNING {RULE_COLUMNS}"
+    ))
+    .bind(guild_id_i64)
+    .bind(rule.kind.as_str())
+    .bind(&rule.patterns)
+    .bind(threshold_i32)
+    .bind(rule.action.as_str())
+    .bind(duration_secs_i64)
+    .bind(created_by_i64)
+    .bind(created_at_i64)
+    .fetch_one(db.pool())
+    .await?;
+
+    row.try_into()
+}
+
+/// Delete a rule, returning `false` if the guild has no rule with that ID.
+pub async fn remove_automod_rule(db: &Database, guild_id: u64, id: u64) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let id_i64 = i64::try_from(id).context("id out of i64 range")?;
+
+    let result = sqlx::query("DELETE FROM automod_rules WHERE guild_id = $1 AND id = $2")
+        .bind(guild_id_i64)
+        .bind(id_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Add or remove an exempt role or channel, returning the updated rule.
+///
+/// Returns `None` if the guild has no rule with that ID.
+pub async fn set_automod_exemption(
+    db: &Database,
+    guild_id: u64,
+    id: u64,
+    exemption: AutomodExemption,
+    exempt: bool,
+) -> anyhow::Result<Option<AutomodRule>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let id_i64 = i64::try_from(id).context("id out of i64 range")?;
+    let (column, target_id) = match exemption {
+        AutomodExemption::Role(role_id) => ("exempt_role_ids", role_id),
+        AutomodExemption::Channel(channel_id) => ("exempt_channel_ids", channel_id),
+    };
+    let target_id_i64 = i64::try_from(target_id).context("target_id out of i64 range")?;
+
+    // Removing first keeps the array free of duplicates when adding
+    let value = if exempt {
+        format!("array_append(array_remove({column}, $3), $3)")
+    } else {
+        format!("array_remove({column}, $3)")
+    };
+
+    let row: Option<AutomodRuleRow> = sqlx::query_as(&format!(
+        "UPDATE automod_rules SET {column} = {value}
+         WHERE guild_id = $1 AND id = $2
+         RETURNING {RULE_COLUMNS}"
+    ))
+    .bind(guild_id_i64)
+    .bind(id_i64)
+    .bind(target_id_i64)
+    .fetch_optional(db.pool())
+    .await?;
+
+    row.map(AutomodRule::try_from).transpose()
+}
+
+/// Swap a channel for its replacement in every rule's exempt channels,
+/// returning how many rules changed.
+pub async fn move_automod_channel_exemptions(
+    db: &Database,
+    guild_id: u64,
+    old_channel_id: u64,
+    new_channel_id: u64,
+) -> anyhow::Result<u64> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let old_channel_id_i64 =
+        i64::try_from(old_channel_id).context("old_channel_id out of i64 range")?;
+    let new_channel_id_i64 =
+        i64::try_from(new_channel_id).context("new_channel_id out of i64 range")?;
+
+    let result = sqlx::query(
+        "UPDATE automod_rules
+         SET exempt_channel_ids = array_replace(exempt_channel_ids, $2, $3)
+         WHERE guild_id = $1 AND $2 = ANY(exempt_channel_ids)",
+    )
+    .bind(guild_id_i64)
+    .bind(old_channel_id_i64)
+    .bind(new_channel_id_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(result.rows_affected())
+}
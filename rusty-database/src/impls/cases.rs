@@ -0,0 +1,180 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    impls::warnings::now_unix_secs,
+    model::cases::{CaseEntry, NewCase},
+};
+
+#[derive(sqlx::FromRow)]
+struct CaseRow {
+    guild_id: i64,
+    case_number: i64,
+    action: String,
+    user_id: Option<i64>,
+    moderator_id: i64,
+    reason: Option<String>,
+    duration_secs: Option<i64>,
+    created_at: i64,
+    expires_at: Option<i64>,
+}
+
+impl TryFrom<CaseRow> for CaseEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CaseRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            guild_id: u64::try_from(row.guild_id).context("guild_id row out of u64 range")?,
+            case_number: u64::try_from(row.case_number)
+                .context("case_number row out of u64 range")?,
+            action: row.action.parse()?,
+            user_id: row
+                .user_id
+                .map(u64::try_from)
+                .transpose()
+                .context("user_id row out of u64 range")?,
+            moderator_id: u64::try_from(row.moderator_id)
+                .context("moderator_id row out of u64 range")?,
+            reason: row.reason,
+            duration_secs: row
+                .duration_secs
+                .map(u64::try_from)
+                .transpose()
+                .context("duration_secs row out of u64 range")?,
+            created_at: u64::try_from(row.created_at).context("created_at row out of u64 range")?,
+            expires_at: row
+                .expires_at
+                .map(u64::try_from)
+                .transpose()
+                .context("expires_at row out of u64 range")?,
+        })
+    }
+}
+
+const CASE_COLUMNS: &str = "guild_id, case_number, action, user_id, moderator_id, reason, duration_secs, created_at, expires_at";
+
+/// Record a moderation action under the next case number for its guild.
+pub async fn record_case(db: &Database, case: NewCase<'_>) -> anyhow::Result<CaseEntry> {
+    let created_at = now_unix_secs();
+    let guild_id_i64 = i64::try_from(case.guild_id).context("guild_id out of i64 range")?;
+    let user_id_i64 = case
+        .user_id
+        .map(i64::try_from)
+        .transpose()
+        .context("user_id out of i64 range")?;
+    let moderator_id_i64 =
+        i64::try_from(case.moderator_id).context("moderator_id out of i64 range")?;
+    let duration_secs_i64 = case
+        .duration_secs
+        .map(i64::try_from)
+        .transpose()
+        .context("duration_secs out of i64 range")?;
+    let created_at_i64 = i64::try_from(created_at).context("created_at out of i64 range")?;
+    let expires_at_i64 = case
+        .expires_at
+        .map(i64::try_from)
+        .transpose()
+        .context("expires_at out of i64 range")?;
+
+    let mut tx = db.pool().begin().await?;
+
+    // The counter row lock serialises concurrent actions in the same guild.
+    let case_number: i64 = sqlx::query_scalar(
+        "INSERT INTO case_counters (guild_id, last_case_number) VALUES ($1, 1)
+         ON CONFLICT (guild_id)
+         DO UPDATE SET last_case_number = case_counters.last_case_number + 1
+         RETURNING last_case_number",
+    )
+    .bind(guild_id_i64)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let row: CaseRow = sqlx::query_as(&format!(
+        "INSERT INTO cases
+            (guild_id, case_number, action, user_id, moderator_id, reason, duration_secs, created_at, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING {CASE_COLUMNS}"
+    ))
+    .bind(guild_id_i64)
+    .bind(case_number)
+    .bind(case.action.as_str())
+    .bind(user_id_i64)
+    .bind(moderator_id_i64)
+    .bind(case.reason)
+    .bind(duration_secs_i64)
+    .bind(created_at_i64)
+    .bind(expires_at_i64)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    row.try_into()
+}
+
+/// Look up a single case by its per-guild number.
+pub async fn case_by_number(
+    db: &Database,
+    guild_id: u64,
+    case_number: u64,
+) -> anyhow::Result<Option<CaseEntry>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let case_number_i64 = i64::try_from(case_number).context("case_number out of i64 range")?;
+
+    let row: Option<CaseRow> = sqlx::query_as(&format!(
+        "SELECT {CASE_COLUMNS} FROM cases WHERE guild_id = $1 AND case_number = $2"
+    ))
+    .bind(guild_id_i64)
+    .bind(case_number_i64)
+    .fetch_optional(db.pool())
+    .await?;
+
+    row.map(CaseEntry::try_from).transpose()
+}
+
+/// Return every case recorded against a user, oldest first.
+pub async fn cases_for_user(
+    db: &Database,
+    guild_id: u64,
+    user_id: u64,
+) -> anyhow::Result<Vec<CaseEntry>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let user_id_i64 = i64::try_from(user_id).context("user_id out of i64 range")?;
+
+    let rows: Vec<CaseRow> = sqlx::query_as(&format!(
+        "SELECT {CASE_COLUMNS}
+         FROM cases
+         WHERE guild_id = $1 AND user_id = $2
+         ORDER BY case_number ASC"
+    ))
+    .bind(guild_id_i64)
+    .bind(user_id_i64)
+    .fetch_all(db.pool())
+    .await?;
+
+    rows.into_iter().map(CaseEntry::try_from).collect()
+}
+
+/// Replace the reason of an existing case, returning the updated case if found.
+pub async fn update_case_reason(
+    db: &Database,
+    guild_id: u64,
+    case_number: u64,
+    reason: &str,
+) -> anyhow::Result<Option<CaseEntry>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let case_number_i64 = i64::try_from(case_number).context("case_number out of i64 range")?;
+
+    let row: Option<CaseRow> = sqlx::query_as(&format!(
+        "UPDATE cases SET reason = $3
+         WHERE guild_id = $1 AND case_number = $2
+         RETURNING {CASE_COLUMNS}"
+    ))
+    .bind(guild_id_i64)
+    .bind(case_number_i64)
+    .bind(reason)
+    .fetch_optional(db.pool())
+    .await?;
+
+    row.map(CaseEntry::try_from).transpose()
+}
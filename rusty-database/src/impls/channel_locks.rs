@@ -0,0 +1,134 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    impls::warnings::now_unix_secs,
+    model::{
+        channel_locks::{ChannelLock, NewChannelLock},
+        lockdowns::OverwriteBits,
+    },
+};
+
+#[derive(sqlx::FromRow)]
+struct ChannelLockRow {
+    guild_id: i64,
+    channel_id: i64,
+    moderator_id: i64,
+    reason: Option<String>,
+    previous_allow: Option<i64>,
+    previous_deny: Option<i64>,
+    created_at: i64,
+    expires_at: Option<i64>,
+}
+
+impl TryFrom<ChannelLockRow> for ChannelLock {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ChannelLockRow) -> anyhow::Result<Self> {
+        let previous = match (row.previous_allow, row.previous_deny) {
+            (Some(allow), Some(deny)) => Some(OverwriteBits {
+                allow: u64::try_from(allow).context("previous_allow row out of u64 range")?,
+                deny: u64::try_from(deny).context("previous_deny row out of u64 range")?,
+            }),
+            _ => None,
+        };
+
+        Ok(Self {
+            guild_id: u64::try_from(row.guild_id).context("guild_id row out of u64 range")?,
+            channel_id: u64::try_from(row.channel_id).context("channel_id row out of u64 range")?,
+            moderator_id: u64::try_from(row.moderator_id)
+                .context("moderator_id row out of u64 range")?,
+            reason: row.reason,
+            previous,
+            created_at: u64::try_from(row.created_at).context("created_at row out of u64 range")?,
+            expires_at: row
+                .expires_at
+                .map(u64::try_from)
+                .transpose()
+                .context("expires_at row out of u64 range")?,
+        })
+    }
+}
+
+/// Store a channel lock, returning `false` if the channel is already locked.
+///
+/// An existing lock keeps the overwrite it saved first.
+pub async fn insert_channel_lock(db: &Database, lock: NewChannelLock<'_>) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(lock.guild_id).context("guild_id out of i64 range")?;
+    let channel_id_i64 = i64::try_from(lock.channel_id).context("channel_id out of i64 range")?;
+    let moderator_id_i64 =
+        i64::try_from(lock.moderator_id).context("moderator_id out of i64 range")?;
+    let (allow_i64, deny_i64) = match lock.previous {
+        Some(bits) => (
+            Some(i64::try_from(bits.allow).context("previous_allow out of i64 range")?),
+            Some(i64::try_from(bits.deny).context("previous_deny out of i64 range")?),
+        ),
+        None => (None, None),
+    };
+    let created_at_i64 = i64::try_from(now_unix_secs()).context("created_at out of i64 range")?;
+    let expires_at_i64 = lock
+        .expires_at
+        .map(i64::try_from)
+        .transpose()
+        .context("expires_at out of i64 range")?;
+
+    let result = sqlx::query(
+        "INSERT INTO channel_locks (guild_id, channel_id, moderator_id, reason, previous_allow,
+                                    previous_deny, created_at, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         ON CONFLICT (guild_id, channel_id) DO NOTHING",
+    )
+    .bind(guild_id_i64)
+    .bind(channel_id_i64)
+    .bind(moderator_id_i64)
+    .bind(lock.reason)
+    .bind(allow_i64)
+    .bind(deny_i64)
+    .bind(created_at_i64)
+    .bind(expires_at_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Look up the lock on a channel, if any.
+pub async fn channel_lock(
+    db: &Database,
+    guild_id: u64,
+    channel_id: u64,
+) -> anyhow::Result<Option<ChannelLock>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let channel_id_i64 = i64::try_from(channel_id).context("channel_id out of i64 range")?;
+
+    let row: Option<ChannelLockRow> = sqlx::query_as(
+        "SELECT guild_id, channel_id, moderator_id, reason, previous_allow, previous_deny,
+                created_at, expires_at
+         FROM channel_locks
+         WHERE guild_id = $1 AND channel_id = $2",
+    )
+    .bind(guild_id_i64)
+    .bind(channel_id_i64)
+    .fetch_optional(db.pool())
+    .await?;
+
+    row.map(ChannelLock::try_from).transpose()
+}
+
+/// Forget a channel lock, returning whether one existed.
+pub async fn delete_channel_lock(
+    db: &Database,
+    guild_id: u64,
+    channel_id: u64,
+) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let channel_id_i64 = i64::try_from(channel_id).context("channel_id out of i64 range")?;
+
+    let result = sqlx::query("DELETE FROM channel_locks WHERE guild_id = $1 AND channel_id = $2")
+        .bind(guild_id_i64)
+        .bind(channel_id_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,78 @@
+use anyhow::Context as _;
+
+use crate::{database::Database, model::command_cooldowns::CooldownWindow};
+
+/// Count a use against a cooldown bucket if it still has room, and return
+/// its current window.
+///
+/// Windows are fixed: the first use after a window ends starts a new
+/// `period_secs` window, and up to `max_uses` uses are counted in it. A
+/// refused use is not counted, so retrying while on cooldown never extends
+/// the wait. The check and the count happen in one statement, so concurrent
+/// processes sharing the database agree on them.
+pub async fn record_cooldown_use(
+    db: &Database,
+    bucket: &str,
+    now: u64,
+    max_uses: u32,
+    period_secs: u64,
+) -> anyhow::Result<CooldownWindow> {
+    let now_i64 = i64::try_from(now).context("now out of i64 range")?;
+    let max_uses_i32 = i32::try_from(max_uses).context("max_uses out of i32 range")?;
+    let period_secs_i64 = i64::try_from(period_secs).context("period_secs out of i64 range")?;
+
+    let counted: Option<(i32, i64)> = sqlx::query_as(
+        "INSERT INTO command_cooldowns (bucket, uses, window_ends_at)
+         VALUES ($1, 1, $2 + $4)
+         ON CONFLICT (bucket) DO UPDATE
+         SET uses = CASE
+                 WHEN command_cooldowns.window_ends_at <= $2 THEN 1
+                 ELSE command_cooldowns.uses + 1
+             END,
+             window_ends_at = CASE
+                 WHEN command_cooldowns.window_ends_at <= $2 THEN $2 + $4
+                 ELSE command_cooldowns.window_ends_at
+             END
+         WHERE command_cooldowns.window_ends_at <= $2
+            OR command_cooldowns.uses < $3
+         RETURNING uses, window_ends_at",
+    )
+    .bind(bucket)
+    .bind(now_i64)
+    .bind(max_uses_i32)
+    .bind(period_secs_i64)
+    .fetch_optional(db.pool())
+    .await?;
+
+    let (allowed, (uses, window_ends_at)) = match counted {
+        Some(row) => (true, row),
+        // The bucket is full; read the window the refused use fell into
+        None => {
+            let row: Option<(i32, i64)> = sqlx::query_as(
+                "SELECT uses, window_ends_at FROM command_cooldowns WHERE bucket = $1",
+            )
+            .bind(bucket)
+            .fetch_optional(db.pool())
+            .await?;
+            (false, row.unwrap_or((max_uses_i32, now_i64)))
+        }
+    };
+
+    Ok(CooldownWindow {
+        allowed,
+        uses: u32::try_from(uses).context("uses row out of u32 range")?,
+        ends_at: u64::try_from(window_ends_at).context("window_ends_at row out of u64 range")?,
+    })
+}
+
+/// Delete buckets whose window ended before `now`, returning how many were removed.
+pub async fn prune_command_cooldowns(db: &Database, now: u64) -> anyhow::Result<u64> {
+    let now_i64 = i64::try_from(now).context("now out of i64 range")?;
+
+    let result = sqlx::query("DELETE FROM command_cooldowns WHERE window_ends_at <= $1")
+        .bind(now_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected())
+}
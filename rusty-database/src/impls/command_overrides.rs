@@ -0,0 +1,162 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    impls::warnings::now_unix_secs,
+    model::command_overrides::{CommandOverride, OverrideMode, OverrideTarget},
+};
+
+#[derive(sqlx::FromRow)]
+struct CommandOverrideRow {
+    command: String,
+    target_kind: String,
+    target_id: i64,
+    allow: bool,
+}
+
+impl TryFrom<CommandOverrideRow> for CommandOverride {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CommandOverrideRow) -> anyhow::Result<Self> {
+        let target_id = u64::try_from(row.target_id).context("target_id row out of u64 range")?;
+
+        Ok(Self {
+            command: row.command,
+            target: OverrideTarget::from_parts(&row.target_kind, target_id)?,
+            mode: if row.allow {
+                OverrideMode::Allow
+            } else {
+                OverrideMode::Deny
+            },
+        })
+    }
+}
+
+/// List a guild's overrides, optionally for a single command.
+pub async fn command_overrides(
+    db: &Database,
+    guild_id: u64,
+    command: Option<&str>,
+) -> anyhow::Result<Vec<CommandOverride>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let rows: Vec<CommandOverrideRow> = sqlx::query_as(
+        "SELECT command, target_kind, target_id, allow
+         FROM command_overrides
+         WHERE guild_id = $1 AND ($2::TEXT IS NULL OR command = $2)
+         ORDER BY command, target_kind, target_id",
+    )
+    .bind(guild_id_i64)
+    .bind(command)
+    .fetch_all(db.pool())
+    .await?;
+
+    rows.into_iter().map(CommandOverride::try_from).collect()
+}
+
+/// Store an override, replacing any existing one for the same command and target.
+pub async fn set_command_override(
+    db: &Database,
+    guild_id: u64,
+    command_override: &CommandOverride,
+    created_by: u64,
+) -> anyhow::Result<()> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let target_id_i64 = i64::try_from(command_override.target.id(guild_id))
+        .context("target_id out of i64 range")?;
+    let created_by_i64 = i64::try_from(created_by).context("created_by out of i64 range")?;
+    let created_at_i64 = i64::try_from(now_unix_secs()).context("created_at out of i64 range")?;
+
+    sqlx::query(
+        "INSERT INTO command_overrides
+            (guild_id, command, target_kind, target_id, allow, created_by, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (guild_id, command, target_kind, target_id) DO UPDATE
+         SET allow = EXCLUDED.allow,
+             created_by = EXCLUDED.created_by,
+             created_at = EXCLUDED.created_at",
+    )
+    .bind(guild_id_i64)
+    .bind(&command_override.command)
+    .bind(command_override.target.kind())
+    .bind(target_id_i64)
+    .bind(command_override.mode == OverrideMode::Allow)
+    .bind(created_by_i64)
+    .bind(created_at_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
+}
+
+/// Remove one override, returning `false` if none existed.
+pub async fn remove_command_override(
+    db: &Database,
+    guild_id: u64,
+    command: &str,
+    target: OverrideTarget,
+) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let target_id_i64 = i64::try_from(target.id(guild_id)).context("target_id out of i64 range")?;
+
+    let result = sqlx::query(
+        "DELETE FROM command_overrides
+         WHERE guild_id = $1 AND command = $2 AND target_kind = $3 AND target_id = $4",
+    )
+    .bind(guild_id_i64)
+    .bind(command)
+    .bind(target.kind())
+    .bind(target_id_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove every role, user and channel override of a command, returning how many were removed.
+///
+/// Whether the command is disabled is left unchanged.
+pub async fn clear_command_overrides(
+    db: &Database,
+    guild_id: u64,
+    command: &str,
+) -> anyhow::Result<u64> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let result = sqlx::query(
+        "DELETE FROM command_overrides
+         WHERE guild_id = $1 AND command = $2 AND target_kind <> 'guild'",
+    )
+    .bind(guild_id_i64)
+    .bind(command)
+    .execute(db.pool())
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Point every channel override at a replacement channel, returning how many moved.
+pub async fn move_channel_overrides(
+    db: &Database,
+    guild_id: u64,
+    old_channel_id: u64,
+    new_channel_id: u64,
+) -> anyhow::Result<u64> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let old_channel_id_i64 =
+        i64::try_from(old_channel_id).context("old_channel_id out of i64 range")?;
+    let new_channel_id_i64 =
+        i64::try_from(new_channel_id).context("new_channel_id out of i64 range")?;
+
+    let result = sqlx::query(
+        "UPDATE command_overrides SET target_id = $3
+         WHERE guild_id = $1 AND target_kind = 'channel' AND target_id = $2",
+    )
+    .bind(guild_id_i64)
+    .bind(old_channel_id_i64)
+    .bind(new_channel_id_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(result.rows_affected())
+}
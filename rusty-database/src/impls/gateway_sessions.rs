@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+use anyhow::Context as _;
+
+use crate::{
+    database::Database, impls::warnings::now_unix_secs, model::gateway_sessions::GatewaySession,
+};
+
+#[derive(sqlx::FromRow)]
+struct GatewaySessionRow {
+    shard_id: i32,
+    shard_total: i32,
+    session_id: String,
+    sequence: i64,
+    resume_url: Option<String>,
+    saved_at: i64,
+}
+
+impl TryFrom<GatewaySessionRow> for GatewaySession {
+    type Error = anyhow::Error;
+
+    fn try_from(row: GatewaySessionRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            shard_id: u32::try_from(row.shard_id).context("shard_id row out of u32 range")?,
+            shard_total: u32::try_from(row.shard_total)
+                .context("shard_total row out of u32 range")?,
+            session_id: row.session_id,
+            sequence: u64::try_from(row.sequence).context("sequence row out of u64 range")?,
+            resume_url: row.resume_url,
+            saved_at: u64::try_from(row.saved_at).context("saved_at row out of u64 range")?,
+        })
+    }
+}
+
+/// Save (or replace) the session a shard can resume after a restart.
+pub async fn save_gateway_session(
+    db: &Database,
+    shard_id: u32,
+    shard_total: u32,
+    session_id: &str,
+    sequence: u64,
+    resume_url: Option<&str>,
+) -> anyhow::Result<()> {
+    let shard_id_i32 = i32::try_from(shard_id).context("shard_id out of i32 range")?;
+    let shard_total_i32 = i32::try_from(shard_total).context("shard_total out of i32 range")?;
+    let sequence_i64 = i64::try_from(sequence).context("sequence out of i64 range")?;
+    let saved_at_i64 = i64::try_from(now_unix_secs()).context("saved_at out of i64 range")?;
+
+    sqlx::query(
+        "INSERT INTO gateway_sessions (shard_id, shard_total, session_id, sequence, resume_url, saved_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (shard_id, shard_total) DO UPDATE
+         SET session_id = EXCLUDED.session_id,
+             sequence = EXCLUDED.sequence,
+             resume_url = EXCLUDED.resume_url,
+             saved_at = EXCLUDED.saved_at",
+    )
+    .bind(shard_id_i32)
+    .bind(shard_total_i32)
+    .bind(session_id)
+    .bind(sequence_i64)
+    .bind(resume_url)
+    .bind(saved_at_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
+}
+
+/// Remove and return the saved sessions for `shards` out of `shard_total`.
+///
+/// Sessions saved before `since` are discarded rather than returned, since
+/// Discord no longer accepts them. Each session is handed out at most once.
+pub async fn take_gateway_sessions(
+    db: &Database,
+    shards: Range<u32>,
+    shard_total: u32,
+    since: u64,
+) -> anyhow::Result<Vec<GatewaySession>> {
+    let start_i32 = i32::try_from(shards.start).context("shard range out of i32 range")?;
+    let end_i32 = i32::try_from(shards.end).context("shard range out of i32 range")?;
+    let shard_total_i32 = i32::try_from(shard_total).context("shard_total out of i32 range")?;
+    let since_i64 = i64::try_from(since).context("since out of i64 range")?;
+
+    let rows: Vec<GatewaySessionRow> = sqlx::query_as(
+        "DELETE FROM gateway_sessions
+         WHERE shard_id >= $1 AND shard_id < $2
+         RETURNING shard_id, shard_total, session_id, sequence, resume_url, saved_at",
+    )
+    .bind(start_i32)
+    .bind(end_i32)
+    .fetch_all(db.pool())
+    .await?;
+
+    rows.into_iter()
+        .filter(|row| row.shard_total == shard_total_i32 && row.saved_at >= since_i64)
+        .map(GatewaySession::try_from)
+        .collect()
+}
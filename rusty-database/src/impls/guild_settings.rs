@@ -0,0 +1,286 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    model::guild_settings::{GuildSettings, SpamLimit},
+};
+
+#[derive(sqlx::FromRow)]
+struct GuildSettingsRow {
+    prefix: Option<String>,
+    modlog_channel_id: Option<i64>,
+    modlog_quiet_replies: bool,
+    default_timeout_secs: Option<i64>,
+    warnings_window_days: Option<i64>,
+    embed_color: Option<i32>,
+    spam_action: Option<String>,
+    spam_user_rate: Option<i32>,
+    spam_user_rate_secs: Option<i64>,
+    spam_user_duplicates: Option<i32>,
+    spam_user_duplicates_secs: Option<i64>,
+    spam_channel_rate: Option<i32>,
+    spam_channel_rate_secs: Option<i64>,
+    spam_channel_duplicates: Option<i32>,
+    spam_channel_duplicates_secs: Option<i64>,
+    raid_action: Option<String>,
+    raid_join_rate: Option<i32>,
+    raid_join_rate_secs: Option<i64>,
+    raid_young_join_rate: Option<i32>,
+    raid_young_join_rate_secs: Option<i64>,
+    raid_account_age_secs: Option<i64>,
+    lockdown_channel_ids: Vec<i64>,
+}
+
+fn spam_limit_from_row(
+    count: Option<i32>,
+    window_secs: Option<i64>,
+    name: &str,
+) -> anyhow::Result<Option<SpamLimit>> {
+    let (Some(count), Some(window_secs)) = (count, window_secs) else {
+        return Ok(None);
+    };
+
+    Ok(Some(SpamLimit {
+        count: u32::try_from(count).with_context(|| format!("{name} row out of u32 range"))?,
+        window_secs: u64::try_from(window_secs)
+            .with_context(|| format!("{name}_secs row out of u64 range"))?,
+    }))
+}
+
+fn spam_limit_columns(
+    limit: Option<SpamLimit>,
+    name: &str,
+) -> anyhow::Result<(Option<i32>, Option<i64>)> {
+    let Some(limit) = limit else {
+        return Ok((None, None));
+    };
+
+    Ok((
+        Some(i32::try_from(limit.count).with_context(|| format!("{name} out of i32 range"))?),
+        Some(
+            i64::try_from(limit.window_secs)
+                .with_context(|| format!("{name}_secs out of i64 range"))?,
+        ),
+    ))
+}
+
+impl TryFrom<GuildSettingsRow> for GuildSettings {
+    type Error = anyhow::Error;
+
+    fn try_from(row: GuildSettingsRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            prefix: row.prefix,
+            modlog_channel_id: row
+                .modlog_channel_id
+                .map(u64::try_from)
+                .transpose()
+                .context("modlog_channel_id row out of u64 range")?,
+            modlog_quiet_replies: row.modlog_quiet_replies,
+            default_timeout_secs: row
+                .default_timeout_secs
+                .map(u64::try_from)
+                .transpose()
+                .context("default_timeout_secs row out of u64 range")?,
+            warnings_window_days: row
+                .warnings_window_days
+                .map(u64::try_from)
+                .transpose()
+                .context("warnings_window_days row out of u64 range")?,
+            embed_color: row
+                .embed_color
+                .map(u32::try_from)
+                .transpose()
+                .context("embed_color row out of u32 range")?,
+            spam_action: row.spam_action.map(|action| action.parse()).transpose()?,
+            spam_user_rate: spam_limit_from_row(
+                row.spam_user_rate,
+                row.spam_user_rate_secs,
+                "spam_user_rate",
+            )?,
+            spam_user_duplicates: spam_limit_from_row(
+                row.spam_user_duplicates,
+                row.spam_user_duplicates_secs,
+                "spam_user_duplicates",
+            )?,
+            spam_channel_rate: spam_limit_from_row(
+                row.spam_channel_rate,
+                row.spam_channel_rate_secs,
+                "spam_channel_rate",
+            )?,
+            spam_channel_duplicates: spam_limit_from_row(
+                row.spam_channel_duplicates,
+                row.spam_channel_duplicates_secs,
+                "spam_channel_duplicates",
+            )?,
+            raid_action: row.raid_action.map(|action| action.parse()).transpose()?,
+            raid_join_rate: spam_limit_from_row(
+                row.raid_join_rate,
+                row.raid_join_rate_secs,
+                "raid_join_rate",
+            )?,
+            raid_young_join_rate: spam_limit_from_row(
+                row.raid_young_join_rate,
+                row.raid_young_join_rate_secs,
+                "raid_young_join_rate",
+            )?,
+            raid_account_age_secs: row
+                .raid_account_age_secs
+                .map(u64::try_from)
+                .transpose()
+                .context("raid_account_age_secs row out of u64 range")?,
+            lockdown_channel_ids: row
+                .lockdown_channel_ids
+                .into_iter()
+                .map(|id| u64::try_from(id).context("lockdown_channel_ids row out of u64 range"))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+/// Load a guild's settings, falling back to defaults when it has none.
+pub async fn guild_settings(db: &Database, guild_id: u64) -> anyhow::Result<GuildSettings> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let row: Option<GuildSettingsRow> = sqlx::query_as(
+        "SELECT prefix, modlog_channel_id, modlog_quiet_replies, default_timeout_secs,
+                warnings_window_days, embed_color, spam_action,
+                spam_user_rate, spam_user_rate_secs,
+                spam_user_duplicates, spam_user_duplicates_secs,
+                spam_channel_rate, spam_channel_rate_secs,
+                spam_channel_duplicates, spam_channel_duplicates_secs,
+                raid_action, raid_join_rate, raid_join_rate_secs,
+                raid_young_join_rate, raid_young_join_rate_secs,
+                raid_account_age_secs, lockdown_channel_ids
+         FROM guild_settings WHERE guild_id = $1",
+    )
+    .bind(guild_id_i64)
+    .fetch_optional(db.pool())
+    .await?;
+
+    row.map_or_else(|| Ok(GuildSettings::default()), GuildSettings::try_from)
+}
+
+/// Count guilds that have a raid action configured.
+pub async fn raid_action_guild_count(db: &Database) -> anyhow::Result<u64> {
+    let count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM guild_settings WHERE raid_action IS NOT NULL")
+            .fetch_one(db.pool())
+            .await?;
+
+    u64::try_from(count).context("guild count out of u64 range")
+}
+
+/// Store every setting of a guild, replacing what was there.
+pub async fn save_guild_settings(
+    db: &Database,
+    guild_id: u64,
+    settings: &GuildSettings,
+) -> anyhow::Result<()> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let modlog_channel_id_i64 = settings
+        .modlog_channel_id
+        .map(i64::try_from)
+        .transpose()
+        .context("modlog_channel_id out of i64 range")?;
+    let default_timeout_secs_i64 = settings
+        .default_timeout_secs
+        .map(i64::try_from)
+        .transpose()
+        .context("default_timeout_secs out of i64 range")?;
+    let warnings_window_days_i64 = settings
+        .warnings_window_days
+        .map(i64::try_from)
+        .transpose()
+        .context("warnings_window_days out of i64 range")?;
+    let embed_color_i32 = settings
+        .embed_color
+        .map(i32::try_from)
+        .transpose()
+        .context("embed_color out of i32 range")?;
+    let (user_rate, user_rate_secs) =
+        spam_limit_columns(settings.spam_user_rate, "spam_user_rate")?;
+    let (user_duplicates, user_duplicates_secs) =
+        spam_limit_columns(settings.spam_user_duplicates, "spam_user_duplicates")?;
+    let (channel_rate, channel_rate_secs) =
+        spam_limit_columns(settings.spam_channel_rate, "spam_channel_rate")?;
+    let (channel_duplicates, channel_duplicates_secs) =
+        spam_limit_columns(settings.spam_channel_duplicates, "spam_channel_duplicates")?;
+    let (join_rate, join_rate_secs) =
+        spam_limit_columns(settings.raid_join_rate, "raid_join_rate")?;
+    let (young_join_rate, young_join_rate_secs) =
+        spam_limit_columns(settings.raid_young_join_rate, "raid_young_join_rate")?;
+    let raid_account_age_secs_i64 = settings
+        .raid_account_age_secs
+        .map(i64::try_from)
+        .transpose()
+        .context("raid_account_age_secs out of i64 range")?;
+    let lockdown_channel_ids_i64 = settings
+        .lockdown_channel_ids
+        .iter()
+        .map(|&id| i64::try_from(id).context("lockdown_channel_ids out of i64 range"))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    sqlx::query(
+        "INSERT INTO guild_settings (guild_id, prefix, modlog_channel_id, modlog_quiet_replies,
+                                     default_timeout_secs, warnings_window_days, embed_color,
+                                     spam_action, spam_user_rate, spam_user_rate_secs,
+                                     spam_user_duplicates, spam_user_duplicates_secs,
+                                     spam_channel_rate, spam_channel_rate_secs,
+                                     spam_channel_duplicates, spam_channel_duplicates_secs,
+                                     raid_action, raid_join_rate, raid_join_rate_secs,
+                                     raid_young_join_rate, raid_young_join_rate_secs,
+                                     raid_account_age_secs, lockdown_channel_ids)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+                 $17, $18, $19, $20, $21, $22, $23)
+         ON CONFLICT (guild_id) DO UPDATE
+         SET prefix = EXCLUDED.prefix,
+             modlog_channel_id = EXCLUDED.modlog_channel_id,
+             modlog_quiet_replies = EXCLUDED.modlog_quiet_replies,
+             default_timeout_secs = EXCLUDED.default_timeout_secs,
+             warnings_window_days = EXCLUDED.warnings_window_days,
+             embed_color = EXCLUDED.embed_color,
+             spam_action = EXCLUDED.spam_action,
+             spam_user_rate = EXCLUDED.spam_user_rate,
+             spam_user_rate_secs = EXCLUDED.spam_user_rate_secs,
+             spam_user_duplicates = EXCLUDED.spam_user_duplicates,
+             spam_user_duplicates_secs = EXCLUDED.spam_user_duplicates_secs,
+             spam_channel_rate = EXCLUDED.spam_channel_rate,
+             spam_channel_rate_secs = EXCLUDED.spam_channel_rate_secs,
+             spam_channel_duplicates = EXCLUDED.spam_channel_duplicates,
+             spam_channel_duplicates_secs = EXCLUDED.spam_channel_duplicates_secs,
+             raid_action = EXCLUDED.raid_action,
+             raid_join_rate = EXCLUDED.raid_join_rate,
+             raid_join_rate_secs = EXCLUDED.raid_join_rate_secs,
+             raid_young_join_rate = EXCLUDED.raid_young_join_rate,
+             raid_young_join_rate_secs = EXCLUDED.raid_young_join_rate_secs,
+             raid_account_age_secs = EXCLUDED.raid_account_age_secs,
+             lockdown_channel_ids = EXCLUDED.lockdown_channel_ids",
+    )
+    .bind(guild_id_i64)
+    .bind(settings.prefix.as_deref())
+    .bind(modlog_channel_id_i64)
+    .bind(settings.modlog_quiet_replies)
+    .bind(default_timeout_secs_i64)
+    .bind(warnings_window_days_i64)
+    .bind(embed_color_i32)
+    .bind(settings.spam_action.map(|action| action.as_str()))
+    .bind(user_rate)
+    .bind(user_rate_secs)
+    .bind(user_duplicates)
+    .bind(user_duplicates_secs)
+    .bind(channel_rate)
+    .bind(channel_rate_secs)
+    .bind(channel_duplicates)
+    .bind(channel_duplicates_secs)
+    .bind(settings.raid_action.map(|action| action.as_str()))
+    .bind(join_rate)
+    .bind(join_rate_secs)
+    .bind(young_join_rate)
+    .bind(young_join_rate_secs)
+    .bind(raid_account_age_secs_i64)
+    .bind(&lockdown_channel_ids_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    impls::warnings::now_unix_secs,
+    model::jobs::{JobEntry, NewJob},
+};
+
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: i64,
+    kind: String,
+    payload: String,
+    idempotency_key: Option<String>,
+    run_at: i64,
+    attempts: i32,
+    max_attempts: i32,
+    locked_until: Option<i64>,
+}
+
+impl TryFrom<JobRow> for JobEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: JobRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: u64::try_from(row.id).context("id row out of u64 range")?,
+            kind: row.kind,
+            payload: row.payload,
+            idempotency_key: row.idempotency_key,
+            run_at: u64::try_from(row.run_at).context("run_at row out of u64 range")?,
+            attempts: u32::try_from(row.attempts).context("attempts row out of u32 range")?,
+            max_attempts: u32::try_from(row.max_attempts)
+                .context("max_attempts row out of u32 range")?,
+            locked_until: row
+                .locked_until
+                .map(u64::try_from)
+                .transpose()
+                .context("locked_until row out of u64 range")?
+                .context("claimed job has no lease")?,
+        })
+    }
+}
+
+/// Queue a job, replacing any pending job with the same idempotency key.
+///
+/// Returns the job ID.
+pub async fn enqueue_job(db: &Database, job: NewJob<'_>) -> anyhow::Result<u64> {
+    let run_at_i64 = i64::try_from(job.run_at).context("run_at out of i64 range")?;
+    let max_attempts_i32 =
+        i32::try_from(job.max_attempts).context("max_attempts out of i32 range")?;
+    let created_at_i64 = i64::try_from(now_unix_secs()).context("created_at out of i64 range")?;
+
+    // Clearing the lease makes a running copy of the old job skip its completion,
+    // so the new schedule survives.
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO jobs (kind, payload, idempotency_key, run_at, max_attempts, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (idempotency_key) DO UPDATE
+         SET kind = EXCLUDED.kind,
+             payload = EXCLUDED.payload,
+             run_at = EXCLUDED.run_at,
+             attempts = 0,
+             max_attempts = EXCLUDED.max_attempts,
+             locked_until = NULL,
+             last_error = NULL,
+             failed_at = NULL
+         RETURNING id",
+    )
+    .bind(job.kind)
+    .bind(job.payload)
+    .bind(job.idempotency_key)
+    .bind(run_at_i64)
+    .bind(max_attempts_i32)
+    .bind(created_at_i64)
+    .fetch_one(db.pool())
+    .await?;
+
+    u64::try_from(id).context("id row out of u64 range")
+}
+
+/// Drop a pending job by its idempotency key, returning whether one existed.
+pub async fn cancel_job(db: &Database, idempotency_key: &str) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM jobs WHERE idempotency_key = $1 AND failed_at IS NULL")
+        .bind(idempotency_key)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Lease up to `limit` due jobs until `lease_until`, counting the attempt.
+///
+/// Jobs whose lease ran out (for example after a crash mid-run) are due again.
+pub async fn claim_due_jobs(
+    db: &Database,
+    now: u64,
+    lease_until: u64,
+    limit: u32,
+) -> anyhow::Result<Vec<JobEntry>> {
+    let now_i64 = i64::try_from(now).context("now out of i64 range")?;
+    let lease_until_i64 = i64::try_from(lease_until).context("lease_until out of i64 range")?;
+    let limit_i64 = i64::from(limit);
+
+    let rows: Vec<JobRow> = sqlx::query_as(
+        "UPDATE jobs
+         SET attempts = attempts + 1, locked_until = $2
+         WHERE id IN (
+             SELECT id FROM jobs
+             WHERE failed_at IS NULL
+               AND run_at <= $1
+               AND (locked_until IS NULL OR locked_until <= $1)
+             ORDER BY run_at ASC
+             LIMIT $3
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING id, kind, payload, idempotency_key, run_at, attempts, max_attempts, locked_until",
+    )
+    .bind(now_i64)
+    .bind(lease_until_i64)
+    .bind(limit_i64)
+    .fetch_all(db.pool())
+    .await?;
+
+    let mut jobs = rows
+        .into_iter()
+        .map(JobEntry::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    jobs.sort_by_key(|job| job.run_at);
+
+    Ok(jobs)
+}
+
+/// Lease up to `limit` pending jobs of one kind whose idempotency key starts
+/// with `key_prefix`, due or not, until `lease_until`.
+///
+/// Used to run work early, such as tidying up before a shutdown.
+pub async fn claim_jobs_of_kind(
+    db: &Database,
+    kind: &str,
+    key_prefix: &str,
+    now: u64,
+    lease_until: u64,
+    limit: u32,
+) -> anyhow::Result<Vec<JobEntry>> {
+    let now_i64 = i64::try_from(now).context("now out of i64 range")?;
+    let lease_until_i64 = i64::try_from(lease_until).context("lease_until out of i64 range")?;
+    let limit_i64 = i64::from(limit);
+
+    let rows: Vec<JobRow> = sqlx::query_as(
+        "UPDATE jobs
+         SET attempts = attempts + 1, locked_until = $4
+         WHERE id IN (
+             SELECT id FROM jobs
+             WHERE failed_at IS NULL
+               AND kind = $1
+               AND starts_with(idempotency_key, $2)
+               AND (locked_until IS NULL OR locked_until <= $3)
+             ORDER BY run_at ASC
+             LIMIT $5
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING id, kind, payload, idempotency_key, run_at, attempts, max_attempts, locked_until",
+    )
+    .bind(kind)
+    .bind(key_prefix)
+    .bind(now_i64)
+    .bind(lease_until_i64)
+    .bind(limit_i64)
+    .fetch_all(db.pool())
+    .await?;
+
+    rows.into_iter().map(JobEntry::try_from).collect()
+}
+
+/// Remove a job that ran successfully.
+pub async fn complete_job(db: &Database, job: &JobEntry) -> anyhow::Result<()> {
+    let id_i64 = i64::try_from(job.id).context("id out of i64 range")?;
+    let locked_until_i64 =
+        i64::try_from(job.locked_until).context("locked_until out of i64 range")?;
+
+    sqlx::query("DELETE FROM jobs WHERE id = $1 AND locked_until = $2")
+        .bind(id_i64)
+        .bind(locked_until_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Release a failed attempt so the job runs again at `run_at`.
+pub async fn retry_job(
+    db: &Database,
+    job: &JobEntry,
+    run_at: u64,
+    error: &str,
+) -> anyhow::Result<()> {
+    let id_i64 = i64::try_from(job.id).context("id out of i64 range")?;
+    let locked_until_i64 =
+        i64::try_from(job.locked_until).context("locked_until out of i64 range")?;
+    let run_at_i64 = i64::try_from(run_at).context("run_at out of i64 range")?;
+
+    sqlx::query(
+        "UPDATE jobs SET run_at = $3, locked_until = NULL, last_error = $4
+         WHERE id = $1 AND locked_until = $2",
+    )
+    .bind(id_i64)
+    .bind(locked_until_i64)
+    .bind(run_at_i64)
+    .bind(error)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
+}
+
+/// Park a job that will not be retried, keeping it for inspection.
+pub async fn fail_job(db: &Database, job: &JobEntry, error: &str) -> anyhow::Result<()> {
+    let id_i64 = i64::try_from(job.id).context("id out of i64 range")?;
+    let locked_until_i64 =
+        i64::try_from(job.locked_until).context("locked_until out of i64 range")?;
+    let failed_at_i64 = i64::try_from(now_unix_secs()).context("failed_at out of i64 range")?;
+
+    sqlx::query(
+        "UPDATE jobs SET failed_at = $3, locked_until = NULL, last_error = $4
+         WHERE id = $1 AND locked_until = $2",
+    )
+    .bind(id_i64)
+    .bind(locked_until_i64)
+    .bind(failed_at_i64)
+    .bind(error)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
+}
+
+/// Earliest time a pending job becomes runnable, if any are queued.
+pub async fn next_job_run_at(db: &Database) -> anyhow::Result<Option<u64>> {
+    let next: Option<i64> = sqlx::query_scalar(
+        "SELECT MIN(GREATEST(run_at, COALESCE(locked_until, 0)))
+         FROM jobs
+         WHERE failed_at IS NULL",
+    )
+    .fetch_one(db.pool())
+    .await?;
+
+    next.map(u64::try_from)
+        .transpose()
+        .context("run_at row out of u64 range")
+}
+
+/// Count jobs that are still waiting to run.
+pub async fn pending_job_count(db: &Database) -> anyhow::Result<u64> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE failed_at IS NULL")
+        .fetch_one(db.pool())
+        .await?;
+
+    u64::try_from(count).context("job count out of u64 range")
+}
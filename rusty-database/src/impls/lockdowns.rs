@@ -0,0 +1,171 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    impls::warnings::now_unix_secs,
+    model::lockdowns::{Lockdown, LockedChannel, NewLockdown, OverwriteBits},
+};
+
+#[derive(sqlx::FromRow)]
+struct LockdownRow {
+    guild_id: i64,
+    moderator_id: i64,
+    reason: Option<String>,
+    previous_verification_level: Option<i16>,
+    created_at: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct LockedChannelRow {
+    channel_id: i64,
+    previous_allow: Option<i64>,
+    previous_deny: Option<i64>,
+}
+
+impl TryFrom<LockedChannelRow> for LockedChannel {
+    type Error = anyhow::Error;
+
+    fn try_from(row: LockedChannelRow) -> anyhow::Result<Self> {
+        let previous = match (row.previous_allow, row.previous_deny) {
+            (Some(allow), Some(deny)) => Some(OverwriteBits {
+                allow: u64::try_from(allow).context("previous_allow row out of u64 range")?,
+                deny: u64::try_from(deny).context("previous_deny row out of u64 range")?,
+            }),
+            _ => None,
+        };
+
+        Ok(Self {
+            channel_id: u64::try_from(row.channel_id).context("channel_id row out of u64 range")?,
+            previous,
+        })
+    }
+}
+
+/// Start a lockdown, returning `false` if the guild is already in one.
+pub async fn start_lockdown(db: &Database, lockdown: NewLockdown<'_>) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(lockdown.guild_id).context("guild_id out of i64 range")?;
+    let moderator_id_i64 =
+        i64::try_from(lockdown.moderator_id).context("moderator_id out of i64 range")?;
+    let created_at_i64 = i64::try_from(now_unix_secs()).context("created_at out of i64 range")?;
+
+    let result = sqlx::query(
+        "INSERT INTO lockdowns (guild_id, moderator_id, reason, previous_verification_level,
+                                created_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (guild_id) DO NOTHING",
+    )
+    .bind(guild_id_i64)
+    .bind(moderator_id_i64)
+    .bind(lockdown.reason)
+    .bind(lockdown.previous_verification_level.map(i16::from))
+    .bind(created_at_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remember a channel the active lockdown locked and what it had before.
+///
+/// A channel that is already recorded keeps its original overwrite.
+pub async fn add_lockdown_channel(
+    db: &Database,
+    guild_id: u64,
+    channel: LockedChannel,
+) -> anyhow::Result<()> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let channel_id_i64 =
+        i64::try_from(channel.channel_id).context("channel_id out of i64 range")?;
+    let (allow_i64, deny_i64) = match channel.previous {
+        Some(bits) => (
+            Some(i64::try_from(bits.allow).context("previous_allow out of i64 range")?),
+            Some(i64::try_from(bits.deny).context("previous_deny out of i64 range")?),
+        ),
+        None => (None, None),
+    };
+
+    sqlx::query(
+        "INSERT INTO lockdown_channels (guild_id, channel_id, previous_allow, previous_deny)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (guild_id, channel_id) DO NOTHING",
+    )
+    .bind(guild_id_i64)
+    .bind(channel_id_i64)
+    .bind(allow_i64)
+    .bind(deny_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
+}
+
+/// Forget a channel whose overwrite has been restored.
+pub async fn remove_lockdown_channel(
+    db: &Database,
+    guild_id: u64,
+    channel_id: u64,
+) -> anyhow::Result<()> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let channel_id_i64 = i64::try_from(channel_id).context("channel_id out of i64 range")?;
+
+    sqlx::query("DELETE FROM lockdown_channels WHERE guild_id = $1 AND channel_id = $2")
+        .bind(guild_id_i64)
+        .bind(channel_id_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Look up a guild's active lockdown, if any, with the channels it still holds.
+pub async fn active_lockdown(db: &Database, guild_id: u64) -> anyhow::Result<Option<Lockdown>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let row: Option<LockdownRow> = sqlx::query_as(
+        "SELECT guild_id, moderator_id, reason, previous_verification_level, created_at
+         FROM lockdowns WHERE guild_id = $1",
+    )
+    .bind(guild_id_i64)
+    .fetch_optional(db.pool())
+    .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let channels: Vec<LockedChannelRow> = sqlx::query_as(
+        "SELECT channel_id, previous_allow, previous_deny
+         FROM lockdown_channels WHERE guild_id = $1 ORDER BY channel_id",
+    )
+    .bind(guild_id_i64)
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(Some(Lockdown {
+        guild_id: u64::try_from(row.guild_id).context("guild_id row out of u64 range")?,
+        moderator_id: u64::try_from(row.moderator_id)
+            .context("moderator_id row out of u64 range")?,
+        reason: row.reason,
+        previous_verification_level: row
+            .previous_verification_level
+            .map(u8::try_from)
+            .transpose()
+            .context("previous_verification_level row out of u8 range")?,
+        created_at: u64::try_from(row.created_at).context("created_at row out of u64 range")?,
+        channels: channels
+            .into_iter()
+            .map(LockedChannel::try_from)
+            .collect::<anyhow::Result<_>>()?,
+    }))
+}
+
+/// End a guild's lockdown, returning `false` if it had none.
+pub async fn end_lockdown(db: &Database, guild_id: u64) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let result = sqlx::query("DELETE FROM lockdowns WHERE guild_id = $1")
+        .bind(guild_id_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
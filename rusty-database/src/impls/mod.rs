@@ -1,2 +1,14 @@
+pub mod automod;
+pub mod cases;
+pub mod channel_locks;
+pub mod command_cooldowns;
+pub mod command_overrides;
+pub mod gateway_sessions;
+pub mod guild_settings;
+pub mod jobs;
 pub mod leveling;
+pub mod lockdowns;
+pub mod protected_roles;
+pub mod tempbans;
+pub mod warn_policies;
 pub mod warnings;
@@ -0,0 +1,58 @@
+use anyhow::Context as _;
+
+use crate::database::Database;
+
+/// List the roles moderation commands refuse to act on in a guild.
+pub async fn protected_roles(db: &Database, guild_id: u64) -> anyhow::Result<Vec<u64>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let rows: Vec<i64> = sqlx::query_scalar(
+        "SELECT role_id FROM protected_roles WHERE guild_id = $1 ORDER BY role_id",
+    )
+    .bind(guild_id_i64)
+    .fetch_all(db.pool())
+    .await?;
+
+    rows.into_iter()
+        .map(|role_id| u64::try_from(role_id).context("role_id row out of u64 range"))
+        .collect()
+}
+
+/// Protect a role, returning `false` if it was already protected.
+pub async fn add_protected_role(
+    db: &Database,
+    guild_id: u64,
+    role_id: u64,
+) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let role_id_i64 = i64::try_from(role_id).context("role_id out of i64 range")?;
+
+    let result = sqlx::query(
+        "INSERT INTO protected_roles (guild_id, role_id) VALUES ($1, $2)
+         ON CONFLICT (guild_id, role_id) DO NOTHING",
+    )
+    .bind(guild_id_i64)
+    .bind(role_id_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Stop protecting a role, returning `false` if it was not protected.
+pub async fn remove_protected_role(
+    db: &Database,
+    guild_id: u64,
+    role_id: u64,
+) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let role_id_i64 = i64::try_from(role_id).context("role_id out of i64 range")?;
+
+    let result = sqlx::query("DELETE FROM protected_roles WHERE guild_id = $1 AND role_id = $2")
+        .bind(guild_id_i64)
+        .bind(role_id_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,96 @@
+use anyhow::Context as _;
+
+use crate::{database::Database, impls::warnings::now_unix_secs, model::tempbans::TempbanEntry};
+
+#[derive(sqlx::FromRow)]
+struct TempbanRow {
+    guild_id: i64,
+    user_id: i64,
+    moderator_id: i64,
+    created_at: i64,
+    expires_at: i64,
+}
+
+impl TryFrom<TempbanRow> for TempbanEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: TempbanRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            guild_id: u64::try_from(row.guild_id).context("guild_id row out of u64 range")?,
+            user_id: u64::try_from(row.user_id).context("user_id row out of u64 range")?,
+            moderator_id: u64::try_from(row.moderator_id)
+                .context("moderator_id row out of u64 range")?,
+            created_at: u64::try_from(row.created_at).context("created_at row out of u64 range")?,
+            expires_at: u64::try_from(row.expires_at).context("expires_at row out of u64 range")?,
+        })
+    }
+}
+
+/// Store (or replace) the expiry of a temporary ban.
+pub async fn upsert_tempban(
+    db: &Database,
+    guild_id: u64,
+    user_id: u64,
+    moderator_id: u64,
+    expires_at: u64,
+) -> anyhow::Result<()> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let user_id_i64 = i64::try_from(user_id).context("user_id out of i64 range")?;
+    let moderator_id_i64 = i64::try_from(moderator_id).context("moderator_id out of i64 range")?;
+    let created_at_i64 = i64::try_from(now_unix_secs()).context("created_at out of i64 range")?;
+    let expires_at_i64 = i64::try_from(expires_at).context("expires_at out of i64 range")?;
+
+    sqlx::query(
+        "INSERT INTO tempbans (guild_id, user_id, moderator_id, created_at, expires_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (guild_id, user_id) DO UPDATE
+         SET moderator_id = EXCLUDED.moderator_id,
+             created_at = EXCLUDED.created_at,
+             expires_at = EXCLUDED.expires_at",
+    )
+    .bind(guild_id_i64)
+    .bind(user_id_i64)
+    .bind(moderator_id_i64)
+    .bind(created_at_i64)
+    .bind(expires_at_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
+}
+
+/// Forget a pending tempban, returning whether one existed.
+pub async fn delete_tempban(db: &Database, guild_id: u64, user_id: u64) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let user_id_i64 = i64::try_from(user_id).context("user_id out of i64 range")?;
+
+    let result = sqlx::query("DELETE FROM tempbans WHERE guild_id = $1 AND user_id = $2")
+        .bind(guild_id_i64)
+        .bind(user_id_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Look up the pending tempban for a user, if any.
+pub async fn tempban_entry(
+    db: &Database,
+    guild_id: u64,
+    user_id: u64,
+) -> anyhow::Result<Option<TempbanEntry>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let user_id_i64 = i64::try_from(user_id).context("user_id out of i64 range")?;
+
+    let row: Option<TempbanRow> = sqlx::query_as(
+        "SELECT guild_id, user_id, moderator_id, created_at, expires_at
+         FROM tempbans
+         WHERE guild_id = $1 AND user_id = $2",
+    )
+    .bind(guild_id_i64)
+    .bind(user_id_i64)
+    .fetch_optional(db.pool())
+    .await?;
+
+    row.map(TempbanEntry::try_from).transpose()
+}
@@ -0,0 +1,111 @@
+use anyhow::Context as _;
+
+use crate::{
+    database::Database,
+    impls::warnings::now_unix_secs,
+    model::warn_policies::{NewWarnPolicy, WarnPolicy},
+};
+
+#[derive(sqlx::FromRow)]
+struct WarnPolicyRow {
+    id: i64,
+    guild_id: i64,
+    threshold: i32,
+    window_secs: i64,
+    action: String,
+    duration_secs: Option<i64>,
+}
+
+impl TryFrom<WarnPolicyRow> for WarnPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(row: WarnPolicyRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: u64::try_from(row.id).context("id row out of u64 range")?,
+            guild_id: u64::try_from(row.guild_id).context("guild_id row out of u64 range")?,
+            threshold: u32::try_from(row.threshold).context("threshold row out of u32 range")?,
+            window_secs: u64::try_from(row.window_secs)
+                .context("window_secs row out of u64 range")?,
+            action: row.action.parse()?,
+            duration_secs: row
+                .duration_secs
+                .map(u64::try_from)
+                .transpose()
+                .context("duration_secs row out of u64 range")?,
+        })
+    }
+}
+
+const POLICY_COLUMNS: &str = "id, guild_id, threshold, window_secs, action, duration_secs";
+
+/// Store a policy, replacing the one with the same threshold and window.
+pub async fn upsert_warn_policy(
+    db: &Database,
+    policy: NewWarnPolicy,
+) -> anyhow::Result<WarnPolicy> {
+    let guild_id_i64 = i64::try_from(policy.guild_id).context("guild_id out of i64 range")?;
+    let threshold_i32 = i32::try_from(policy.threshold).context("threshold out of i32 range")?;
+    let window_secs_i64 =
+        i64::try_from(policy.window_secs).context("window_secs out of i64 range")?;
+    let duration_secs_i64 = policy
+        .duration_secs
+        .map(i64::try_from)
+        .transpose()
+        .context("duration_secs out of i64 range")?;
+    let created_by_i64 = i64::try_from(policy.created_by).context("created_by out of i64 range")?;
+    let created_at_i64 = i64::try_from(now_unix_secs()).context("created_at out of i64 range")?;
+
+    let row: WarnPolicyRow = sqlx::query_as(&format!(
+        "INSERT INTO warn_policies
+            (guild_id, threshold, window_secs, action, duration_secs, created_by, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (guild_id, threshold, window_secs) DO UPDATE
+         SET action = EXCLUDED.action,
+             duration_secs = EXCLUDED.duration_secs,
+             created_by = EXCLUDED.created_by,
+             created_at = EXCLUDED.created_at
+         RETURNING {POLICY_COLUMNS}"
+    ))
+    .bind(guild_id_i64)
+    .bind(threshold_i32)
+    .bind(window_secs_i64)
+    .bind(policy.action.as_str())
+    .bind(duration_secs_i64)
+    .bind(created_by_i64)
+    .bind(created_at_i64)
+    .fetch_one(db.pool())
+    .await?;
+
+    row.try_into()
+}
+
+/// Delete a guild's policy by ID, returning whether it existed.
+pub async fn delete_warn_policy(db: &Database, guild_id: u64, id: u64) -> anyhow::Result<bool> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let id_i64 = i64::try_from(id).context("id out of i64 range")?;
+
+    let result = sqlx::query("DELETE FROM warn_policies WHERE guild_id = $1 AND id = $2")
+        .bind(guild_id_i64)
+        .bind(id_i64)
+        .execute(db.pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Return a guild's policies, smallest threshold first.
+pub async fn warn_policies(db: &Database, guild_id: u64) -> anyhow::Result<Vec<WarnPolicy>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let rows: Vec<WarnPolicyRow> = sqlx::query_as(&format!(
+        "SELECT {POLICY_COLUMNS}
+         FROM warn_policies
+         WHERE guild_id = $1
+         ORDER BY threshold ASC, window_secs ASC"
+    ))
+    .bind(guild_id_i64)
+    .fetch_all(db.pool())
+    .await?;
+
+    rows.into_iter().map(WarnPolicy::try_from).collect()
+}
@@ -4,17 +4,51 @@ use anyhow::Context as _;
 
 use crate::{
     database::Database,
-    model::warnings::{WarningEntry, WarningRecord},
+    model::warnings::{WarningChange, WarningEntry, WarningRecord, WarningSettings},
 };
 
 #[derive(sqlx::FromRow)]
 struct WarningRow {
+    id: i64,
+    user_id: i64,
     warned_at: i64,
     moderator_id: i64,
     reason: String,
 }
 
-/// Record a warning for a target user and return the new warning number.
+impl TryFrom<WarningRow> for WarningEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: WarningRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: u64::try_from(row.id).context("id row out of u64 range")?,
+            user_id: u64::try_from(row.user_id).context("user_id row out of u64 range")?,
+            warned_at: u64::try_from(row.warned_at).context("warned_at row out of u64 range")?,
+            moderator_id: u64::try_from(row.moderator_id)
+                .context("moderator_id row out of u64 range")?,
+            reason: row.reason,
+        })
+    }
+}
+
+const WARNING_COLUMNS: &str = "id, user_id, warned_at, moderator_id, reason";
+
+/// SQL filter matching warnings that still count: not deleted and not past the
+/// guild's expiry. Expects the table aliased as `w` and the current time bound
+/// to parameter `now_param`.
+fn active_filter(now_param: usize) -> String {
+    format!(
+        "w.deleted_at IS NULL
+         AND NOT EXISTS (
+             SELECT 1 FROM warning_settings s
+             WHERE s.guild_id = w.guild_id
+               AND s.expiry_secs IS NOT NULL
+               AND w.warned_at < ${now_param} - s.expiry_secs
+         )"
+    )
+}
+
+/// Record a warning for a target user and return its ID and the active warning count.
 pub async fn record_warning(
     db: &Database,
     guild_id: u64,
@@ -28,30 +62,39 @@ pub async fn record_warning(
     let moderator_id_i64 = i64::try_from(moderator_id).context("moderator_id out of i64 range")?;
     let warned_at_i64 = i64::try_from(warned_at).context("warned_at out of i64 range")?;
 
-    sqlx::query(
-        "INSERT INTO warnings (guild_id, user_id, moderator_id, reason, warned_at) VALUES ($1, $2, $3, $4, $5)",
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO warnings (guild_id, user_id, moderator_id, reason, warned_at)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id",
     )
     .bind(guild_id_i64)
     .bind(user_id_i64)
     .bind(moderator_id_i64)
     .bind(reason)
     .bind(warned_at_i64)
-    .execute(db.pool())
+    .fetch_one(db.pool())
     .await?;
 
-    let warn_number: i64 =
-        sqlx::query_scalar("SELECT COUNT(*) FROM warnings WHERE guild_id = $1 AND user_id = $2")
-            .bind(guild_id_i64)
-            .bind(user_id_i64)
-            .fetch_one(db.pool())
-            .await?;
-
-    let warn_number = usize::try_from(warn_number).context("warn count out of usize range")?;
+    let active_count: i64 = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM warnings w
+         WHERE w.guild_id = $1 AND w.user_id = $2 AND {}",
+        active_filter(3)
+    ))
+    .bind(guild_id_i64)
+    .bind(user_id_i64)
+    .bind(warned_at_i64)
+    .fetch_one(db.pool())
+    .await?;
 
-    Ok(WarningRecord { warn_number })
+    Ok(WarningRecord {
+        id: u64::try_from(id).context("id row out of u64 range")?,
+        active_count: usize::try_from(active_count).context("warn count out of usize range")?,
+    })
 }
 
-/// Return warning entries for a target user in the inclusive [since, now] range.
+/// Return active warnings for a target user in the inclusive [since, now] range.
+///
+/// Deleted warnings and warnings past the guild's expiry are left out.
 pub async fn warnings_since(
     db: &Database,
     guild_id: u64,
@@ -61,33 +104,241 @@ pub async fn warnings_since(
     let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
     let user_id_i64 = i64::try_from(user_id).context("user_id out of i64 range")?;
     let since_i64 = i64::try_from(since).context("since out of i64 range")?;
+    let now_i64 = i64::try_from(now_unix_secs()).context("now out of i64 range")?;
 
-    let rows: Vec<WarningRow> = sqlx::query_as(
-        "SELECT warned_at, moderator_id, reason
-         FROM warnings
-         WHERE guild_id = $1 AND user_id = $2 AND warned_at >= $3
-         ORDER BY warned_at ASC",
-    )
+    let rows: Vec<WarningRow> = sqlx::query_as(&format!(
+        "SELECT {WARNING_COLUMNS}
+         FROM warnings w
+         WHERE w.guild_id = $1 AND w.user_id = $2 AND w.warned_at >= $3 AND {}
+         ORDER BY w.warned_at ASC, w.id ASC",
+        active_filter(4)
+    ))
     .bind(guild_id_i64)
     .bind(user_id_i64)
     .bind(since_i64)
+    .bind(now_i64)
     .fetch_all(db.pool())
     .await?;
 
-    let mut entries = Vec::with_capacity(rows.len());
-    for row in rows {
-        let warned_at = u64::try_from(row.warned_at).context("warned_at row out of u64 range")?;
-        let moderator_id =
-            u64::try_from(row.moderator_id).context("moderator_id row out of u64 range")?;
-        entries.push(WarningEntry {
-            warned_at,
-            moderator_id,
-            reason: row.reason,
-        });
+    rows.into_iter().map(WarningEntry::try_from).collect()
+}
+
+/// Look up a warning that has not been deleted, whether or not it has expired.
+pub async fn warning_by_id(
+    db: &Database,
+    guild_id: u64,
+    warning_id: u64,
+) -> anyhow::Result<Option<WarningEntry>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let warning_id_i64 = i64::try_from(warning_id).context("warning_id out of i64 range")?;
+
+    let row: Option<WarningRow> = sqlx::query_as(&format!(
+        "SELECT {WARNING_COLUMNS}
+         FROM warnings
+         WHERE guild_id = $1 AND id = $2 AND deleted_at IS NULL"
+    ))
+    .bind(guild_id_i64)
+    .bind(warning_id_i64)
+    .fetch_optional(db.pool())
+    .await?;
+
+    row.map(WarningEntry::try_from).transpose()
+}
+
+/// Replace a warning's reason, keeping the old one in the audit trail.
+///
+/// Returns the updated warning, or `None` if it does not exist or was deleted.
+pub async fn edit_warning_reason(
+    db: &Database,
+    guild_id: u64,
+    warning_id: u64,
+    moderator_id: u64,
+    reason: &str,
+) -> anyhow::Result<Option<WarningEntry>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let warning_id_i64 = i64::try_from(warning_id).context("warning_id out of i64 range")?;
+    let moderator_id_i64 = i64::try_from(moderator_id).context("moderator_id out of i64 range")?;
+    let changed_at_i64 = i64::try_from(now_unix_secs()).context("changed_at out of i64 range")?;
+
+    let mut tx = db.pool().begin().await?;
+
+    let old_reason: Option<String> = sqlx::query_scalar(
+        "SELECT reason FROM warnings
+         WHERE guild_id = $1 AND id = $2 AND deleted_at IS NULL
+         FOR UPDATE",
+    )
+    .bind(guild_id_i64)
+    .bind(warning_id_i64)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(old_reason) = old_reason else {
+        return Ok(None);
+    };
+
+    let row: WarningRow = sqlx::query_as(&format!(
+        "UPDATE warnings SET reason = $3
+         WHERE guild_id = $1 AND id = $2
+         RETURNING {WARNING_COLUMNS}"
+    ))
+    .bind(guild_id_i64)
+    .bind(warning_id_i64)
+    .bind(reason)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO warning_revisions
+            (warning_id, guild_id, change, moderator_id, old_reason, new_reason, changed_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(warning_id_i64)
+    .bind(guild_id_i64)
+    .bind(WarningChange::Edit.as_str())
+    .bind(moderator_id_i64)
+    .bind(old_reason)
+    .bind(reason)
+    .bind(changed_at_i64)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    row.try_into().map(Some)
+}
+
+/// Soft-delete a single warning, returning it if it was still active.
+pub async fn delete_warning(
+    db: &Database,
+    guild_id: u64,
+    warning_id: u64,
+    moderator_id: u64,
+) -> anyhow::Result<Option<WarningEntry>> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let warning_id_i64 = i64::try_from(warning_id).context("warning_id out of i64 range")?;
+
+    let deleted = soft_delete_warnings(
+        db,
+        guild_id_i64,
+        "id = $2",
+        warning_id_i64,
+        moderator_id,
+        WarningChange::Delete,
+    )
+    .await?;
+
+    Ok(deleted.into_iter().next())
+}
+
+/// Soft-delete every remaining warning of a user, returning how many were removed.
+pub async fn clear_warnings(
+    db: &Database,
+    guild_id: u64,
+    user_id: u64,
+    moderator_id: u64,
+) -> anyhow::Result<usize> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let user_id_i64 = i64::try_from(user_id).context("user_id out of i64 range")?;
+
+    let deleted = soft_delete_warnings(
+        db,
+        guild_id_i64,
+        "user_id = $2",
+        user_id_i64,
+        moderator_id,
+        WarningChange::Clear,
+    )
+    .await?;
+
+    Ok(deleted.len())
+}
+
+async fn soft_delete_warnings(
+    db: &Database,
+    guild_id_i64: i64,
+    target_filter: &str,
+    target_i64: i64,
+    moderator_id: u64,
+    change: WarningChange,
+) -> anyhow::Result<Vec<WarningEntry>> {
+    let moderator_id_i64 = i64::try_from(moderator_id).context("moderator_id out of i64 range")?;
+    let deleted_at_i64 = i64::try_from(now_unix_secs()).context("deleted_at out of i64 range")?;
+
+    let mut tx = db.pool().begin().await?;
+
+    let rows: Vec<WarningRow> = sqlx::query_as(&format!(
+        "UPDATE warnings SET deleted_at = $3, deleted_by = $4
+         WHERE guild_id = $1 AND {target_filter} AND deleted_at IS NULL
+         RETURNING {WARNING_COLUMNS}"
+    ))
+    .bind(guild_id_i64)
+    .bind(target_i64)
+    .bind(deleted_at_i64)
+    .bind(moderator_id_i64)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for row in &rows {
+        sqlx::query(
+            "INSERT INTO warning_revisions
+                (warning_id, guild_id, change, moderator_id, old_reason, changed_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(row.id)
+        .bind(guild_id_i64)
+        .bind(change.as_str())
+        .bind(moderator_id_i64)
+        .bind(&row.reason)
+        .bind(deleted_at_i64)
+        .execute(&mut *tx)
+        .await?;
     }
 
-    entries.sort_by_key(|entry| entry.warned_at);
-    Ok(entries)
+    tx.commit().await?;
+
+    rows.into_iter().map(WarningEntry::try_from).collect()
+}
+
+/// Load a guild's warning settings, falling back to defaults.
+pub async fn warning_settings(db: &Database, guild_id: u64) -> anyhow::Result<WarningSettings> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+
+    let expiry_secs: Option<Option<i64>> =
+        sqlx::query_scalar("SELECT expiry_secs FROM warning_settings WHERE guild_id = $1")
+            .bind(guild_id_i64)
+            .fetch_optional(db.pool())
+            .await?;
+
+    Ok(WarningSettings {
+        expiry_secs: expiry_secs
+            .flatten()
+            .map(u64::try_from)
+            .transpose()
+            .context("expiry_secs row out of u64 range")?,
+    })
+}
+
+/// Set or clear the age after which a guild's warnings stop counting.
+pub async fn set_warning_expiry(
+    db: &Database,
+    guild_id: u64,
+    expiry_secs: Option<u64>,
+) -> anyhow::Result<()> {
+    let guild_id_i64 = i64::try_from(guild_id).context("guild_id out of i64 range")?;
+    let expiry_secs_i64 = expiry_secs
+        .map(i64::try_from)
+        .transpose()
+        .context("expiry_secs out of i64 range")?;
+
+    sqlx::query(
+        "INSERT INTO warning_settings (guild_id, expiry_secs) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET expiry_secs = EXCLUDED.expiry_secs",
+    )
+    .bind(guild_id_i64)
+    .bind(expiry_secs_i64)
+    .execute(db.pool())
+    .await?;
+
+    Ok(())
 }
 
 pub fn now_unix_secs() -> u64 {
@@ -1,5 +1,6 @@
 pub mod database;
 pub mod impls;
+pub mod migrations;
 pub mod model;
 
 pub use database::{Database, MIGRATOR};
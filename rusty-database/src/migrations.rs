@@ -0,0 +1,129 @@
+//! Applying, reverting and inspecting the schema migrations in [`MIGRATOR`].
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Context as _;
+use sqlx::migrate::Migrate as _;
+
+use crate::database::{Database, MIGRATOR};
+
+/// Where a single migration stands against the connected database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the local file no longer matches what was run.
+    ChecksumMismatch,
+    /// Started but never finished; the schema needs manual repair.
+    Dirty,
+    /// Recorded in the database but missing from this build.
+    Unknown,
+}
+
+impl fmt::Display for MigrationState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Applied => "applied",
+            Self::Pending => "pending",
+            Self::ChecksumMismatch => "checksum mismatch",
+            Self::Dirty => "dirty",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    /// Empty for [`MigrationState::Unknown`] migrations.
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// Report every known or recorded migration, oldest first.
+pub async fn migration_status(db: &Database) -> anyhow::Result<Vec<MigrationStatus>> {
+    let mut conn = db.pool().acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let dirty_version = conn.dirty_version().await?;
+    let mut applied: HashMap<i64, Vec<u8>> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| (migration.version, migration.checksum.into_owned()))
+        .collect();
+
+    let mut statuses: Vec<MigrationStatus> = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| {
+            let state = if dirty_version == Some(migration.version) {
+                MigrationState::Dirty
+            } else {
+                match applied.remove(&migration.version) {
+                    Some(checksum) if checksum == *migration.checksum => MigrationState::Applied,
+                    Some(_) => MigrationState::ChecksumMismatch,
+                    None => MigrationState::Pending,
+                }
+            };
+
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+            }
+        })
+        .collect();
+
+    statuses.extend(applied.into_keys().map(|version| MigrationStatus {
+        version,
+        description: String::new(),
+        state: MigrationState::Unknown,
+    }));
+    statuses.sort_by_key(|status| status.version);
+
+    Ok(statuses)
+}
+
+/// Apply every pending migration, returning the versions that were applied.
+pub async fn run_migrations(db: &Database) -> anyhow::Result<Vec<i64>> {
+    let pending: Vec<i64> = migration_status(db)
+        .await?
+        .into_iter()
+        .filter(|status| status.state == MigrationState::Pending)
+        .map(|status| status.version)
+        .collect();
+
+    MIGRATOR
+        .run(db.pool())
+        .await
+        .context("failed to apply migrations")?;
+
+    Ok(pending)
+}
+
+/// Revert the `steps` most recently applied migrations with their `.down.sql`
+/// files, returning the reverted versions newest first.
+pub async fn rollback_migrations(db: &Database, steps: usize) -> anyhow::Result<Vec<i64>> {
+    let mut applied: Vec<i64> = migration_status(db)
+        .await?
+        .into_iter()
+        .filter(|status| status.state != MigrationState::Pending)
+        .map(|status| status.version)
+        .collect();
+    applied.reverse();
+
+    let reverted: Vec<i64> = applied.iter().copied().take(steps).collect();
+    if reverted.is_empty() {
+        return Ok(reverted);
+    }
+    // `undo` reverts everything newer than the target version.
+    let target = applied.get(steps).copied().unwrap_or(0);
+
+    MIGRATOR
+        .undo(db.pool(), target)
+        .await
+        .context("failed to revert migrations")?;
+
+    Ok(reverted)
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// What an automod rule looks for in a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AutomodKind {
+    /// Any of the listed words, matched case-insensitively on word boundaries.
+    Words,
+    /// Any of the listed regular expressions.
+    Regex,
+    /// Discord invite links.
+    Invites,
+    /// Links to any domain not in the list.
+    LinkAllowlist,
+    /// Links to any domain in the list.
+    LinkDenylist,
+    /// More than `threshold` percent of the letters are uppercase.
+    Caps,
+    /// More than `threshold` user and role mentions.
+    Mentions,
+    /// More than `threshold` emoji.
+    Emoji,
+    /// A character carrying more than `threshold` combining marks.
+    Zalgo,
+}
+
+impl AutomodKind {
+    pub const ALL: [Self; 9] = [
+        Self::Words,
+        Self::Regex,
+        Self::Invites,
+        Self::LinkAllowlist,
+        Self::LinkDenylist,
+        Self::Caps,
+        Self::Mentions,
+        Self::Emoji,
+        Self::Zalgo,
+    ];
+
+    /// Stable identifier stored in the `automod_rules.kind` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Words => "words",
+            Self::Regex => "regex",
+            Self::Invites => "invites",
+            Self::LinkAllowlist => "link-allowlist",
+            Self::LinkDenylist => "link-denylist",
+            Self::Caps => "caps",
+            Self::Mentions => "mentions",
+            Self::Emoji => "emoji",
+            Self::Zalgo => "zalgo",
+        }
+    }
+}
+
+impl fmt::Display for AutomodKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AutomodKind {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+            .ok_or_else(|| anyhow::anyhow!("unknown automod rule kind `{value}`"))
+    }
+}
+
+/// What happens to the author of a message an automod rule matched.
+///
+/// The message itself is always deleted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AutomodAction {
+    // Declared from least to most severe so the derived ordering ranks them.
+    Delete,
+    Warn,
+    Timeout,
+    Ban,
+}
+
+impl AutomodAction {
+    /// Stable identifier stored in the `automod_rules.action` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Warn => "warn",
+            Self::Timeout => "timeout",
+            Self::Ban => "ban",
+        }
+    }
+}
+
+impl fmt::Display for AutomodAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AutomodAction {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value {
+            "delete" => Self::Delete,
+            "warn" => Self::Warn,
+            "timeout" => Self::Timeout,
+            "ban" => Self::Ban,
+            other => anyhow::bail!("unknown automod action `{other}`"),
+        })
+    }
+}
+
+/// A stored automod rule.
+#[derive(Clone, Debug)]
+pub struct AutomodRule {
+    pub id: u64,
+    pub guild_id: u64,
+    pub kind: AutomodKind,
+    /// Words, regular expressions or domains, depending on `kind`.
+    pub patterns: Vec<String>,
+    /// Limit used by the caps, mentions, emoji and zalgo kinds.
+    pub threshold: Option<u32>,
+    pub action: AutomodAction,
+    /// Timeout length, or ban length for a temporary ban.
+    pub duration_secs: Option<u64>,
+    pub exempt_role_ids: Vec<u64>,
+    pub exempt_channel_ids: Vec<u64>,
+}
+
+/// An automod rule about to be stored.
+#[derive(Clone, Debug)]
+pub struct NewAutomodRule {
+    pub guild_id: u64,
+    pub kind: AutomodKind,
+    pub patterns: Vec<String>,
+    pub threshold: Option<u32>,
+    pub action: AutomodAction,
+    pub duration_secs: Option<u64>,
+    pub created_by: u64,
+}
+
+/// A role or channel an automod rule ignores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutomodExemption {
+    Role(u64),
+    Channel(u64),
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Kind of moderation action recorded in a case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CaseAction {
+    Ban,
+    Unban,
+    Kick,
+    Timeout,
+    Untimeout,
+    Warn,
+    Purge,
+    Terminate,
+    Lockdown,
+    LiftLockdown,
+    Lock,
+    Unlock,
+    Slowmode,
+    Nuke,
+}
+
+impl CaseAction {
+    /// Stable identifier stored in the `cases.action` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ban => "ban",
+            Self::Unban => "unban",
+            Self::Kick => "kick",
+            Self::Timeout => "timeout",
+            Self::Untimeout => "untimeout",
+            Self::Warn => "warn",
+            Self::Purge => "purge",
+            Self::Terminate => "terminate",
+            Self::Lockdown => "lockdown",
+            Self::LiftLockdown => "lift-lockdown",
+            Self::Lock => "lock",
+            Self::Unlock => "unlock",
+            Self::Slowmode => "slowmode",
+            Self::Nuke => "nuke-channel",
+        }
+    }
+
+    /// Human-readable label for embeds.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ban => "Ban",
+            Self::Unban => "Unban",
+            Self::Kick => "Kick",
+            Self::Timeout => "Timeout",
+            Self::Untimeout => "Timeout removed",
+            Self::Warn => "Warning",
+            Self::Purge => "Purge",
+            Self::Terminate => "Termination",
+            Self::Lockdown => "Lockdown",
+            Self::LiftLockdown => "Lockdown lifted",
+            Self::Lock => "Channel locked",
+            Self::Unlock => "Channel unlocked",
+            Self::Slowmode => "Slowmode",
+            Self::Nuke => "Channel nuked",
+        }
+    }
+}
+
+impl fmt::Display for CaseAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CaseAction {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value {
+            "ban" => Self::Ban,
+            "unban" => Self::Unban,
+            "kick" => Self::Kick,
+            "timeout" => Self::Timeout,
+            "untimeout" => Self::Untimeout,
+            "warn" => Self::Warn,
+            "purge" => Self::Purge,
+            "terminate" => Self::Terminate,
+            "lockdown" => Self::Lockdown,
+            "lift-lockdown" => Self::LiftLockdown,
+            "lock" => Self::Lock,
+            "unlock" => Self::Unlock,
+            "slowmode" => Self::Slowmode,
+            "nuke-channel" => Self::Nuke,
+            other => anyhow::bail!("unknown case action `{other}`"),
+        })
+    }
+}
+
+/// A moderation action about to be recorded.
+#[derive(Clone, Debug)]
+pub struct NewCase<'a> {
+    pub guild_id: u64,
+    pub action: CaseAction,
+    /// `None` for actions without a single target user, such as purges.
+    pub user_id: Option<u64>,
+    pub moderator_id: u64,
+    pub reason: Option<&'a str>,
+    pub duration_secs: Option<u64>,
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CaseEntry {
+    pub guild_id: u64,
+    pub case_number: u64,
+    pub action: CaseAction,
+    pub user_id: Option<u64>,
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+    pub duration_secs: Option<u64>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+}
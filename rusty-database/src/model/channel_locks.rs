@@ -0,0 +1,26 @@
+use crate::model::lockdowns::OverwriteBits;
+
+/// A channel lock about to be stored.
+#[derive(Clone, Debug)]
+pub struct NewChannelLock<'a> {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub moderator_id: u64,
+    pub reason: Option<&'a str>,
+    /// The channel's `@everyone` overwrite beforehand; `None` if it had none.
+    pub previous: Option<OverwriteBits>,
+    /// When the lock lifts itself; `None` keeps it until someone unlocks it.
+    pub expires_at: Option<u64>,
+}
+
+/// A channel locked by hand with `!lock`.
+#[derive(Clone, Debug)]
+pub struct ChannelLock {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+    pub previous: Option<OverwriteBits>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+}
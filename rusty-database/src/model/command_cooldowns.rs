@@ -0,0 +1,10 @@
+/// Uses counted in a cooldown bucket's current window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CooldownWindow {
+    /// Whether this use fit within the limit and was counted.
+    pub allowed: bool,
+    /// Uses counted so far, including this one when it was allowed.
+    pub uses: u32,
+    /// Unix timestamp at which the count resets.
+    pub ends_at: u64,
+}
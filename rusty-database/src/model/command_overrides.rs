@@ -0,0 +1,81 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// What a command override applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverrideTarget {
+    /// The whole guild. A denying guild override disables the command.
+    Guild,
+    Role(u64),
+    User(u64),
+    Channel(u64),
+}
+
+impl OverrideTarget {
+    /// Stable identifier stored in the `command_overrides.target_kind` column.
+    pub fn kind(self) -> &'static str {
+        match self {
+            Self::Guild => "guild",
+            Self::Role(_) => "role",
+            Self::User(_) => "user",
+            Self::Channel(_) => "channel",
+        }
+    }
+
+    /// ID stored in the `command_overrides.target_id` column.
+    ///
+    /// Guild-wide overrides store the guild's own ID.
+    pub fn id(self, guild_id: u64) -> u64 {
+        match self {
+            Self::Guild => guild_id,
+            Self::Role(id) | Self::User(id) | Self::Channel(id) => id,
+        }
+    }
+
+    /// Rebuild a target from its stored kind and ID.
+    pub fn from_parts(kind: &str, id: u64) -> anyhow::Result<Self> {
+        Ok(match kind {
+            "guild" => Self::Guild,
+            "role" => Self::Role(id),
+            "user" => Self::User(id),
+            "channel" => Self::Channel(id),
+            other => anyhow::bail!("unknown command override target `{other}`"),
+        })
+    }
+}
+
+/// Whether an override grants or denies the command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverrideMode {
+    Allow,
+    Deny,
+}
+
+impl fmt::Display for OverrideMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        })
+    }
+}
+
+impl FromStr for OverrideMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value {
+            "allow" => Self::Allow,
+            "deny" => Self::Deny,
+            other => anyhow::bail!("unknown command override mode `{other}`"),
+        })
+    }
+}
+
+/// A guild's decision about who may run a command, and where.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandOverride {
+    pub command: String,
+    pub target: OverrideTarget,
+    pub mode: OverrideMode,
+}
@@ -0,0 +1,11 @@
+/// Gateway session state saved at shutdown so a restart can resume it.
+#[derive(Clone, Debug)]
+pub struct GatewaySession {
+    pub shard_id: u32,
+    pub shard_total: u32,
+    pub session_id: String,
+    /// Last sequence number the shard received.
+    pub sequence: u64,
+    pub resume_url: Option<String>,
+    pub saved_at: u64,
+}
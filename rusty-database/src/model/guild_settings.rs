@@ -0,0 +1,57 @@
+use crate::model::automod::AutomodAction;
+use crate::model::lockdowns::RaidAction;
+use crate::model::modlog::ModLogSettings;
+
+/// Per-guild configuration. `None` means the guild uses the bot's default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuildSettings {
+    /// Message-command prefix.
+    pub prefix: Option<String>,
+    /// Channel every moderation action is published to, if configured.
+    pub modlog_channel_id: Option<u64>,
+    /// Keep replies in the invoking channel short (or ephemeral for slash commands).
+    pub modlog_quiet_replies: bool,
+    /// Timeout length used when `!timeout` is given no duration.
+    pub default_timeout_secs: Option<u64>,
+    /// Look-back window used when `!warnings` is given no period.
+    pub warnings_window_days: Option<u64>,
+    /// Color of moderation embeds, as `0xRRGGBB`.
+    pub embed_color: Option<u32>,
+    /// What happens to members caught spamming; `None` leaves spam detection off.
+    pub spam_action: Option<AutomodAction>,
+    /// Messages one member may send in a row.
+    pub spam_user_rate: Option<SpamLimit>,
+    /// Near-duplicate messages one member may send in a row.
+    pub spam_user_duplicates: Option<SpamLimit>,
+    /// Messages a single channel may receive from everyone combined.
+    pub spam_channel_rate: Option<SpamLimit>,
+    /// Near-duplicate messages a single channel may receive from everyone combined.
+    pub spam_channel_duplicates: Option<SpamLimit>,
+    /// How the bot answers a raid; `None` leaves raid detection off.
+    pub raid_action: Option<RaidAction>,
+    /// Members who may join in a row.
+    pub raid_join_rate: Option<SpamLimit>,
+    /// Members with young accounts who may join in a row.
+    pub raid_young_join_rate: Option<SpamLimit>,
+    /// Accounts younger than this count as young.
+    pub raid_account_age_secs: Option<u64>,
+    /// Channels a lockdown stops `@everyone` from talking in.
+    pub lockdown_channel_ids: Vec<u64>,
+}
+
+/// At most `count` messages, or joins, within `window_secs`. A count of zero
+/// turns the limit off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpamLimit {
+    pub count: u32,
+    pub window_secs: u64,
+}
+
+impl GuildSettings {
+    pub fn modlog(&self) -> ModLogSettings {
+        ModLogSettings {
+            channel_id: self.modlog_channel_id,
+            quiet_replies: self.modlog_quiet_replies,
+        }
+    }
+}
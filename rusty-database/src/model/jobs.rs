@@ -0,0 +1,26 @@
+/// A job to insert into the scheduler queue.
+#[derive(Clone, Copy, Debug)]
+pub struct NewJob<'a> {
+    pub kind: &'a str,
+    /// Serialized payload, decoded by the handler registered for `kind`.
+    pub payload: &'a str,
+    /// Scheduling again with the same key replaces the pending job.
+    pub idempotency_key: Option<&'a str>,
+    pub run_at: u64,
+    pub max_attempts: u32,
+}
+
+/// A job claimed by the scheduler for execution.
+#[derive(Clone, Debug)]
+pub struct JobEntry {
+    pub id: u64,
+    pub kind: String,
+    pub payload: String,
+    pub idempotency_key: Option<String>,
+    pub run_at: u64,
+    /// Attempts made so far, including the one this claim is for.
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Lease set when claimed; completion is ignored if the job was rescheduled meanwhile.
+    pub locked_until: u64,
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// How the bot answers a detected raid. Every response starts a lockdown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RaidAction {
+    /// Lock the server down and leave the new members alone.
+    Lockdown,
+    /// Lock down and kick the members whose joins triggered it.
+    Kick,
+    /// Lock down and time out the members whose joins triggered it.
+    Timeout,
+}
+
+impl RaidAction {
+    /// Stable identifier stored in the `guild_settings.raid_action` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lockdown => "lockdown",
+            Self::Kick => "kick",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+impl fmt::Display for RaidAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RaidAction {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value {
+            "lockdown" => Self::Lockdown,
+            "kick" => Self::Kick,
+            "timeout" => Self::Timeout,
+            other => anyhow::bail!("unknown raid action `{other}`"),
+        })
+    }
+}
+
+/// The allow and deny bits of a permission overwrite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverwriteBits {
+    pub allow: u64,
+    pub deny: u64,
+}
+
+/// A lockdown about to be started.
+#[derive(Clone, Debug)]
+pub struct NewLockdown<'a> {
+    pub guild_id: u64,
+    pub moderator_id: u64,
+    pub reason: Option<&'a str>,
+    /// Level to restore afterwards, or `None` when the lockdown left it alone.
+    pub previous_verification_level: Option<u8>,
+}
+
+/// A guild's active lockdown.
+#[derive(Clone, Debug)]
+pub struct Lockdown {
+    pub guild_id: u64,
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+    pub previous_verification_level: Option<u8>,
+    pub created_at: u64,
+    /// Channels still locked, by channel ID.
+    pub channels: Vec<LockedChannel>,
+}
+
+/// A channel locked by a lockdown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockedChannel {
+    pub channel_id: u64,
+    /// The channel's `@everyone` overwrite beforehand; `None` if it had none.
+    pub previous: Option<OverwriteBits>,
+}
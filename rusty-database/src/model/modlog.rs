@@ -0,0 +1,8 @@
+/// Per-guild mod-log configuration.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModLogSettings {
+    /// Channel every moderation action is published to, if configured.
+    pub channel_id: Option<u64>,
+    /// Keep replies in the invoking channel short (or ephemeral for slash commands).
+    pub quiet_replies: bool,
+}
@@ -0,0 +1,9 @@
+/// A ban that should be lifted automatically once it expires.
+#[derive(Clone, Copy, Debug)]
+pub struct TempbanEntry {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub moderator_id: u64,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Punishment applied when a warning policy triggers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WarnPolicyAction {
+    // Declared from least to most severe so the derived ordering ranks them.
+    Timeout,
+    Kick,
+    Ban,
+}
+
+impl WarnPolicyAction {
+    /// Stable identifier stored in the `warn_policies.action` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::Kick => "kick",
+            Self::Ban => "ban",
+        }
+    }
+}
+
+impl fmt::Display for WarnPolicyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WarnPolicyAction {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value {
+            "timeout" => Self::Timeout,
+            "kick" => Self::Kick,
+            "ban" => Self::Ban,
+            other => anyhow::bail!("unknown warn policy action `{other}`"),
+        })
+    }
+}
+
+/// An escalation rule: `threshold` warnings within `window_secs` trigger `action`.
+#[derive(Clone, Copy, Debug)]
+pub struct WarnPolicy {
+    pub id: u64,
+    pub guild_id: u64,
+    pub threshold: u32,
+    pub window_secs: u64,
+    pub action: WarnPolicyAction,
+    /// Timeout length, or ban length for temporary bans.
+    pub duration_secs: Option<u64>,
+}
+
+/// A warning policy about to be stored.
+#[derive(Clone, Copy, Debug)]
+pub struct NewWarnPolicy {
+    pub guild_id: u64,
+    pub threshold: u32,
+    pub window_secs: u64,
+    pub action: WarnPolicyAction,
+    pub duration_secs: Option<u64>,
+    pub created_by: u64,
+}
@@ -1,5 +1,10 @@
+use std::fmt;
+
 #[derive(Clone, Debug)]
 pub struct WarningEntry {
+    /// Stable ID shown to moderators; unaffected by deletions.
+    pub id: u64,
+    pub user_id: u64,
     pub warned_at: u64,
     pub moderator_id: u64,
     pub reason: String,
@@ -7,5 +12,39 @@ pub struct WarningEntry {
 
 #[derive(Clone, Copy, Debug)]
 pub struct WarningRecord {
-    pub warn_number: usize,
+    pub id: u64,
+    /// Warnings that still count against the user, including this one.
+    pub active_count: usize,
+}
+
+/// Kind of change recorded in the warning audit trail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarningChange {
+    Edit,
+    Delete,
+    Clear,
+}
+
+impl WarningChange {
+    /// Stable identifier stored in the `warning_revisions.change` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Edit => "edit",
+            Self::Delete => "delete",
+            Self::Clear => "clear",
+        }
+    }
+}
+
+impl fmt::Display for WarningChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Per-guild warning configuration.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WarningSettings {
+    /// Age after which warnings stop counting; `None` keeps them forever.
+    pub expiry_secs: Option<u64>,
 }
@@ -1,15 +1,19 @@
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, sleep};
 use tracing::error;
 use twilight_http::Client;
 use twilight_model::{
     channel::ChannelType,
     id::{
         Id,
-        marker::{GuildMarker, MessageMarker, UserMarker},
+        marker::{ChannelMarker, GuildMarker, MessageMarker, UserMarker},
     },
 };
-use tokio::time::{Duration, sleep};
 
+use crate::http_error::is_not_found;
 use crate::time::now_unix_secs;
+use rusty_core::Context;
+use rusty_core::jobs::{JobPayload, schedule_job};
 
 const BULK_DELETE_MAX_AGE_SECS: u64 = 14 * 24 * 60 * 60;
 const BULK_DELETE_SAFETY_BUFFER_SECS: u64 = 60 * 60;
@@ -26,9 +30,6 @@ pub async fn purge_user_globally(
 ) -> anyhow::Result<u64> {
     let channels = http.guild_channels(guild_id).await?.model().await?;
     let mut deleted_count = 0_u64;
-    let bulk_delete_cutoff = now_unix_secs()
-        .saturating_sub(BULK_DELETE_MAX_AGE_SECS.saturating_sub(BULK_DELETE_SAFETY_BUFFER_SECS))
-        as i64;
 
     for channel in channels {
         if !matches!(
@@ -78,56 +79,17 @@ pub async fn purge_user_globally(
                     .unwrap_or(false)
             });
 
-            let mut bulk_candidate_ids: Vec<Id<MessageMarker>> = Vec::new();
-            let mut single_delete_ids: Vec<Id<MessageMarker>> = Vec::new();
-
-            for message in messages {
-                if message.author.id != target_user_id {
-                    continue;
-                }
-
-                if let Some(cutoff) = cutoff_secs
-                    && message.timestamp.as_secs() < cutoff as i64
-                {
-                    continue;
-                }
-
-                if message.timestamp.as_secs() >= bulk_delete_cutoff {
-                    bulk_candidate_ids.push(message.id);
-                } else {
-                    single_delete_ids.push(message.id);
-                }
-            }
-
-            if !bulk_candidate_ids.is_empty() {
-                for chunk in bulk_candidate_ids.chunks(100) {
-                    if chunk.len() < 2 {
-                        single_delete_ids.extend_from_slice(chunk);
-                        continue;
-                    }
-
-                    match http.delete_messages(channel_id, chunk).await {
-                        Ok(_) => {
-                            deleted_count = deleted_count.saturating_add(chunk.len() as u64);
-                        }
-                        Err(source) => {
-                            error!(
-                                ?source,
-                                channel_id = channel_id.get(),
-                                count = chunk.len(),
-                                "bulk delete failed, falling back to single delete"
-                            );
-                            single_delete_ids.extend_from_slice(chunk);
-                        }
-                    }
-                }
-            }
+            let targets: Vec<(Id<MessageMarker>, i64)> = messages
+                .iter()
+                .filter(|message| message.author.id == target_user_id)
+                .filter(|message| {
+                    cutoff_secs.is_none_or(|cutoff| message.timestamp.as_secs() >= cutoff as i64)
+                })
+                .map(|message| (message.id, message.timestamp.as_secs()))
+                .collect();
 
-            for message_id in single_delete_ids {
-                if http.delete_message(channel_id, message_id).await.is_ok() {
-                    deleted_count = deleted_count.saturating_add(1);
-                }
-            }
+            let outcome = delete_channel_messages(http, channel_id, &targets).await;
+            deleted_count = deleted_count.saturating_add(outcome.deleted);
 
             if should_break_for_cutoff {
                 break;
@@ -139,3 +101,123 @@ pub async fn purge_user_globally(
 
     Ok(deleted_count)
 }
+
+/// How a batch of deletions went.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeleteOutcome {
+    pub deleted: u64,
+    pub failed: u64,
+}
+
+/// The oldest creation time (unix seconds) a message can have and still be
+/// bulk-deleted, with a safety buffer so requests don't race the limit.
+pub fn bulk_delete_cutoff() -> i64 {
+    now_unix_secs()
+        .saturating_sub(BULK_DELETE_MAX_AGE_SECS.saturating_sub(BULK_DELETE_SAFETY_BUFFER_SECS))
+        as i64
+}
+
+/// Delete messages from one channel, given with their creation times.
+///
+/// Recent messages are bulk-deleted in chunks of 100. Older ones, a lone
+/// leftover and any chunk Discord rejects are deleted one at a time.
+pub async fn delete_channel_messages(
+    http: &Client,
+    channel_id: Id<ChannelMarker>,
+    messages: &[(Id<MessageMarker>, i64)],
+) -> DeleteOutcome {
+    let bulk_delete_cutoff = bulk_delete_cutoff();
+    let mut outcome = DeleteOutcome::default();
+
+    let mut bulk_candidate_ids: Vec<Id<MessageMarker>> = Vec::new();
+    let mut single_delete_ids: Vec<Id<MessageMarker>> = Vec::new();
+    for &(message_id, created_at) in messages {
+        if created_at >= bulk_delete_cutoff {
+            bulk_candidate_ids.push(message_id);
+        } else {
+            single_delete_ids.push(message_id);
+        }
+    }
+
+    for chunk in bulk_candidate_ids.chunks(100) {
+        if chunk.len() < 2 {
+            single_delete_ids.extend_from_slice(chunk);
+            continue;
+        }
+
+        match http.delete_messages(channel_id, chunk).await {
+            Ok(_) => {
+                outcome.deleted = outcome.deleted.saturating_add(chunk.len() as u64);
+            }
+            Err(source) => {
+                error!(
+                    ?source,
+                    channel_id = channel_id.get(),
+                    count = chunk.len(),
+                    "bulk delete failed, falling back to single delete"
+                );
+                single_delete_ids.extend_from_slice(chunk);
+            }
+        }
+    }
+
+    for message_id in single_delete_ids {
+        match http.delete_message(channel_id, message_id).await {
+            Ok(_) => outcome.deleted = outcome.deleted.saturating_add(1),
+            Err(_) => outcome.failed = outcome.failed.saturating_add(1),
+        }
+    }
+
+    outcome
+}
+
+/// Job payload that deletes a single message, such as a short-lived confirmation.
+#[derive(Serialize, Deserialize)]
+pub struct DeleteMessage {
+    pub channel_id: u64,
+    pub message_id: u64,
+}
+
+impl JobPayload for DeleteMessage {
+    const KIND: &'static str = "message.delete";
+    const MAX_ATTEMPTS: u32 = 3;
+}
+
+/// Schedule a message for deletion after `delay_secs`.
+pub async fn schedule_message_delete(
+    ctx: &Context,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+    delay_secs: u64,
+) -> anyhow::Result<()> {
+    let payload = DeleteMessage {
+        channel_id: channel_id.get(),
+        message_id: message_id.get(),
+    };
+    let key = format!("message-delete:{}", message_id.get());
+
+    schedule_job(
+        &ctx.db,
+        &payload,
+        now_unix_secs().saturating_add(delay_secs),
+        Some(&key),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Job handler for [`DeleteMessage`].
+pub async fn run_delete_message(ctx: Context, job: DeleteMessage) -> anyhow::Result<()> {
+    let (Some(channel_id), Some(message_id)) = (
+        Id::<ChannelMarker>::new_checked(job.channel_id),
+        Id::<MessageMarker>::new_checked(job.message_id),
+    ) else {
+        return Ok(());
+    };
+
+    match ctx.http.delete_message(channel_id, message_id).await {
+        Ok(_) => Ok(()),
+        Err(source) if is_not_found(&source) => Ok(()),
+        Err(source) => Err(source.into()),
+    }
+}
@@ -0,0 +1,9 @@
+use twilight_http::{Error, error::ErrorType, response::StatusCode};
+
+/// Whether a Discord request failed because the target no longer exists.
+pub fn is_not_found(source: &Error) -> bool {
+    matches!(
+        source.kind(),
+        ErrorType::Response { status, .. } if *status == StatusCode::NOT_FOUND
+    )
+}
@@ -1,5 +1,6 @@
 use twilight_http::Client;
 use twilight_model::{
+    application::command::{CommandOptionChoice, CommandOptionChoiceValue},
     channel::message::{
         component::{ActionRow, Button, ButtonStyle, Component},
         embed::Embed,
@@ -186,6 +187,40 @@ pub async fn respond_ephemeral_notice(
     respond_ephemeral_message(http, interaction, content).await
 }
 
+/// Maximum number of suggestions Discord accepts in an autocomplete response.
+pub const AUTOCOMPLETE_MAX_CHOICES: usize = 25;
+
+/// Respond to an autocomplete interaction with string suggestions.
+pub async fn respond_autocomplete_choices(
+    http: &Client,
+    interaction: &InteractionCreate,
+    choices: &[String],
+) -> anyhow::Result<()> {
+    let choices = choices
+        .iter()
+        .take(AUTOCOMPLETE_MAX_CHOICES)
+        .map(|choice| CommandOptionChoice {
+            name: choice.clone(),
+            name_localizations: None,
+            value: CommandOptionChoiceValue::String(choice.clone()),
+        });
+
+    let response = InteractionResponse {
+        kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .choices(choices)
+                .build(),
+        ),
+    };
+
+    http.interaction(interaction.application_id)
+        .create_response(interaction.id, &interaction.token, &response)
+        .await?;
+
+    Ok(())
+}
+
 pub async fn defer_component_update(
     http: &Client,
     interaction: &InteractionCreate,
@@ -0,0 +1,273 @@
+//! Source-agnostic command invocation and reply helpers.
+//!
+//! Commands can be triggered either by a prefixed message or by an
+//! application (slash) command. [`Invocation`] hides that difference so the
+//! same business logic can answer both.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use twilight_http::Client;
+use twilight_model::{
+    channel::{
+        Message,
+        message::{MessageFlags, component::Component, embed::Embed},
+    },
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+    guild::Permissions,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+    },
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// Where a command invocation came from.
+pub enum InvocationSource {
+    /// A prefixed text command.
+    Message(Box<MessageCreate>),
+    /// An application (slash) command interaction.
+    Interaction(Box<InteractionCreate>),
+}
+
+/// A single command invocation, independent of its trigger.
+pub struct Invocation {
+    source: InvocationSource,
+    guild_id: Option<Id<GuildMarker>>,
+    channel_id: Id<ChannelMarker>,
+    author_id: Id<UserMarker>,
+    responded: AtomicBool,
+}
+
+impl Invocation {
+    /// Build an invocation from a prefixed message command.
+    pub fn from_message(msg: Box<MessageCreate>) -> Self {
+        Self {
+            guild_id: msg.guild_id,
+            channel_id: msg.channel_id,
+            author_id: msg.author.id,
+            source: InvocationSource::Message(msg),
+            responded: AtomicBool::new(false),
+        }
+    }
+
+    /// Build an invocation from an application command interaction.
+    ///
+    /// Returns `None` when the interaction has no channel or author.
+    pub fn from_interaction(interaction: Box<InteractionCreate>) -> Option<Self> {
+        let channel_id = interaction.channel.as_ref()?.id;
+        let author_id = interaction.author_id()?;
+
+        Some(Self {
+            guild_id: interaction.guild_id,
+            channel_id,
+            author_id,
+            source: InvocationSource::Interaction(interaction),
+            responded: AtomicBool::new(false),
+        })
+    }
+
+    pub fn source(&self) -> &InvocationSource {
+        &self.source
+    }
+
+    pub fn guild_id(&self) -> Option<Id<GuildMarker>> {
+        self.guild_id
+    }
+
+    pub fn channel_id(&self) -> Id<ChannelMarker> {
+        self.channel_id
+    }
+
+    pub fn author_id(&self) -> Id<UserMarker> {
+        self.author_id
+    }
+
+    /// Whether this invocation is an application command interaction.
+    pub fn is_interaction(&self) -> bool {
+        matches!(self.source, InvocationSource::Interaction(_))
+    }
+
+    /// Permissions Discord resolved for the invoking member, when provided.
+    pub fn member_permissions(&self) -> Option<Permissions> {
+        match &self.source {
+            InvocationSource::Message(msg) => msg.member.as_ref().and_then(|m| m.permissions),
+            InvocationSource::Interaction(interaction) => interaction
+                .member
+                .as_ref()
+                .and_then(|member| member.permissions),
+        }
+    }
+
+    /// Acknowledge an interaction that will take longer than Discord waits.
+    ///
+    /// The invoker sees a loading state until the next reply replaces it, and
+    /// that reply is only visible to them when `ephemeral` is set. Message
+    /// commands have nothing to acknowledge.
+    pub async fn defer(&self, http: &Client, ephemeral: bool) -> anyhow::Result<()> {
+        let InvocationSource::Interaction(interaction) = &self.source else {
+            return Ok(());
+        };
+        if self.responded.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let data = ephemeral.then(|| {
+            InteractionResponseDataBuilder::new()
+                .flags(MessageFlags::EPHEMERAL)
+                .build()
+        });
+        let response = InteractionResponse {
+            kind: InteractionResponseType::DeferredChannelMessageWithSource,
+            data,
+        };
+        http.interaction(interaction.application_id)
+            .create_response(interaction.id, &interaction.token, &response)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Reply with plain text content.
+    pub async fn reply_content(&self, http: &Client, content: &str) -> anyhow::Result<()> {
+        self.reply(http, Some(content), &[], &[], false).await
+    }
+
+    /// Reply with one or more embeds.
+    pub async fn reply_embeds(&self, http: &Client, embeds: &[Embed]) -> anyhow::Result<()> {
+        self.reply(http, None, embeds, &[], false).await
+    }
+
+    /// Reply with text visible only to the invoker.
+    ///
+    /// Message commands cannot send ephemeral replies, so they fall back to a
+    /// normal channel message.
+    pub async fn reply_ephemeral(&self, http: &Client, content: &str) -> anyhow::Result<()> {
+        self.reply(http, Some(content), &[], &[], true).await
+    }
+
+    /// Reply with embeds visible only to the invoker.
+    ///
+    /// Message commands fall back to a normal channel message.
+    pub async fn reply_ephemeral_embeds(
+        &self,
+        http: &Client,
+        embeds: &[Embed],
+    ) -> anyhow::Result<()> {
+        self.reply(http, None, embeds, &[], true).await
+    }
+
+    /// Reply with content, embeds and components, returning the created message.
+    pub async fn reply_message(
+        &self,
+        http: &Client,
+        content: Option<&str>,
+        embeds: &[Embed],
+        components: &[Component],
+    ) -> anyhow::Result<Message> {
+        match &self.source {
+            InvocationSource::Message(_) => {
+                let mut request = http
+                    .create_message(self.channel_id)
+                    .embeds(embeds)
+                    .components(components);
+                if let Some(content) = content {
+                    request = request.content(content);
+                }
+
+                Ok(request.await?.model().await?)
+            }
+            InvocationSource::Interaction(interaction) => {
+                let client = http.interaction(interaction.application_id);
+                if self.responded.swap(true, Ordering::SeqCst) {
+                    let mut request = client
+                        .create_followup(&interaction.token)
+                        .embeds(embeds)
+                        .components(components);
+                    if let Some(content) = content {
+                        request = request.content(content);
+                    }
+
+                    return Ok(request.await?.model().await?);
+                }
+
+                let response = channel_message_response(content, embeds, components, false);
+                client
+                    .create_response(interaction.id, &interaction.token, &response)
+                    .await?;
+
+                Ok(client.response(&interaction.token).await?.model().await?)
+            }
+        }
+    }
+
+    async fn reply(
+        &self,
+        http: &Client,
+        content: Option<&str>,
+        embeds: &[Embed],
+        components: &[Component],
+        ephemeral: bool,
+    ) -> anyhow::Result<()> {
+        match &self.source {
+            InvocationSource::Message(_) => {
+                let mut request = http
+                    .create_message(self.channel_id)
+                    .embeds(embeds)
+                    .components(components);
+                if let Some(content) = content {
+                    request = request.content(content);
+                }
+
+                request.await?;
+            }
+            InvocationSource::Interaction(interaction) => {
+                let client = http.interaction(interaction.application_id);
+                if self.responded.swap(true, Ordering::SeqCst) {
+                    let mut request = client
+                        .create_followup(&interaction.token)
+                        .embeds(embeds)
+                        .components(components);
+                    if let Some(content) = content {
+                        request = request.content(content);
+                    }
+                    if ephemeral {
+                        request = request.flags(MessageFlags::EPHEMERAL);
+                    }
+
+                    request.await?;
+                    return Ok(());
+                }
+
+                let response = channel_message_response(content, embeds, components, ephemeral);
+                client
+                    .create_response(interaction.id, &interaction.token, &response)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn channel_message_response(
+    content: Option<&str>,
+    embeds: &[Embed],
+    components: &[Component],
+    ephemeral: bool,
+) -> InteractionResponse {
+    let mut data = InteractionResponseDataBuilder::new()
+        .embeds(embeds.to_vec())
+        .components(components.to_vec());
+    if let Some(content) = content {
+        data = data.content(content);
+    }
+    if ephemeral {
+        data = data.flags(MessageFlags::EPHEMERAL);
+    }
+
+    InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(data.build()),
+    }
+}
@@ -2,10 +2,16 @@
 pub mod cleanup;
 /// Generic embed builders shared across commands.
 pub mod embed;
+/// Discord HTTP error inspection helpers.
+pub mod http_error;
 /// Generic interaction helpers for component-confirmation flows.
 pub mod interaction;
-/// Single source of truth for the message-command prefix.
-pub const COMMAND_PREFIX: char = '!';
+/// Source-agnostic command invocation and reply helpers.
+pub mod invocation;
+/// Message-command prefix for guilds that have not configured their own.
+pub const DEFAULT_COMMAND_PREFIX: &str = "!";
+/// Application command option accessors.
+pub mod options;
 /// Shared pagination helpers and interaction utilities.
 pub mod pagination;
 /// Pure parser helpers.
@@ -0,0 +1,72 @@
+//! Typed accessors for application command options.
+
+use twilight_model::{
+    application::interaction::application_command::{CommandData, CommandOptionValue},
+    id::{
+        Id,
+        marker::{ChannelMarker, RoleMarker, UserMarker},
+    },
+};
+
+fn option_value<'a>(data: &'a CommandData, name: &str) -> Option<&'a CommandOptionValue> {
+    data.options
+        .iter()
+        .find(|option| option.name == name)
+        .map(|option| &option.value)
+}
+
+/// Read a string option by name.
+pub fn option_string<'a>(data: &'a CommandData, name: &str) -> Option<&'a str> {
+    match option_value(data, name)? {
+        CommandOptionValue::String(value) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+/// Read an integer option by name.
+pub fn option_integer(data: &CommandData, name: &str) -> Option<i64> {
+    match option_value(data, name)? {
+        CommandOptionValue::Integer(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Read a boolean option by name.
+pub fn option_bool(data: &CommandData, name: &str) -> Option<bool> {
+    match option_value(data, name)? {
+        CommandOptionValue::Boolean(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Read a user option by name.
+pub fn option_user(data: &CommandData, name: &str) -> Option<Id<UserMarker>> {
+    match option_value(data, name)? {
+        CommandOptionValue::User(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Read a role option by name.
+pub fn option_role(data: &CommandData, name: &str) -> Option<Id<RoleMarker>> {
+    match option_value(data, name)? {
+        CommandOptionValue::Role(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Read a channel option by name.
+pub fn option_channel(data: &CommandData, name: &str) -> Option<Id<ChannelMarker>> {
+    match option_value(data, name)? {
+        CommandOptionValue::Channel(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Return the partially typed value of the focused autocomplete option.
+pub fn focused_option(data: &CommandData) -> Option<(&str, &str)> {
+    data.options.iter().find_map(|option| match &option.value {
+        CommandOptionValue::Focused(value, _) => Some((option.name.as_str(), value.as_str())),
+        _ => None,
+    })
+}
@@ -1,11 +1,7 @@
 //! Shared interaction response helpers for pagination flows.
 
-use std::{
-    collections::HashMap,
-    sync::{Arc, OnceLock},
-    time::Duration,
-};
-
+use serde::{Deserialize, Serialize};
+use tracing::error;
 use twilight_http::Client;
 use twilight_model::{
     channel::message::{
@@ -23,13 +19,11 @@ use twilight_model::{
 use twilight_util::builder::InteractionResponseDataBuilder;
 
 use super::token::{PaginationToken, build_modal_custom_id};
-
-type CleanupTaskMap = HashMap<u64, tokio::task::JoinHandle<()>>;
-
-fn cleanup_tasks() -> &'static tokio::sync::Mutex<CleanupTaskMap> {
-    static TASKS: OnceLock<tokio::sync::Mutex<CleanupTaskMap>> = OnceLock::new();
-    TASKS.get_or_init(|| tokio::sync::Mutex::new(HashMap::new()))
-}
+use crate::http_error::is_not_found;
+use crate::invocation::Invocation;
+use crate::time::now_unix_secs;
+use rusty_core::Context;
+use rusty_core::jobs::{JobPayload, run_jobs_now, schedule_job};
 
 /// Generic message shown when a pagination interaction belongs to another user.
 pub const PAGINATION_WRONG_USER_MESSAGE: &str = "This pagination session belongs to another user.";
@@ -185,14 +179,15 @@ pub async fn open_jump_modal_from_token(
 
 /// Send a new paginated message and schedule component cleanup when needed.
 pub async fn send_paginated_message(
-    http: Arc<Client>,
+    ctx: &Context,
     channel_id: Id<ChannelMarker>,
     embed: Embed,
     components: Vec<Component>,
     total_pages: usize,
     timeout_secs: u64,
 ) -> anyhow::Result<()> {
-    let created_message = http
+    let created_message = ctx
+        .http
         .create_message(channel_id)
         .embeds(&[embed])
         .components(&components)
@@ -202,7 +197,33 @@ pub async fn send_paginated_message(
 
     if total_pages > 1 {
         schedule_component_cleanup(
-            Arc::clone(&http),
+            ctx,
+            created_message.channel_id,
+            created_message.id,
+            timeout_secs,
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+/// Reply to a command invocation with a paginated message and schedule cleanup.
+pub async fn send_paginated_reply(
+    ctx: &Context,
+    invocation: &Invocation,
+    embed: Embed,
+    components: Vec<Component>,
+    total_pages: usize,
+    timeout_secs: u64,
+) -> anyhow::Result<()> {
+    let created_message = invocation
+        .reply_message(&ctx.http, None, &[embed], &components)
+        .await?;
+
+    if total_pages > 1 {
+        schedule_component_cleanup(
+            ctx,
             created_message.channel_id,
             created_message.id,
             timeout_secs,
@@ -215,57 +236,94 @@ pub async fn send_paginated_message(
 
 /// Update an existing paginated interaction message and refresh cleanup timing.
 pub async fn update_paginated_interaction_message(
-    http: Arc<Client>,
+    ctx: &Context,
     interaction: &InteractionCreate,
     embed: Embed,
     components: Vec<Component>,
     total_pages: usize,
     timeout_secs: u64,
 ) -> anyhow::Result<()> {
-    respond_update_message(&http, interaction, &[embed], &components).await?;
+    respond_update_message(&ctx.http, interaction, &[embed], &components).await?;
 
     if total_pages > 1
         && let Some(message) = interaction.message.as_ref()
     {
-        schedule_component_cleanup(
-            Arc::clone(&http),
-            message.channel_id,
-            message.id,
-            timeout_secs,
-        )
-        .await;
+        schedule_component_cleanup(ctx, message.channel_id, message.id, timeout_secs).await;
     }
 
     Ok(())
 }
 
+/// Job payload that strips the buttons from an expired pagination message.
+#[derive(Serialize, Deserialize)]
+pub struct ComponentCleanup {
+    pub channel_id: u64,
+    pub message_id: u64,
+}
+
+impl JobPayload for ComponentCleanup {
+    const KIND: &'static str = "pagination.cleanup";
+    const MAX_ATTEMPTS: u32 = 3;
+}
+
 /// Schedule removal of interactive components shortly before pagination timeout.
+///
+/// Rescheduling for the same message replaces the earlier cleanup. Failures are
+/// only logged; the buttons then simply outlive the session.
 pub async fn schedule_component_cleanup(
-    http: Arc<Client>,
+    ctx: &Context,
     channel_id: Id<ChannelMarker>,
     message_id: Id<MessageMarker>,
     timeout_secs: u64,
 ) {
-    let delay_secs = timeout_secs.saturating_sub(1);
-    let message_key = message_id.get();
+    let run_at = now_unix_secs().saturating_add(timeout_secs.saturating_sub(1));
+    let key = format!("{}{}", cleanup_key_prefix(ctx), message_id.get());
+    let payload = ComponentCleanup {
+        channel_id: channel_id.get(),
+        message_id: message_id.get(),
+    };
 
-    let mut tasks = cleanup_tasks().lock().await;
-    if let Some(existing_task) = tasks.remove(&message_key) {
-        existing_task.abort();
+    if let Err(source) = schedule_job(&ctx.db, &payload, run_at, Some(&key)).await {
+        error!(
+            ?source,
+            message_id = message_id.get(),
+            "failed to schedule pagination cleanup"
+        );
     }
+}
 
-    let cleanup_task = tokio::spawn(async move {
-        tokio::time::sleep(Duration::from_secs(delay_secs)).await;
+/// Strip the buttons from every paginated message this process still serves.
+///
+/// Called on shutdown so nobody is left clicking controls that will not
+/// answer. Paginators owned by other processes sharing the database are left
+/// alone. Returns how many messages were cleaned up.
+pub async fn cleanup_pending_components(ctx: &Context) -> anyhow::Result<usize> {
+    run_jobs_now(ctx, &cleanup_key_prefix(ctx), run_component_cleanup).await
+}
 
-        let empty_components: [Component; 0] = [];
-        let _ = http
-            .update_message(channel_id, message_id)
-            .components(Some(&empty_components))
-            .await;
+fn cleanup_key_prefix(ctx: &Context) -> String {
+    format!("pagination-cleanup:{}:", ctx.instance)
+}
 
-        let mut tasks = cleanup_tasks().lock().await;
-        tasks.remove(&message_key);
-    });
+/// Job handler for [`ComponentCleanup`].
+pub async fn run_component_cleanup(ctx: Context, job: ComponentCleanup) -> anyhow::Result<()> {
+    let (Some(channel_id), Some(message_id)) = (
+        Id::<ChannelMarker>::new_checked(job.channel_id),
+        Id::<MessageMarker>::new_checked(job.message_id),
+    ) else {
+        return Ok(());
+    };
 
-    tasks.insert(message_key, cleanup_task);
+    let empty_components: [Component; 0] = [];
+    match ctx
+        .http
+        .update_message(channel_id, message_id)
+        .components(Some(&empty_components))
+        .await
+    {
+        Ok(_) => Ok(()),
+        // The message was deleted before its session expired.
+        Err(source) if is_not_found(&source) => Ok(()),
+        Err(source) => Err(source.into()),
+    }
 }
@@ -158,3 +158,26 @@ pub fn parse_modal_custom_id(custom_id: &str) -> Option<PaginationModalToken> {
 pub fn is_expired(expires_at: u64) -> bool {
     now_unix_secs() > expires_at
 }
+
+/// Encode a number compactly for command keys, which share the 100-character
+/// custom ID limit with the rest of the pagination token.
+pub fn to_base36(mut value: u64) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    let mut digits = Vec::new();
+    loop {
+        digits.push(DIGITS[(value % 36) as usize]);
+        value /= 36;
+        if value == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+
+    String::from_utf8(digits).unwrap_or_default()
+}
+
+/// Decode a number written by [`to_base36`].
+pub fn from_base36(raw: &str) -> Option<u64> {
+    u64::from_str_radix(raw, 36).ok()
+}
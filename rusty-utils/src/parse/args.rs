@@ -0,0 +1,752 @@
+//! Commands describe their arguments by pulling typed values off an
+//! [`ArgParser`] in declaration order. Input is tokenized once: whitespace
+//! separates arguments, double quotes group them (also as in `key:"a b"`),
+//! and `--name value` switches may appear anywhere. Every failure carries the command usage
+//! string so replies can name the offending argument.
+
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::RangeInclusive;
+use std::str::CharIndices;
+
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, RoleMarker, UserMarker},
+};
+
+use super::{
+    MessageLink, parse_channel_id, parse_duration_seconds, parse_message_link, parse_role_id,
+    parse_target_user_id,
+};
+
+/// A value that can be extracted from a single argument token.
+pub trait FromArg: Sized {
+    /// What the argument should look like, phrased to follow "expected".
+    const EXPECTED: &'static str;
+
+    fn from_arg(raw: &str) -> Option<Self>;
+}
+
+impl FromArg for String {
+    const EXPECTED: &'static str = "a value";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        Some(raw.to_owned())
+    }
+}
+
+impl FromArg for i64 {
+    const EXPECTED: &'static str = "a whole number";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        raw.parse().ok()
+    }
+}
+
+impl FromArg for bool {
+    const EXPECTED: &'static str = "`on` or `off`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_str() {
+            "on" | "true" | "yes" | "enable" | "enabled" => Some(true),
+            "off" | "false" | "no" | "disable" | "disabled" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl FromArg for Id<UserMarker> {
+    const EXPECTED: &'static str = "a user mention or ID";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        parse_target_user_id(raw)
+    }
+}
+
+impl FromArg for Id<RoleMarker> {
+    const EXPECTED: &'static str = "a role mention or ID";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        parse_role_id(raw)
+    }
+}
+
+impl FromArg for Id<ChannelMarker> {
+    const EXPECTED: &'static str = "a channel mention or ID";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        parse_channel_id(raw)
+    }
+}
+
+impl FromArg for MessageLink {
+    const EXPECTED: &'static str = "a message link";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        parse_message_link(raw)
+    }
+}
+
+/// A user argument that the command resolves against the current guild.
+///
+/// Parsing only checks the mention or ID syntax; membership is verified by
+/// whatever the command does with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemberArg(pub Id<UserMarker>);
+
+impl FromArg for MemberArg {
+    const EXPECTED: &'static str = "a member mention or ID";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        parse_target_user_id(raw).map(Self)
+    }
+}
+
+/// A compact duration (`30s`, `10m`, `2h`, `1d`) and the text it was written as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedDuration {
+    pub secs: u64,
+    pub label: String,
+}
+
+impl FromArg for ParsedDuration {
+    const EXPECTED: &'static str = "a duration like `30s`, `10m`, `2h` or `1d`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        parse_duration_seconds(raw).map(|secs| Self {
+            secs,
+            label: raw.trim().to_owned(),
+        })
+    }
+}
+
+/// A [`ParsedDuration`] that must be written with a unit, so a bare number
+/// such as the `3` in `3 strikes` is not read as seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitDuration(pub ParsedDuration);
+
+impl FromArg for UnitDuration {
+    const EXPECTED: &'static str = "a duration with a unit like `30s`, `10m`, `2h` or `1d`";
+
+    fn from_arg(raw: &str) -> Option<Self> {
+        if !raw.trim().ends_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        ParsedDuration::from_arg(raw).map(Self)
+    }
+}
+
+/// Why an argument could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgErrorKind {
+    Missing,
+    Invalid {
+        value: String,
+        expected: &'static str,
+    },
+    OutOfRange {
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+    MissingFlagValue,
+    Unexpected {
+        value: String,
+    },
+}
+
+/// An argument parsing failure, rendered as a user-facing usage hint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgError {
+    name: String,
+    kind: ArgErrorKind,
+    usage: &'static str,
+}
+
+impl ArgError {
+    pub fn new(usage: &'static str, name: impl Into<String>, kind: ArgErrorKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            usage,
+        }
+    }
+
+    /// A required argument was not supplied.
+    pub fn missing(usage: &'static str, name: &str) -> Self {
+        Self::new(usage, name, ArgErrorKind::Missing)
+    }
+
+    /// An argument was supplied but does not parse as `T`.
+    pub fn invalid<T: FromArg>(usage: &'static str, name: &str, value: &str) -> Self {
+        Self::new(
+            usage,
+            name,
+            ArgErrorKind::Invalid {
+                value: value.to_owned(),
+                expected: T::EXPECTED,
+            },
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &ArgErrorKind {
+        &self.kind
+    }
+
+    pub fn usage(&self) -> &'static str {
+        self.usage
+    }
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let placeholder = usage_placeholder(self.usage, &self.name);
+
+        match &self.kind {
+            ArgErrorKind::Missing => write!(f, "Missing {placeholder}.")?,
+            ArgErrorKind::Invalid { value, expected } => write!(
+                f,
+                "Invalid {placeholder} `{}`: expected {expected}.",
+                sanitize(value)
+            )?,
+            ArgErrorKind::OutOfRange { value, min, max } => write!(
+                f,
+                "Invalid {placeholder} `{value}`: must be between {min} and {max}."
+            )?,
+            ArgErrorKind::MissingFlagValue => write!(f, "Flag `--{}` needs a value.", self.name)?,
+            ArgErrorKind::Unexpected { value } => {
+                write!(f, "Unexpected argument `{}`.", sanitize(value))?
+            }
+        }
+
+        write!(f, "\nUsage: `{}`", self.usage)
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+/// Find how `name` is written in the usage string, e.g. `<user>` or `[days|all]`.
+fn usage_placeholder(usage: &str, name: &str) -> String {
+    let mut rest = usage;
+    while let Some(open) = rest.find(['<', '[']) {
+        let close_char = if rest[open..].starts_with('<') {
+            '>'
+        } else {
+            ']'
+        };
+        let Some(close) = rest[open..].find(close_char) else {
+            break;
+        };
+
+        let segment = &rest[open..open + close + 1];
+        let inner = &segment[1..segment.len() - 1];
+        let matches = inner
+            .split(['|', ' '])
+            .map(|part| part.trim_start_matches("--").trim_end_matches("..."))
+            .any(|part| part.eq_ignore_ascii_case(name));
+        if matches {
+            return format!("`{segment}`");
+        }
+
+        rest = &rest[open + close + 1..];
+    }
+
+    format!("`{name}`")
+}
+
+/// Keep echoed input from breaking out of inline code formatting.
+fn sanitize(value: &str) -> String {
+    const MAX_ECHO_CHARS: usize = 50;
+
+    let cleaned: String = value
+        .replace('`', "'")
+        .chars()
+        .take(MAX_ECHO_CHARS)
+        .collect();
+    if value.chars().count() > MAX_ECHO_CHARS {
+        format!("{cleaned}…")
+    } else {
+        cleaned
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    /// Position in the original token stream, used to rebuild rest-of-line text.
+    index: usize,
+    value: String,
+    start: usize,
+    end: usize,
+    quoted: bool,
+}
+
+impl Token {
+    fn flag_name(&self) -> Option<&str> {
+        if self.quoted {
+            return None;
+        }
+
+        self.value
+            .strip_prefix("--")
+            .filter(|name| !name.is_empty())
+    }
+}
+
+fn tokenize(raw: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some(&(start, ch)) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        // A quote that is never closed groups nothing and stays part of the text
+        if ch == '"' {
+            let mut lookahead = chars.clone();
+            lookahead.next();
+            if let Some((value, end)) = read_quoted(&mut lookahead) {
+                chars = lookahead;
+                tokens.push(Token {
+                    index: tokens.len(),
+                    value,
+                    start,
+                    end,
+                    quoted: true,
+                });
+                continue;
+            }
+        }
+
+        let mut value = String::new();
+        let mut end = raw.len();
+        while let Some(&(position, ch)) = chars.peek() {
+            if ch.is_whitespace() {
+                end = position;
+                break;
+            }
+            chars.next();
+
+            // `key:"two words"` and `--name="two words"` quote only the value
+            if ch == '"' && (value.ends_with(':') || value.ends_with('=')) {
+                let mut lookahead = chars.clone();
+                if let Some((quoted, _)) = read_quoted(&mut lookahead) {
+                    chars = lookahead;
+                    value.push_str(&quoted);
+                    continue;
+                }
+            }
+            value.push(ch);
+        }
+
+        tokens.push(Token {
+            index: tokens.len(),
+            value,
+            start,
+            end,
+            quoted: false,
+        });
+    }
+
+    tokens
+}
+
+/// Read up to and past the closing quote, unescaping `\"` and `\\`.
+///
+/// Returns the text and the byte offset just past the closing quote, or
+/// `None` when the quote is never closed.
+fn read_quoted(chars: &mut Peekable<CharIndices<'_>>) -> Option<(String, usize)> {
+    let mut value = String::new();
+    while let Some((position, ch)) = chars.next() {
+        match ch {
+            '\\' if matches!(chars.peek(), Some((_, '"' | '\\'))) => {
+                if let Some((_, escaped)) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            '"' => return Some((value, position + ch.len_utf8())),
+            _ => value.push(ch),
+        }
+    }
+
+    None
+}
+
+/// Sequential, typed access to a message command's arguments.
+///
+/// Read switches with [`ArgParser::flag`] / [`ArgParser::switch`] first, then
+/// positional arguments in the order the usage string lists them.
+pub struct ArgParser<'a> {
+    raw: &'a str,
+    usage: &'static str,
+    tokens: Vec<Token>,
+}
+
+impl<'a> ArgParser<'a> {
+    /// Tokenize raw argument text for a command with the given usage string.
+    pub fn new(raw: &'a str, usage: &'static str) -> Self {
+        Self {
+            raw,
+            usage,
+            tokens: tokenize(raw),
+        }
+    }
+
+    /// Build an error tied to this command's usage string.
+    pub fn error(&self, name: &str, kind: ArgErrorKind) -> ArgError {
+        ArgError::new(self.usage, name, kind)
+    }
+
+    /// Whether every argument has been consumed.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// The next positional argument without consuming it.
+    pub fn peek(&self) -> Option<&str> {
+        self.tokens.first().map(|token| token.value.as_str())
+    }
+
+    /// Consume a required argument of type `T`.
+    pub fn required<T: FromArg>(&mut self, name: &str) -> Result<T, ArgError> {
+        let Some(token) = self.tokens.first() else {
+            return Err(ArgError::missing(self.usage, name));
+        };
+
+        let Some(value) = T::from_arg(&token.value) else {
+            return Err(ArgError::invalid::<T>(self.usage, name, &token.value));
+        };
+
+        self.tokens.remove(0);
+        Ok(value)
+    }
+
+    /// Consume the next argument only if it parses as `T`.
+    ///
+    /// Anything else is left in place for the following argument, which is how
+    /// `[duration] [reason]` style usages are handled.
+    pub fn optional<T: FromArg>(&mut self) -> Option<T> {
+        let value = T::from_arg(&self.tokens.first()?.value)?;
+        self.tokens.remove(0);
+        Some(value)
+    }
+
+    /// Consume consecutive arguments for as long as they parse as `T`.
+    pub fn repeated<T: FromArg>(&mut self) -> Vec<T> {
+        std::iter::from_fn(|| self.optional::<T>()).collect()
+    }
+
+    /// Consume the next argument if it is `word`, ignoring case.
+    pub fn keyword(&mut self, word: &str) -> bool {
+        let matched = self
+            .tokens
+            .first()
+            .is_some_and(|token| !token.quoted && token.value.eq_ignore_ascii_case(word));
+        if matched {
+            self.tokens.remove(0);
+        }
+
+        matched
+    }
+
+    /// Consume a required integer that must fall inside `range`.
+    pub fn integer(&mut self, name: &str, range: RangeInclusive<i64>) -> Result<i64, ArgError> {
+        let value = self.required::<i64>(name)?;
+        self.check_range(name, value, range)
+    }
+
+    /// Consume an integer if one is next, rejecting it when outside `range`.
+    pub fn optional_integer(
+        &mut self,
+        name: &str,
+        range: RangeInclusive<i64>,
+    ) -> Result<Option<i64>, ArgError> {
+        match self.optional::<i64>() {
+            Some(value) => self.check_range(name, value, range).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Consume everything that is left as free text, preserving its spacing.
+    ///
+    /// A single quoted argument is returned without its quotes.
+    pub fn rest(&mut self) -> Option<String> {
+        let tokens = std::mem::take(&mut self.tokens);
+        match tokens.as_slice() {
+            [] => None,
+            [token] if token.quoted => Some(token.value.clone()).filter(|value| !value.is_empty()),
+            _ => {
+                let mut text = String::new();
+                let mut previous: Option<&Token> = None;
+                for token in &tokens {
+                    if let Some(previous) = previous {
+                        if previous.index + 1 == token.index {
+                            text.push_str(&self.raw[previous.end..token.start]);
+                        } else {
+                            text.push(' ');
+                        }
+                    }
+                    text.push_str(&self.raw[token.start..token.end]);
+                    previous = Some(token);
+                }
+
+                Some(text)
+            }
+        }
+    }
+
+    /// Consume everything that is left, failing when nothing was given.
+    pub fn required_rest(&mut self, name: &str) -> Result<String, ArgError> {
+        self.rest()
+            .ok_or_else(|| ArgError::missing(self.usage, name))
+    }
+
+    /// Remove a `--name value` (or `--name=value`) switch from anywhere in the input.
+    pub fn flag<T: FromArg>(&mut self, name: &str) -> Result<Option<T>, ArgError> {
+        let Some(position) = self.find_flag(name) else {
+            return Ok(None);
+        };
+
+        let token = self.tokens.remove(position);
+        let inline = token
+            .flag_name()
+            .and_then(|flag| flag.split_once('='))
+            .map(|(_, value)| value.to_owned());
+
+        let raw_value = match inline {
+            Some(value) if value.is_empty() => {
+                return Err(self.error(name, ArgErrorKind::MissingFlagValue));
+            }
+            Some(value) => value,
+            None => {
+                let has_value = self
+                    .tokens
+                    .get(position)
+                    .is_some_and(|next| next.flag_name().is_none());
+                if !has_value {
+                    return Err(self.error(name, ArgErrorKind::MissingFlagValue));
+                }
+                self.tokens.remove(position).value
+            }
+        };
+
+        T::from_arg(&raw_value)
+            .map(Some)
+            .ok_or_else(|| ArgError::invalid::<T>(self.usage, name, &raw_value))
+    }
+
+    /// Remove a bare `--name` switch, reporting whether it was present.
+    pub fn switch(&mut self, name: &str) -> bool {
+        match self.find_flag(name) {
+            Some(position) => {
+                self.tokens.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fail if any arguments were not consumed.
+    pub fn finish(self) -> Result<(), ArgError> {
+        match self.tokens.first() {
+            Some(token) => Err(self.error(
+                &token.value,
+                ArgErrorKind::Unexpected {
+                    value: token.value.clone(),
+                },
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn find_flag(&self, name: &str) -> Option<usize> {
+        self.tokens.iter().position(|token| {
+            token.flag_name().is_some_and(|flag| {
+                let flag = flag.split_once('=').map_or(flag, |(flag, _)| flag);
+                flag.eq_ignore_ascii_case(name)
+            })
+        })
+    }
+
+    fn check_range(
+        &self,
+        name: &str,
+        value: i64,
+        range: RangeInclusive<i64>,
+    ) -> Result<i64, ArgError> {
+        if range.contains(&value) {
+            return Ok(value);
+        }
+
+        Err(self.error(
+            name,
+            ArgErrorKind::OutOfRange {
+                value,
+                min: *range.start(),
+                max: *range.end(),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USAGE: &str = "!test <amount> [--days <n>] [reason]";
+
+    fn parser(raw: &str) -> ArgParser<'_> {
+        ArgParser::new(raw, USAGE)
+    }
+
+    fn values(raw: &str) -> Vec<String> {
+        tokenize(raw).into_iter().map(|token| token.value).collect()
+    }
+
+    #[test]
+    fn whitespace_separates_and_quotes_group() {
+        assert_eq!(
+            values(r#"  one   "two three"  four "#),
+            ["one", "two three", "four"]
+        );
+    }
+
+    #[test]
+    fn quoted_values_unescape_quotes_and_backslashes() {
+        assert_eq!(values(r#""say \"hi\" \\ bye""#), [r#"say "hi" \ bye"#]);
+    }
+
+    #[test]
+    fn quote_after_colon_or_equals_groups_the_value() {
+        assert_eq!(
+            values(r#"contains:"two words" --reason="a b" next"#),
+            ["contains:two words", "--reason=a b", "next"]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_is_kept_as_text() {
+        assert_eq!(values(r#""two words"#), [r#""two"#, "words"]);
+        assert_eq!(values(r#"key:"two words"#), [r#"key:"two"#, "words"]);
+        assert_eq!(
+            parser(r#""two   words"#).rest().as_deref(),
+            Some(r#""two   words"#)
+        );
+    }
+
+    #[test]
+    fn flag_value_can_follow_or_be_inline() {
+        let mut args = parser("5 --days 7 spam");
+        assert_eq!(args.flag::<i64>("days"), Ok(Some(7)));
+        assert_eq!(args.required::<i64>("amount"), Ok(5));
+        assert_eq!(args.rest().as_deref(), Some("spam"));
+
+        let mut args = parser("--DAYS=3");
+        assert_eq!(args.flag::<i64>("days"), Ok(Some(3)));
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn flag_without_a_value_is_rejected() {
+        for raw in ["--days=", "--days", "--days --other"] {
+            let error = parser(raw).flag::<i64>("days").unwrap_err();
+            assert_eq!(error.kind(), &ArgErrorKind::MissingFlagValue, "{raw}");
+        }
+    }
+
+    #[test]
+    fn absent_flag_is_none() {
+        assert_eq!(parser("5 spam").flag::<i64>("days"), Ok(None));
+        assert!(!parser("5").switch("silent"));
+        assert!(parser("5 --silent").switch("silent"));
+    }
+
+    #[test]
+    fn rest_keeps_spacing_and_unwraps_a_single_quoted_argument() {
+        assert_eq!(parser("a  b   c").rest().as_deref(), Some("a  b   c"));
+        assert_eq!(
+            parser(r#""just this""#).rest().as_deref(),
+            Some("just this")
+        );
+        assert_eq!(parser("").rest(), None);
+    }
+
+    #[test]
+    fn rest_joins_around_removed_flags() {
+        let mut args = parser("spam --days 3 links");
+        assert_eq!(args.flag::<i64>("days"), Ok(Some(3)));
+        assert_eq!(args.rest().as_deref(), Some("spam links"));
+    }
+
+    #[test]
+    fn optional_leaves_unparsed_arguments() {
+        let mut args = parser("reason text");
+        assert_eq!(args.optional::<i64>(), None);
+        assert_eq!(args.required_rest("reason").as_deref(), Ok("reason text"));
+    }
+
+    #[test]
+    fn integer_outside_range_is_reported() {
+        let error = parser("500").integer("amount", 1..=100).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ArgErrorKind::OutOfRange {
+                value: 500,
+                min: 1,
+                max: 100
+            }
+        );
+        assert!(error.to_string().starts_with("Invalid `<amount>` `500`"));
+
+        assert_eq!(parser("50").integer("amount", 1..=100), Ok(50));
+    }
+
+    #[test]
+    fn missing_and_invalid_arguments_are_reported() {
+        let error = parser("").required::<i64>("amount").unwrap_err();
+        assert_eq!(error.kind(), &ArgErrorKind::Missing);
+
+        let error = parser("many").required::<i64>("amount").unwrap_err();
+        assert!(matches!(error.kind(), ArgErrorKind::Invalid { value, .. } if value == "many"));
+    }
+
+    #[test]
+    fn finish_rejects_leftovers() {
+        let mut args = parser("5 extra");
+        assert_eq!(args.required::<i64>("amount"), Ok(5));
+        let error = args.finish().unwrap_err();
+        assert!(matches!(error.kind(), ArgErrorKind::Unexpected { value } if value == "extra"));
+    }
+
+    #[test]
+    fn durations_parse_with_and_without_units() {
+        let secs = |raw| ParsedDuration::from_arg(raw).map(|duration| duration.secs);
+        assert_eq!(secs("30s"), Some(30));
+        assert_eq!(secs("10M"), Some(600));
+        assert_eq!(secs("2h"), Some(7_200));
+        assert_eq!(secs("1d"), Some(86_400));
+        assert_eq!(secs("45"), Some(45));
+        assert_eq!(secs("0m"), None);
+        assert_eq!(secs("5w"), None);
+        assert_eq!(secs("m"), None);
+    }
+
+    #[test]
+    fn unit_durations_need_a_unit() {
+        assert_eq!(UnitDuration::from_arg("3"), None);
+        assert_eq!(
+            UnitDuration::from_arg("3h").map(|UnitDuration(duration)| duration.secs),
+            Some(10_800)
+        );
+
+        let mut args = parser("3 strikes");
+        assert_eq!(args.optional::<UnitDuration>(), None);
+        assert_eq!(args.rest().as_deref(), Some("3 strikes"));
+    }
+}
@@ -0,0 +1,125 @@
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
+};
+
+/// Declarative argument parsing for message commands.
+pub mod args;
+
+pub use args::{
+    ArgError, ArgErrorKind, ArgParser, FromArg, MemberArg, ParsedDuration, UnitDuration,
+};
+
+/// A parsed `https://discord.com/channels/...` message link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageLink {
+    /// `None` for links into direct messages (`@me`).
+    pub guild_id: Option<Id<GuildMarker>>,
+    pub channel_id: Id<ChannelMarker>,
+    pub message_id: Id<MessageMarker>,
+}
+
+/// Parse a target user from a raw argument (`<@id>`, `<@!id>`, or raw ID).
+pub fn parse_target_user_id(raw: &str) -> Option<Id<UserMarker>> {
+    let trimmed = raw.trim();
+
+    let numeric = if trimmed.starts_with("<@") && trimmed.ends_with('>') {
+        let without_wrappers = trimmed.strip_prefix("<@")?.strip_suffix('>')?;
+        without_wrappers
+            .strip_prefix('!')
+            .unwrap_or(without_wrappers)
+    } else {
+        trimmed
+    };
+
+    parse_id(numeric)
+}
+
+/// Parse a role from a raw argument (`<@&id>` or raw ID).
+pub fn parse_role_id(raw: &str) -> Option<Id<RoleMarker>> {
+    let trimmed = raw.trim();
+    let numeric = trimmed
+        .strip_prefix("<@&")
+        .and_then(|value| value.strip_suffix('>'))
+        .unwrap_or(trimmed);
+
+    parse_id(numeric)
+}
+
+/// Parse a channel from a raw argument (`<#id>` or raw ID).
+pub fn parse_channel_id(raw: &str) -> Option<Id<ChannelMarker>> {
+    let trimmed = raw.trim();
+    let numeric = trimmed
+        .strip_prefix("<#")
+        .and_then(|value| value.strip_suffix('>'))
+        .unwrap_or(trimmed);
+
+    parse_id(numeric)
+}
+
+/// Parse a message link copied from the Discord client.
+pub fn parse_message_link(raw: &str) -> Option<MessageLink> {
+    let trimmed = raw.trim().trim_start_matches('<').trim_end_matches('>');
+    let without_scheme = trimmed
+        .strip_prefix("https://")
+        .or_else(|| trimmed.strip_prefix("http://"))?;
+
+    let (host, path) = without_scheme.split_once('/')?;
+    let host = host
+        .strip_prefix("ptb.")
+        .or_else(|| host.strip_prefix("canary."))
+        .unwrap_or(host);
+    if host != "discord.com" && host != "discordapp.com" {
+        return None;
+    }
+
+    let mut segments = path.strip_prefix("channels/")?.split('/');
+    let guild_raw = segments.next()?;
+    let channel_id = parse_id(segments.next()?)?;
+    let message_id = parse_id(segments.next()?)?;
+    if segments.next().is_some() {
+        return None;
+    }
+
+    let guild_id = match guild_raw {
+        "@me" => None,
+        value => Some(parse_id(value)?),
+    };
+
+    Some(MessageLink {
+        guild_id,
+        channel_id,
+        message_id,
+    })
+}
+
+/// Parse a compact duration token like `30s`, `10m`, `2h`, `1d`, or plain seconds.
+pub fn parse_duration_seconds(raw: &str) -> Option<u64> {
+    let value = raw.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    let mut chars = value.chars();
+    let unit = chars.next_back();
+
+    let (number_raw, multiplier) = match unit {
+        Some('s') | Some('S') => (chars.as_str(), 1_u64),
+        Some('m') | Some('M') => (chars.as_str(), 60_u64),
+        Some('h') | Some('H') => (chars.as_str(), 60_u64 * 60),
+        Some('d') | Some('D') => (chars.as_str(), 60_u64 * 60 * 24),
+        Some(last) if last.is_ascii_digit() => (value, 1_u64),
+        _ => return None,
+    };
+
+    let number = number_raw.parse::<u64>().ok()?;
+    if number == 0 {
+        return None;
+    }
+
+    number.checked_mul(multiplier)
+}
+
+fn parse_id<T>(raw: &str) -> Option<Id<T>> {
+    Id::new_checked(raw.parse::<u64>().ok()?)
+}
@@ -1,9 +1,44 @@
-use twilight_http::Client;
+use std::cmp::Reverse;
+use std::future::Future;
+
+use anyhow::{Context as _, bail};
+use rusty_core::{Context, cache::CachedGuild};
 use twilight_model::{
+    channel::{
+        Channel,
+        permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+    },
     gateway::payload::incoming::{InteractionCreate, MessageCreate},
-    guild::Permissions,
+    guild::{Permissions, Role},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+    },
 };
 
+use crate::http_error::is_not_found;
+use crate::invocation::{Invocation, InvocationSource};
+
+tokio::task_local! {
+    /// Set while running a command that a guild override granted to its invoker.
+    static COMMAND_GRANTED: bool;
+}
+
+/// Run a command, passing every permission check inside it when `granted`.
+///
+/// Guild overrides can grant a command to members Discord would refuse. The
+/// dispatcher decides that once, up front, and the `has_*_permission` and
+/// `check_*_permissions` helpers below honour it.
+pub async fn with_command_grant<F: Future>(granted: bool, command: F) -> F::Output {
+    COMMAND_GRANTED.scope(granted, command).await
+}
+
+fn command_granted() -> bool {
+    COMMAND_GRANTED
+        .try_with(|granted| *granted)
+        .unwrap_or(false)
+}
+
 /// Convert a permission bitset into a sorted display list.
 ///
 /// If `ADMINISTRATOR` is present, only `ADMINISTRATOR` is returned because
@@ -21,11 +56,12 @@ pub fn permission_names(perms: Permissions) -> Vec<String> {
     names
 }
 
-/// Resolve the invoking author's effective guild permissions for a message command.
+/// Resolve the invoking author's effective permissions in the channel a message
+/// command was sent in, including channel overwrites.
 ///
 /// Returns `Ok(None)` when the message is not from a guild context.
 pub async fn resolve_message_author_permissions(
-    http: &Client,
+    ctx: &Context,
     msg: &MessageCreate,
 ) -> anyhow::Result<Option<Permissions>> {
     if let Some(perms) = msg.member.as_ref().and_then(|m| m.permissions) {
@@ -36,34 +72,246 @@ pub async fn resolve_message_author_permissions(
         return Ok(None);
     };
 
-    let member = http
-        .guild_member(guild_id, msg.author.id)
+    // Message payloads carry the author's roles even when they omit permissions
+    let member_roles = match msg.member.as_ref() {
+        Some(member) => member.roles.clone(),
+        None => member_roles(ctx, guild_id, msg.author.id).await?,
+    };
+
+    channel_permissions(ctx, guild_id, msg.channel_id, msg.author.id, &member_roles)
+        .await
+        .map(Some)
+}
+
+/// Resolve a member's effective permissions in a channel.
+///
+/// Starts from the guild permissions of `@everyone` and the member's roles,
+/// then applies the channel's overwrites for `@everyone`, the member's roles
+/// and the member, in that order. The guild owner and administrators get
+/// every permission. Threads use their parent channel's overwrites.
+pub async fn member_channel_permissions(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    user_id: Id<UserMarker>,
+) -> anyhow::Result<Permissions> {
+    let member_roles = member_roles(ctx, guild_id, user_id).await?;
+    channel_permissions(ctx, guild_id, channel_id, user_id, &member_roles).await
+}
+
+/// Fetch a channel from the cache, or over REST on a miss.
+pub async fn guild_channel(
+    ctx: &Context,
+    channel_id: Id<ChannelMarker>,
+) -> anyhow::Result<Channel> {
+    if let Some(channel) = ctx.cache.channel(channel_id) {
+        return Ok(channel);
+    }
+
+    let channel = ctx.http.channel(channel_id).await?.model().await?;
+    ctx.cache.insert_channel(&channel);
+    Ok(channel)
+}
+
+async fn channel_permissions(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    user_id: Id<UserMarker>,
+    member_roles: &[Id<RoleMarker>],
+) -> anyhow::Result<Permissions> {
+    let base = guild_permissions(ctx, guild_id, user_id, member_roles).await?;
+    // Overwrites can't take anything from an administrator, so skip the channel lookup
+    if base.contains(Permissions::ADMINISTRATOR) {
+        return Ok(Permissions::all());
+    }
+
+    let mut channel = guild_channel(ctx, channel_id).await?;
+    if channel.kind.is_thread()
+        && let Some(parent_id) = channel.parent_id
+    {
+        channel = guild_channel(ctx, parent_id).await?;
+    }
+    if channel.guild_id.is_some_and(|id| id != guild_id) {
+        bail!("channel {channel_id} is not in guild {guild_id}");
+    }
+
+    Ok(apply_overwrites(
+        base,
+        guild_id,
+        user_id,
+        member_roles,
+        channel.permission_overwrites.as_deref().unwrap_or_default(),
+    ))
+}
+
+/// Apply channel overwrites to guild-level permissions in Discord's order:
+/// `@everyone`, then the member's roles together, then the member.
+fn apply_overwrites(
+    base: Permissions,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    member_roles: &[Id<RoleMarker>],
+    overwrites: &[PermissionOverwrite],
+) -> Permissions {
+    if base.contains(Permissions::ADMINISTRATOR) {
+        return Permissions::all();
+    }
+
+    let mut resolved = base;
+
+    if let Some(everyone) = overwrites
+        .iter()
+        .find(|overwrite| overwrite.id == guild_id.cast())
+    {
+        resolved.remove(everyone.deny);
+        resolved.insert(everyone.allow);
+    }
+
+    // Role overwrites are combined first so an allow on any role beats a deny on another
+    let (mut allow, mut deny) = (Permissions::empty(), Permissions::empty());
+    for overwrite in overwrites.iter().filter(|overwrite| {
+        overwrite.kind == PermissionOverwriteType::Role
+            && member_roles.contains(&overwrite.id.cast())
+    }) {
+        allow |= overwrite.allow;
+        deny |= overwrite.deny;
+    }
+    resolved.remove(deny);
+    resolved.insert(allow);
+
+    if let Some(member) = overwrites.iter().find(|overwrite| {
+        overwrite.kind == PermissionOverwriteType::Member && overwrite.id == user_id.cast()
+    }) {
+        resolved.remove(member.deny);
+        resolved.insert(member.allow);
+    }
+
+    resolved
+}
+
+/// Resolve a member's guild-level permissions from their roles.
+///
+/// Reads the member and the guild's roles from the cache and only falls back
+/// to REST on a miss. Channel overwrites are not applied.
+pub async fn member_guild_permissions(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> anyhow::Result<Permissions> {
+    let member_roles = member_roles(ctx, guild_id, user_id).await?;
+    guild_permissions(ctx, guild_id, user_id, &member_roles).await
+}
+
+/// Fetch a guild's roles from the cache, or over REST on a miss.
+pub async fn guild_roles(ctx: &Context, guild_id: Id<GuildMarker>) -> anyhow::Result<Vec<Role>> {
+    if let Some(roles) = ctx.cache.roles(guild_id) {
+        return Ok(roles);
+    }
+
+    let roles = ctx.http.roles(guild_id).await?.model().await?;
+    ctx.cache.insert_roles(guild_id, &roles);
+    Ok(roles)
+}
+
+async fn member_roles(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> anyhow::Result<Vec<Id<RoleMarker>>> {
+    find_member_roles(ctx, guild_id, user_id)
         .await?
-        .model()
-        .await?;
+        .with_context(|| format!("user {user_id} is not a member of guild {guild_id}"))
+}
 
-    let roles = http.roles(guild_id).await?.model().await?;
+/// Like [`member_roles`], but `Ok(None)` when the user is not in the guild.
+async fn find_member_roles(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> anyhow::Result<Option<Vec<Id<RoleMarker>>>> {
+    if let Some(member) = ctx.cache.member(guild_id, user_id) {
+        return Ok(Some(member.roles));
+    }
 
-    let mut resolved = Permissions::empty();
+    let member = match ctx.http.guild_member(guild_id, user_id).await {
+        Ok(response) => response.model().await?,
+        Err(source) if is_not_found(&source) => return Ok(None),
+        Err(source) => return Err(source.into()),
+    };
+    ctx.cache.insert_member(guild_id, &member);
+    Ok(Some(member.roles))
+}
 
-    for role in roles {
-        if role.id == guild_id.cast() || member.roles.contains(&role.id) {
-            resolved |= role.permissions;
-        }
+async fn guild_owner_id(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+) -> anyhow::Result<Id<UserMarker>> {
+    if let Some(guild) = ctx.cache.guild(guild_id) {
+        return Ok(guild.owner_id);
     }
 
-    Ok(Some(resolved))
+    let guild = ctx.http.guild(guild_id).await?.model().await?;
+    ctx.cache.insert_guild(CachedGuild {
+        id: guild.id,
+        name: guild.name,
+        owner_id: guild.owner_id,
+    });
+    Ok(guild.owner_id)
+}
+
+async fn current_user_id(ctx: &Context) -> anyhow::Result<Id<UserMarker>> {
+    if let Some(user_id) = ctx.cache.current_user_id() {
+        return Ok(user_id);
+    }
+
+    Ok(ctx.http.current_user().await?.model().await?.id)
+}
+
+async fn guild_permissions(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    member_roles: &[Id<RoleMarker>],
+) -> anyhow::Result<Permissions> {
+    if guild_owner_id(ctx, guild_id).await? == user_id {
+        return Ok(Permissions::all());
+    }
+
+    let roles = guild_roles(ctx, guild_id).await?;
+    Ok(role_permissions(
+        guild_id,
+        member_roles,
+        roles.iter().map(|role| (role.id, role.permissions)),
+    ))
+}
+
+/// Combine `@everyone` with the member's roles.
+fn role_permissions(
+    guild_id: Id<GuildMarker>,
+    member_roles: &[Id<RoleMarker>],
+    roles: impl IntoIterator<Item = (Id<RoleMarker>, Permissions)>,
+) -> Permissions {
+    roles
+        .into_iter()
+        .filter(|(role_id, _)| *role_id == guild_id.cast() || member_roles.contains(role_id))
+        .fold(Permissions::empty(), |resolved, (_, permissions)| {
+            resolved | permissions
+        })
 }
 
 /// Check whether the message author has a required permission (or administrator).
 ///
 /// Returns `Ok(false)` when the message is outside a guild context.
 pub async fn has_message_permission(
-    http: &Client,
+    ctx: &Context,
     msg: &MessageCreate,
     required: Permissions,
 ) -> anyhow::Result<bool> {
-    let Some(perms) = resolve_message_author_permissions(http, msg).await? else {
+    if command_granted() {
+        return Ok(true);
+    }
+    let Some(perms) = resolve_message_author_permissions(ctx, msg).await? else {
         return Ok(false);
     };
 
@@ -71,10 +319,16 @@ pub async fn has_message_permission(
 }
 
 /// Check whether an interaction member has required permissions (or administrator).
+///
+/// Discord resolves `member.permissions` on interactions for the channel the
+/// interaction came from, overwrites included, so no lookup is needed.
 pub fn check_interaction_permissions(
     interaction: &InteractionCreate,
     required: Permissions,
 ) -> bool {
+    if command_granted() {
+        return true;
+    }
     let Some(perms) = interaction
         .member
         .as_ref()
@@ -85,3 +339,303 @@ pub fn check_interaction_permissions(
 
     perms.contains(Permissions::ADMINISTRATOR) || perms.contains(required)
 }
+
+/// Resolve the invoking member's effective permissions in the invoking channel.
+///
+/// Returns `Ok(None)` when the invocation is not from a guild context.
+pub async fn resolve_invocation_permissions(
+    ctx: &Context,
+    invocation: &Invocation,
+) -> anyhow::Result<Option<Permissions>> {
+    match invocation.source() {
+        InvocationSource::Message(msg) => resolve_message_author_permissions(ctx, msg).await,
+        InvocationSource::Interaction(_) => {
+            if invocation.guild_id().is_none() {
+                return Ok(None);
+            }
+
+            Ok(Some(
+                invocation
+                    .member_permissions()
+                    .unwrap_or_else(Permissions::empty),
+            ))
+        }
+    }
+}
+
+/// Check whether the invoking member has a required permission (or administrator).
+///
+/// Returns `Ok(false)` when the invocation is outside a guild context.
+pub async fn has_invocation_permission(
+    ctx: &Context,
+    invocation: &Invocation,
+    required: Permissions,
+) -> anyhow::Result<bool> {
+    if command_granted() {
+        return Ok(true);
+    }
+    let Some(perms) = resolve_invocation_permissions(ctx, invocation).await? else {
+        return Ok(false);
+    };
+
+    Ok(perms.contains(Permissions::ADMINISTRATOR) || perms.contains(required))
+}
+
+/// Why a moderation action against a target was refused before reaching Discord.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TargetRefusal {
+    /// The moderator targeted themselves.
+    Yourself,
+    /// The target is the bot.
+    Bot,
+    /// The target owns the guild.
+    Owner,
+    /// The target holds a role the guild marked as protected.
+    ProtectedRole { name: String },
+    /// The target's highest role is not below the moderator's.
+    ModeratorOutranked,
+    /// The target's highest role is not below the bot's, so Discord would reject the action.
+    BotOutranked,
+}
+
+impl TargetRefusal {
+    /// Explain the refusal for an action such as `"ban"`.
+    pub fn message(&self, action: &str) -> String {
+        match self {
+            Self::Yourself => format!("You can't {action} yourself."),
+            Self::Bot => format!("I can't {action} myself."),
+            Self::Owner => format!("You can't {action} the server owner."),
+            Self::ProtectedRole { name } => format!(
+                "That user has the protected role **{}**, so I won't {action} them.",
+                name.replace('@', "@\u{200B}")
+            ),
+            Self::ModeratorOutranked => format!(
+                "That user's highest role is equal to or above yours, so you can't {action} them."
+            ),
+            Self::BotOutranked => format!(
+                "That user's highest role is equal to or above mine, so I can't {action} them. \
+                 Move my role above theirs first."
+            ),
+        }
+    }
+}
+
+/// Check that `moderator_id` may act on `target_id` before calling Discord.
+///
+/// Refuses self-targeting, the bot, the guild owner and members holding any of
+/// `protected_roles`, then compares highest roles: the target must sit below
+/// both the moderator (unless the moderator owns the guild) and the bot.
+/// Users who are not in the guild only get the identity checks, so they can
+/// still be banned by ID.
+pub async fn check_moderation_target(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    moderator_id: Id<UserMarker>,
+    target_id: Id<UserMarker>,
+    protected_roles: &[Id<RoleMarker>],
+) -> anyhow::Result<Option<TargetRefusal>> {
+    if target_id == moderator_id {
+        return Ok(Some(TargetRefusal::Yourself));
+    }
+
+    let bot_id = current_user_id(ctx).await?;
+    if target_id == bot_id {
+        return Ok(Some(TargetRefusal::Bot));
+    }
+
+    let owner_id = guild_owner_id(ctx, guild_id).await?;
+    if target_id == owner_id {
+        return Ok(Some(TargetRefusal::Owner));
+    }
+
+    let Some(target_roles) = find_member_roles(ctx, guild_id, target_id).await? else {
+        return Ok(None);
+    };
+
+    let roles = guild_roles(ctx, guild_id).await?;
+    if let Some(role) = roles
+        .iter()
+        .find(|role| protected_roles.contains(&role.id) && target_roles.contains(&role.id))
+    {
+        return Ok(Some(TargetRefusal::ProtectedRole {
+            name: role.name.clone(),
+        }));
+    }
+
+    let target_rank = highest_role_rank(&roles, &target_roles);
+    if moderator_id != owner_id {
+        let moderator_roles = member_roles(ctx, guild_id, moderator_id).await?;
+        if target_rank >= highest_role_rank(&roles, &moderator_roles) {
+            return Ok(Some(TargetRefusal::ModeratorOutranked));
+        }
+    }
+
+    let bot_roles = member_roles(ctx, guild_id, bot_id).await?;
+    if target_rank >= highest_role_rank(&roles, &bot_roles) {
+        return Ok(Some(TargetRefusal::BotOutranked));
+    }
+
+    Ok(None)
+}
+
+/// Rank of a member's highest role; `None` means only `@everyone`.
+///
+/// Discord orders roles by position, breaking ties in favour of the older role.
+fn highest_role_rank(
+    roles: &[Role],
+    member_roles: &[Id<RoleMarker>],
+) -> Option<(i64, Reverse<Id<RoleMarker>>)> {
+    roles
+        .iter()
+        .filter(|role| member_roles.contains(&role.id))
+        .map(|role| (role.position, Reverse(role.id)))
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: Id<GuildMarker> = Id::new(1);
+    const USER: Id<UserMarker> = Id::new(2);
+    const MOD_ROLE: Id<RoleMarker> = Id::new(3);
+    const MUTED_ROLE: Id<RoleMarker> = Id::new(4);
+
+    fn overwrite(
+        id: u64,
+        kind: PermissionOverwriteType,
+        allow: Permissions,
+        deny: Permissions,
+    ) -> PermissionOverwrite {
+        PermissionOverwrite {
+            allow,
+            deny,
+            id: Id::new(id),
+            kind,
+        }
+    }
+
+    #[test]
+    fn role_permissions_include_everyone_and_member_roles_only() {
+        let roles = [
+            (GUILD.cast(), Permissions::VIEW_CHANNEL),
+            (MOD_ROLE, Permissions::MANAGE_MESSAGES),
+            (MUTED_ROLE, Permissions::BAN_MEMBERS),
+        ];
+
+        assert_eq!(
+            role_permissions(GUILD, &[MOD_ROLE], roles),
+            Permissions::VIEW_CHANNEL | Permissions::MANAGE_MESSAGES
+        );
+    }
+
+    #[test]
+    fn everyone_overwrite_applies_before_roles() {
+        let overwrites = [
+            overwrite(
+                GUILD.get(),
+                PermissionOverwriteType::Role,
+                Permissions::empty(),
+                Permissions::SEND_MESSAGES,
+            ),
+            overwrite(
+                MOD_ROLE.get(),
+                PermissionOverwriteType::Role,
+                Permissions::SEND_MESSAGES,
+                Permissions::empty(),
+            ),
+        ];
+
+        let resolved = apply_overwrites(
+            Permissions::SEND_MESSAGES,
+            GUILD,
+            USER,
+            &[MOD_ROLE],
+            &overwrites,
+        );
+        assert!(resolved.contains(Permissions::SEND_MESSAGES));
+
+        let resolved = apply_overwrites(Permissions::SEND_MESSAGES, GUILD, USER, &[], &overwrites);
+        assert!(!resolved.contains(Permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn role_allow_beats_deny_on_another_role() {
+        let overwrites = [
+            overwrite(
+                MUTED_ROLE.get(),
+                PermissionOverwriteType::Role,
+                Permissions::empty(),
+                Permissions::SEND_MESSAGES,
+            ),
+            overwrite(
+                MOD_ROLE.get(),
+                PermissionOverwriteType::Role,
+                Permissions::SEND_MESSAGES,
+                Permissions::empty(),
+            ),
+        ];
+
+        let resolved = apply_overwrites(
+            Permissions::empty(),
+            GUILD,
+            USER,
+            &[MOD_ROLE, MUTED_ROLE],
+            &overwrites,
+        );
+        assert!(resolved.contains(Permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn member_overwrite_applies_last() {
+        let overwrites = [
+            overwrite(
+                MOD_ROLE.get(),
+                PermissionOverwriteType::Role,
+                Permissions::SEND_MESSAGES,
+                Permissions::empty(),
+            ),
+            overwrite(
+                USER.get(),
+                PermissionOverwriteType::Member,
+                Permissions::empty(),
+                Permissions::SEND_MESSAGES,
+            ),
+        ];
+
+        let resolved = apply_overwrites(
+            Permissions::SEND_MESSAGES,
+            GUILD,
+            USER,
+            &[MOD_ROLE],
+            &overwrites,
+        );
+        assert!(!resolved.contains(Permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn member_overwrite_for_another_user_is_ignored() {
+        let overwrites = [overwrite(
+            99,
+            PermissionOverwriteType::Member,
+            Permissions::empty(),
+            Permissions::SEND_MESSAGES,
+        )];
+
+        let resolved = apply_overwrites(Permissions::SEND_MESSAGES, GUILD, USER, &[], &overwrites);
+        assert!(resolved.contains(Permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn administrator_ignores_overwrites() {
+        let overwrites = [overwrite(
+            USER.get(),
+            PermissionOverwriteType::Member,
+            Permissions::empty(),
+            Permissions::all(),
+        )];
+
+        let resolved = apply_overwrites(Permissions::ADMINISTRATOR, GUILD, USER, &[], &overwrites);
+        assert_eq!(resolved, Permissions::all());
+    }
+}
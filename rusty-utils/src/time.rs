@@ -6,3 +6,31 @@ pub fn now_unix_secs() -> u64 {
         .duration_since(UNIX_EPOCH)
         .map_or(0, |duration| duration.as_secs())
 }
+
+/// Unix timestamp in seconds at which a Discord ID, such as an account, was created.
+pub fn snowflake_unix_secs(id: u64) -> u64 {
+    const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+    ((id >> 22) + DISCORD_EPOCH_MS) / 1_000
+}
+
+/// Format a duration compactly, largest units first (e.g. `1d 2h 30m`).
+pub fn format_duration_secs(secs: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")];
+
+    if secs == 0 {
+        return "0s".to_owned();
+    }
+
+    let mut remaining = secs;
+    let mut parts = Vec::new();
+    for (unit_secs, suffix) in UNITS {
+        let count = remaining / unit_secs;
+        if count > 0 {
+            parts.push(format!("{count}{suffix}"));
+            remaining %= unit_secs;
+        }
+    }
+
+    parts.join(" ")
+}